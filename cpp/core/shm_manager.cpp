@@ -9,14 +9,45 @@
 #include <sstream>
 #include <iomanip>
 #include <stdexcept>
+#include <iostream>
+#include <algorithm>
+#include <cerrno>
 
 namespace anyserve {
 
+namespace {
+
+// 名称冲突（EEXIST）时换一个随机名重试的次数
+constexpr int SHM_CREATE_ATTEMPTS = 8;
+
+// macOS PSHM_NAME_LEN=31（含结尾 '\0'），名称最长 30 字节
+constexpr size_t MAX_SHM_NAME = 30;
+
+/**
+ * 生成 SHM 名称 /as_<pid>_<16 位十六进制随机数>
+ *
+ * 名称带上 pid，进程崩溃遗留的段可由 reaper 按 pid 存活判断清理；
+ * 64 位随机段使同一主机上大量代理并发启动时也几乎不会冲突，
+ * 按 7 位 pid 计算也在 MAX_SHM_NAME 以内
+ */
+std::string make_shm_name() {
+    static thread_local std::mt19937_64 gen(std::random_device{}());
+    std::ostringstream ss;
+    ss << "/as_" << getpid() << "_" << std::hex << std::setw(16) << std::setfill('0') << gen();
+    return ss.str().substr(0, MAX_SHM_NAME);
+}
+
+} // anonymous namespace
+
 ShmManager::RawShm::RawShm(RawShm&& other) noexcept 
-    : fd(other.fd), ptr(other.ptr), size(other.size), name(std::move(other.name)) {
+    : fd(other.fd), ptr(other.ptr), size(other.size), name(std::move(other.name)),
+      locked(other.locked), linked(other.linked), high_water(other.high_water), wraps(other.wraps),
+      bytes_moved(other.bytes_moved), write_end(other.write_end) {
     other.fd = -1;
     other.ptr = nullptr;
     other.size = 0;
+    other.locked = false;
+    other.linked = false;
 }
 
 ShmManager::RawShm& ShmManager::RawShm::operator=(RawShm&& other) noexcept {
@@ -26,9 +57,17 @@ ShmManager::RawShm& ShmManager::RawShm::operator=(RawShm&& other) noexcept {
         ptr = other.ptr;
         size = other.size;
         name = std::move(other.name);
+        locked = other.locked;
+        linked = other.linked;
+        high_water = other.high_water;
+        wraps = other.wraps;
+        bytes_moved = other.bytes_moved;
+        write_end = other.write_end;
         other.fd = -1;
         other.ptr = nullptr;
         other.size = 0;
+        other.locked = false;
+        other.linked = false;
     }
     return *this;
 }
@@ -39,6 +78,10 @@ ShmManager::RawShm::~RawShm() {
 
 void ShmManager::RawShm::cleanup() {
     if (ptr && ptr != MAP_FAILED) {
+        if (locked) {
+            munlock(ptr, size);
+            locked = false;
+        }
         munmap(ptr, size);
         ptr = nullptr;
     }
@@ -46,31 +89,148 @@ void ShmManager::RawShm::cleanup() {
         close(fd);
         fd = -1;
     }
+    if (linked) {
+        shm_unlink(name.c_str());
+        linked = false;
+    }
+}
+
+void* ShmManager::RawShm::region(size_t offset, size_t len) const {
+    // 先比较 offset 再做减法，避免 offset + len 溢出绕过检查
+    if (!ptr || ptr == MAP_FAILED || offset > size || len > size - offset) {
+        return nullptr;
+    }
+    return static_cast<char*>(ptr) + offset;
+}
+
+void ShmManager::RawShm::record_write(size_t offset, size_t len) {
+    if (offset < write_end) {
+        ++wraps;
+    }
+    write_end = offset + len;
+    high_water = std::max(high_water, write_end);
+    bytes_moved += len;
 }
 
-ShmManager::RawShm ShmManager::create(size_t size) {
+#ifdef MFD_HUGETLB
+namespace {
+
+/**
+ * 尝试用 hugetlbfs 支持的 memfd 创建大页 SHM（仅 Linux）
+ * @return 成功时返回已映射的 RawShm，失败时 fd 为 -1
+ */
+ShmManager::RawShm try_create_huge(size_t size) {
+    ShmManager::RawShm shm;
+    shm.size = size;
+    shm.name = "as_huge";
+
+    // 不带 MFD_CLOEXEC，子进程可以继承
+    shm.fd = memfd_create(shm.name.c_str(), MFD_HUGETLB);
+    if (shm.fd < 0) {
+        return shm;
+    }
+    // cleanup() 的 close/munmap 会覆盖 errno，调用方还要用它输出失败原因
+    auto fail = [&shm]() {
+        const int err = errno;
+        shm.cleanup();
+        errno = err;
+    };
+    if (ftruncate(shm.fd, static_cast<off_t>(size)) < 0) {
+        fail();
+        return shm;
+    }
+    // 预留的大页不足时 mmap 失败（ENOMEM）
+    shm.ptr = mmap(nullptr, size, PROT_READ | PROT_WRITE, MAP_SHARED, shm.fd, 0);
+    if (shm.ptr == MAP_FAILED) {
+        fail();
+    }
+    return shm;
+}
+
+} // anonymous namespace
+#endif
+
+ShmManager::RawShm ShmManager::create(size_t size, const Options& options) {
+    RawShm shm;
+
+    // 0. 可选：大页（减少大块拷贝时的 TLB miss），不可用时回退到普通页
+    if (options.huge_pages && !options.keep_name.empty()) {
+        // memfd 没有 /dev/shm 条目，保留名称时只能用普通页
+        std::cerr << "[ShmManager] Warning: huge pages cannot be used with a kept name,"
+                  << " using normal pages." << std::endl;
+    } else if (options.huge_pages) {
+#ifdef MFD_HUGETLB
+        if (size % HUGE_PAGE_SIZE != 0) {
+            std::cerr << "[ShmManager] Warning: size " << size
+                      << " is not a multiple of the huge page size (" << HUGE_PAGE_SIZE
+                      << "), using normal pages." << std::endl;
+        } else {
+            shm = try_create_huge(size);
+            if (shm.fd < 0) {
+                std::cerr << "[ShmManager] Warning: huge page SHM unavailable ("
+                          << strerror(errno) << "), using normal pages."
+                          << " Reserve pages via /proc/sys/vm/nr_hugepages." << std::endl;
+            }
+        }
+#else
+        std::cerr << "[ShmManager] Warning: huge pages are only supported on Linux,"
+                  << " using normal pages." << std::endl;
+#endif
+    }
+
+    if (shm.fd < 0) {
+        shm = create_posix(size, options.keep_name);
+    }
+
+    // 6. 可选：锁定物理内存（失败不致命）
+    if (options.lock_memory) {
+        if (mlock(shm.ptr, size) == 0) {
+            shm.locked = true;
+        } else {
+            std::cerr << "[ShmManager] Warning: mlock(" << size << " bytes) failed: "
+                      << strerror(errno)
+                      << ". Raise RLIMIT_MEMLOCK (ulimit -l) or grant CAP_IPC_LOCK;"
+                      << " continuing with swappable SHM." << std::endl;
+        }
+    }
+
+    return shm;
+}
+
+ShmManager::RawShm ShmManager::create_posix(size_t size, const std::string& keep_name) {
     RawShm shm;
     shm.size = size;
     
-    // 生成随机名称（macOS PSHM_NAME_LEN=31 限制）
-    std::random_device rd;
-    std::mt19937 gen(rd());
-    std::uniform_int_distribution<> dis(0, 15);
-    std::stringstream ss;
-    ss << "/as_";
-    for (int i = 0; i < 8; ++i) {
-        ss << std::hex << dis(gen);
-    }
-    shm.name = ss.str();
-
-    // 1. 创建 SHM (O_CREAT | O_RDWR | O_EXCL)
-    shm.fd = shm_open(shm.name.c_str(), O_CREAT | O_RDWR | O_EXCL, 0600);
+    // 调试用固定名称：不重试，已存在多半是上次崩溃遗留的段
+    if (!keep_name.empty()) {
+        shm.name = "/" + keep_name;
+        shm.fd = shm_open(shm.name.c_str(), O_CREAT | O_RDWR | O_EXCL, 0600);
+        if (shm.fd < 0) {
+            const int err = errno;
+            throw std::runtime_error("shm_open(" + shm.name + ") failed: " + strerror(err) +
+                                     (err == EEXIST ? " (stale segment? remove /dev/shm" +
+                                                      shm.name + ")" : ""));
+        }
+        shm.linked = true;
+    }
+
+    // 1. 以随机名称创建 SHM (O_CREAT | O_RDWR | O_EXCL)，名称已存在时换名重试
+    for (int attempt = 0; attempt < SHM_CREATE_ATTEMPTS && shm.fd < 0; ++attempt) {
+        shm.name = make_shm_name();
+        shm.fd = shm_open(shm.name.c_str(), O_CREAT | O_RDWR | O_EXCL, 0600);
+        if (shm.fd < 0 && errno != EEXIST) {
+            throw std::runtime_error("shm_open failed: " + std::string(strerror(errno)));
+        }
+    }
     if (shm.fd < 0) {
-        throw std::runtime_error("shm_open failed: " + std::string(strerror(errno)));
+        throw std::runtime_error("shm_open failed: name collision after " +
+                                 std::to_string(SHM_CREATE_ATTEMPTS) + " attempts");
     }
 
-    // 2. 立即 unlink（匿名行为）
-    shm_unlink(shm.name.c_str());
+    // 2. 立即 unlink（匿名行为），保留名称时由 cleanup 负责
+    if (!shm.linked) {
+        shm_unlink(shm.name.c_str());
+    }
 
     // 3. 清除 FD_CLOEXEC，让子进程可以继承
     int flags = fcntl(shm.fd, F_GETFD);
@@ -78,16 +238,14 @@ ShmManager::RawShm ShmManager::create(size_t size) {
         fcntl(shm.fd, F_SETFD, flags & ~FD_CLOEXEC);
     }
 
-    // 4. 调整大小
+    // 4. 调整大小（失败时由 RawShm 析构关闭 fd）
     if (ftruncate(shm.fd, static_cast<off_t>(size)) < 0) {
-        close(shm.fd);
         throw std::runtime_error("ftruncate failed: " + std::string(strerror(errno)));
     }
 
     // 5. 内存映射
     shm.ptr = mmap(nullptr, size, PROT_READ | PROT_WRITE, MAP_SHARED, shm.fd, 0);
     if (shm.ptr == MAP_FAILED) {
-        close(shm.fd);
         throw std::runtime_error("mmap failed: " + std::string(strerror(errno)));
     }
 
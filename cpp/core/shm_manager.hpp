@@ -2,6 +2,7 @@
 
 #include <string>
 #include <cstddef>
+#include <cstdint>
 
 namespace anyserve {
 
@@ -10,6 +11,7 @@ namespace anyserve {
  * 
  * 用于在控制平面（C++）和执行平面（Python Worker）之间高效传输大数据块。
  * 使用匿名 SHM（创建后立即 unlink），通过 fd 继承传递给子进程。
+ * 调试时可用 Options::keep_name 保留名称（见其说明）。
  */
 class ShmManager {
 public:
@@ -21,6 +23,14 @@ public:
         void* ptr = nullptr;
         size_t size = 0;
         std::string name;
+        bool locked = false;  // 是否已 mlock
+        bool linked = false;  // 名称仍在 /dev/shm 中（Options::keep_name），cleanup 时 unlink
+
+        // 使用统计（由写入方调用 record_write 更新，调用方负责同步）
+        size_t high_water = 0;     // 写入到达过的最大结束偏移
+        uint64_t wraps = 0;        // 写入回到更小偏移（环绕）的次数
+        uint64_t bytes_moved = 0;  // 累计写入字节数
+        size_t write_end = 0;      // 上一次写入的结束偏移
 
         RawShm() = default;
         RawShm(RawShm&& other) noexcept;
@@ -32,15 +42,51 @@ public:
         RawShm& operator=(const RawShm&) = delete;
 
         void cleanup();
+
+        /**
+         * 获取 [offset, offset + len) 区间的指针，越界时返回 nullptr
+         *
+         * offset/len 可能来自对端（如 Worker 回传的参数），访问映射区域前
+         * 必须经过这里检查，不能直接 ptr + offset
+         */
+        void* region(size_t offset, size_t len) const;
+
+        /**
+         * 记录一次 [offset, offset + len) 的写入，更新使用统计
+         */
+        void record_write(size_t offset, size_t len);
     };
 
+    /**
+     * Options - SHM 创建选项
+     */
+    struct Options {
+        // mlock 映射区域，防止推理过程中被换出。
+        // 受 RLIMIT_MEMLOCK 限制，失败时仅打印警告
+        bool lock_memory = false;
+        // 使用 2MB 大页（仅 Linux，size 需为大页整数倍），不可用时回退到普通页
+        bool huge_pages = false;
+        // 仅供调试：以此固定名称（不含前导 '/'）创建且不立即 unlink，
+        // 可在 /dev/shm 中查看运行中的段，RawShm 析构时才 unlink。
+        // 失去匿名 SHM 的安全性：同用户的任何进程都能按名称打开该段，
+        // 进程崩溃时段会遗留（再次以同名创建会失败）。与 huge_pages 互斥
+        std::string keep_name;
+    };
+
+    static constexpr size_t HUGE_PAGE_SIZE = 2 * 1024 * 1024;
+
     /**
      * 创建指定大小的共享内存段
      * @param size 内存大小（字节）
+     * @param options 创建选项
      * @return RawShm 对象
      * @throws std::runtime_error 如果创建失败
      */
-    static RawShm create(size_t size);
+    static RawShm create(size_t size, const Options& options);
+    static RawShm create(size_t size) { return create(size, Options()); }
+
+private:
+    static RawShm create_posix(size_t size, const std::string& keep_name);
 };
 
 } // namespace anyserve
@@ -8,12 +8,30 @@
 #include <pybind11/stl.h>
 #include <pybind11/functional.h>
 
+#include <cerrno>
+
 #include "../server/anyserve_core.hpp"
 
 namespace py = pybind11;
 
 namespace anyserve {
 
+namespace {
+
+/**
+ * OSError 的 errno 是否表示磁盘写满（ENOSPC）或配额耗尽（EDQUOT）
+ */
+bool is_out_of_space(const py::object& error) {
+    py::object code = error.attr("errno");
+    if (code.is_none()) {
+        return false;
+    }
+    const int value = code.cast<int>();
+    return value == ENOSPC || value == EDQUOT;
+}
+
+} // anonymous namespace
+
 /**
  * Python 兼容的 AnyserveCore 包装器
  * 
@@ -26,8 +44,19 @@ public:
     PyAnyserveCore(const std::string& root_dir,
                    const std::string& instance_id,
                    int port,
-                   py::object dispatcher)
-        : core_(root_dir, instance_id, port), py_dispatcher_(std::move(dispatcher)) {
+                   py::object dispatcher,
+                   const std::string& uds_path,
+                   int max_message_size,
+                   const std::vector<std::string>& auth_tokens,
+                   const std::string& ns,
+                   const std::vector<std::string>& warm_peers,
+                   bool force,
+                   int server_threads,
+                   const std::string& bind_host,
+                   int default_remote_port)
+        : core_(root_dir, instance_id, port, uds_path, max_message_size, auth_tokens, ns, warm_peers, force,
+                nullptr, server_threads, bind_host, default_remote_port),
+          py_dispatcher_(std::move(dispatcher)) {
         
         // 设置 dispatcher 回调
         if (!py_dispatcher_.is_none()) {
@@ -51,6 +80,15 @@ public:
                         return "";
                     }
                 } catch (const py::error_already_set& e) {
+                    // KeyError 表示 capability 不存在，对端据此返回 NOT_FOUND
+                    if (e.matches(PyExc_KeyError)) {
+                        throw CapabilityNotFoundError(std::string("Capability not found: ") + capability);
+                    }
+                    // 存储写满或超出配额（ObjectStoreFullError / ObjectStoreQuotaError
+                    // 带 ENOSPC / EDQUOT）对端返回 RESOURCE_EXHAUSTED
+                    if (e.matches(PyExc_OSError) && is_out_of_space(e.value())) {
+                        throw ResourceExhaustedError(std::string("Python dispatch error: ") + e.what());
+                    }
                     throw std::runtime_error(std::string("Python dispatch error: ") + e.what());
                 }
             });
@@ -84,21 +122,72 @@ public:
         }
         return result;
     }
+
+    py::list lookup_capability_endpoints(const std::string& name) {
+        std::vector<AnyserveCore::Endpoint> endpoints;
+        {
+            py::gil_scoped_release release;
+            endpoints = core_.lookup_capability_endpoints(name);
+        }
+
+        py::list result;
+        for (const auto& ep : endpoints) {
+            py::dict entry;
+            entry["grpc"] = ep.grpc;
+            entry["uds"] = ep.uds;
+            result.append(entry);
+        }
+        return result;
+    }
+    
+    std::optional<std::string> pick_instance(const std::string& name, const std::string& strategy) {
+        py::gil_scoped_release release;
+        return core_.pick_instance(name, strategy);
+    }
+    
+    std::unordered_map<std::string, uint64_t> pick_counts(const std::string& name) {
+        return core_.pick_counts(name);
+    }
     
     py::bytes remote_call(const std::string& address,
                           const std::string& capability,
                           py::bytes args_pickle,
-                          bool is_delegated) {
+                          bool is_delegated,
+                          double timeout_secs,
+                          const std::optional<std::string>& store) {
         std::string args_str = py::cast<std::string>(args_pickle);
         std::string result;
         
         {
             py::gil_scoped_release release;
-            result = core_.remote_call(address, capability, args_str, is_delegated);
+            result = core_.remote_call(address, capability, args_str, is_delegated, timeout_secs,
+                                       store.value_or(""));
         }
         
         return py::bytes(result);
     }
+
+    std::optional<py::bytes> try_remote_call(const std::string& address,
+                                             const std::string& capability,
+                                             py::bytes args_pickle,
+                                             bool is_delegated,
+                                             double timeout_secs,
+                                             const std::optional<std::string>& store) {
+        std::string args_str = py::cast<std::string>(args_pickle);
+        std::string result;
+
+        {
+            py::gil_scoped_release release;
+            try {
+                result = core_.remote_call(address, capability, args_str, is_delegated, timeout_secs,
+                                           store.value_or(""));
+            } catch (const RemoteNotFoundError&) {
+                return std::nullopt;
+            }
+        }
+
+        return py::bytes(result);
+    }
     
     std::string get_address() const {
         return core_.get_address();
@@ -111,10 +200,112 @@ public:
     int port() const {
         return core_.port();
     }
+
+    std::string uds_address() const {
+        return core_.uds_address();
+    }
+
+    int max_message_size() const {
+        return core_.max_message_bytes();
+    }
+
+    int server_threads() const {
+        return core_.server_threads();
+    }
+
+    std::string bind_host() const {
+        return core_.bind_host();
+    }
+
+    std::string get_namespace() const {
+        return core_.get_namespace();
+    }
+
+    std::string objects_dir() const {
+        return core_.objects_dir();
+    }
+
+    void add_store(const std::string& name, const std::string& path) {
+        core_.add_store(name, path);
+    }
+
+    std::string store_dir(const std::string& store) const {
+        return core_.store_dir(store);
+    }
+
+    std::optional<py::bytes> get_object_local(const std::string& object_id,
+                                              const std::optional<std::string>& store) {
+        std::optional<std::string> data;
+        {
+            py::gil_scoped_release release;
+            data = core_.get_object_local(object_id, store.value_or(""));
+        }
+        if (!data) {
+            return std::nullopt;
+        }
+        return py::bytes(*data);
+    }
+
+    std::vector<std::string> warm_capability(const std::string& name, double timeout_secs) {
+        py::gil_scoped_release release;
+        return core_.warm_capability(name, timeout_secs);
+    }
+
+    std::vector<std::string> connected_peers() const {
+        return core_.connected_peers();
+    }
+
+    py::dict shm_stats() const {
+        py::dict result;
+        for (const auto& [segment, stats] : core_.shm_stats()) {
+            py::dict entry;
+            entry["name"] = stats.name;
+            entry["fd"] = stats.fd;
+            entry["size"] = stats.size;
+            entry["locked"] = stats.locked;
+            entry["high_water"] = stats.high_water;
+            entry["wraps"] = stats.wraps;
+            entry["bytes_moved"] = stats.bytes_moved;
+            result[py::str(segment)] = entry;
+        }
+        return result;
+    }
+
+    int default_remote_port() const {
+        return core_.default_remote_port();
+    }
+
+    void set_default_remote_port(int port) {
+        core_.set_default_remote_port(port);
+    }
+
+    void set_circuit_breaker(int failure_threshold, double cooldown_secs) {
+        core_.set_circuit_breaker(failure_threshold, cooldown_secs);
+    }
+
+    std::string compression() const {
+        return core_.compression();
+    }
+
+    void set_compression(const std::string& algorithm) {
+        core_.set_compression(algorithm);
+    }
     
     bool is_running() const {
         return core_.is_running();
     }
+
+    void wait_ready(double timeout_secs) {
+        bool ready;
+        {
+            py::gil_scoped_release release;
+            ready = core_.wait_ready(timeout_secs);
+        }
+        if (!ready) {
+            PyErr_SetString(PyExc_TimeoutError, "gRPC server not ready within timeout");
+            throw py::error_already_set();
+        }
+    }
     
     void stop() {
         py::gil_scoped_release release;
@@ -130,21 +321,93 @@ private:
 
 PYBIND11_MODULE(_core, m) {
     m.doc() = "AnyServe C++ Core - Capability-Oriented Serving Runtime";
+
+    // 远程调用异常层次：AnyserveError 为基类，子类同时继承对应的内置异常，
+    // 以兼容按 TimeoutError / ConnectionError / KeyError 捕获的旧代码。
+    // 异常类型对象随进程常驻，有意不释放引用。
+    auto new_exception = [&m](const char* name, PyObject* bases) {
+        PyObject* type = PyErr_NewException(
+            (std::string("anyserve._core.") + name).c_str(), bases, nullptr);
+        if (!type) {
+            throw py::error_already_set();
+        }
+        m.attr(name) = py::handle(type);
+        return type;
+    };
+    auto with_builtin = [](PyObject* base, PyObject* builtin) {
+        return py::make_tuple(py::handle(base), py::handle(builtin));
+    };
+    static PyObject* anyserve_error = new_exception("AnyserveError", PyExc_RuntimeError);
+    static PyObject* not_found_error = new_exception(
+        "ObjectNotFoundError", with_builtin(anyserve_error, PyExc_KeyError).ptr());
+    static PyObject* unreachable_error = new_exception(
+        "PeerUnreachableError", with_builtin(anyserve_error, PyExc_ConnectionError).ptr());
+    static PyObject* unavailable_error = new_exception("PeerUnavailableError", unreachable_error);
+    static PyObject* timeout_error = new_exception(
+        "RemoteTimeoutError", with_builtin(anyserve_error, PyExc_TimeoutError).ptr());
+    static PyObject* transport_error = new_exception("TransportError", anyserve_error);
+    static PyObject* duplicate_instance_error = new_exception("DuplicateInstanceError", PyExc_RuntimeError);
+
+    py::register_exception_translator([](std::exception_ptr p) {
+        try {
+            if (p) std::rethrow_exception(p);
+        } catch (const anyserve::RemoteNotFoundError& e) {
+            PyErr_SetString(not_found_error, e.what());
+        } catch (const anyserve::PeerUnavailableError& e) {
+            PyErr_SetString(unavailable_error, e.what());
+        } catch (const anyserve::RemoteUnreachableError& e) {
+            PyErr_SetString(unreachable_error, e.what());
+        } catch (const anyserve::RemoteTimeoutError& e) {
+            PyErr_SetString(timeout_error, e.what());
+        } catch (const anyserve::RemoteTransportError& e) {
+            PyErr_SetString(transport_error, e.what());
+        } catch (const anyserve::RemoteError& e) {
+            PyErr_SetString(anyserve_error, e.what());
+        } catch (const anyserve::DuplicateInstanceError& e) {
+            PyErr_SetString(duplicate_instance_error, e.what());
+        }
+    });
     
     py::class_<anyserve::PyAnyserveCore>(m, "AnyserveCore")
-        .def(py::init<const std::string&, const std::string&, int, py::object>(),
+        .def(py::init<const std::string&, const std::string&, int, py::object, const std::string&, int,
+                      const std::vector<std::string>&, const std::string&,
+                      const std::vector<std::string>&, bool, int, const std::string&, int>(),
              py::arg("root_dir"),
              py::arg("instance_id"),
              py::arg("port"),
              py::arg("dispatcher"),
+             py::arg("uds_path") = "",
+             py::arg("max_message_size") = anyserve::AnyserveCore::DEFAULT_MAX_MESSAGE_BYTES,
+             py::arg("auth_tokens") = std::vector<std::string>{},
+             py::arg("namespace") = "",
+             py::arg("warm_peers") = std::vector<std::string>{},
+             py::arg("force") = false,
+             py::arg("server_threads") = 0,
+             py::arg("bind_host") = anyserve::AnyserveCore::DEFAULT_BIND_HOST,
+             py::arg("default_remote_port") = anyserve::AnyserveCore::DEFAULT_REMOTE_PORT,
              R"doc(
              创建 AnyserveCore 实例
              
              Args:
                  root_dir: 根目录（用于存储状态、服务发现）
                  instance_id: 实例唯一标识
-                 port: gRPC 服务端口（0 = 随机分配）
+                 port: gRPC 服务端口（0 = 由系统分配，启动后 port/get_address 返回实际端口）
                  dispatcher: Python dispatcher 对象，需要有 dispatch(capability, args_pickle, is_delegated) 方法
+                 uds_path: 额外监听的 Unix Domain Socket 路径（可选），同机 peer 用 "unix:<path>" 访问
+                 max_message_size: 单条 gRPC 消息的最大字节数，服务端和 remote_call 共用（默认 64MB）
+                 auth_tokens: 接受的 bearer token 列表（默认为空，不鉴权）；remote_call 携带第一个 token
+                 namespace: 租户命名空间（默认为空）；非空时注册表和实例目录位于 root_dir/ns/<namespace>/
+                 warm_peers: 启动后在后台预先建连的 peer 地址列表；建连失败只记录日志，首次调用时重连
+                 force: instance_id 已被另一个存活的实例登记时仍然启动并接管登记（默认 False，
+                        此时抛出 DuplicateInstanceError）
+                 server_threads: gRPC 服务器最多使用的线程数（默认 0 = gRPC 默认，按 CPU 核数）；
+                                 同一进程内运行大量实例时用于限制线程数，至少为 2，
+                                 并发请求超过 server_threads - 1 时多出的请求被拒绝
+                 bind_host: gRPC 服务器监听的地址（默认 "0.0.0.0"，仅 IPv4）；"::" 为双栈，
+                            仅 IPv6 的主机需要它。监听所有地址时 get_address 返回本机回环
+                            （localhost / [::1]），否则返回 bind_host 本身
+                 default_remote_port: 地址只有 host 时使用的端口（默认 8000），warm_peers 也按它规范化；
+                                      不在 1-65535 内抛出 ValueError
              )doc")
         .def("register_capability", &anyserve::PyAnyserveCore::register_capability,
              py::arg("name"),
@@ -152,22 +415,104 @@ PYBIND11_MODULE(_core, m) {
         .def("lookup_capability", &anyserve::PyAnyserveCore::lookup_capability,
              py::arg("name"),
              "查找提供指定 capability 的端点列表")
+        .def("lookup_capability_endpoints", &anyserve::PyAnyserveCore::lookup_capability_endpoints,
+             py::arg("name"),
+             "查找提供指定 capability 的实例，每项为 {'grpc': host:port, 'uds': unix:<path> 或 ''}")
+        .def("pick_instance", &anyserve::PyAnyserveCore::pick_instance,
+             py::arg("name"),
+             py::arg("strategy") = "round_robin",
+             "选择一个提供指定 capability 的端点（strategy: random / round_robin / least_recent；无端点时返回 None）")
+        .def("pick_counts", &anyserve::PyAnyserveCore::pick_counts,
+             py::arg("name"),
+             "pick_instance 为指定 capability 选中各端点的次数，{address: count}；"
+             "已从注册表下线的端点在下一次 pick_instance 时被移除")
         .def("remote_call", &anyserve::PyAnyserveCore::remote_call,
              py::arg("address"),
              py::arg("capability"),
              py::arg("args_pickle"),
              py::arg("is_delegated"),
-             "远程调用指定地址的 capability")
+             py::arg("timeout_secs") = anyserve::AnyserveCore::DEFAULT_REMOTE_TIMEOUT_SECS,
+             py::arg("store") = py::none(),
+             "远程调用指定地址的 capability（address 格式不合法时抛出 ValueError；调用失败抛出 AnyserveError 子类："
+             "ObjectNotFoundError / PeerUnreachableError / RemoteTimeoutError / TransportError；"
+             "断路器打开时为 PeerUnreachableError 的子类 PeerUnavailableError）；"
+             "store 指定对端 add_store 注册的存储，对端以请求参数 store_dir 把其根目录交给 dispatcher")
+        .def("try_remote_call", &anyserve::PyAnyserveCore::try_remote_call,
+             py::arg("address"),
+             py::arg("capability"),
+             py::arg("args_pickle"),
+             py::arg("is_delegated"),
+             py::arg("timeout_secs") = anyserve::AnyserveCore::DEFAULT_REMOTE_TIMEOUT_SECS,
+             py::arg("store") = py::none(),
+             "同 remote_call，但对端返回 NOT_FOUND 时返回 None 而不是抛出 ObjectNotFoundError；"
+             "其他失败照常抛出")
+        .def("set_circuit_breaker", &anyserve::PyAnyserveCore::set_circuit_breaker,
+             py::arg("failure_threshold") = anyserve::AnyserveCore::DEFAULT_BREAKER_FAILURES,
+             py::arg("cooldown_secs") = anyserve::AnyserveCore::DEFAULT_BREAKER_COOLDOWN_SECS,
+             "配置 remote_call 的按地址断路器：连续 failure_threshold 次不可达或超时后，"
+             "cooldown_secs 内对该地址的调用直接抛出 PeerUnavailableError，之后放行一次探测调用；"
+             "failure_threshold=0 关闭断路器")
         .def("get_address", &anyserve::PyAnyserveCore::get_address,
              "获取本实例的地址")
         .def_property_readonly("instance_id", &anyserve::PyAnyserveCore::instance_id,
              "实例 ID")
         .def_property_readonly("port", &anyserve::PyAnyserveCore::port,
              "gRPC 服务端口")
+        .def_property_readonly("uds_address", &anyserve::PyAnyserveCore::uds_address,
+             "UDS 地址（unix:<path>），未启用时为空字符串")
+        .def_property_readonly("max_message_size", &anyserve::PyAnyserveCore::max_message_size,
+             "单条 gRPC 消息的最大字节数")
+        .def_property_readonly("server_threads", &anyserve::PyAnyserveCore::server_threads,
+             "gRPC 服务器的线程上限（0 = gRPC 默认）")
+        .def_property_readonly("bind_host", &anyserve::PyAnyserveCore::bind_host,
+             "gRPC 服务器监听的 host（IPv6 带方括号，如 \"[::]\"）")
+        .def_property_readonly("namespace", &anyserve::PyAnyserveCore::get_namespace,
+             "租户命名空间，未设置时为空字符串")
+        .def_property_readonly("objects_dir", &anyserve::PyAnyserveCore::objects_dir,
+             "本实例对象存储的目录（位于命名空间内），用于构造 ObjectStore")
+        .def("add_store", &anyserve::PyAnyserveCore::add_store,
+             py::arg("name"), py::arg("path"),
+             "注册具名的对象存储根目录（如按租户划分的卷），对象方法可通过 store=name 选择；"
+             "名称已注册为其他路径时抛出 ValueError")
+        .def("store_dir", &anyserve::PyAnyserveCore::store_dir,
+             py::arg("store"),
+             "add_store 注册的存储目录（空字符串为 objects_dir），用于构造 ObjectStore；"
+             "未注册时抛出 ValueError")
+        .def("get_object_local", &anyserve::PyAnyserveCore::get_object_local,
+             py::arg("object_id"), py::arg("store") = py::none(),
+             "不经过网络直接读取本实例对象目录中的对象（服务未运行时也可用），返回原始字节；"
+             "object_id 为对象文件名或不带扩展名的 key，不存在时返回 None，"
+             "object_id 含路径分隔符或以 '.' 开头时抛出 ValueError；"
+             "store 指定时读取 add_store 注册的存储，未注册时抛出 ValueError")
+        .def("warm_capability", &anyserve::PyAnyserveCore::warm_capability,
+             py::arg("name"),
+             py::arg("timeout_secs") = anyserve::AnyserveCore::DEFAULT_WARM_TIMEOUT_SECS,
+             "查找提供指定 capability 的端点并预先建连，返回 timeout_secs 内连上的端点"
+             "（连接留在连接池中供 remote_call 复用；连不上的被剔除）")
+        .def("connected_peers", &anyserve::PyAnyserveCore::connected_peers,
+             "连接池中已建立连接的 peer 地址（规范化形式，如 127.0.0.1:8000）")
+        .def("shm_stats", &anyserve::PyAnyserveCore::shm_stats,
+             "各 SHM 段的状态：{\"h2d\"|\"d2h\": {name, fd, size, locked, high_water, wraps, bytes_moved}}")
+        .def_property("compression",
+             &anyserve::PyAnyserveCore::compression,
+             &anyserve::PyAnyserveCore::set_compression,
+             "gRPC 传输层压缩：\"none\"（默认）、\"gzip\" 或 \"deflate\"。作用于 remote_call 的请求和"
+             "本实例返回的响应（对端支持时）；收到的压缩消息总是可以解压。其他取值抛出 ValueError")
+        .def_property("default_remote_port",
+             &anyserve::PyAnyserveCore::default_remote_port,
+             &anyserve::PyAnyserveCore::set_default_remote_port,
+             "remote_call 地址未指定端口时使用的默认端口；不在 1-65535 内抛出 ValueError。"
+             "构造时已规范化的 warm_peers 不受之后的修改影响")
         .def_property_readonly("is_running", &anyserve::PyAnyserveCore::is_running,
              "是否正在运行")
+        .def("wait_ready", &anyserve::PyAnyserveCore::wait_ready,
+             py::arg("timeout_secs") = 5.0,
+             "阻塞直到本实例的 gRPC 服务器可以接受连接；超时抛出 TimeoutError，未运行时抛出 RuntimeError")
         .def("stop", &anyserve::PyAnyserveCore::stop,
-             "停止服务");
+             "停止服务：注销实例、关闭 gRPC 服务器并等待服务线程退出，释放端口")
+        .def("__enter__", [](py::object self) { return self; })
+        .def("__exit__", [](anyserve::PyAnyserveCore& self, py::args) { self.stop(); },
+             "退出 with 语句块时调用 stop()");
     
     m.attr("__version__") = "0.1.0";
 }
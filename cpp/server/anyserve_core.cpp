@@ -1,10 +1,17 @@
 #include "anyserve_core.hpp"
 
 #include <iostream>
-#include <fstream>
+#include <algorithm>
 #include <filesystem>
+#include <fstream>
+#include <iterator>
 #include <random>
 #include <chrono>
+#include <cerrno>
+#include <cstring>
+#include <cstdlib>
+#include <stdexcept>
+#include <unistd.h>
 
 #include <grpcpp/grpcpp.h>
 #include "grpc_predict_v2.grpc.pb.h"
@@ -13,6 +20,305 @@ namespace fs = std::filesystem;
 
 namespace anyserve {
 
+namespace {
+
+/**
+ * 校验默认远程端口
+ * @throws std::invalid_argument port 不在 1-65535 内（Python 侧为 ValueError）
+ */
+int checked_remote_port(int port) {
+    if (port < 1 || port > 65535) {
+        throw std::invalid_argument("default_remote_port must be in 1-65535, got " + std::to_string(port));
+    }
+    return port;
+}
+
+/**
+ * 规范化远程地址
+ *
+ * 支持 "host:port"、"host"（使用 default_port）、"[v6]:port"，以及可选的
+ * "http://" 前缀。localhost 解析为 127.0.0.1（服务端默认只监听 IPv4）。
+ * "unix:/path" 或 "unix:///path" 规范化为 "unix:/path"。
+ * @throws std::invalid_argument 地址格式不合法（Python 侧为 ValueError）
+ */
+std::string normalize_address(const std::string& address, int default_port) {
+    const std::string expected =
+        "expected 'host:port' or 'host' (e.g. '10.0.0.1:8000'), got '" + address + "'";
+
+    if (address.rfind("unix:", 0) == 0) {
+        std::string path = address.substr(5);
+        if (path.rfind("//", 0) == 0) {
+            path = path.substr(2);
+        }
+        if (path.empty() || path.front() != '/') {
+            throw std::invalid_argument(
+                "Invalid address: expected 'unix:/absolute/path', got '" + address + "'");
+        }
+        return "unix:" + path;
+    }
+
+    std::string addr = address;
+    for (const char* scheme : {"http://", "grpc://"}) {
+        if (addr.rfind(scheme, 0) == 0) {
+            addr = addr.substr(std::strlen(scheme));
+        }
+    }
+    if (!addr.empty() && addr.back() == '/') {
+        addr.pop_back();
+    }
+    if (addr.empty() || addr.find_first_of(" /") != std::string::npos) {
+        throw std::invalid_argument("Invalid address: " + expected);
+    }
+
+    std::string host;
+    std::string port;
+    if (addr.front() == '[') {
+        // IPv6 字面量: [::1] 或 [::1]:port
+        auto close = addr.find(']');
+        if (close == std::string::npos) {
+            throw std::invalid_argument("Invalid address: " + expected);
+        }
+        host = addr.substr(0, close + 1);
+        if (close + 1 < addr.size()) {
+            if (addr[close + 1] != ':') {
+                throw std::invalid_argument("Invalid address: " + expected);
+            }
+            port = addr.substr(close + 2);
+        }
+    } else {
+        auto colon = addr.find(':');
+        if (colon != addr.rfind(':')) {
+            throw std::invalid_argument(
+                "Invalid address: IPv6 hosts must be bracketed, " + expected);
+        }
+        host = addr.substr(0, colon);
+        if (colon != std::string::npos) {
+            port = addr.substr(colon + 1);
+        }
+    }
+
+    if (host.empty()) {
+        throw std::invalid_argument("Invalid address: " + expected);
+    }
+    if (host == "localhost") {
+        host = "127.0.0.1";
+    }
+
+    int port_num = default_port;
+    if (!port.empty()) {
+        if (port.find_first_not_of("0123456789") != std::string::npos || port.size() > 5) {
+            throw std::invalid_argument("Invalid port in address: " + expected);
+        }
+        port_num = std::stoi(port);
+    }
+    if (port_num <= 0 || port_num > 65535) {
+        throw std::invalid_argument("Port out of range in address: " + expected);
+    }
+
+    return host + ":" + std::to_string(port_num);
+}
+
+/**
+ * 规范化监听 host：IPv6 字面量加上方括号（"::" -> "[::]"），IPv4 地址和主机名原样返回
+ * @throws std::invalid_argument host 为空或方括号不配对
+ */
+std::string bracket_host(const std::string& host) {
+    if (host.empty()) {
+        throw std::invalid_argument("bind_host must not be empty");
+    }
+    if (host.front() == '[') {
+        if (host.size() < 3 || host.back() != ']' || host.find(']') != host.size() - 1) {
+            throw std::invalid_argument("Invalid bind_host: '" + host + "'");
+        }
+        return host;
+    }
+    if (host.find_first_of("[]/ ") != std::string::npos) {
+        throw std::invalid_argument("Invalid bind_host: '" + host + "'");
+    }
+    return host.find(':') != std::string::npos ? "[" + host + "]" : host;
+}
+
+/**
+ * 登记给 peer 的 host：监听所有地址时用本机回环（仅 IPv6 的主机上没有 127.0.0.1，
+ * 双栈监听时登记 [::1]），否则就是监听的地址
+ */
+std::string advertised_host(const std::string& bind_host) {
+    if (bind_host == "0.0.0.0") {
+        return "localhost";
+    }
+    if (bind_host == "[::]") {
+        return "[::1]";
+    }
+    return bind_host;
+}
+
+/**
+ * 常量时间比较，避免通过响应时间逐字节猜测 token
+ */
+bool constant_time_equals(const std::string& a, const std::string& b) {
+    if (a.size() != b.size()) {
+        return false;
+    }
+    unsigned char diff = 0;
+    for (size_t i = 0; i < a.size(); ++i) {
+        diff |= static_cast<unsigned char>(a[i] ^ b[i]);
+    }
+    return diff == 0;
+}
+
+constexpr const char* BEARER_PREFIX = "Bearer ";
+
+/**
+ * set_compression 接受的算法名称
+ */
+constexpr std::pair<const char*, grpc_compression_algorithm> COMPRESSION_ALGORITHMS[] = {
+    {"none", GRPC_COMPRESS_NONE},
+    {"gzip", GRPC_COMPRESS_GZIP},
+    {"deflate", GRPC_COMPRESS_DEFLATE},
+};
+
+/**
+ * 校验 capability 名称：名称直接作为 names/ 下的目录名，
+ * 不能为空、不能含路径分隔符或控制字符、不能以 '.' 开头（含 "." 和 ".."，
+ * 隐藏名称留给临时文件），且不超过文件名长度上限
+ * @throws std::invalid_argument 名称不合法（Python 侧为 ValueError）
+ */
+void validate_capability_name(const std::string& name) {
+    constexpr size_t MAX_NAME_LENGTH = 255;
+    const bool bad_char = std::any_of(name.begin(), name.end(), [](unsigned char c) {
+        return c == '/' || c == '\\' || c < 0x20 || c == 0x7f;
+    });
+    if (name.empty() || name[0] == '.' || bad_char || name.size() > MAX_NAME_LENGTH) {
+        throw std::invalid_argument("Invalid capability name: '" + name + "'");
+    }
+}
+
+/**
+ * 从环境变量读取八进制权限（如 "0750"），未设置时返回 fallback
+ * @throws std::invalid_argument 取值不是 0-0777 的八进制数
+ */
+unsigned int mode_from_env(const char* name, unsigned int fallback) {
+    const char* value = std::getenv(name);
+    if (!value || !*value) {
+        return fallback;
+    }
+    char* end = nullptr;
+    unsigned long mode = std::strtoul(value, &end, 8);
+    if (*end != '\0' || mode > 0777) {
+        throw std::invalid_argument(std::string(name) + " must be an octal mode such as 0700, got '" +
+                                    value + "'");
+    }
+    return static_cast<unsigned int>(mode);
+}
+
+/**
+ * 对象 key 所在的分片目录名：md5(key) 的前两位十六进制，与 Python 端
+ * ObjectStore._shard_of（hashlib.md5(key).hexdigest()[:2]）一致
+ *
+ * 只为一个目录名不值得引入 OpenSSL，这里按 RFC 1321 直接实现 md5
+ */
+std::string shard_of(const std::string& key) {
+    static const uint32_t K[64] = {
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee,
+        0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+        0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be,
+        0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+        0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa,
+        0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+        0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+        0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+        0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c,
+        0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+        0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05,
+        0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+        0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039,
+        0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1,
+        0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+    };
+    static const int SHIFTS[4][4] = {{7, 12, 17, 22}, {5, 9, 14, 20}, {4, 11, 16, 23}, {6, 10, 15, 21}};
+
+    // 填充：0x80，补零到 56 (mod 64) 字节，再追加小端的位长度
+    std::string msg = key;
+    const uint64_t bit_len = static_cast<uint64_t>(key.size()) * 8;
+    msg.push_back(static_cast<char>(0x80));
+    while (msg.size() % 64 != 56) {
+        msg.push_back('\0');
+    }
+    for (int i = 0; i < 8; ++i) {
+        msg.push_back(static_cast<char>((bit_len >> (8 * i)) & 0xff));
+    }
+
+    uint32_t state[4] = {0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476};
+    for (size_t block = 0; block < msg.size(); block += 64) {
+        uint32_t m[16];
+        for (int i = 0; i < 16; ++i) {
+            const auto* p = reinterpret_cast<const unsigned char*>(msg.data() + block + 4 * i);
+            m[i] = p[0] | (p[1] << 8) | (p[2] << 16) | (static_cast<uint32_t>(p[3]) << 24);
+        }
+        uint32_t a = state[0], b = state[1], c = state[2], d = state[3];
+        for (int i = 0; i < 64; ++i) {
+            uint32_t f;
+            int g;
+            if (i < 16) {
+                f = (b & c) | (~b & d);
+                g = i;
+            } else if (i < 32) {
+                f = (d & b) | (~d & c);
+                g = (5 * i + 1) % 16;
+            } else if (i < 48) {
+                f = b ^ c ^ d;
+                g = (3 * i + 5) % 16;
+            } else {
+                f = c ^ (b | ~d);
+                g = (7 * i) % 16;
+            }
+            f += a + K[i] + m[g];
+            const int s = SHIFTS[i / 16][i % 4];
+            a = d;
+            d = c;
+            c = b;
+            b += (f << s) | (f >> (32 - s));
+        }
+        state[0] += a;
+        state[1] += b;
+        state[2] += c;
+        state[3] += d;
+    }
+
+    // 摘要的第一个字节是 state[0] 的最低字节
+    static const char HEX[] = "0123456789abcdef";
+    const uint8_t first = state[0] & 0xff;
+    return std::string{HEX[first >> 4], HEX[first & 0xf]};
+}
+
+/**
+ * 按请求的 "store" 参数选择对象存储
+ *
+ * 把 add_store 注册的根目录作为 "store_dir" 参数交给 dispatcher，Python 侧据此
+ * 构造 ObjectStore；调用方自带的 "store_dir" 一律丢弃，不能借此指定任意目录。
+ * @return 需要改写时为改写后的请求，否则为 std::nullopt（原样转交）
+ * @throws std::invalid_argument store 未注册
+ */
+std::optional<inference::ModelInferRequest> resolve_store(const AnyserveCore& core,
+                                                          const inference::ModelInferRequest& request) {
+    const auto& params = request.parameters();
+    auto it = params.find("store");
+    if (it == params.end() && params.find("store_dir") == params.end()) {
+        return std::nullopt;
+    }
+
+    inference::ModelInferRequest resolved = request;
+    auto* mutable_params = resolved.mutable_parameters();
+    mutable_params->erase("store_dir");
+    if (it != params.end()) {
+        (*mutable_params)["store_dir"].set_string_param(core.store_dir(it->second.string_param()));
+    }
+    return resolved;
+}
+
+} // anonymous namespace
+
 // ============================================================================
 // gRPC Service Implementation (Async)
 // ============================================================================
@@ -21,6 +327,25 @@ class GrpcServiceImpl final : public inference::GRPCInferenceService::Service {
 public:
     explicit GrpcServiceImpl(AnyserveCore* core) : core_(core) {}
 
+    /**
+     * 鉴权检查（ServerLive/ServerReady 不检查，供健康探针使用）
+     * @return 未通过时为 UNAUTHENTICATED，否则为 OK
+     */
+    grpc::Status authenticate(grpc::ServerContext* context) const {
+        std::string authorization;
+        for (const auto& [key, value] : context->client_metadata()) {
+            if (std::string(key.data(), key.size()) == "authorization") {
+                authorization.assign(value.data(), value.size());
+                break;
+            }
+        }
+        if (!core_->is_authorized(authorization)) {
+            return grpc::Status(grpc::StatusCode::UNAUTHENTICATED,
+                                "Missing or invalid bearer token");
+        }
+        return grpc::Status::OK;
+    }
+
     grpc::Status ServerLive(
         grpc::ServerContext* context,
         const inference::ServerLiveRequest* request,
@@ -41,6 +366,9 @@ public:
         grpc::ServerContext* context,
         const inference::ModelReadyRequest* request,
         inference::ModelReadyResponse* response) override {
+        if (auto status = authenticate(context); !status.ok()) {
+            return status;
+        }
         response->set_ready(true);
         return grpc::Status::OK;
     }
@@ -49,6 +377,9 @@ public:
         grpc::ServerContext* context,
         const inference::ServerMetadataRequest* request,
         inference::ServerMetadataResponse* response) override {
+        if (auto status = authenticate(context); !status.ok()) {
+            return status;
+        }
         response->set_name("anyserve");
         response->set_version("0.1.0");
         return grpc::Status::OK;
@@ -58,6 +389,9 @@ public:
         grpc::ServerContext* context,
         const inference::ModelMetadataRequest* request,
         inference::ModelMetadataResponse* response) override {
+        if (auto status = authenticate(context); !status.ok()) {
+            return status;
+        }
         response->set_name(request->name());
         response->set_platform("anyserve");
         return grpc::Status::OK;
@@ -68,9 +402,27 @@ public:
         const inference::ModelInferRequest* request,
         inference::ModelInferResponse* response) override {
 
+        if (auto status = authenticate(context); !status.ok()) {
+            return status;
+        }
+        if (int algorithm = core_->compression_algorithm(); algorithm != GRPC_COMPRESS_NONE) {
+            context->set_compression_algorithm(static_cast<grpc_compression_algorithm>(algorithm));
+        }
+
         // KServe v2 协议：model_name 作为 capability
         std::string capability = request->model_name();
 
+        // 请求指定了 store 时解析为该存储的根目录，未注册的 store 直接拒绝
+        std::optional<inference::ModelInferRequest> resolved;
+        try {
+            resolved = resolve_store(*core_, *request);
+        } catch (const std::invalid_argument& e) {
+            return grpc::Status(grpc::StatusCode::INVALID_ARGUMENT, e.what());
+        }
+        if (resolved) {
+            request = &*resolved;
+        }
+
         // 将整个 ModelInferRequest 序列化为 protobuf bytes
         // 传递给 Python dispatcher（当前使用 pickle，未来改为 protobuf）
         std::string request_bytes;
@@ -107,6 +459,10 @@ public:
 
             return grpc::Status::OK;
 
+        } catch (const CapabilityNotFoundError& e) {
+            return grpc::Status(grpc::StatusCode::NOT_FOUND, e.what());
+        } catch (const ResourceExhaustedError& e) {
+            return grpc::Status(grpc::StatusCode::RESOURCE_EXHAUSTED, e.what());
         } catch (const std::exception& e) {
             return grpc::Status(grpc::StatusCode::INTERNAL, e.what());
         }
@@ -122,28 +478,74 @@ private:
 
 AnyserveCore::AnyserveCore(const std::string& root_dir,
                            const std::string& instance_id,
-                           int port)
-    : root_dir_(root_dir), instance_id_(instance_id), port_(port) {
+                           int port,
+                           const std::string& uds_path,
+                           int max_message_bytes,
+                           const std::vector<std::string>& auth_tokens,
+                           const std::string& ns,
+                           const std::vector<std::string>& warm_peers,
+                           bool force,
+                           std::shared_ptr<CapabilityRegistry> registry,
+                           int server_threads,
+                           const std::string& bind_host,
+                           int default_remote_port)
+    : root_dir_(root_dir), namespace_(ns), scope_dir_(ns.empty() ? root_dir : root_dir + "/ns/" + ns),
+      instance_id_(instance_id), port_(port), uds_path_(uds_path),
+      max_message_bytes_(max_message_bytes), server_threads_(server_threads),
+      bind_host_(bracket_host(bind_host)), auth_tokens_(auth_tokens),
+      default_remote_port_(checked_remote_port(default_remote_port)) {
     
-    // 如果端口为 0，随机选择一个
-    if (port_ == 0) {
-        std::random_device rd;
-        std::mt19937 gen(rd());
-        std::uniform_int_distribution<> dis(10000, 20000);
-        port_ = dis(gen);
+    if (!ns.empty() && (ns[0] == '.' || ns.find('/') != std::string::npos ||
+                        ns.find('\0') != std::string::npos)) {
+        throw std::invalid_argument("Invalid namespace: '" + ns + "'");
+    }
+    for (const auto& peer : warm_peers) {
+        warm_peers_.push_back(normalize_address(peer, default_remote_port_.load()));
+    }
+    if (max_message_bytes_ <= 0) {
+        throw std::invalid_argument("max_message_bytes must be positive");
+    }
+    if (server_threads_ < 0 || server_threads_ == 1) {
+        throw std::invalid_argument("server_threads must be 0 (gRPC default) or at least 2");
+    }
+    for (const auto& token : auth_tokens_) {
+        if (token.empty()) {
+            throw std::invalid_argument("auth_tokens must not contain empty tokens");
+        }
     }
     
-    address_ = "localhost:" + std::to_string(port_);
+    // 端口为 0 时由系统分配，start() 绑定后再更新 port_ 和 address_
+    address_ = advertised_host(bind_host_) + ":" + std::to_string(port_);
     
-    // 确保目录存在
-    fs::create_directories(root_dir_);
-    fs::create_directories(root_dir_ + "/instances");
-    fs::create_directories(root_dir_ + "/names");
+    // 确保目录存在（默认 0700 目录 / 0600 文件，可用 ANYSERVE_DIR_MODE / ANYSERVE_FILE_MODE 覆盖）
+    dir_mode_ = mode_from_env("ANYSERVE_DIR_MODE", DEFAULT_DIR_MODE);
+    file_mode_ = mode_from_env("ANYSERVE_FILE_MODE", DEFAULT_FILE_MODE);
+    create_private_directories(root_dir_, dir_mode_);
+    registry_ = registry ? std::move(registry)
+                         : std::make_shared<FsCapabilityRegistry>(scope_dir_, dir_mode_, file_mode_);
+
+    // 两个进程误用同一 instance_id 时会互相覆盖登记，请求被路由到错误的端口
+    std::string owner = registry_->live_owner(instance_id_);
+    if (!owner.empty()) {
+        if (!force) {
+            throw DuplicateInstanceError("Instance '" + instance_id_ + "' is already registered by " +
+                                         owner + "; use force to take over the registration");
+        }
+        std::cerr << "[AnyserveCore] Warning: taking over instance '" << instance_id_
+                  << "' from " << owner << std::endl;
+    }
     
-    // 创建 SHM
+    // 创建 SHM（ANYSERVE_SHM_MLOCK=1 锁定内存，ANYSERVE_SHM_HUGEPAGES=1 使用大页）
+    auto env_enabled = [](const char* name) {
+        const char* value = std::getenv(name);
+        return value && std::string(value) == "1";
+    };
+    ShmManager::Options shm_options;
+    shm_options.lock_memory = env_enabled("ANYSERVE_SHM_MLOCK");
+    shm_options.huge_pages = env_enabled("ANYSERVE_SHM_HUGEPAGES");
     try {
-        shm_h2d_ = ShmManager::create(SHM_SIZE);
-        shm_d2h_ = ShmManager::create(SHM_SIZE);
+        shm_h2d_ = ShmManager::create(SHM_SIZE, shm_options);
+        shm_d2h_ = ShmManager::create(SHM_SIZE, shm_options);
         std::cout << "[AnyserveCore] Created SHM. H2D_FD=" << shm_h2d_.fd 
                   << ", D2H_FD=" << shm_d2h_.fd << std::endl;
     } catch (const std::exception& e) {
@@ -164,52 +566,109 @@ void AnyserveCore::set_dispatcher(DispatcherCallback callback) {
 }
 
 void AnyserveCore::register_capability(const std::string& name) {
+    validate_capability_name(name);
     {
         std::lock_guard<std::mutex> lock(capabilities_mutex_);
         local_capabilities_.insert(name);
     }
     
-    // 注册到调度器（文件系统方式）
-    std::string cap_dir = root_dir_ + "/names/" + name;
-    fs::create_directories(cap_dir);
-    
-    std::string instance_file = cap_dir + "/" + instance_id_;
-    std::ofstream ofs(instance_file);
-    ofs << address_;
-    ofs.close();
+    registry_->register_instance(name, instance_id_, local_endpoint());
     
     std::cout << "[AnyserveCore] Registered capability: " << name << std::endl;
 }
 
+Endpoint AnyserveCore::local_endpoint() const {
+    Endpoint endpoint;
+    endpoint.grpc = address_;
+    if (!uds_path_.empty()) {
+        endpoint.uds = uds_address();
+    }
+    return endpoint;
+}
+
 std::vector<std::string> AnyserveCore::lookup_capability(const std::string& name) {
-    std::vector<std::string> endpoints;
-    
-    std::string cap_dir = root_dir_ + "/names/" + name;
-    if (!fs::exists(cap_dir)) {
-        return endpoints;
+    std::vector<std::string> addresses;
+    for (auto& endpoint : lookup_capability_endpoints(name)) {
+        addresses.push_back(std::move(endpoint.grpc));
     }
-    
-    for (const auto& entry : fs::directory_iterator(cap_dir)) {
-        if (entry.is_regular_file()) {
-            std::ifstream ifs(entry.path());
-            std::string address;
-            std::getline(ifs, address);
-            if (!address.empty()) {
-                endpoints.push_back(address);
+    return addresses;
+}
+
+std::vector<Endpoint> AnyserveCore::lookup_capability_endpoints(const std::string& name) {
+    validate_capability_name(name);
+    return registry_->lookup(name);
+}
+
+std::optional<std::string> AnyserveCore::pick_instance(const std::string& name,
+                                                       const std::string& strategy) {
+    if (strategy != "random" && strategy != "round_robin" && strategy != "least_recent") {
+        throw std::invalid_argument("Unknown strategy: " + strategy +
+                                    " (expected random, round_robin or least_recent)");
+    }
+
+    auto endpoints = lookup_capability(name);
+    // 目录遍历顺序不固定，排序后轮询才有意义
+    std::sort(endpoints.begin(), endpoints.end());
+
+    std::lock_guard<std::mutex> lock(pick_mutex_);
+    // 已下线的端点不再保留选择记录和计数，否则实例不断更替（如每次重启换端口）时无限增长
+    auto& seen = last_picked_[name];
+    auto& counts = pick_counts_[name];
+    for (auto* records : {&seen, &counts}) {
+        for (auto it = records->begin(); it != records->end();) {
+            if (std::binary_search(endpoints.begin(), endpoints.end(), it->first)) {
+                ++it;
+            } else {
+                it = records->erase(it);
             }
         }
     }
-    
-    return endpoints;
+    if (endpoints.empty()) {
+        last_picked_.erase(name);
+        pick_counts_.erase(name);
+        return std::nullopt;
+    }
+
+    std::string picked;
+    if (strategy == "random") {
+        static thread_local std::mt19937 gen(std::random_device{}());
+        std::uniform_int_distribution<size_t> dis(0, endpoints.size() - 1);
+        picked = endpoints[dis(gen)];
+    } else if (strategy == "round_robin") {
+        size_t& next = round_robin_next_[name];
+        picked = endpoints[next % endpoints.size()];
+        next = (next + 1) % endpoints.size();
+    } else {
+        // 从未选过的端点序号为 0，优先被选中；同序号取字典序最小
+        picked = *std::min_element(endpoints.begin(), endpoints.end(),
+            [&seen](const std::string& a, const std::string& b) {
+                auto ia = seen.find(a);
+                auto ib = seen.find(b);
+                return (ia == seen.end() ? 0 : ia->second) < (ib == seen.end() ? 0 : ib->second);
+            });
+    }
+    seen[picked] = ++pick_sequence_;
+    ++counts[picked];
+    return picked;
+}
+
+std::unordered_map<std::string, uint64_t> AnyserveCore::pick_counts(const std::string& name) {
+    std::lock_guard<std::mutex> lock(pick_mutex_);
+    auto it = pick_counts_.find(name);
+    return it == pick_counts_.end() ? std::unordered_map<std::string, uint64_t>{} : it->second;
 }
 
 std::string AnyserveCore::remote_call(const std::string& address,
                                        const std::string& capability,
                                        const std::string& args_pickle,
-                                       bool is_delegated) {
-    // 获取或创建 gRPC channel
-    auto channel = get_or_create_channel(address);
-    auto stub = inference::GRPCInferenceService::NewStub(channel);
+                                       bool is_delegated,
+                                       double timeout_secs,
+                                       const std::string& store) {
+    if (timeout_secs <= 0) {
+        throw std::invalid_argument("timeout_secs must be positive");
+    }
+
+    const std::string target = normalize_address(address, default_remote_port_.load());
     
     // 构建请求
     inference::ModelInferRequest request;
@@ -228,16 +687,74 @@ std::string AnyserveCore::remote_call(const std::string& address,
     if (is_delegated) {
         (*request.mutable_parameters())["is_delegated"].set_bool_param(true);
     }
+    // 由对端按自己注册的存储解析
+    if (!store.empty()) {
+        (*request.mutable_parameters())["store"].set_string_param(store);
+    }
+    
+    // 本地快速路径：目标就是本实例时直接调用 dispatcher，跳过 gRPC 往返。
+    // stop() 之后本实例不再对外服务，走网络路径，与调用其他已停止的实例一样报不可达
+    if (running_.load() && is_self_target(target) && dispatcher_) {
+        if (auto resolved = resolve_store(*this, request)) {
+            return dispatch_locally(*resolved, address, is_delegated);
+        }
+        return dispatch_locally(request, address, is_delegated);
+    }
+    
+    // 断路器打开时直接失败，不再为已知宕机的 peer 等待超时
+    enter_circuit(target, address);
+
+    // 获取或创建 gRPC channel（同一地址复用已建立的连接）
+    auto channel = get_or_create_channel(target);
+    auto stub = inference::GRPCInferenceService::NewStub(channel);
     
     // 发起同步调用（PoC 简化）
+    // deadline 同时约束建连和调用，避免不可达的 peer 阻塞到系统默认超时
     inference::ModelInferResponse response;
     grpc::ClientContext context;
-    context.set_deadline(std::chrono::system_clock::now() + std::chrono::seconds(30));
+    context.set_deadline(std::chrono::system_clock::now() +
+                         std::chrono::duration_cast<std::chrono::milliseconds>(
+                             std::chrono::duration<double>(timeout_secs)));
+    if (!auth_tokens_.empty()) {
+        context.AddMetadata("authorization", BEARER_PREFIX + auth_tokens_.front());
+    }
+    if (int algorithm = compression_.load(); algorithm != GRPC_COMPRESS_NONE) {
+        context.set_compression_algorithm(static_cast<grpc_compression_algorithm>(algorithm));
+    }
     
     grpc::Status status = stub->ModelInfer(&context, request, &response);
+    // 必须在逐出 channel 之前读取连接状态
+    const bool connected = channel->GetState(false) == GRPC_CHANNEL_READY;
     
-    if (!status.ok()) {
-        throw std::runtime_error("Remote call failed: " + status.error_message());
+    // 连接断开时逐出缓存的 channel，下次调用重新建连。超时只说明这次调用慢，
+    // 连接本身仍可用，逐出会让后续调用白白重新建连；断路器仍把超时计为失败
+    const bool unavailable = status.error_code() == grpc::StatusCode::UNAVAILABLE;
+    if (unavailable) {
+        evict_channel(target);
+    }
+    record_circuit(target, unavailable || status.error_code() == grpc::StatusCode::DEADLINE_EXCEEDED);
+
+    switch (status.error_code()) {
+        case grpc::StatusCode::OK:
+            break;
+        case grpc::StatusCode::DEADLINE_EXCEEDED:
+            // 始终未建连说明对端宕机，而非响应慢
+            if (!connected) {
+                throw RemoteUnreachableError("Peer " + address + " unreachable: no connection within " +
+                                             std::to_string(timeout_secs) + "s");
+            }
+            throw RemoteTimeoutError("Remote call to " + address + " timed out after " +
+                                     std::to_string(timeout_secs) + "s");
+        case grpc::StatusCode::UNAVAILABLE:
+            throw RemoteUnreachableError("Peer " + address + " unreachable: " +
+                                         status.error_message());
+        case grpc::StatusCode::NOT_FOUND:
+            throw RemoteNotFoundError("Capability '" + capability + "' not found on " +
+                                      address + ": " + status.error_message());
+        default:
+            throw RemoteTransportError("Remote call to " + address + " failed (code " +
+                                       std::to_string(static_cast<int>(status.error_code())) +
+                                       "): " + status.error_message());
     }
     
     // 提取结果
@@ -248,10 +765,85 @@ std::string AnyserveCore::remote_call(const std::string& address,
     return "";
 }
 
+void AnyserveCore::add_store(const std::string& name, const std::string& path) {
+    if (name.empty() || name[0] == '.' ||
+        name.find_first_of(std::string("/\\\0", 3)) != std::string::npos) {
+        throw std::invalid_argument("Invalid store name: '" + name + "'");
+    }
+    if (path.empty()) {
+        throw std::invalid_argument("Store '" + name + "' needs a path");
+    }
+    std::lock_guard<std::mutex> lock(stores_mutex_);
+    auto [it, inserted] = stores_.emplace(name, path);
+    if (!inserted && it->second != path) {
+        throw std::invalid_argument("Store '" + name + "' is already registered at " + it->second);
+    }
+}
+
+std::string AnyserveCore::store_dir(const std::string& store) const {
+    if (store.empty()) {
+        return objects_dir();
+    }
+    std::lock_guard<std::mutex> lock(stores_mutex_);
+    auto it = stores_.find(store);
+    if (it == stores_.end()) {
+        throw std::invalid_argument("Unknown store: '" + store + "'");
+    }
+    return it->second;
+}
+
+std::optional<std::string> AnyserveCore::get_object_local(const std::string& object_id,
+                                                          const std::string& store) const {
+    if (object_id.empty() || object_id[0] == '.' ||
+        object_id.find_first_of(std::string("/\\\0", 3)) != std::string::npos) {
+        throw std::invalid_argument("Invalid object id: '" + object_id + "'");
+    }
+
+    // 带不带后缀都查：key 本身可能含 "."（如 "model.v2"），不能据此判断是否已带后缀
+    std::vector<std::string> names{object_id};
+    for (const char* ext : {".pkl", ".bin", ".json"}) {
+        names.push_back(object_id + ext);
+    }
+
+    // 平铺布局与分片布局（分片名与 Python 端 _find 一致：取文件名去掉最后一个后缀后的 md5）
+    const fs::path dir = store_dir(store);
+    std::vector<fs::path> candidates;
+    for (const auto& name : names) {
+        candidates.push_back(dir / name);
+        candidates.push_back(dir / shard_of(fs::path(name).stem().string()) / name);
+    }
+
+    for (const auto& path : candidates) {
+        std::error_code ec;
+        if (!fs::is_regular_file(path, ec)) {
+            continue;
+        }
+        std::ifstream in(path, std::ios::binary);
+        if (!in) {
+            // 查找与打开之间被删除
+            if (!fs::exists(path, ec)) {
+                continue;
+            }
+            throw std::runtime_error("Failed to open object " + path.string() + ": " +
+                                     std::strerror(errno));
+        }
+        std::string data((std::istreambuf_iterator<char>(in)), std::istreambuf_iterator<char>());
+        if (in.bad()) {
+            throw std::runtime_error("Failed to read object " + path.string());
+        }
+        return data;
+    }
+    return std::nullopt;
+}
+
 std::string AnyserveCore::get_address() const {
     return address_;
 }
 
+void AnyserveCore::set_default_remote_port(int port) {
+    default_remote_port_ = checked_remote_port(port);
+}
+
 void AnyserveCore::start() {
     if (running_.load()) {
         return;
@@ -260,22 +852,51 @@ void AnyserveCore::start() {
     running_ = true;
     
     // 启动 gRPC 服务器
-    std::string server_address = "0.0.0.0:" + std::to_string(port_);
+    std::string server_address = bind_host_ + ":" + std::to_string(port_);
     
     auto service = std::make_unique<GrpcServiceImpl>(this);
     
     grpc::ServerBuilder builder;
-    builder.AddListeningPort(server_address, grpc::InsecureServerCredentials());
+    int selected_port = 0;
+    builder.AddListeningPort(server_address, grpc::InsecureServerCredentials(), &selected_port);
+    if (!uds_path_.empty()) {
+        // 清除上次运行遗留的 socket 文件，否则 bind 失败
+        std::error_code ec;
+        fs::remove(uds_path_, ec);
+        builder.AddListeningPort(uds_address(), grpc::InsecureServerCredentials());
+    }
+    builder.SetMaxReceiveMessageSize(max_message_bytes_);
+    builder.SetMaxSendMessageSize(max_message_bytes_);
+    if (server_threads_ > 0) {
+        // 默认每个 CPU 核一个完成队列和轮询线程，实例多时线程数成倍增长
+        grpc::ResourceQuota quota("anyserve-" + instance_id_);
+        quota.SetMaxThreads(server_threads_);
+        builder.SetResourceQuota(quota);
+        builder.SetSyncServerOption(grpc::ServerBuilder::SyncServerOption::NUM_CQS, 1);
+        builder.SetSyncServerOption(grpc::ServerBuilder::SyncServerOption::MIN_POLLERS, 1);
+        builder.SetSyncServerOption(grpc::ServerBuilder::SyncServerOption::MAX_POLLERS, 1);
+    }
     builder.RegisterService(service.get());
     
     server_ = builder.BuildAndStart();
     
-    if (!server_) {
+    // selected_port 为 0 表示 TCP 端口绑定失败
+    if (!server_ || selected_port == 0) {
+        if (server_) {
+            server_->Shutdown();
+            server_.reset();
+        }
         running_ = false;
         throw std::runtime_error("Failed to start gRPC server on " + server_address);
     }
     
-    std::cout << "[AnyserveCore] gRPC server listening on " << server_address << std::endl;
+    // 记录实际绑定的端口（请求端口 0 时由系统分配），注册表和 get_address() 都使用它
+    port_ = selected_port;
+    address_ = advertised_host(bind_host_) + ":" + std::to_string(port_);
+    server_address = bind_host_ + ":" + std::to_string(port_);
+    
+    std::cout << "[AnyserveCore] gRPC server listening on " << server_address
+              << (uds_path_.empty() ? "" : " and " + uds_address()) << std::endl;
     
     // 在后台线程运行服务器
     server_thread_ = std::thread([this, svc = std::move(service)]() {
@@ -284,6 +905,10 @@ void AnyserveCore::start() {
     
     // 注册实例
     register_to_scheduler();
+    
+    if (!warm_peers_.empty()) {
+        warm_thread_ = std::thread(&AnyserveCore::warm_connect, this);
+    }
 }
 
 void AnyserveCore::stop() {
@@ -293,17 +918,30 @@ void AnyserveCore::stop() {
     
     running_ = false;
     
+    // 预连接线程按 running_ 退出
+    if (warm_thread_.joinable()) {
+        warm_thread_.join();
+    }
+    
     // 注销实例
     unregister_from_scheduler();
     
-    // 停止 gRPC 服务器
+    // 停止 gRPC 服务器：卡住的请求（例如 dispatcher 不返回）超过宽限期后被取消，
+    // 否则 stop() 会无限期阻塞
     if (server_) {
-        server_->Shutdown();
+        server_->Shutdown(std::chrono::system_clock::now() + SHUTDOWN_GRACE);
     }
     
     if (server_thread_.joinable()) {
         server_thread_.join();
     }
+    // 销毁服务器对象才会释放监听端口和完成队列
+    server_.reset();
+    
+    if (!uds_path_.empty()) {
+        std::error_code ec;
+        fs::remove(uds_path_, ec);
+    }
     
     std::cout << "[AnyserveCore] Stopped." << std::endl;
 }
@@ -314,31 +952,58 @@ void AnyserveCore::run_server() {
 
 void AnyserveCore::register_to_scheduler() {
     // 注册实例信息
-    std::string instance_dir = root_dir_ + "/instances/" + instance_id_;
-    fs::create_directories(instance_dir);
-    
-    std::ofstream ofs(instance_dir + "/address");
-    ofs << address_;
-    ofs.close();
+    registry_->heartbeat(instance_id_, local_endpoint());
     
     std::cout << "[AnyserveCore] Registered to scheduler." << std::endl;
 }
 
 void AnyserveCore::unregister_from_scheduler() {
-    // 移除实例信息
-    std::string instance_dir = root_dir_ + "/instances/" + instance_id_;
-    fs::remove_all(instance_dir);
-    
     // 移除 capability 注册
-    std::lock_guard<std::mutex> lock(capabilities_mutex_);
-    for (const auto& cap : local_capabilities_) {
-        std::string cap_file = root_dir_ + "/names/" + cap + "/" + instance_id_;
-        fs::remove(cap_file);
+    {
+        std::lock_guard<std::mutex> lock(capabilities_mutex_);
+        for (const auto& cap : local_capabilities_) {
+            registry_->deregister(cap, instance_id_);
+        }
     }
     
+    // 移除实例信息
+    registry_->remove_instance(instance_id_);
+    
     std::cout << "[AnyserveCore] Unregistered from scheduler." << std::endl;
 }
 
+std::unordered_map<std::string, AnyserveCore::ShmSegmentStats> AnyserveCore::shm_stats() const {
+    auto stats_of = [](const ShmManager::RawShm& shm) {
+        ShmSegmentStats stats;
+        stats.name = shm.name;
+        stats.fd = shm.fd;
+        stats.size = shm.size;
+        stats.locked = shm.locked;
+        stats.high_water = shm.high_water;
+        stats.wraps = shm.wraps;
+        stats.bytes_moved = shm.bytes_moved;
+        return stats;
+    };
+    return {{"h2d", stats_of(shm_h2d_)}, {"d2h", stats_of(shm_d2h_)}};
+}
+
+bool AnyserveCore::is_authorized(const std::string& authorization) const {
+    if (auth_tokens_.empty()) {
+        return true;
+    }
+    const std::string prefix = BEARER_PREFIX;
+    if (authorization.rfind(prefix, 0) != 0) {
+        return false;
+    }
+    const std::string presented = authorization.substr(prefix.size());
+    bool matched = false;
+    for (const auto& token : auth_tokens_) {
+        // 不提前返回，检查耗时与匹配到第几个 token 无关
+        matched |= constant_time_equals(presented, token);
+    }
+    return matched;
+}
+
 std::shared_ptr<grpc::Channel> AnyserveCore::get_or_create_channel(const std::string& address) {
     std::lock_guard<std::mutex> lock(clients_mutex_);
     
@@ -348,10 +1013,239 @@ std::shared_ptr<grpc::Channel> AnyserveCore::get_or_create_channel(const std::st
     }
     
     // 创建新 channel
-    auto channel = grpc::CreateChannel(address, grpc::InsecureChannelCredentials());
+    grpc::ChannelArguments channel_args;
+    channel_args.SetMaxReceiveMessageSize(max_message_bytes_);
+    channel_args.SetMaxSendMessageSize(max_message_bytes_);
+    auto channel = grpc::CreateCustomChannel(address, grpc::InsecureChannelCredentials(), channel_args);
     client_channels_[address] = channel;
     
     return channel;
 }
 
+void AnyserveCore::evict_channel(const std::string& address) {
+    std::lock_guard<std::mutex> lock(clients_mutex_);
+    client_channels_.erase(address);
+}
+
+bool AnyserveCore::wait_ready(double timeout_secs) {
+    if (timeout_secs < 0) {
+        throw std::invalid_argument("timeout_secs must not be negative");
+    }
+    if (!running_.load()) {
+        throw std::runtime_error("AnyserveCore is not running");
+    }
+    // 不经过连接池：就绪探测不应留下指向自身的缓存连接
+    auto channel = grpc::CreateChannel(self_target(), grpc::InsecureChannelCredentials());
+    auto deadline = std::chrono::system_clock::now() +
+        std::chrono::duration_cast<std::chrono::system_clock::duration>(
+            std::chrono::duration<double>(timeout_secs));
+    return channel->WaitForConnected(deadline);
+}
+
+void AnyserveCore::set_compression(const std::string& algorithm) {
+    for (const auto& [name, value] : COMPRESSION_ALGORITHMS) {
+        if (algorithm == name) {
+            compression_ = value;
+            return;
+        }
+    }
+    throw std::invalid_argument("Unsupported compression: '" + algorithm +
+                                "' (expected none, gzip or deflate)");
+}
+
+std::string AnyserveCore::compression() const {
+    const int algorithm = compression_.load();
+    for (const auto& [name, value] : COMPRESSION_ALGORITHMS) {
+        if (algorithm == value) {
+            return name;
+        }
+    }
+    return "none";
+}
+
+void AnyserveCore::set_circuit_breaker(int failure_threshold, double cooldown_secs) {
+    if (failure_threshold < 0) {
+        throw std::invalid_argument("failure_threshold must not be negative");
+    }
+    if (cooldown_secs <= 0) {
+        throw std::invalid_argument("cooldown_secs must be positive");
+    }
+    std::lock_guard<std::mutex> lock(clients_mutex_);
+    breaker_failures_ = failure_threshold;
+    breaker_cooldown_ = std::chrono::duration_cast<std::chrono::steady_clock::duration>(
+        std::chrono::duration<double>(cooldown_secs));
+    circuits_.clear();
+}
+
+void AnyserveCore::enter_circuit(const std::string& target, const std::string& address) {
+    std::lock_guard<std::mutex> lock(clients_mutex_);
+    auto it = circuits_.find(target);
+    if (breaker_failures_ == 0 || it == circuits_.end() || it->second.failures < breaker_failures_) {
+        return;
+    }
+
+    auto& circuit = it->second;
+    const auto now = std::chrono::steady_clock::now();
+    if (now < circuit.open_until) {
+        const auto remaining = std::chrono::duration<double>(circuit.open_until - now).count();
+        throw PeerUnavailableError("Peer " + address + " unavailable: " +
+                                   std::to_string(circuit.failures) + " consecutive failures, " +
+                                   "retrying in " + std::to_string(remaining) + "s");
+    }
+    // 冷却结束：只放行一个探测调用，结果由 record_circuit 决定恢复还是重新打开
+    if (circuit.probing) {
+        throw PeerUnavailableError("Peer " + address + " unavailable: probe call in progress");
+    }
+    circuit.probing = true;
+}
+
+void AnyserveCore::record_circuit(const std::string& target, bool failed) {
+    std::lock_guard<std::mutex> lock(clients_mutex_);
+    if (breaker_failures_ == 0) {
+        return;
+    }
+    if (!failed) {
+        auto it = circuits_.find(target);
+        if (it != circuits_.end()) {
+            if (it->second.failures >= breaker_failures_) {
+                std::cout << "[AnyserveCore] Peer " << target << " recovered" << std::endl;
+            }
+            circuits_.erase(it);
+        }
+        return;
+    }
+
+    auto& circuit = circuits_[target];
+    circuit.failures++;
+    circuit.probing = false;
+    if (circuit.failures >= breaker_failures_) {
+        circuit.open_until = std::chrono::steady_clock::now() + breaker_cooldown_;
+        std::cerr << "[AnyserveCore] Peer " << target << " failed " << circuit.failures
+                  << " times in a row, rejecting calls for "
+                  << std::chrono::duration<double>(breaker_cooldown_).count() << "s" << std::endl;
+    }
+}
+
+void AnyserveCore::warm_connect() {
+    // 未连上的不影响启动：channel 已被逐出，首次 remote_call 时重新建连
+    for (const auto& target : connect_peers(warm_peers_, WARM_CONNECT_TIMEOUT)) {
+        std::cerr << "[AnyserveCore] Pre-connect to " << target << " failed, "
+                  << "will connect on first call" << std::endl;
+    }
+}
+
+std::vector<std::string> AnyserveCore::warm_capability(const std::string& name, double timeout_secs) {
+    if (timeout_secs < 0) {
+        throw std::invalid_argument("timeout_secs must not be negative");
+    }
+    auto addresses = lookup_capability(name);
+    std::vector<std::string> targets;
+    for (const auto& address : addresses) {
+        targets.push_back(normalize_address(address, default_remote_port_.load()));
+    }
+    
+    const auto failed = connect_peers(targets, std::chrono::duration_cast<std::chrono::steady_clock::duration>(
+                                                   std::chrono::duration<double>(timeout_secs)));
+    std::vector<std::string> reachable;
+    for (size_t i = 0; i < addresses.size(); ++i) {
+        if (failed.count(targets[i]) == 0) {
+            reachable.push_back(addresses[i]);
+        }
+    }
+    return reachable;
+}
+
+std::unordered_set<std::string> AnyserveCore::connect_peers(const std::vector<std::string>& targets,
+                                                            std::chrono::steady_clock::duration timeout) {
+    // 同时对所有 peer 发起建连，再在共同的截止时间内轮询状态
+    std::vector<std::pair<std::string, std::shared_ptr<grpc::Channel>>> pending;
+    for (const auto& target : targets) {
+        if (is_self_target(target)) {
+            continue;
+        }
+        pending.emplace_back(target, get_or_create_channel(target));
+    }
+    
+    const auto deadline = std::chrono::steady_clock::now() + timeout;
+    while (!pending.empty() && running_.load()) {
+        pending.erase(std::remove_if(pending.begin(), pending.end(), [](const auto& entry) {
+            // try_to_connect=true：处于 IDLE 或失败重试中的 channel 继续建连
+            if (entry.second->GetState(true) != GRPC_CHANNEL_READY) {
+                return false;
+            }
+            std::cout << "[AnyserveCore] Pre-connected to " << entry.first << std::endl;
+            return true;
+        }), pending.end());
+        if (pending.empty() || std::chrono::steady_clock::now() >= deadline) {
+            break;
+        }
+        std::this_thread::sleep_for(std::chrono::milliseconds(50));
+    }
+    
+    // 返回未连上的 peer 并逐出其 channel
+    std::unordered_set<std::string> failed;
+    for (const auto& entry : pending) {
+        evict_channel(entry.first);
+        failed.insert(entry.first);
+    }
+    return failed;
+}
+
+std::vector<std::string> AnyserveCore::connected_peers() const {
+    std::lock_guard<std::mutex> lock(clients_mutex_);
+    std::vector<std::string> peers;
+    for (const auto& [address, channel] : client_channels_) {
+        if (channel->GetState(false) == GRPC_CHANNEL_READY) {
+            peers.push_back(address);
+        }
+    }
+    std::sort(peers.begin(), peers.end());
+    return peers;
+}
+
+bool AnyserveCore::is_self_target(const std::string& target) const {
+    // target 已由 normalize_address 规范化为 host:port（localhost 已映射为 127.0.0.1）。
+    // 回环地址只有在监听范围覆盖它时才指向本实例：只监听某个具体地址时，
+    // 127.0.0.1:<port> 可能是本机上监听同一端口的另一个进程
+    const std::string port_suffix = ":" + std::to_string(port_);
+    const bool serves_ipv4_loopback = bind_host_ == "0.0.0.0" || bind_host_ == "[::]" ||
+                                      bind_host_ == "127.0.0.1" || bind_host_ == "localhost";
+    const bool serves_ipv6_loopback = bind_host_ == "[::]" || bind_host_ == "[::1]";
+    return (serves_ipv4_loopback && target == "127.0.0.1" + port_suffix) ||
+           (serves_ipv6_loopback && target == "[::1]" + port_suffix) ||
+           target == self_target() || (!uds_path_.empty() && target == uds_address());
+}
+
+std::string AnyserveCore::self_target() const {
+    return normalize_address(address_, port_);
+}
+
+std::string AnyserveCore::dispatch_locally(const inference::ModelInferRequest& request,
+                                           const std::string& address,
+                                           bool is_delegated) {
+    std::string request_bytes;
+    if (!request.SerializeToString(&request_bytes)) {
+        throw RemoteTransportError("Failed to serialize request for " + address);
+    }
+
+    std::string response_bytes;
+    try {
+        response_bytes = dispatcher_(request.model_name(), request_bytes, is_delegated);
+    } catch (const CapabilityNotFoundError& e) {
+        throw RemoteNotFoundError("Capability '" + request.model_name() + "' not found on " +
+                                  address + ": " + e.what());
+    } catch (const std::exception& e) {
+        throw RemoteTransportError("Local call to " + address + " failed: " + e.what());
+    }
+
+    inference::ModelInferResponse response;
+    if (!response.ParseFromString(response_bytes)) {
+        throw RemoteTransportError("Failed to parse local response from " + address);
+    }
+    if (response.raw_output_contents_size() > 0) {
+        return response.raw_output_contents(0);
+    }
+    return "";
+}
+
 } // namespace anyserve
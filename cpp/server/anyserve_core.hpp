@@ -9,8 +9,13 @@
 #include <mutex>
 #include <unordered_map>
 #include <unordered_set>
+#include <stdexcept>
+#include <optional>
+#include <cstdint>
+#include <chrono>
 
 #include "../core/shm_manager.hpp"
+#include "capability_registry.hpp"
 #include "process_supervisor.hpp"
 
 // Forward declarations for gRPC types
@@ -22,6 +27,7 @@ class Channel;
 
 namespace inference {
 class GRPCInferenceService;
+class ModelInferRequest;
 }
 
 namespace anyserve {
@@ -37,6 +43,78 @@ using DispatcherCallback = std::function<std::string(
     bool is_delegated
 )>;
 
+/**
+ * CapabilityNotFoundError - dispatcher 找不到请求的 capability（服务端返回 NOT_FOUND）
+ */
+class CapabilityNotFoundError : public std::runtime_error {
+public:
+    using std::runtime_error::runtime_error;
+};
+
+/**
+ * ResourceExhaustedError - dispatcher 因存储空间或配额耗尽而失败（服务端返回 RESOURCE_EXHAUSTED）
+ */
+class ResourceExhaustedError : public std::runtime_error {
+public:
+    using std::runtime_error::runtime_error;
+};
+
+/**
+ * RemoteError - 远程调用失败的基类（Python 侧为 AnyserveError）
+ */
+class RemoteError : public std::runtime_error {
+public:
+    using std::runtime_error::runtime_error;
+};
+
+/**
+ * RemoteNotFoundError - 对端可达，但目标 capability/对象不存在（gRPC NOT_FOUND）
+ */
+class RemoteNotFoundError : public RemoteError {
+public:
+    using RemoteError::RemoteError;
+};
+
+/**
+ * RemoteUnreachableError - 对端不可达（gRPC UNAVAILABLE，或在 deadline 内始终未建连）
+ */
+class RemoteUnreachableError : public RemoteError {
+public:
+    using RemoteError::RemoteError;
+};
+
+/**
+ * PeerUnavailableError - 对端连续失败，断路器处于打开状态，调用未发出即失败
+ */
+class PeerUnavailableError : public RemoteUnreachableError {
+public:
+    using RemoteUnreachableError::RemoteUnreachableError;
+};
+
+/**
+ * RemoteTimeoutError - 已建连但调用超过 deadline（对端存活但响应慢）
+ */
+class RemoteTimeoutError : public RemoteError {
+public:
+    using RemoteError::RemoteError;
+};
+
+/**
+ * RemoteTransportError - 其他 gRPC 错误（对端内部错误、协议错误等）
+ */
+class RemoteTransportError : public RemoteError {
+public:
+    using RemoteError::RemoteError;
+};
+
+/**
+ * DuplicateInstanceError - instance_id 已被另一个存活的实例登记
+ */
+class DuplicateInstanceError : public std::runtime_error {
+public:
+    using std::runtime_error::runtime_error;
+};
+
 /**
  * AnyserveCore - 核心控制平面
  * 
@@ -49,15 +127,72 @@ using DispatcherCallback = std::function<std::string(
  */
 class AnyserveCore {
 public:
+    /**
+     * ShmSegmentStats - 单个 SHM 段的状态
+     *
+     * 使用统计见 ShmManager::RawShm::record_write
+     */
+    struct ShmSegmentStats {
+        std::string name;
+        int fd = -1;
+        size_t size = 0;
+        bool locked = false;
+        size_t high_water = 0;
+        uint64_t wraps = 0;
+        uint64_t bytes_moved = 0;
+    };
+
     /**
      * 构造函数
      * @param root_dir 根目录（用于存储状态、发现）
      * @param instance_id 实例唯一标识
-     * @param port gRPC 服务端口（0 = 随机分配）
+     * @param port gRPC 服务端口（0 = 由系统分配，启动后 port/get_address 返回实际端口）
+     * @param uds_path 额外监听的 Unix Domain Socket 路径（空 = 只监听 TCP），
+     *                 同机的 peer 可通过 "unix:<path>" 调用，绕过 TCP 回环
+     * @param max_message_bytes 单条 gRPC 消息的最大字节数，同时作用于服务端和
+     *                          remote_call 客户端的收发（默认 64MB；gRPC 自身默认只接收 4MB）
+     * @param auth_tokens 接受的 bearer token 列表（空 = 不鉴权）。非空时，除
+     *                    ServerLive/ServerReady 探针外的请求都必须携带
+     *                    "authorization: Bearer <token>"，否则返回 UNAUTHENTICATED；
+     *                    remote_call 发出的请求携带第一个 token（其余用于轮换）
+     * @param ns 租户命名空间（空 = 不隔离）。非空时注册表和实例目录位于
+     *           root_dir/ns/<ns>/ 下，lookup 只能看到同一命名空间的实例
+     * @param warm_peers start() 后在后台预先建连的 peer 地址（格式同 remote_call），
+     *                   首次 remote_call 无需等待建连；失败只记录日志，调用时再重连
+     * @param force 即使 instance_id 已被另一个存活的实例登记也照常启动并覆盖其登记
+     *              （用于旧进程尚未退出时的重启）
+     * @param registry 服务发现后端（空 = 使用 scope 目录下的 FsCapabilityRegistry）
+     * @param server_threads gRPC 服务器最多使用的线程数（0 = gRPC 默认，按 CPU 核数轮询）。
+     *                       同一进程内运行大量实例时用于限制线程总数；至少为 2
+     *                       （一个轮询、一个处理请求），并发请求超过 server_threads - 1 时
+     *                       多出的请求返回 RESOURCE_EXHAUSTED
+     * @param bind_host gRPC 服务器监听的地址（默认 "0.0.0.0"，仅 IPv4）。"::" 为双栈监听
+     *                  所有地址，仅 IPv6 的主机需要它；IPv6 字面量可带或不带方括号。
+     *                  监听所有地址时登记的地址为本机回环（localhost / [::1]），
+     *                  否则为 bind_host 本身
+     * @param default_remote_port remote_call、warm_peers 等地址只有 host 时使用的端口（默认 8000）
+     * @throws std::invalid_argument 命名空间包含 '/'、以 '.' 开头等非法名称，
+     *                               warm_peers 中的地址格式不合法，server_threads 为负数或 1，
+     *                               bind_host 为空、方括号不配对，或 default_remote_port 不在 1-65535 内
+     * @throws DuplicateInstanceError instance_id 已被另一个存活的实例登记且 force 为 false
      */
     AnyserveCore(const std::string& root_dir, 
                  const std::string& instance_id,
-                 int port);
+                 int port,
+                 const std::string& uds_path = "",
+                 int max_message_bytes = DEFAULT_MAX_MESSAGE_BYTES,
+                 const std::vector<std::string>& auth_tokens = {},
+                 const std::string& ns = "",
+                 const std::vector<std::string>& warm_peers = {},
+                 bool force = false,
+                 std::shared_ptr<CapabilityRegistry> registry = nullptr,
+                 int server_threads = 0,
+                 const std::string& bind_host = DEFAULT_BIND_HOST,
+                 int default_remote_port = DEFAULT_REMOTE_PORT);
+
+    static constexpr int DEFAULT_MAX_MESSAGE_BYTES = 64 * 1024 * 1024;
+    static constexpr const char* DEFAULT_BIND_HOST = "0.0.0.0";
+    static constexpr int DEFAULT_REMOTE_PORT = 8000;
     
     ~AnyserveCore();
 
@@ -73,34 +208,134 @@ public:
     /**
      * 注册本地 capability
      * @param name capability 名称
+     * @throws std::invalid_argument 名称为空、以 '.' 开头或含路径分隔符/控制字符
      */
     void register_capability(const std::string& name);
 
     /**
      * 查找提供指定 capability 的端点列表
      * @param name capability 名称
-     * @return 端点地址列表
+     * @return 端点地址列表（gRPC 地址）
+     * @throws std::invalid_argument 名称不合法（同 register_capability）
      */
     std::vector<std::string> lookup_capability(const std::string& name);
 
+    using Endpoint = anyserve::Endpoint;
+
+    /**
+     * 查找提供指定 capability 的实例及其全部地址
+     *
+     * 文件系统注册表的条目格式见 FsCapabilityRegistry（只读第一行的旧版本仍然兼容）。
+     * @param name capability 名称
+     */
+    std::vector<Endpoint> lookup_capability_endpoints(const std::string& name);
+
+    /**
+     * 查找提供指定 capability 的端点，并预先通过连接池建连
+     *
+     * 同时向所有端点建连，在 timeout_secs 内连上的 channel 留在连接池中供之后的
+     * remote_call 复用；连不上的被逐出，不出现在返回值中。本实例自身总是视为可达。
+     * @param name capability 名称
+     * @param timeout_secs 等待建连的最长时间（秒）
+     * @return 可达的端点地址（顺序同 lookup_capability）
+     * @throws std::invalid_argument 名称不合法，或 timeout_secs 为负
+     */
+    std::vector<std::string> warm_capability(const std::string& name,
+                                             double timeout_secs = DEFAULT_WARM_TIMEOUT_SECS);
+
+    static constexpr double DEFAULT_WARM_TIMEOUT_SECS = 5.0;
+
+    /**
+     * 从提供指定 capability 的端点中选择一个
+     * @param name capability 名称
+     * @param strategy "random"、"round_robin"（每个 capability 独立轮询）
+     *                 或 "least_recent"（选择最久未被选中的端点）
+     * @return 选中的端点；没有可用端点时为空
+     * @throws std::invalid_argument 未知的 strategy
+     */
+    std::optional<std::string> pick_instance(const std::string& name, const std::string& strategy);
+
+    /**
+     * pick_instance 为指定 capability 选中各端点的次数（任意 strategy 都计入）
+     *
+     * 只包含最近一次 pick_instance 查找时仍在注册表中的端点，下线端点的计数被丢弃
+     * @param name capability 名称
+     */
+    std::unordered_map<std::string, uint64_t> pick_counts(const std::string& name);
+
     /**
      * 远程调用
+     *
+     * 目标为本实例（见 is_self_target：登记的地址、UDS 地址，或监听范围覆盖的回环地址
+     * + 本实例端口）且服务运行中时直接调用 dispatcher，不经过 gRPC；此时 timeout_secs 不生效。
+     * 同机的其他实例仍走 gRPC。
+     *
      * @param address 目标地址
      * @param capability capability 名称
      * @param args_pickle 序列化的参数
      * @param is_delegated 是否为委托请求
+     * @param timeout_secs 连接 + 调用的总超时（秒）
+     * @param store 对端 add_store 注册的存储名称，作为 "store" 参数随请求发送；
+     *              对端把它解析为根目录，以 "store_dir" 参数交给 dispatcher
      * @return 序列化的结果
+     * @throws RemoteNotFoundError 对端不存在该 capability
+     * @throws PeerUnavailableError 该地址的断路器处于打开状态（见 set_circuit_breaker）
+     * @throws RemoteUnreachableError 对端不可达
+     * @throws RemoteTimeoutError 超时
+     * @throws RemoteTransportError 其他 gRPC 错误（含对端未注册 store 时的 INVALID_ARGUMENT）
+     * @throws std::invalid_argument 目标为本实例且 store 未注册
      */
     std::string remote_call(const std::string& address,
                             const std::string& capability,
                             const std::string& args_pickle,
-                            bool is_delegated);
+                            bool is_delegated,
+                            double timeout_secs = DEFAULT_REMOTE_TIMEOUT_SECS,
+                            const std::string& store = "");
+
+    static constexpr double DEFAULT_REMOTE_TIMEOUT_SECS = 30.0;
+
+    /**
+     * 配置 remote_call 的按地址断路器
+     *
+     * 同一地址连续 failure_threshold 次不可达或超时后，断路器打开：cooldown_secs 内
+     * 对该地址的调用直接抛出 PeerUnavailableError，不再建连或等待超时。冷却结束后
+     * 放行一次探测调用（其间的其他调用仍快速失败），成功则恢复，失败则再次打开。
+     * 对端返回的其他错误（NOT_FOUND 等）说明对端存活，视为成功。
+     * @param failure_threshold 打开断路器所需的连续失败次数（0 = 关闭断路器）
+     * @param cooldown_secs 打开后拒绝调用的时长（秒）
+     * @throws std::invalid_argument failure_threshold 为负或 cooldown_secs 不为正
+     */
+    void set_circuit_breaker(int failure_threshold, double cooldown_secs);
+
+    /**
+     * 设置 gRPC 传输层压缩算法
+     *
+     * 作用于 remote_call 发出的请求，以及本实例 gRPC 服务器返回的响应（仅当对端
+     * 声明支持该算法时才压缩，否则照常以未压缩形式发送）。收到的压缩消息总是可以解压，
+     * 与本设置无关。对已经压缩过的数据（如图片）没有收益。
+     * @param algorithm "none"（默认）、"gzip" 或 "deflate"
+     * @throws std::invalid_argument 不支持的算法
+     */
+    void set_compression(const std::string& algorithm);
+    std::string compression() const;
+
+    static constexpr int DEFAULT_BREAKER_FAILURES = 5;
+    static constexpr double DEFAULT_BREAKER_COOLDOWN_SECS = 10.0;
 
     /**
      * 获取本实例的地址
      */
     std::string get_address() const;
 
+    /**
+     * 设置远程地址未指定端口时使用的默认端口
+     *
+     * 只影响之后规范化的地址；构造时已规范化的 warm_peers 不变。
+     * @throws std::invalid_argument port 不在 1-65535 内
+     */
+    void set_default_remote_port(int port);
+    int default_remote_port() const { return default_remote_port_.load(); }
+
     /**
      * 启动服务（gRPC 服务器、Worker 等）
      */
@@ -108,9 +343,32 @@ public:
 
     /**
      * 停止服务
+     *
+     * 注销实例，关闭 gRPC 服务器（进行中的请求最多等待 SHUTDOWN_GRACE，之后取消），
+     * 等待服务线程退出并释放监听端口。可重复调用；之后可再次 start()。
      */
     void stop();
 
+    static constexpr auto SHUTDOWN_GRACE = std::chrono::seconds(5);
+
+    /**
+     * 连接池中已建立连接（READY）的 peer 地址（规范化后的形式）
+     */
+    std::vector<std::string> connected_peers() const;
+
+    static constexpr auto WARM_CONNECT_TIMEOUT = std::chrono::seconds(5);
+
+    /**
+     * 等待本实例的 gRPC 服务器可以接受连接
+     *
+     * start() 返回时端口已绑定；本方法再经由一条独立连接确认服务器确实在应答，
+     * 以便调用方在首次请求前不必 sleep。
+     * @param timeout_secs 最长等待时间（秒），不能为负
+     * @return 超时仍未就绪时返回 false
+     * @throws std::runtime_error 服务未启动或已停止
+     */
+    bool wait_ready(double timeout_secs);
+
     /**
      * 检查是否正在运行
      */
@@ -126,29 +384,129 @@ public:
      */
     int port() const { return port_; }
 
+    /**
+     * 获取 UDS 地址（"unix:<path>"），未启用时为空
+     */
+    std::string uds_address() const { return uds_path_.empty() ? "" : "unix:" + uds_path_; }
+
+    /**
+     * 获取单条 gRPC 消息的最大字节数
+     */
+    int max_message_bytes() const { return max_message_bytes_; }
+
+    /**
+     * 获取 gRPC 服务器的线程上限（0 = gRPC 默认）
+     */
+    int server_threads() const { return server_threads_; }
+
+    /**
+     * 获取 gRPC 服务器监听的 host（IPv6 带方括号，如 "[::]"）
+     */
+    const std::string& bind_host() const { return bind_host_; }
+
+    /**
+     * 获取租户命名空间（未设置时为空）
+     */
+    const std::string& get_namespace() const { return namespace_; }
+
+    /**
+     * 本实例对象存储的目录：<scope>/instances/<instance_id>/objects
+     *
+     * scope 为 root_dir（无命名空间）或 root_dir/ns/<ns>，ObjectStore 的
+     * federated 查找因此也只会看到同一命名空间的实例。
+     */
+    std::string objects_dir() const { return scope_dir_ + "/instances/" + instance_id_ + "/objects"; }
+
+    /**
+     * 注册一个具名的对象存储根目录（如按租户划分的卷），供对象方法通过 store 参数选择
+     *
+     * 目录不必已存在；同一名称重复注册相同路径时忽略。
+     * @param name 存储名，不能为空或含路径分隔符
+     * @param path ObjectStore 的根目录
+     * @throws std::invalid_argument 名称或路径非法，或名称已注册为其他路径
+     */
+    void add_store(const std::string& name, const std::string& path);
+
+    /**
+     * 对象存储的目录
+     * @param store add_store 注册的名称；为空时为本实例的 objects_dir()
+     * @throws std::invalid_argument store 未注册
+     */
+    std::string store_dir(const std::string& store) const;
+
+    /**
+     * 直接从本实例的对象目录读取对象，不经过网络，服务未启动或已停止时也可用
+     *
+     * 平铺和分片（objects/<xx>/）两种布局都会查找。object_id 为对象文件名
+     * （ObjRef.path 的文件名，如 "obj-1234.bin"），或不带扩展名的 key，
+     * 此时依次尝试 ObjectStore 的 .pkl / .bin / .json。返回文件的原始字节，不做反序列化。
+     * @param store 在 add_store 注册的存储中读取；为空时读取 objects_dir()
+     * @return 对象内容；不存在时为 nullopt
+     * @throws std::invalid_argument object_id 为空、以 '.' 开头或含路径分隔符，或 store 未注册
+     * @throws std::runtime_error 文件存在但读取失败
+     */
+    std::optional<std::string> get_object_local(const std::string& object_id,
+                                                const std::string& store = "") const;
+
+    /**
+     * 获取各 SHM 段的状态
+     * @return {"h2d": ..., "d2h": ...}
+     */
+    std::unordered_map<std::string, ShmSegmentStats> shm_stats() const;
+
+    /**
+     * 检查 authorization 元数据是否携带可接受的 token
+     * @param authorization "authorization" 元数据的值（未携带时为空）
+     * @return 未启用鉴权或 token 匹配时为 true
+     */
+    bool is_authorized(const std::string& authorization) const;
+
     /**
      * 获取 dispatcher（用于 gRPC service implementation）
      */
     const DispatcherCallback& get_dispatcher() const { return dispatcher_; }
 
+    /**
+     * 获取响应使用的压缩算法（grpc_compression_algorithm 的取值，用于 gRPC service implementation）
+     */
+    int compression_algorithm() const { return compression_.load(); }
+
 private:
     // 配置
     std::string root_dir_;
+    std::string namespace_;
+    std::string scope_dir_;  // 注册表和实例目录的根：root_dir_ 或 root_dir_/ns/<namespace_>
     std::string instance_id_;
     int port_;
     std::string address_;
+    std::string uds_path_;
+    int max_message_bytes_;
+    int server_threads_;
+    std::string bind_host_;  // 规范化后的监听 host，IPv6 带方括号
+    std::vector<std::string> auth_tokens_;
 
     // 状态
     std::atomic<bool> running_{false};
 
+    // 新建目录 / 文件的权限，默认只对当前用户可见
+    static constexpr unsigned int DEFAULT_DIR_MODE = 0700;
+    static constexpr unsigned int DEFAULT_FILE_MODE = 0600;
+    unsigned int dir_mode_ = DEFAULT_DIR_MODE;
+    unsigned int file_mode_ = DEFAULT_FILE_MODE;
+
     // SHM
     static constexpr size_t SHM_SIZE = 10 * 1024 * 1024; // 10MB
     ShmManager::RawShm shm_h2d_; // Host to Device
     ShmManager::RawShm shm_d2h_; // Device to Host
 
-    // Capability 注册表（简单 PoC：使用文件系统做服务发现）
+    // Capability 注册表：本实例注册过的 capability，以及服务发现后端
     mutable std::mutex capabilities_mutex_;
     std::unordered_set<std::string> local_capabilities_;
+    std::shared_ptr<CapabilityRegistry> registry_;
+
+    // add_store 注册的对象存储：名称 -> 根目录
+    mutable std::mutex stores_mutex_;
+    std::unordered_map<std::string, std::string> stores_;
 
     // Dispatcher 回调
     DispatcherCallback dispatcher_;
@@ -158,15 +516,68 @@ private:
     std::unique_ptr<grpc::ServerCompletionQueue> cq_;
     std::thread server_thread_;
 
-    // gRPC 客户端连接池（简单 PoC：按需创建）
+    // 远程地址缺省端口（remote_call 的 address 只有 host 时使用）
+    std::atomic<int> default_remote_port_;
+
+    // gRPC 客户端连接池（按地址复用 channel，连接失败时逐出）
     mutable std::mutex clients_mutex_;
     std::unordered_map<std::string, std::shared_ptr<grpc::Channel>> client_channels_;
 
+    // 按地址的断路器状态（由 clients_mutex_ 保护），地址恢复后即移除
+    struct PeerCircuit {
+        int failures = 0;                                // 连续失败次数
+        std::chrono::steady_clock::time_point open_until; // 打开状态的截止时间
+        bool probing = false;                             // 半开状态下的探测调用进行中
+    };
+    std::unordered_map<std::string, PeerCircuit> circuits_;
+    int breaker_failures_ = DEFAULT_BREAKER_FAILURES;
+
+    // 传输层压缩（grpc_compression_algorithm 的取值，0 = 不压缩）
+    std::atomic<int> compression_{0};
+    std::chrono::steady_clock::duration breaker_cooldown_ =
+        std::chrono::duration_cast<std::chrono::steady_clock::duration>(
+            std::chrono::duration<double>(DEFAULT_BREAKER_COOLDOWN_SECS));
+
+    // 启动时预先建连的 peer（已规范化）及执行预连接的后台线程
+    std::vector<std::string> warm_peers_;
+    std::thread warm_thread_;
+
+    // pick_instance 的选择状态（按 capability），在实例生命周期内保持；
+    // last_picked_ 和 pick_counts_ 只保留最近一次查找结果中仍存在的端点
+    std::mutex pick_mutex_;
+    std::unordered_map<std::string, size_t> round_robin_next_;
+    std::unordered_map<std::string, std::unordered_map<std::string, uint64_t>> last_picked_;
+    std::unordered_map<std::string, std::unordered_map<std::string, uint64_t>> pick_counts_;
+    uint64_t pick_sequence_ = 0;
+
     // 辅助方法
     void run_server();
     void register_to_scheduler();
+    Endpoint local_endpoint() const;
     void unregister_from_scheduler();
     std::shared_ptr<grpc::Channel> get_or_create_channel(const std::string& address);
+    void evict_channel(const std::string& address);
+    void enter_circuit(const std::string& target, const std::string& address);
+    void record_circuit(const std::string& target, bool failed);
+    void warm_connect();
+    std::unordered_set<std::string> connect_peers(const std::vector<std::string>& targets,
+                                                  std::chrono::steady_clock::duration timeout);
+
+    /**
+     * 规范化后的 target 是否就是本实例（remote_call 的本地快速路径）
+     *
+     * 本实例登记的地址、UDS 地址，以及监听范围覆盖的本机回环地址。只识别本实例自身，
+     * 同机的其他实例仍走 gRPC；它们的对象由共享根目录下的 ObjectStore（peek / fetch）直接访问文件
+     */
+    bool is_self_target(const std::string& target) const;
+
+    /**
+     * 本实例 TCP 端口的规范化地址（normalize_address 的结果），用于连接自身
+     */
+    std::string self_target() const;
+    std::string dispatch_locally(const inference::ModelInferRequest& request,
+                                 const std::string& address,
+                                 bool is_delegated);
 };
 
 } // namespace anyserve
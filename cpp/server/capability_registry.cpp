@@ -0,0 +1,217 @@
+#include "capability_registry.hpp"
+
+#include <iostream>
+#include <fstream>
+#include <filesystem>
+#include <stdexcept>
+#include <cerrno>
+#include <csignal>
+#include <unistd.h>
+
+namespace fs = std::filesystem;
+
+namespace anyserve {
+
+namespace {
+
+/**
+ * 去除首尾空白
+ */
+std::string trim(const std::string& line) {
+    auto first = line.find_first_not_of(" \t\r\n");
+    auto last = line.find_last_not_of(" \t\r\n");
+    return first == std::string::npos ? std::string() : line.substr(first, last - first + 1);
+}
+
+/**
+ * 本机主机名（失败时为空串）
+ */
+std::string local_hostname() {
+    char buf[256] = {};
+    if (gethostname(buf, sizeof(buf) - 1) != 0) {
+        return "";
+    }
+    return buf;
+}
+
+} // anonymous namespace
+
+void create_private_directories(const std::string& path, unsigned int mode) {
+    std::vector<fs::path> missing;
+    for (fs::path p = path; !p.empty() && !fs::exists(p); p = p.parent_path()) {
+        missing.push_back(p);
+        if (p == p.parent_path()) {
+            break;
+        }
+    }
+
+    if (missing.empty()) {
+        auto current = static_cast<unsigned int>(fs::status(path).permissions() & fs::perms::mask);
+        if (current & ~mode) {
+            std::cerr << "[AnyserveCore] Warning: " << path << " has mode " << std::oct << current
+                      << ", more permissive than " << mode << std::dec << std::endl;
+        }
+        return;
+    }
+
+    fs::create_directories(path);
+    // create_directories 受 umask 影响，这里显式设置
+    for (const auto& p : missing) {
+        fs::permissions(p, static_cast<fs::perms>(mode), fs::perm_options::replace);
+    }
+}
+
+FsCapabilityRegistry::FsCapabilityRegistry(const std::string& scope_dir,
+                                           unsigned int dir_mode,
+                                           unsigned int file_mode)
+    : scope_dir_(scope_dir), dir_mode_(dir_mode), file_mode_(file_mode) {
+    create_private_directories(scope_dir_ + "/instances", dir_mode_);
+    create_private_directories(scope_dir_ + "/names", dir_mode_);
+}
+
+void FsCapabilityRegistry::register_instance(const std::string& name,
+                                             const std::string& instance_id,
+                                             const Endpoint& endpoint) {
+    std::string cap_dir = scope_dir_ + "/names/" + name;
+    create_private_directories(cap_dir, dir_mode_);
+
+    // 先写隐藏的临时文件再 rename，崩溃时不会留下截断的条目
+    std::string instance_file = cap_dir + "/" + instance_id;
+    std::string tmp_file = cap_dir + "/." + instance_id + ".tmp." + std::to_string(getpid());
+    {
+        std::ofstream ofs(tmp_file, std::ios::trunc);
+        ofs << endpoint.grpc << "\n";
+        if (!endpoint.uds.empty()) {
+            ofs << "uds=" << endpoint.uds << "\n";
+        }
+        ofs.flush();
+        if (!ofs) {
+            std::error_code ec;
+            fs::remove(tmp_file, ec);
+            throw std::runtime_error("Failed to write registry entry " + tmp_file);
+        }
+    }
+    fs::permissions(tmp_file, static_cast<fs::perms>(file_mode_), fs::perm_options::replace);
+    fs::rename(tmp_file, instance_file);
+}
+
+void FsCapabilityRegistry::deregister(const std::string& name, const std::string& instance_id) {
+    std::error_code ec;
+    fs::remove(scope_dir_ + "/names/" + name + "/" + instance_id, ec);
+}
+
+std::vector<Endpoint> FsCapabilityRegistry::lookup(const std::string& name) {
+    std::vector<Endpoint> endpoints;
+
+    std::string cap_dir = scope_dir_ + "/names/" + name;
+    std::error_code ec;
+    if (!fs::is_directory(cap_dir, ec)) {
+        return endpoints;
+    }
+
+    // 读取失败的条目直接跳过，绝不返回空地址
+    for (fs::directory_iterator it(cap_dir, ec), end; !ec && it != end; it.increment(ec)) {
+        const auto& entry = *it;
+        std::error_code entry_ec;
+        // 隐藏文件是写入中的临时条目
+        if (!entry.is_regular_file(entry_ec) || entry.path().filename().string()[0] == '.') {
+            continue;
+        }
+
+        std::ifstream ifs(entry.path());
+        std::string address;
+        if (!ifs || !std::getline(ifs, address)) {
+            std::cerr << "[AnyserveCore] Skipping unreadable registry entry: "
+                      << entry.path() << std::endl;
+            continue;
+        }
+
+        Endpoint endpoint;
+        endpoint.grpc = trim(address);
+        if (endpoint.grpc.empty()) {
+            continue;
+        }
+        // 其余行为 key=value，未知的 key 忽略
+        for (std::string line; std::getline(ifs, line);) {
+            line = trim(line);
+            if (line.rfind("uds=", 0) == 0) {
+                endpoint.uds = line.substr(4);
+            }
+        }
+        endpoints.push_back(std::move(endpoint));
+    }
+
+    if (ec) {
+        std::cerr << "[AnyserveCore] Failed to scan " << cap_dir << ": "
+                  << ec.message() << std::endl;
+    }
+
+    return endpoints;
+}
+
+void FsCapabilityRegistry::heartbeat(const std::string& instance_id, const Endpoint& endpoint) {
+    std::string instance_dir = scope_dir_ + "/instances/" + instance_id;
+    create_private_directories(instance_dir, dir_mode_);
+
+    std::ofstream ofs(instance_dir + "/address");
+    ofs << endpoint.grpc;
+    ofs.close();
+    fs::permissions(instance_dir + "/address", static_cast<fs::perms>(file_mode_),
+                    fs::perm_options::replace);
+
+    std::ofstream owner(instance_dir + "/owner");
+    owner << "host=" << local_hostname() << "\n" << "pid=" << getpid() << "\n";
+    owner.close();
+    fs::permissions(instance_dir + "/owner", static_cast<fs::perms>(file_mode_),
+                    fs::perm_options::replace);
+}
+
+void FsCapabilityRegistry::remove_instance(const std::string& instance_id) {
+    std::error_code ec;
+    fs::remove_all(scope_dir_ + "/instances/" + instance_id, ec);
+}
+
+std::string FsCapabilityRegistry::live_owner(const std::string& instance_id) {
+    std::string instance_dir = scope_dir_ + "/instances/" + instance_id;
+
+    std::string address;
+    {
+        std::ifstream ifs(instance_dir + "/address");
+        if (!ifs || !std::getline(ifs, address)) {
+            return "";
+        }
+    }
+
+    // 没有 owner 文件（旧版本写入，或写到一半崩溃）时无法判断，按已失效处理
+    std::ifstream ifs(instance_dir + "/owner");
+    std::string host;
+    long pid = 0;
+    for (std::string line; std::getline(ifs, line);) {
+        line = trim(line);
+        if (line.rfind("host=", 0) == 0) {
+            host = line.substr(5);
+        } else if (line.rfind("pid=", 0) == 0) {
+            try {
+                pid = std::stol(line.substr(4));
+            } catch (const std::exception&) {
+                pid = 0;
+            }
+        }
+    }
+    if (pid <= 0) {
+        return "";
+    }
+
+    std::string owner = trim(address) + " (pid " + std::to_string(pid) + " on " +
+                        (host.empty() ? "unknown host" : host) + ")";
+    // 其他主机上的进程无法探测，保守地视为存活
+    if (host != local_hostname()) {
+        return owner;
+    }
+    if (kill(static_cast<pid_t>(pid), 0) == 0 || errno == EPERM) {
+        return owner;
+    }
+    return "";
+}
+
+} // namespace anyserve
@@ -0,0 +1,109 @@
+#pragma once
+
+#include <string>
+#include <vector>
+
+namespace anyserve {
+
+/**
+ * Endpoint - 注册表中一个实例的全部地址
+ */
+struct Endpoint {
+    std::string grpc;  // TCP gRPC 地址（host:port）
+    std::string uds;   // UDS 地址（unix:<path>），未启用时为空
+};
+
+/**
+ * CapabilityRegistry - capability → 实例的服务发现后端
+ *
+ * AnyserveCore 的 register_capability / lookup_capability 以及启动、停止时的
+ * 实例注册都经由该接口。默认实现是 FsCapabilityRegistry（共享目录）；
+ * 跨主机且没有共享卷时可以实现其他后端（如 Redis、etcd）并传给 AnyserveCore。
+ *
+ * capability 名称在传入前已由 AnyserveCore 校验。实现必须是线程安全的。
+ */
+class CapabilityRegistry {
+public:
+    virtual ~CapabilityRegistry() = default;
+
+    /**
+     * 登记实例提供某个 capability（已登记时覆盖其端点）
+     * @param name capability 名称
+     * @param instance_id 实例唯一标识
+     * @param endpoint 实例的地址
+     */
+    virtual void register_instance(const std::string& name,
+                                   const std::string& instance_id,
+                                   const Endpoint& endpoint) = 0;
+
+    /**
+     * 撤销实例对某个 capability 的登记（未登记时什么也不做）
+     */
+    virtual void deregister(const std::string& name, const std::string& instance_id) = 0;
+
+    /**
+     * 查找提供某个 capability 的实例端点（顺序不保证）
+     */
+    virtual std::vector<Endpoint> lookup(const std::string& name) = 0;
+
+    /**
+     * 声明实例存活并记录其地址。AnyserveCore 在 start() 时调用一次；
+     * 带过期时间的后端需要自行续期
+     */
+    virtual void heartbeat(const std::string& instance_id, const Endpoint& endpoint) = 0;
+
+    /**
+     * 移除实例的存活记录（stop() 时调用，在逐个 deregister 之后）
+     */
+    virtual void remove_instance(const std::string& instance_id) = 0;
+
+    /**
+     * 若 instance_id 已被另一个仍存活的实例占用，返回对方的描述（用于报错），否则返回空串。
+     * AnyserveCore 构造时据此拒绝重复的 instance_id；无法判断存活的后端可以保持默认实现
+     */
+    virtual std::string live_owner(const std::string& /*instance_id*/) { return ""; }
+};
+
+/**
+ * FsCapabilityRegistry - 基于共享目录的注册表（默认实现）
+ *
+ * 目录结构（scope_dir 为 root_dir 或 root_dir/ns/<namespace>）：
+ *   scope_dir/names/<capability>/<instance_id>  第一行 gRPC 地址，之后每行一个
+ *                                               "key=value"（目前有 uds=unix:<path>）
+ *   scope_dir/instances/<instance_id>/address   实例地址
+ *   scope_dir/instances/<instance_id>/owner     "host=<hostname>" 和 "pid=<pid>" 两行，
+ *                                               用于判断登记者是否存活
+ * 条目先写隐藏的临时文件再 rename，读取时跳过隐藏文件和不可读的条目。
+ */
+class FsCapabilityRegistry : public CapabilityRegistry {
+public:
+    /**
+     * @param scope_dir 注册表根目录（不存在时创建 names/ 和 instances/）
+     * @param dir_mode 新建目录的权限
+     * @param file_mode 条目文件的权限
+     */
+    FsCapabilityRegistry(const std::string& scope_dir, unsigned int dir_mode, unsigned int file_mode);
+
+    void register_instance(const std::string& name,
+                           const std::string& instance_id,
+                           const Endpoint& endpoint) override;
+    void deregister(const std::string& name, const std::string& instance_id) override;
+    std::vector<Endpoint> lookup(const std::string& name) override;
+    void heartbeat(const std::string& instance_id, const Endpoint& endpoint) override;
+    void remove_instance(const std::string& instance_id) override;
+    std::string live_owner(const std::string& instance_id) override;
+
+private:
+    std::string scope_dir_;
+    unsigned int dir_mode_;
+    unsigned int file_mode_;
+};
+
+/**
+ * 创建目录（含父目录），并把新建的各级目录设为 mode
+ *
+ * 已存在的目录不修改；若其权限比 mode 宽松则打印警告。
+ */
+void create_private_directories(const std::string& path, unsigned int mode);
+
+} // namespace anyserve
@@ -0,0 +1,161 @@
+#include "health_server.hpp"
+
+#include <iostream>
+#include <sstream>
+#include <cstring>
+#include <stdexcept>
+#include <unistd.h>
+#include <poll.h>
+#include <netinet/in.h>
+#include <sys/socket.h>
+
+namespace anyserve {
+
+namespace {
+
+// 单个客户端发送请求行的最长等待时间；请求串行处理，不能被空闲连接卡住
+constexpr int CLIENT_READ_TIMEOUT_MS = 1000;
+
+std::string build_response(int status, const std::string& body) {
+    std::ostringstream oss;
+    oss << "HTTP/1.1 " << status << (status == 200 ? " OK" : status == 503 ? " Service Unavailable" : " Not Found") << "\r\n"
+        << "Content-Type: application/json\r\n"
+        << "Content-Length: " << body.size() << "\r\n"
+        << "Connection: close\r\n"
+        << "\r\n"
+        << body;
+    return oss.str();
+}
+
+/**
+ * 转义 JSON 字符串中的引号和反斜杠（监听地址中不会出现控制字符）
+ */
+std::string json_quote(const std::string& value) {
+    std::string out = "\"";
+    for (char c : value) {
+        if (c == '"' || c == '\\') {
+            out += '\\';
+        }
+        out += c;
+    }
+    return out + "\"";
+}
+
+} // anonymous namespace
+
+HealthServer::HealthServer(int port, StateProvider provider)
+    : port_(port), provider_(std::move(provider)) {}
+
+HealthServer::~HealthServer() {
+    stop();
+}
+
+void HealthServer::start() {
+    if (running_.load()) {
+        return;
+    }
+
+    listen_fd_ = socket(AF_INET, SOCK_STREAM, 0);
+    if (listen_fd_ < 0) {
+        throw std::runtime_error("Failed to create health socket: " + std::string(strerror(errno)));
+    }
+
+    int opt = 1;
+    setsockopt(listen_fd_, SOL_SOCKET, SO_REUSEADDR, &opt, sizeof(opt));
+
+    struct sockaddr_in addr;
+    std::memset(&addr, 0, sizeof(addr));
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_ANY);
+    addr.sin_port = htons(static_cast<uint16_t>(port_));
+
+    if (bind(listen_fd_, (struct sockaddr*)&addr, sizeof(addr)) < 0 ||
+        listen(listen_fd_, 16) < 0) {
+        std::string err = strerror(errno);
+        close(listen_fd_);
+        listen_fd_ = -1;
+        throw std::runtime_error("Failed to listen on health port " + std::to_string(port_) + ": " + err);
+    }
+
+    running_ = true;
+    thread_ = std::thread(&HealthServer::serve_loop, this);
+
+    std::cout << "[HealthServer] Listening on 0.0.0.0:" << port_ << " (/healthz, /readyz)" << std::endl;
+}
+
+void HealthServer::stop() {
+    if (!running_.exchange(false)) {
+        return;
+    }
+    if (thread_.joinable()) {
+        thread_.join();
+    }
+    if (listen_fd_ >= 0) {
+        close(listen_fd_);
+        listen_fd_ = -1;
+    }
+}
+
+void HealthServer::serve_loop() {
+    while (running_.load()) {
+        // 使用 poll 超时，以便及时响应 stop()
+        struct pollfd pfd;
+        pfd.fd = listen_fd_;
+        pfd.events = POLLIN;
+        if (poll(&pfd, 1, 200) <= 0) {
+            continue;
+        }
+
+        int client_fd = accept(listen_fd_, nullptr, nullptr);
+        if (client_fd < 0) {
+            continue;
+        }
+        handle_client(client_fd);
+        close(client_fd);
+    }
+}
+
+void HealthServer::handle_client(int client_fd) {
+    // 连接后迟迟不发请求的客户端直接关闭，否则 /healthz 和 /readyz 都会卡住
+    struct pollfd pfd;
+    pfd.fd = client_fd;
+    pfd.events = POLLIN;
+    if (poll(&pfd, 1, CLIENT_READ_TIMEOUT_MS) <= 0) {
+        return;
+    }
+
+    // 只需要请求行，例如 "GET /readyz HTTP/1.1"
+    char buf[1024];
+    ssize_t n = recv(client_fd, buf, sizeof(buf) - 1, 0);
+    if (n <= 0) {
+        return;
+    }
+    buf[n] = '\0';
+
+    std::istringstream request_line(buf);
+    std::string method, path;
+    request_line >> method >> path;
+
+    WorkerState state = provider_();
+    std::ostringstream body;
+    body << "{\"ready\": " << (state.ready ? "true" : "false")
+         << ", \"worker_pid\": " << state.pid
+         << ", \"uptime_seconds\": " << state.uptime_seconds;
+    if (!state.listen.empty()) {
+        body << ", \"listen\": " << json_quote(state.listen);
+    }
+    body << "}";
+
+    std::string response;
+    if (path == "/healthz") {
+        response = build_response(200, body.str());
+    } else if (path == "/readyz") {
+        response = build_response(state.ready ? 200 : 503, body.str());
+    } else {
+        response = build_response(404, "{\"error\": \"not found\"}");
+    }
+
+    send(client_fd, response.data(), response.size(), MSG_NOSIGNAL);
+}
+
+} // namespace anyserve
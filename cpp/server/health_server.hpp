@@ -0,0 +1,71 @@
+#pragma once
+
+#include <string>
+#include <atomic>
+#include <thread>
+#include <functional>
+#include <sys/types.h>
+
+namespace anyserve {
+
+/**
+ * HealthServer - 代理进程自身的 HTTP 健康检查端点
+ *
+ * 供 Kubernetes 等编排系统探测：
+ * - GET /healthz  进程存活即返回 200
+ * - GET /readyz   Worker 已连接且就绪返回 200，否则 503
+ *
+ * 响应体为 JSON，包含 Worker pid、运行时长和代理 gRPC 的监听地址。
+ */
+class HealthServer {
+public:
+    /**
+     * WorkerState - 当前 Worker 状态快照
+     */
+    struct WorkerState {
+        bool ready = false;
+        pid_t pid = -1;
+        double uptime_seconds = 0.0;
+        std::string listen;  // 代理 gRPC 监听地址（host:port 或 unix:PATH），为空时不输出
+    };
+
+    using StateProvider = std::function<WorkerState()>;
+
+    /**
+     * 构造函数
+     * @param port HTTP 监听端口
+     * @param provider 返回当前 Worker 状态的回调（在服务线程中调用）
+     */
+    HealthServer(int port, StateProvider provider);
+
+    ~HealthServer();
+
+    // 禁止拷贝
+    HealthServer(const HealthServer&) = delete;
+    HealthServer& operator=(const HealthServer&) = delete;
+
+    /**
+     * 启动 HTTP 监听线程
+     * @throws std::runtime_error 如果 bind/listen 失败
+     */
+    void start();
+
+    /**
+     * 停止监听线程
+     */
+    void stop();
+
+    int port() const { return port_; }
+
+private:
+    void serve_loop();
+    void handle_client(int client_fd);
+
+    int port_;
+    StateProvider provider_;
+    int listen_fd_ = -1;
+    std::atomic<bool> running_{false};
+    std::thread thread_;
+};
+
+} // namespace anyserve
@@ -1,20 +1,44 @@
 /**
  * main.cpp - 独立可执行文件入口
  * 
- * 用法: anyserve_node [--port PORT] [APP_TARGET]
+ * 用法: anyserve_node [--port PORT | --listen unix:PATH] [--health-port PORT] [--report-fd N]
+ *                      [--worker-transport uds|tcp] [--worker-addr HOST:PORT]
+ *                      [--worker-output inherit|prefix|file:PATH] [APP_TARGET]
+ *       anyserve_node [--port PORT] --model NAME=APP_TARGET [--model NAME=APP_TARGET ...]
  * 
  * 这个可执行文件用于：
  * 1. 作为独立的 gRPC 代理服务器
  * 2. 派生和管理 Python Worker 进程
  * 3. 在 Python Worker 和外部客户端之间中转请求
+ * 4. 多模型模式下按 model_name 路由到各自的 Worker
  */
 
 #include <iostream>
+#include <fstream>
 #include <string>
 #include <csignal>
 #include <atomic>
+#include <chrono>
+#include <memory>
+#include <algorithm>
+#include <map>
+#include <mutex>
+#include <condition_variable>
+#include <vector>
+#include <thread>
+#include <optional>
+#include <cstring>
+#include <cerrno>
+#include <cctype>
+#include <iomanip>
+#include <sstream>
+#include <fcntl.h>
+#include <unistd.h>
+#include <sys/stat.h>
+#include <sys/un.h>
 
 #include "anyserve_core.hpp"
+#include "health_server.hpp"
 #include "process_supervisor.hpp"
 #include "shm_manager.hpp"
 
@@ -25,6 +49,164 @@ namespace {
 
 std::atomic<bool> g_shutdown_requested{false};
 
+// gRPC 默认入站上限为 4MB，未走 SHM 的内联大 tensor 会直接失败
+constexpr int DEFAULT_MAX_MESSAGE_MB = 64;
+
+// 每个 Worker 的并发推理上限：Worker 串行处理连接且 SHM 只有一对，
+// 过多并发只会堆积在 Worker 侧
+constexpr int DEFAULT_MAX_CONCURRENT_INFERS = 16;
+constexpr int DEFAULT_MAX_QUEUED_INFERS = 64;
+constexpr auto INFER_TIMEOUT = std::chrono::seconds(60);
+
+// 同步服务端没有取消回调，转发 ModelInfer 期间按此间隔检查客户端是否已取消
+constexpr auto CANCEL_POLL_INTERVAL = std::chrono::milliseconds(50);
+
+// Worker 启动（加载模型）超时，大模型可通过 --worker-timeout 放宽
+constexpr auto DEFAULT_WORKER_TIMEOUT = std::chrono::seconds(10);
+
+// 不小于该大小的 raw 输入经 SHM 传输，更小的内联拷贝更快；
+// 请求可用 __force_shm__ / __inline__ 参数覆盖
+constexpr size_t DEFAULT_SHM_THRESHOLD = 64 * 1024;
+constexpr const char* FORCE_SHM_PARAM = "__force_shm__";
+constexpr const char* INLINE_PARAM = "__inline__";
+
+// 代理与 Worker 之间经 H2D 传递输入的参数（见 ProxyService::offload_inputs）。
+// 槽按 64 字节对齐；Worker 不回 ACK 时槽在超时后强制释放，默认与转发超时一致
+constexpr const char* SHM_RAW_PARAM = "shm_raw";
+constexpr const char* SHM_CONTENTS_PARAM = "shm_contents";
+constexpr const char* H2D_ACK_PARAM = "h2d_ack";
+constexpr const char* SHM_CHECKSUM_PARAM = "shm_checksum";
+constexpr const char* H2D_CHECKSUM_PARAM = "h2d_checksum";
+constexpr size_t H2D_SLOT_ALIGN = 64;
+constexpr std::chrono::milliseconds DEFAULT_SHM_ACK_TIMEOUT = INFER_TIMEOUT;
+
+// sockaddr_un::sun_path 可容纳的最长路径（不含结尾的 '\0'），Linux 为 107，macOS 为 103
+constexpr size_t MAX_UDS_PATH = sizeof(sockaddr_un{}.sun_path) - 1;
+
+/**
+ * 生成 Worker UDS 路径 <dir>/anyserve_<随机数>.sock
+ *
+ * 超出 sun_path 长度上限时改用 4 位十六进制的随机段（reaper 仍按 anyserve_*.sock 匹配），
+ * 仍然超出时返回空串
+ */
+std::string make_uds_path(const std::string& dir) {
+    std::string path = dir + "/anyserve_" + std::to_string(std::rand()) + ".sock";
+    if (path.size() <= MAX_UDS_PATH) {
+        return path;
+    }
+    std::ostringstream shortened;
+    shortened << dir << "/anyserve_" << std::hex << std::setw(4) << std::setfill('0')
+              << (std::rand() & 0xffff) << ".sock";
+    path = shortened.str();
+    return path.size() <= MAX_UDS_PATH ? path : "";
+}
+
+/**
+ * 检查环境变量名是否合法：[A-Za-z_][A-Za-z0-9_]*
+ */
+bool is_env_name(const std::string& name) {
+    if (name.empty() || std::isdigit(static_cast<unsigned char>(name[0]))) {
+        return false;
+    }
+    return std::all_of(name.begin(), name.end(), [](unsigned char c) {
+        return std::isalnum(c) || c == '_';
+    });
+}
+
+/**
+ * 解析时长："500ms"、"30s"、"5m"、"1h"，不带单位按秒计算
+ * @return 解析失败时为空
+ */
+std::optional<std::chrono::milliseconds> parse_duration(const std::string& text) {
+    size_t pos = 0;
+    double value = 0;
+    try {
+        value = std::stod(text, &pos);
+    } catch (const std::exception&) {
+        return std::nullopt;
+    }
+    if (pos == 0 || value <= 0) {
+        return std::nullopt;
+    }
+    const std::string unit = text.substr(pos);
+    double ms_per_unit = 0;
+    if (unit == "ms") {
+        ms_per_unit = 1;
+    } else if (unit.empty() || unit == "s") {
+        ms_per_unit = 1000;
+    } else if (unit == "m") {
+        ms_per_unit = 60 * 1000;
+    } else if (unit == "h") {
+        ms_per_unit = 60 * 60 * 1000;
+    } else {
+        return std::nullopt;
+    }
+    return std::chrono::milliseconds(static_cast<int64_t>(value * ms_per_unit));
+}
+
+/**
+ * 解析非负整数参数：只接受数字，且不超过 9 位（不会溢出 int）
+ * @return 解析失败时为空
+ */
+std::optional<int> parse_count(const std::string& text) {
+    if (text.empty() || text.size() > 9 ||
+        !std::all_of(text.begin(), text.end(), [](unsigned char c) { return std::isdigit(c); })) {
+        return std::nullopt;
+    }
+    return std::stoi(text);
+}
+
+/**
+ * 把字符串写成 JSON 字符串字面量（含引号），空字符串写成 null
+ */
+std::string json_string_or_null(const std::string& value) {
+    if (value.empty()) {
+        return "null";
+    }
+    std::ostringstream out;
+    out << '"';
+    for (unsigned char c : value) {
+        if (c == '"' || c == '\\') {
+            out << '\\' << c;
+        } else if (c < 0x20) {
+            out << "\\u" << std::hex << std::setw(4) << std::setfill('0') << static_cast<int>(c)
+                << std::dec;
+        } else {
+            out << c;
+        }
+    }
+    out << '"';
+    return out.str();
+}
+
+std::string json_int_or_null(long value) {
+    return value < 0 ? "null" : std::to_string(value);
+}
+
+/**
+ * 把一行数据完整写入 fd 后关闭（读端据此得到 EOF）
+ * @return 写入失败时为 false
+ */
+bool write_line_and_close(int fd, const std::string& line) {
+    const std::string data = line + "\n";
+    size_t written = 0;
+    while (written < data.size()) {
+        ssize_t n = ::write(fd, data.data() + written, data.size() - written);
+        if (n < 0 && errno == EINTR) {
+            continue;
+        }
+        if (n <= 0) {
+            const int saved = errno;
+            ::close(fd);
+            errno = saved;
+            return false;
+        }
+        written += static_cast<size_t>(n);
+    }
+    ::close(fd);
+    return true;
+}
+
 void signal_handler(int signal) {
     std::cout << "\n[main] Received signal " << signal << ", shutting down..." << std::endl;
     g_shutdown_requested = true;
@@ -34,20 +216,1232 @@ void print_usage(const char* program) {
     std::cerr << "Usage: " << program << " [OPTIONS] [APP_TARGET]\n"
               << "\n"
               << "Options:\n"
-              << "  --port PORT    gRPC server port (default: 8080)\n"
-              << "  --help         Show this help message\n"
+              << "  --port PORT        gRPC server port (default: 8080)\n"
+              << "  --listen unix:PATH Serve gRPC on a Unix socket at PATH instead\n"
+              << "                     of --port, for same-host clients. A stale\n"
+              << "                     socket file is replaced; removed on exit\n"
+              << "  --health-port PORT HTTP port for proxy /healthz and /readyz\n"
+              << "  --report-fd N      Once serving, write one JSON line with port,\n"
+              << "                     listen, uds_path, h2d_fd, d2h_fd and worker_pid to\n"
+              << "                     file descriptor N, then close it\n"
+              << "                     (disabled by default)\n"
+              << "  --worker-transport uds|uds-abstract|tcp\n"
+              << "                     Proxy<->worker transport (default: uds).\n"
+              << "                     uds-abstract (Linux only) uses an abstract\n"
+              << "                     socket with no file to clean up\n"
+              << "  --uds-dir DIR      Directory for the worker's UDS socket\n"
+              << "                     (default: $TMPDIR, or /tmp)\n"
+              << "  --worker-addr HOST:PORT\n"
+              << "                     Worker address for tcp. Without APP_TARGET\n"
+              << "                     the worker is external and SHM is disabled\n"
+              << "  --worker-timeout DURATION\n"
+              << "                     How long to wait for the worker to signal\n"
+              << "                     ready, and then for it to report ready\n"
+              << "                     before serving, e.g. 90s, 5m (default: 10s,\n"
+              << "                     or ANYSERVE_WORKER_TIMEOUT)\n"
+              << "  --worker-env KEY=VALUE\n"
+              << "                     Extra environment variable for the worker(s)\n"
+              << "                     (repeatable; ANSERVE_* is reserved)\n"
+              << "  --worker-arg ARG   Extra argument appended to the worker command\n"
+              << "                     line after APP_TARGET (repeatable)\n"
+              << "  --worker-output inherit|prefix|file:PATH\n"
+              << "                     Where worker stdout/stderr go: inherited\n"
+              << "                     as-is (default), forwarded line by line\n"
+              << "                     with a [worker] prefix, or appended to PATH\n"
+              << "  --max-message-size MB\n"
+              << "                     Max inbound/outbound gRPC message size for\n"
+              << "                     clients and workers (default: 64). Payloads\n"
+              << "                     above the SHM threshold bypass this limit\n"
+              << "  --compression ALGO Compress responses to clients that accept it:\n"
+              << "                     none, gzip or deflate (default: none).\n"
+              << "                     Compressed requests are always accepted\n"
+              << "  --max-concurrent-infers N\n"
+              << "                     In-flight ModelInfer calls per worker\n"
+              << "                     (default: 16)\n"
+              << "  --max-queued-infers N\n"
+              << "                     Calls allowed to wait for a slot; beyond\n"
+              << "                     this RESOURCE_EXHAUSTED (default: 64)\n"
+              << "  --max-input-bytes BYTES\n"
+              << "                     Reject ModelInfer requests whose raw inputs\n"
+              << "                     total more than this with INVALID_ARGUMENT\n"
+              << "                     (default: 0, no limit)\n"
+              << "  --max-inputs N     Reject ModelInfer requests with more than N\n"
+              << "                     inputs (default: 0, no limit). Raw inputs\n"
+              << "                     whose size doesn't match their declared shape\n"
+              << "                     and datatype are always rejected\n"
+              << "  --model NAME=APP_TARGET\n"
+              << "                     Route model NAME to its own worker running\n"
+              << "                     APP_TARGET (repeatable; unknown models get\n"
+              << "                     NOT_FOUND)\n"
+              << "  --shm-mlock        mlock SHM regions to prevent swapping\n"
+              << "                     (requires RLIMIT_MEMLOCK / CAP_IPC_LOCK)\n"
+              << "  --shm-hugepages    Back SHM with 2MB huge pages (Linux only,\n"
+              << "                     falls back to normal pages if unavailable)\n"
+              << "  --shm-keep-name PREFIX\n"
+              << "                     DEBUG ONLY: name SHM /dev/shm/PREFIX_h2d and\n"
+              << "                     PREFIX_d2h (PREFIX_<i>_h2d per --model) and\n"
+              << "                     keep them linked until exit so they can be\n"
+              << "                     inspected. Any process of the same user can\n"
+              << "                     open them, and a crash leaves them behind\n"
+              << "  --echo             Spawn no worker; ModelInfer echoes inputs back\n"
+              << "                     through the H2D/D2H SHM regions (for testing\n"
+              << "                     the proxy without a model)\n"
+              << "  --shm-threshold BYTES\n"
+              << "                     Inputs of at least this size go through SHM,\n"
+              << "                     smaller ones are copied inline (default:\n"
+              << "                     65536): with --echo, and for workers that read\n"
+              << "                     inputs from H2D (shm_inputs=1 in their ready\n"
+              << "                     message; other workers get every input inline).\n"
+              << "                     Applies to raw_input_contents and to typed\n"
+              << "                     contents (fp32_contents, ...), measured by\n"
+              << "                     their serialized size. Requests can override\n"
+              << "                     with the bool parameters __force_shm__ /\n"
+              << "                     __inline__\n"
+              << "  --shm-ack-timeout DURATION\n"
+              << "                     How long an input written to a worker's H2D\n"
+              << "                     slot may go unacknowledged before the slot\n"
+              << "                     is freed anyway, with a warning (default: 60s)\n"
+              << "  --verify-shm       Checksum SHM transfers and fail the call with\n"
+              << "                     DATA_LOSS on a mismatch (costs CPU). With\n"
+              << "                     --echo, covers the bytes written to H2D and\n"
+              << "                     read back from D2H, and reports the bytes\n"
+              << "                     copied from D2H into raw outputs. Otherwise\n"
+              << "                     covers inputs written to the worker's H2D\n"
+              << "                     slots; the worker must return a checksum of\n"
+              << "                     what it read (h2d_checksum)\n"
+              << "  --cache-metadata   Remember the last server/model metadata each\n"
+              << "                     worker returned (prefetched once it is ready)\n"
+              << "                     and serve it while the worker is unreachable,\n"
+              << "                     e.g. during an external worker's restart\n"
+              << "  --check            Start the worker(s), probe ServerReady, then\n"
+              << "                     shut down and exit 0 (1 on failure) without\n"
+              << "                     binding --port or --health-port\n"
+              << "  --help             Show this help message\n"
               << "\n"
               << "Arguments:\n"
-              << "  APP_TARGET     Python app target (e.g., 'myapp:app')\n"
+              << "  APP_TARGET         Python app target (e.g., 'myapp:app'),\n"
+              << "                     single-worker mode only\n"
               << std::endl;
 }
 
+/**
+ * FNV-1a 64 位校验和
+ *
+ * 只用于发现 SHM 偏移错误导致的数据错乱，不需要抗碰撞。
+ */
+uint64_t fnv1a64(const void* data, size_t len) {
+    uint64_t hash = 14695981039346656037ULL;
+    const auto* bytes = static_cast<const unsigned char*>(data);
+    for (size_t i = 0; i < len; ++i) {
+        hash ^= bytes[i];
+        hash *= 1099511628211ULL;
+    }
+    return hash;
+}
+
+/**
+ * 校验和的文本形式：16 位十六进制，补零
+ */
+std::string checksum_hex(uint64_t checksum) {
+    std::ostringstream hex;
+    hex << std::hex << std::setw(16) << std::setfill('0') << checksum;
+    return hex.str();
+}
+
+/**
+ * 解析 "a:b,c:d" 形式的 SHM 参数：逗号分隔的项，每项为 fields 个以 ':' 分隔的非负整数
+ * @return 格式不合法时为空
+ */
+std::optional<std::vector<std::vector<size_t>>> parse_shm_entries(const std::string& text, size_t fields) {
+    std::vector<std::vector<size_t>> entries;
+    std::istringstream items(text);
+    std::string item;
+    while (std::getline(items, item, ',')) {
+        std::vector<size_t> values;
+        std::istringstream parts(item);
+        std::string part;
+        while (std::getline(parts, part, ':')) {
+            if (part.empty() || part.size() > 19 ||
+                !std::all_of(part.begin(), part.end(), [](unsigned char c) { return std::isdigit(c); })) {
+                return std::nullopt;
+            }
+            values.push_back(std::stoull(part));
+        }
+        if (values.size() != fields) {
+            return std::nullopt;
+        }
+        entries.push_back(std::move(values));
+    }
+    return entries;
+}
+
+/**
+ * H2dSlots - Worker H2D 段上的输入槽分配器
+ *
+ * ProxyService 把大输入写入各自的槽（H2D_SLOT_ALIGN 对齐，首次适配），并发请求
+ * 各用各的槽，拷贝在锁外进行。槽在 Worker 确认读完（响应参数 h2d_ack）后才释放，
+ * 在此之前不会分给其他请求。Worker 出错、调用被取消或 Worker 不回 ACK 时，槽在
+ * ack_timeout 之后的下一次分配时强制释放，并打印警告。
+ */
+class H2dSlots {
+public:
+    H2dSlots(anyserve::ShmManager::RawShm& shm, std::chrono::milliseconds ack_timeout)
+        : shm_(shm), ack_timeout_(ack_timeout) {}
+
+    /**
+     * 分配一个槽并写入 data
+     * @return 槽的偏移；段中没有足够的连续空间时为空（调用方改为内联发送）
+     */
+    std::optional<size_t> put(const std::string& data) {
+        const std::optional<size_t> offset = allocate(data.size());
+        if (!offset) {
+            return std::nullopt;
+        }
+        std::memcpy(shm_.region(*offset, data.size()), data.data(), data.size());
+        std::lock_guard<std::mutex> lock(mutex_);
+        shm_.record_write(*offset, data.size());
+        return offset;
+    }
+
+    /**
+     * Worker 确认已读完 [offset, offset + len)，释放该槽
+     * @return 没有这样的槽（已被强制释放，或 Worker 回传了错误的值）时为 false
+     */
+    bool ack(size_t offset, size_t len) {
+        std::lock_guard<std::mutex> lock(mutex_);
+        auto it = used_.find(offset);
+        if (it == used_.end() || it->second.len != len) {
+            return false;
+        }
+        used_.erase(it);
+        return true;
+    }
+
+private:
+    struct Slot {
+        size_t len;
+        std::chrono::steady_clock::time_point expiry;  // 过期后视为 ACK 丢失
+    };
+
+    std::optional<size_t> allocate(size_t len) {
+        if (len == 0 || len > shm_.size) {
+            return std::nullopt;
+        }
+        const auto now = std::chrono::steady_clock::now();
+        std::lock_guard<std::mutex> lock(mutex_);
+        for (auto it = used_.begin(); it != used_.end();) {
+            if (it->second.expiry > now) {
+                ++it;
+                continue;
+            }
+            std::cerr << "[Proxy] Warning: worker never acknowledged H2D slot at offset " << it->first
+                      << " (" << it->second.len << " bytes) within " << ack_timeout_.count()
+                      << "ms, freeing it" << std::endl;
+            it = used_.erase(it);
+        }
+        // 槽按偏移排序且都已对齐：依次检查相邻两个槽之间的空隙
+        size_t candidate = 0;
+        for (const auto& [offset, slot] : used_) {
+            if (offset - candidate >= len) {
+                break;
+            }
+            candidate = (offset + slot.len + H2D_SLOT_ALIGN - 1) / H2D_SLOT_ALIGN * H2D_SLOT_ALIGN;
+        }
+        if (candidate > shm_.size || shm_.size - candidate < len) {
+            return std::nullopt;
+        }
+        used_[candidate] = Slot{len, now + ack_timeout_};
+        return candidate;
+    }
+
+    anyserve::ShmManager::RawShm& shm_;
+    const std::chrono::milliseconds ack_timeout_;
+    std::mutex mutex_;
+    std::map<size_t, Slot> used_;  // 偏移 -> 已分配的槽
+};
+
+/**
+ * WorkerSlot - 一个 Worker 进程及其 SHM、地址和 gRPC 连接
+ */
+struct WorkerSlot {
+    std::string model;       // 路由的模型名（单 Worker 模式为空）
+    std::string app_target;
+    bool spawned = false;    // 是否由本进程派生（外部 TCP Worker 为 false）
+    std::unique_ptr<anyserve::ProcessSupervisor> supervisor;
+    anyserve::ShmManager::RawShm shm_h2d;
+    anyserve::ShmManager::RawShm shm_d2h;
+    std::unique_ptr<H2dSlots> h2d_slots;  // Worker 声明 shm_inputs=1 时才有，见 ProxyService
+    std::string uds_path;    // 仅基于文件的 UDS
+    std::string worker_name; // 传给 Worker 的 UDS 路径或抽象 socket 名
+    std::string pid_marker;
+    std::string address;
+    std::shared_ptr<grpc::Channel> channel;
+    std::unique_ptr<inference::GRPCInferenceService::Stub> stub;
+};
+
+using Stub = inference::GRPCInferenceService::Stub;
+
+/**
+ * 探测 Worker 是否可以处理请求
+ *
+ * 多模型模式查询该模型的 ModelReady，单 Worker 模式查询 ServerReady。
+ * @param error 未就绪时写入原因
+ */
+bool probe_worker_ready(const WorkerSlot& slot, std::string* error) {
+    grpc::ClientContext context;
+    context.set_deadline(std::chrono::system_clock::now() + std::chrono::seconds(5));
+    grpc::Status status;
+    bool ready = false;
+    std::string what;
+    if (slot.model.empty()) {
+        inference::ServerReadyRequest request;
+        inference::ServerReadyResponse response;
+        status = slot.stub->ServerReady(&context, request, &response);
+        ready = response.ready();
+        what = "ServerReady";
+    } else {
+        inference::ModelReadyRequest request;
+        request.set_name(slot.model);
+        inference::ModelReadyResponse response;
+        status = slot.stub->ModelReady(&context, request, &response);
+        ready = response.ready();
+        what = "ModelReady for model " + slot.model;
+    }
+    if (!status.ok()) {
+        *error = what + " returned " + std::to_string(static_cast<int>(status.error_code())) +
+                 ": " + status.error_message();
+        return false;
+    }
+    if (!ready) {
+        *error = what + " reports not ready";
+        return false;
+    }
+    return true;
+}
+
+/**
+ * 校验 Worker 在就绪消息中报告的 SHM 映射大小（h2d_size= / d2h_size=）
+ *
+ * fd 继承出错（如被关闭后复用为其他文件）时 Worker 映射到的内容不是代理的
+ * SHM，张量会悄悄变成垃圾数据，因此大小不符时拒绝启动。未报告大小的 Worker
+ * （只发送 "ready"）照常启动，只打印警告：无从确认映射是否正确
+ * （Worker 可使用 anyserve.worker.handshake.signal_ready() 报告）。
+ * @param error 不匹配时写入原因
+ */
+bool check_shm_sizes(const WorkerSlot& slot, std::string* error) {
+    const std::string h2d = slot.supervisor->reported("h2d_size");
+    const std::string d2h = slot.supervisor->reported("d2h_size");
+    if (h2d.empty() && d2h.empty()) {
+        std::cerr << "[main] Warning: worker did not report its SHM sizes, skipping SHM check"
+                  << " (see anyserve.worker.handshake.signal_ready)" << std::endl;
+        return true;
+    }
+    if (h2d == std::to_string(slot.shm_h2d.size) && d2h == std::to_string(slot.shm_d2h.size)) {
+        return true;
+    }
+    auto shown = [](const std::string& reported) { return reported.empty() ? "none" : reported; };
+    *error = "SHM size mismatch: worker mapped H2D=" + shown(h2d) + " D2H=" + shown(d2h) +
+             " bytes, expected H2D=" + std::to_string(slot.shm_h2d.size) +
+             " D2H=" + std::to_string(slot.shm_d2h.size) +
+             " (were ANSERVE_H2D_FD / ANSERVE_D2H_FD inherited?)";
+    return false;
+}
+
+/**
+ * 执行 ModelInfer 的处理逻辑，把逃逸的异常转换为明确的状态码
+ *
+ * gRPC 同步 API 会把 handler 抛出的异常一律变成 UNKNOWN
+ * （"Unexpected error in RPC handling"），客户端无从得知原因：
+ * 内存不足返回 RESOURCE_EXHAUSTED，其他异常返回带原因的 INTERNAL。
+ */
+template <typename Fn>
+grpc::Status infer_status_from_exceptions(const char* component, Fn&& fn) {
+    try {
+        return fn();
+    } catch (const std::bad_alloc&) {
+        std::cerr << "[" << component << "] ModelInfer ran out of memory" << std::endl;
+        return grpc::Status(grpc::StatusCode::RESOURCE_EXHAUSTED, "Out of memory handling ModelInfer");
+    } catch (const std::exception& e) {
+        std::cerr << "[" << component << "] ModelInfer failed: " << e.what() << std::endl;
+        return grpc::Status(grpc::StatusCode::INTERNAL, std::string("ModelInfer failed: ") + e.what());
+    }
+}
+
+/**
+ * InferLimits - ModelInfer 请求的大小限制（0 = 不限制）
+ */
+struct InferLimits {
+    size_t max_input_bytes = 0;  // raw_input_contents 的总字节数
+    size_t max_inputs = 0;       // inputs / raw_input_contents 的条数
+};
+
+/**
+ * 定长数据类型的单个元素字节数；BYTES 等变长或未知类型返回 0
+ */
+size_t datatype_size(const std::string& datatype) {
+    static const std::map<std::string, size_t> sizes = {
+        {"BOOL", 1}, {"INT8", 1}, {"UINT8", 1},
+        {"INT16", 2}, {"UINT16", 2}, {"FP16", 2}, {"BF16", 2},
+        {"INT32", 4}, {"UINT32", 4}, {"FP32", 4},
+        {"INT64", 8}, {"UINT64", 8}, {"FP64", 8},
+    };
+    auto it = sizes.find(datatype);
+    return it == sizes.end() ? 0 : it->second;
+}
+
+/**
+ * 在转发或写入 SHM 之前检查 ModelInfer 请求，不合法时返回 INVALID_ARGUMENT
+ *
+ * 除 limits 外，还要求定长类型输入的 raw 内容长度等于 shape 各维之积乘以元素大小，
+ * 以及 inputs 与 raw_input_contents 的条数一致（请求只带 raw 内容、不声明 inputs 时不检查）。
+ */
+grpc::Status validate_infer_request(const inference::ModelInferRequest& request, const InferLimits& limits) {
+    auto invalid = [](const std::string& message) {
+        return grpc::Status(grpc::StatusCode::INVALID_ARGUMENT, message);
+    };
+    const size_t count = static_cast<size_t>(std::max(request.inputs_size(), request.raw_input_contents_size()));
+    if (limits.max_inputs > 0 && count > limits.max_inputs) {
+        return invalid("Request has " + std::to_string(count) + " inputs, limit is " +
+                       std::to_string(limits.max_inputs));
+    }
+    size_t total = 0;
+    for (const auto& raw : request.raw_input_contents()) {
+        total += raw.size();
+    }
+    if (limits.max_input_bytes > 0 && total > limits.max_input_bytes) {
+        return invalid("Request has " + std::to_string(total) + " bytes of raw input, limit is " +
+                       std::to_string(limits.max_input_bytes));
+    }
+    if (request.raw_input_contents_size() == 0 || request.inputs_size() == 0) {
+        return grpc::Status::OK;
+    }
+    if (request.raw_input_contents_size() != request.inputs_size()) {
+        return invalid("raw_input_contents has " + std::to_string(request.raw_input_contents_size()) +
+                       " entries for " + std::to_string(request.inputs_size()) + " inputs");
+    }
+
+    for (int i = 0; i < request.inputs_size(); ++i) {
+        const auto& input = request.inputs(i);
+        const size_t raw_size = request.raw_input_contents(i).size();
+        std::string shape;
+        bool empty = false;
+        for (int64_t dim : input.shape()) {
+            if (dim < 0) {
+                return invalid("Input '" + input.name() + "' has negative dimension " + std::to_string(dim));
+            }
+            shape += (shape.empty() ? "" : ",") + std::to_string(dim);
+            empty |= dim == 0;
+        }
+        const size_t element_size = datatype_size(input.datatype());
+        if (element_size == 0) {
+            continue;
+        }
+        // 元素个数一旦超过 raw 字节数就必然不匹配，此时停止相乘以免溢出
+        uint64_t elements = empty ? 0 : 1;
+        bool too_large = false;
+        for (int64_t dim : input.shape()) {
+            if (empty) {
+                break;
+            }
+            if (elements > raw_size / static_cast<uint64_t>(dim)) {
+                too_large = true;
+                break;
+            }
+            elements *= static_cast<uint64_t>(dim);
+        }
+        if (too_large || elements * element_size != raw_size) {
+            return invalid("Input '" + input.name() + "' has shape [" + shape + "] of " +
+                           input.datatype() + " but " + std::to_string(raw_size) + " raw bytes");
+        }
+    }
+    return grpc::Status::OK;
+}
+
+/**
+ * 读取请求参数 __force_shm__ / __inline__（bool），两者都为 true 时返回 INVALID_ARGUMENT
+ */
+grpc::Status read_shm_hints(const inference::ModelInferRequest& request, bool* force_shm, bool* force_inline) {
+    auto hint = [&](const char* name) {
+        auto it = request.parameters().find(name);
+        return it != request.parameters().end() && it->second.bool_param();
+    };
+    *force_shm = hint(FORCE_SHM_PARAM);
+    *force_inline = hint(INLINE_PARAM);
+    if (*force_shm && *force_inline) {
+        return grpc::Status(grpc::StatusCode::INVALID_ARGUMENT,
+                            std::string(FORCE_SHM_PARAM) + " and " + INLINE_PARAM + " are mutually exclusive");
+    }
+    return grpc::Status::OK;
+}
+
+/**
+ * InferLimiter - 单个 Worker 的推理并发限制
+ *
+ * 最多 max_in_flight 个请求同时转发；超出的请求最多 max_queued 个排队等待，
+ * 队列已满或等待超过 deadline 时拒绝。
+ */
+class InferLimiter {
+public:
+    InferLimiter(size_t max_in_flight, size_t max_queued)
+        : max_in_flight_(max_in_flight), max_queued_(max_queued) {}
+    
+    /**
+     * 获取一个并发名额
+     * @param deadline 排队等待的截止时间
+     * @return true 如果获得名额（之后必须调用 release）
+     */
+    bool acquire(std::chrono::system_clock::time_point deadline) {
+        std::unique_lock<std::mutex> lock(mutex_);
+        if (in_flight_ < max_in_flight_) {
+            ++in_flight_;
+            return true;
+        }
+        if (queued_ >= max_queued_) {
+            return false;
+        }
+        ++queued_;
+        bool ok = cv_.wait_until(lock, deadline, [this] { return in_flight_ < max_in_flight_; });
+        --queued_;
+        if (!ok) {
+            return false;
+        }
+        ++in_flight_;
+        return true;
+    }
+    
+    void release() {
+        {
+            std::lock_guard<std::mutex> lock(mutex_);
+            --in_flight_;
+        }
+        cv_.notify_one();
+    }
+    
+    /**
+     * Slot - 已获得的名额，析构时归还
+     *
+     * 转发途中抛出异常也会归还，不会永久占用名额，使后续请求一直排队
+     */
+    class Slot {
+    public:
+        explicit Slot(InferLimiter& limiter) : limiter_(limiter) {}
+        ~Slot() { limiter_.release(); }
+        Slot(const Slot&) = delete;
+        Slot& operator=(const Slot&) = delete;
+    private:
+        InferLimiter& limiter_;
+    };
+    
+private:
+    const size_t max_in_flight_;
+    const size_t max_queued_;
+    std::mutex mutex_;
+    std::condition_variable cv_;
+    size_t in_flight_ = 0;
+    size_t queued_ = 0;
+};
+
+/**
+ * ProxyService - 把 KServe 请求转发到 Worker
+ *
+ * 单 Worker 模式下所有请求都转发到同一个 Worker；
+ * 多模型模式下按 model_name 路由，未知模型返回 NOT_FOUND。
+ * 每个 Worker 的 ModelInfer 并发受 InferLimiter 限制，超限返回 RESOURCE_EXHAUSTED。
+ * Worker 进程退出后（worker_dead 置位）ModelInfer 直接返回 UNAVAILABLE。
+ * ModelInfer 转发前先经 validate_infer_request 检查，不合法的请求不会到达 Worker。
+ * 客户端取消 ModelInfer 时，转发给 Worker 的调用也被取消，并发名额立即归还。
+ *
+ * 就绪消息中声明 shm_inputs=1 的 Worker 有 H2dSlots：不小于 shm_threshold 的
+ * 输入（raw_input_contents 和 typed contents）写入 H2D 槽再转发（见 offload_inputs），请求参数 __force_shm__ / __inline__
+ * （bool）覆盖这一选择；Worker 在响应中确认后释放槽，经 H2D 的字节数写入响应参数
+ * h2d_bytes。其他 Worker 的输入都原样内联转发，两个参数只做互斥检查。
+ * verify_shm（--verify-shm）时核对 Worker 读到的每个 H2D 输入的校验和，不一致
+ * 返回 DATA_LOSS（见 release_acked）。
+ *
+ * cache_metadata（--cache-metadata）时记住每个 Worker 最近一次成功返回的
+ * ServerMetadata / ModelMetadata：Worker 短暂不可达（重启中）时返回缓存，
+ * Worker 恢复后下一次成功转发即刷新缓存，因此重启后元数据变化也会生效。
+ */
+class ProxyService final : public inference::GRPCInferenceService::Service {
+public:
+    ProxyService(std::vector<Stub*> workers, std::map<std::string, Stub*> routes,
+                 size_t max_concurrent, size_t max_queued, const std::atomic<bool>& worker_dead,
+                 bool cache_metadata, InferLimits limits, std::map<Stub*, H2dSlots*> h2d_slots,
+                 size_t shm_threshold, bool verify_shm)
+        : workers_(std::move(workers)), routes_(std::move(routes)), worker_dead_(worker_dead),
+          cache_metadata_(cache_metadata), limits_(limits), h2d_slots_(std::move(h2d_slots)),
+          shm_threshold_(shm_threshold), verify_shm_(verify_shm) {
+        for (auto* stub : workers_) {
+            limiters_[stub] = std::make_unique<InferLimiter>(max_concurrent, max_queued);
+        }
+    }
+    
+    grpc::Status ServerLive(
+        grpc::ServerContext* context,
+        const inference::ServerLiveRequest* request,
+        inference::ServerLiveResponse* response) override {
+        // 所有 Worker 都存活才算存活
+        for (auto* stub : workers_) {
+            grpc::ClientContext client_ctx;
+            grpc::Status status = stub->ServerLive(&client_ctx, *request, response);
+            if (!status.ok() || !response->live()) {
+                return status;
+            }
+        }
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ServerReady(
+        grpc::ServerContext* context,
+        const inference::ServerReadyRequest* request,
+        inference::ServerReadyResponse* response) override {
+        // 所有 Worker 都就绪才算就绪
+        for (auto* stub : workers_) {
+            grpc::ClientContext client_ctx;
+            grpc::Status status = stub->ServerReady(&client_ctx, *request, response);
+            if (!status.ok() || !response->ready()) {
+                return status;
+            }
+        }
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ModelReady(
+        grpc::ServerContext* context,
+        const inference::ModelReadyRequest* request,
+        inference::ModelReadyResponse* response) override {
+        Stub* stub = route(request->name());
+        if (!stub) {
+            return unknown_model(request->name());
+        }
+        grpc::ClientContext client_ctx;
+        return stub->ModelReady(&client_ctx, *request, response);
+    }
+    
+    grpc::Status ServerMetadata(
+        grpc::ServerContext* context,
+        const inference::ServerMetadataRequest* request,
+        inference::ServerMetadataResponse* response) override {
+        return forward_server_metadata(workers_.front(), *request, response);
+    }
+    
+    grpc::Status ModelMetadata(
+        grpc::ServerContext* context,
+        const inference::ModelMetadataRequest* request,
+        inference::ModelMetadataResponse* response) override {
+        Stub* stub = route(request->name());
+        if (!stub) {
+            return unknown_model(request->name());
+        }
+        return forward_model_metadata(stub, *request, response);
+    }
+    
+    /**
+     * Worker 就绪后预先拉取元数据：ServerMetadata，以及多模型模式下每个模型的
+     * ModelMetadata（单 Worker 模式不知道模型名，首次成功请求时再缓存）
+     */
+    void prefetch_metadata() {
+        inference::ServerMetadataResponse server_response;
+        grpc::Status status = forward_server_metadata(workers_.front(), {}, &server_response);
+        if (!status.ok()) {
+            std::cerr << "[Proxy] Warning: failed to prefetch server metadata: "
+                      << status.error_message() << std::endl;
+        }
+        for (const auto& [model, stub] : routes_) {
+            inference::ModelMetadataRequest request;
+            request.set_name(model);
+            inference::ModelMetadataResponse response;
+            status = forward_model_metadata(stub, request, &response);
+            if (!status.ok()) {
+                std::cerr << "[Proxy] Warning: failed to prefetch metadata for model " << model
+                          << ": " << status.error_message() << std::endl;
+            }
+        }
+    }
+    
+    grpc::Status ModelInfer(
+        grpc::ServerContext* context,
+        const inference::ModelInferRequest* request,
+        inference::ModelInferResponse* response) override {
+        Stub* stub = route(request->model_name());
+        if (!stub) {
+            return unknown_model(request->model_name());
+        }
+        if (worker_dead_) {
+            return worker_exited();
+        }
+        grpc::Status valid = validate_infer_request(*request, limits_);
+        if (!valid.ok()) {
+            return valid;
+        }
+        bool force_shm = false;
+        bool force_inline = false;
+        grpc::Status hints = read_shm_hints(*request, &force_shm, &force_inline);
+        if (!hints.ok()) {
+            return hints;
+        }
+        
+        // 排队时间计入总超时，不超过客户端自己的 deadline
+        auto deadline = std::min(context->deadline(), std::chrono::system_clock::now() + INFER_TIMEOUT);
+        InferLimiter& limiter = *limiters_.at(stub);
+        if (!limiter.acquire(deadline)) {
+            return grpc::Status(grpc::StatusCode::RESOURCE_EXHAUSTED,
+                                "Too many concurrent requests for model: " + request->model_name());
+        }
+        InferLimiter::Slot slot(limiter);
+        // 排队期间客户端已取消：不再转发给 Worker
+        if (context->IsCancelled()) {
+            return grpc::Status(grpc::StatusCode::CANCELLED, "Request cancelled by client");
+        }
+        
+        return infer_status_from_exceptions("Proxy", [&] {
+            auto h2d = h2d_slots_.find(stub);
+            inference::ModelInferRequest offloaded;
+            std::vector<H2dInput> sent;
+            auto use_shm = [&](size_t size) {
+                return force_shm || (!force_inline && size >= shm_threshold_);
+            };
+            const bool uses_h2d = h2d != h2d_slots_.end() &&
+                                  offload_inputs(*request, *h2d->second, use_shm, &offloaded, &sent);
+            
+            grpc::ClientContext client_ctx;
+            client_ctx.set_deadline(deadline);
+            // Worker 返回的错误（状态码、消息、error details）原样转发给客户端
+            grpc::Status status = forward_infer(stub, context, &client_ctx,
+                                                uses_h2d ? offloaded : *request, response);
+            grpc::Status verified = uses_h2d ? release_acked(*h2d->second, sent, response) : grpc::Status::OK;
+            // 转发途中 Worker 退出：返回明确的原因而不是底层连接错误
+            if (!status.ok() && worker_dead_) {
+                return worker_exited();
+            }
+            if (status.ok() && !verified.ok()) {
+                return verified;
+            }
+            if (status.ok() && h2d != h2d_slots_.end()) {
+                int64_t h2d_bytes = 0;
+                for (const auto& input : sent) {
+                    h2d_bytes += static_cast<int64_t>(input.len);
+                }
+                (*response->mutable_parameters())["h2d_bytes"].set_int64_param(h2d_bytes);
+            }
+            return status;
+        });
+    }
+    
+private:
+    Stub* route(const std::string& model) const {
+        if (routes_.empty()) {
+            return workers_.front();
+        }
+        auto it = routes_.find(model);
+        return it != routes_.end() ? it->second : nullptr;
+    }
+    
+    static grpc::Status unknown_model(const std::string& model) {
+        return grpc::Status(grpc::StatusCode::NOT_FOUND, "Unknown model: " + model);
+    }
+    
+    static grpc::Status worker_exited() {
+        return grpc::Status(grpc::StatusCode::UNAVAILABLE, "Worker process exited");
+    }
+    
+    /**
+     * H2dInput - 写入 H2D 槽的一个输入
+     */
+    struct H2dInput {
+        bool typed;         // true：inputs[index] 的 typed contents；false：raw_input_contents[index]
+        int index;
+        size_t offset;
+        size_t len;
+        uint64_t checksum;  // 仅 verify_shm 时计算
+        
+        std::string describe() const {
+            return (typed ? "typed contents of input " : "raw input ") + std::to_string(index);
+        }
+    };
+    
+    /**
+     * 把 use_shm 选中的输入写入 Worker 的 H2D 槽，构造转发给 Worker 的请求
+     *
+     * 两种编码都支持，位置以 "<下标>:<偏移>:<长度>" 列出（多项以逗号分隔）：
+     * - raw_input_contents 按原始字节写入，在 forwarded 中替换为空串（与 inputs 的
+     *   下标保持对应），位置写入请求参数 shm_raw
+     * - typed contents（fp32_contents 等）按 InferTensorContents 的 wire 格式写入
+     *   （字段号即数据类型，以序列化后的大小与阈值比较），forwarded 中该输入不带
+     *   contents，位置写入请求参数 shm_contents，下标为 inputs 中的下标
+     * Worker 从 H2D 读取这些输入，读完后在响应参数 h2d_ack 中回传 "<偏移>:<长度>"
+     * （见 release_acked）。verify_shm 时另有请求参数 shm_checksum：各输入的 FNV-1a 64
+     * 校验和（16 位十六进制），先按 shm_raw、再按 shm_contents 的顺序逐项对应。
+     * 超过整个 H2D 段或段中暂无空间的输入照常内联。
+     * @param use_shm 按输入大小决定是否经 H2D（阈值与请求的覆盖参数）
+     * @param sent 写入 H2D 的输入
+     * @return 是否构造了 forwarded；use_shm 没有选中任何输入时为 false，原样转发 request
+     */
+    template <typename UseShm>
+    bool offload_inputs(const inference::ModelInferRequest& request, H2dSlots& h2d, UseShm&& use_shm,
+                        inference::ModelInferRequest* forwarded, std::vector<H2dInput>* sent) const {
+        const auto& raw = request.raw_input_contents();
+        const auto& inputs = request.inputs();
+        auto typed_shm = [&](const inference::ModelInferRequest::InferInputTensor& input) {
+            return input.has_contents() && use_shm(input.contents().ByteSizeLong());
+        };
+        if (std::none_of(raw.begin(), raw.end(), [&](const std::string& data) { return use_shm(data.size()); }) &&
+            std::none_of(inputs.begin(), inputs.end(), typed_shm)) {
+            return false;
+        }
+        // 逐个字段拷贝，写入 H2D 的输入不必再拷贝一份
+        forwarded->set_model_name(request.model_name());
+        forwarded->set_model_version(request.model_version());
+        forwarded->set_id(request.id());
+        *forwarded->mutable_parameters() = request.parameters();
+        *forwarded->mutable_outputs() = request.outputs();
+        auto offload = [&](bool typed, int index, const std::string& data) {
+            const std::optional<size_t> offset = h2d.put(data);
+            if (offset) {
+                const uint64_t checksum = verify_shm_ ? fnv1a64(data.data(), data.size()) : 0;
+                sent->push_back(H2dInput{typed, index, *offset, data.size(), checksum});
+            }
+            return offset.has_value();
+        };
+        for (int i = 0; i < raw.size(); ++i) {
+            const std::string& data = raw.Get(i);
+            if (use_shm(data.size()) && offload(false, i, data)) {
+                forwarded->add_raw_input_contents();
+            } else {
+                forwarded->add_raw_input_contents(data);
+            }
+        }
+        for (int i = 0; i < inputs.size(); ++i) {
+            const auto& input = inputs.Get(i);
+            auto* copy = forwarded->add_inputs();
+            if (!typed_shm(input) || !offload(true, i, input.contents().SerializeAsString())) {
+                *copy = input;
+                continue;
+            }
+            copy->set_name(input.name());
+            copy->set_datatype(input.datatype());
+            *copy->mutable_shape() = input.shape();
+            *copy->mutable_parameters() = input.parameters();
+        }
+        std::string raw_entries;
+        std::string typed_entries;
+        std::string checksums;
+        for (const auto& input : *sent) {
+            std::string& entries = input.typed ? typed_entries : raw_entries;
+            entries += (entries.empty() ? "" : ",") + std::to_string(input.index) + ":" +
+                       std::to_string(input.offset) + ":" + std::to_string(input.len);
+        }
+        // shm_checksum 先列 raw 输入、再列 typed contents，与两个参数的顺序一致
+        for (bool typed : {false, true}) {
+            for (const auto& input : *sent) {
+                if (input.typed == typed) {
+                    checksums += (checksums.empty() ? "" : ",") + checksum_hex(input.checksum);
+                }
+            }
+        }
+        auto& params = *forwarded->mutable_parameters();
+        if (!raw_entries.empty()) {
+            params[SHM_RAW_PARAM].set_string_param(raw_entries);
+        }
+        if (!typed_entries.empty()) {
+            params[SHM_CONTENTS_PARAM].set_string_param(typed_entries);
+        }
+        if (verify_shm_ && !sent->empty()) {
+            params[SHM_CHECKSUM_PARAM].set_string_param(checksums);
+        }
+        return true;
+    }
+    
+    /**
+     * 按 Worker 响应参数 h2d_ack（"<偏移>:<长度>"，多项以逗号分隔）释放 H2D 槽，
+     * 并从响应中移除该参数。没有确认的槽留待超时后强制释放（见 H2dSlots）。
+     *
+     * verify_shm 时 Worker 还要在 h2d_checksum 中回传它从各槽读到的内容的校验和
+     * （与 h2d_ack 逐项对应），缺失或与发送时不一致返回 DATA_LOSS。
+     */
+    grpc::Status release_acked(H2dSlots& h2d, const std::vector<H2dInput>& sent,
+                               inference::ModelInferResponse* response) const {
+        auto& params = *response->mutable_parameters();
+        auto take = [&](const char* name) {
+            auto it = params.find(name);
+            if (it == params.end()) {
+                return std::string();
+            }
+            std::string value = it->second.string_param();
+            params.erase(it);
+            return value;
+        };
+        const std::string acks = take(H2D_ACK_PARAM);
+        const std::string received = take(H2D_CHECKSUM_PARAM);
+        const auto entries = parse_shm_entries(acks, 2);
+        if (!entries) {
+            std::cerr << "[Proxy] Warning: ignoring malformed " << H2D_ACK_PARAM << " from worker: "
+                      << acks << std::endl;
+        }
+        std::map<size_t, size_t> acked;  // 偏移 -> h2d_ack 中的序号
+        for (const auto& entry : entries.value_or(std::vector<std::vector<size_t>>{})) {
+            acked.emplace(entry[0], acked.size());
+            if (!h2d.ack(entry[0], entry[1])) {
+                std::cerr << "[Proxy] Warning: worker acknowledged unknown H2D slot at offset "
+                          << entry[0] << " (" << entry[1] << " bytes)" << std::endl;
+            }
+        }
+        if (!verify_shm_) {
+            return grpc::Status::OK;
+        }
+        std::vector<std::string> checksums;
+        std::istringstream items(received);
+        for (std::string item; std::getline(items, item, ',');) {
+            checksums.push_back(item);
+        }
+        for (const auto& input : sent) {
+            auto it = acked.find(input.offset);
+            if (it == acked.end() || it->second >= checksums.size() ||
+                checksums[it->second] != checksum_hex(input.checksum)) {
+                std::cerr << "[Proxy] SHM checksum mismatch on " << input.describe() << std::endl;
+                return grpc::Status(grpc::StatusCode::DATA_LOSS, "SHM checksum mismatch on " + input.describe());
+            }
+        }
+        return grpc::Status::OK;
+    }
+    
+    /**
+     * 转发 ModelInfer；客户端取消时一并取消发往 Worker 的调用
+     *
+     * 同步 API 的 stub 调用会一直阻塞到 Worker 返回或 deadline，期间无法察觉客户端
+     * 已断开，Worker 白白算完、并发名额也一直被占用。这里改用 stub 的回调 API 发出
+     * 调用，等待结果时每隔 CANCEL_POLL_INTERVAL 检查 IsCancelled，取消后对转发的
+     * 调用 TryCancel：Worker 侧的 context 随即失效，调用以 CANCELLED 结束
+     */
+    static grpc::Status forward_infer(Stub* stub, grpc::ServerContext* context,
+                                      grpc::ClientContext* client_ctx,
+                                      const inference::ModelInferRequest& request,
+                                      inference::ModelInferResponse* response) {
+        std::mutex mutex;
+        std::condition_variable cv;
+        bool done = false;
+        grpc::Status status;
+        stub->async()->ModelInfer(client_ctx, &request, response, [&](grpc::Status result) {
+            // 持锁通知：等待方返回（cv 随之析构）前回调一定已不再访问它们
+            std::lock_guard<std::mutex> lock(mutex);
+            status = std::move(result);
+            done = true;
+            cv.notify_one();
+        });
+        std::unique_lock<std::mutex> lock(mutex);
+        bool cancelled = false;
+        while (!cv.wait_for(lock, CANCEL_POLL_INTERVAL, [&] { return done; })) {
+            if (!cancelled && context->IsCancelled()) {
+                client_ctx->TryCancel();
+                cancelled = true;
+            }
+        }
+        return status;
+    }
+    
+    grpc::Status forward_server_metadata(Stub* stub, const inference::ServerMetadataRequest& request,
+                                         inference::ServerMetadataResponse* response) {
+        grpc::ClientContext client_ctx;
+        grpc::Status status = stub->ServerMetadata(&client_ctx, request, response);
+        return cache_metadata_ ? remember_or_fallback(status, server_metadata_, stub, response) : status;
+    }
+    
+    grpc::Status forward_model_metadata(Stub* stub, const inference::ModelMetadataRequest& request,
+                                        inference::ModelMetadataResponse* response) {
+        grpc::ClientContext client_ctx;
+        grpc::Status status = stub->ModelMetadata(&client_ctx, request, response);
+        if (!cache_metadata_) {
+            return status;
+        }
+        return remember_or_fallback(status, model_metadata_,
+                                    std::make_pair(stub, request.name() + "\n" + request.version()),
+                                    response);
+    }
+    
+    /**
+     * 转发成功时更新缓存；Worker 不可达（UNAVAILABLE）且有缓存时返回缓存，
+     * 其他错误（如 NOT_FOUND）原样返回
+     */
+    template <typename Key, typename Response>
+    grpc::Status remember_or_fallback(const grpc::Status& status, std::map<Key, Response>& cache,
+                                      const Key& key, Response* response) {
+        std::lock_guard<std::mutex> lock(metadata_mutex_);
+        if (status.ok()) {
+            cache[key] = *response;
+        } else if (status.error_code() == grpc::StatusCode::UNAVAILABLE) {
+            auto it = cache.find(key);
+            if (it != cache.end()) {
+                *response = it->second;
+                return grpc::Status::OK;
+            }
+        }
+        return status;
+    }
+    
+    std::vector<Stub*> workers_;
+    std::map<std::string, Stub*> routes_;
+    const std::atomic<bool>& worker_dead_;
+    std::map<Stub*, std::unique_ptr<InferLimiter>> limiters_;
+    const bool cache_metadata_;
+    const InferLimits limits_;
+    const std::map<Stub*, H2dSlots*> h2d_slots_;
+    const size_t shm_threshold_;
+    const bool verify_shm_;
+    std::mutex metadata_mutex_;
+    std::map<Stub*, inference::ServerMetadataResponse> server_metadata_;
+    // key: (Worker, "<model>\n<version>")
+    std::map<std::pair<Stub*, std::string>, inference::ModelMetadataResponse> model_metadata_;
+};
+
+/**
+ * EchoService - 不派生 Worker，把输入原样作为输出返回（--echo）
+ *
+ * raw_input_contents 按 SHM 段大小分块，依次写入 H2D、拷贝到 D2H、再从 D2H
+ * 读出，以便在没有 Python 进程和模型的情况下测试 SHM 与 gRPC 链路。
+ * 只有一对 SHM 段，各输入的 SHM 往返串行执行；其余处理在锁外并发。
+ *
+ * 小于 shm_threshold 的输入直接内联拷贝；请求参数 __force_shm__ / __inline__
+ * （bool）覆盖这一选择。经 SHM 传输的字节数写入响应参数 shm_bytes。
+ * 两种编码都支持：raw_input_contents 按原始字节传输；typed contents（fp32_contents
+ * 等）按 InferTensorContents 的 wire 格式传输（以其序列化后的大小与阈值比较），
+ * 输出与输入的编码一致。
+ *
+ * verify_shm（--verify-shm）时对写入 H2D 的字节和从 D2H 读出的字节分别计算
+ * 校验和，不一致返回 DATA_LOSS；一致时 raw 输入的校验和写入响应参数 shm_checksum
+ * （typed contents 只比较，不报告）。同时把从 D2H 到 raw 输出的拷贝字节数（含
+ * 输出缓冲区重新分配时的搬移）写入 shm_output_copy_bytes，只拷贝一次时等于经
+ * SHM 传输的 raw 字节数。
+ *
+ * 与 ProxyService 一样，请求先经 validate_infer_request 检查，再写入 SHM。
+ */
+class EchoService final : public inference::GRPCInferenceService::Service {
+public:
+    EchoService(anyserve::ShmManager::RawShm& h2d, anyserve::ShmManager::RawShm& d2h,
+                size_t shm_threshold, bool verify_shm, InferLimits limits)
+        : h2d_(h2d), d2h_(d2h), shm_threshold_(shm_threshold), verify_shm_(verify_shm),
+          limits_(limits) {}
+    
+    grpc::Status ServerLive(
+        grpc::ServerContext* context,
+        const inference::ServerLiveRequest* request,
+        inference::ServerLiveResponse* response) override {
+        response->set_live(true);
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ServerReady(
+        grpc::ServerContext* context,
+        const inference::ServerReadyRequest* request,
+        inference::ServerReadyResponse* response) override {
+        response->set_ready(true);
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ModelReady(
+        grpc::ServerContext* context,
+        const inference::ModelReadyRequest* request,
+        inference::ModelReadyResponse* response) override {
+        response->set_ready(true);
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ServerMetadata(
+        grpc::ServerContext* context,
+        const inference::ServerMetadataRequest* request,
+        inference::ServerMetadataResponse* response) override {
+        response->set_name("anyserve-echo");
+        response->set_version("0.1.0");
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ModelMetadata(
+        grpc::ServerContext* context,
+        const inference::ModelMetadataRequest* request,
+        inference::ModelMetadataResponse* response) override {
+        response->set_name(request->name());
+        response->set_platform("echo");
+        return grpc::Status::OK;
+    }
+    
+    grpc::Status ModelInfer(
+        grpc::ServerContext* context,
+        const inference::ModelInferRequest* request,
+        inference::ModelInferResponse* response) override {
+        return infer_status_from_exceptions("Echo", [&] { return echo(request, response); });
+    }
+    
+private:
+    grpc::Status echo(const inference::ModelInferRequest* request, inference::ModelInferResponse* response) {
+        grpc::Status valid = validate_infer_request(*request, limits_);
+        if (!valid.ok()) {
+            return valid;
+        }
+        bool force_shm = false;
+        bool force_inline = false;
+        grpc::Status hints = read_shm_hints(*request, &force_shm, &force_inline);
+        if (!hints.ok()) {
+            return hints;
+        }
+        
+        response->set_model_name(request->model_name());
+        response->set_model_version(request->model_version());
+        response->set_id(request->id());
+        
+        auto use_shm = [&](size_t size) { return force_shm || (!force_inline && size >= shm_threshold_); };
+        int64_t shm_bytes = 0;
+        
+        // 输入张量的描述原样作为输出。typed contents 序列化为 InferTensorContents
+        // 的 wire 格式（字段号即数据类型）；达到阈值时同样经 SHM 往返，再解析为输出的
+        // contents。两侧是不同的消息类型，但字段定义一致，wire 格式可直接互转
+        for (const auto& input : request->inputs()) {
+            auto* output = response->add_outputs();
+            output->set_name(input.name());
+            output->set_datatype(input.datatype());
+            *output->mutable_shape() = input.shape();
+            if (!input.has_contents()) {
+                continue;
+            }
+            const std::string typed = input.contents().SerializeAsString();
+            const bool via_shm = use_shm(typed.size());
+            std::string received;
+            if (via_shm) {
+                grpc::Status status = round_trip(typed, &received);
+                if (!status.ok()) {
+                    return status;
+                }
+                shm_bytes += static_cast<int64_t>(typed.size());
+                if (verify_shm_ && received != typed) {
+                    std::cerr << "[Echo] SHM mismatch on typed contents of " << input.name() << std::endl;
+                    return grpc::Status(grpc::StatusCode::DATA_LOSS,
+                                        "SHM mismatch on typed contents of input " + input.name());
+                }
+            }
+            if (!output->mutable_contents()->ParseFromString(via_shm ? received : typed)) {
+                return grpc::Status(grpc::StatusCode::INTERNAL, "Failed to copy tensor contents");
+            }
+        }
+        
+        std::string checksums;
+        size_t output_copy_bytes = 0;
+        for (const auto& raw : request->raw_input_contents()) {
+            std::string* out = response->add_raw_output_contents();
+            if (!use_shm(raw.size())) {
+                out->assign(raw);
+            } else {
+                grpc::Status status = round_trip(raw, out, &output_copy_bytes);
+                if (!status.ok()) {
+                    return status;
+                }
+                shm_bytes += static_cast<int64_t>(raw.size());
+            }
+            if (verify_shm_) {
+                // 内联的输入同样计算，shm_checksum 与 raw 输出一一对应
+                const uint64_t sent = fnv1a64(raw.data(), raw.size());
+                const uint64_t received = fnv1a64(out->data(), out->size());
+                if (sent != received) {
+                    const int index = response->raw_output_contents_size() - 1;
+                    std::cerr << "[Echo] SHM checksum mismatch on raw output " << index << std::endl;
+                    return grpc::Status(grpc::StatusCode::DATA_LOSS,
+                                        "SHM checksum mismatch on raw output " + std::to_string(index));
+                }
+                checksums += (checksums.empty() ? "" : ",") + checksum_hex(sent);
+            }
+        }
+        (*response->mutable_parameters())["shm_bytes"].set_int64_param(shm_bytes);
+        if (verify_shm_) {
+            (*response->mutable_parameters())["shm_checksum"].set_string_param(checksums);
+            (*response->mutable_parameters())["shm_output_copy_bytes"].set_int64_param(
+                static_cast<int64_t>(output_copy_bytes));
+        }
+        return grpc::Status::OK;
+    }
+    
+    /**
+     * 把 data 依次写入 H2D、拷贝到 D2H、再从 D2H 读出追加到 out
+     *
+     * D2H 的数据直接追加到 out（如响应的 bytes 字段），只拷贝一次：protobuf 的 bytes
+     * 字段必须持有自己的内存，无法借用 SHM 映射区。超过 SHM 段大小的数据按段大小
+     * 分块往返，每块都经 region() 做越界检查，不会像按偏移回绕那样越过映射区末尾
+     *
+     * @param copied 非空时累加写入 out 的拷贝字节数：从 D2H 读出的字节，加上 out
+     *               重新分配时搬移的已有内容
+     */
+    grpc::Status round_trip(const std::string& data, std::string* out, size_t* copied = nullptr) {
+        const size_t chunk = std::min(h2d_.size, d2h_.size);
+        if (chunk == 0) {
+            return grpc::Status(grpc::StatusCode::INTERNAL, "SHM segments are not mapped");
+        }
+        // 会分配内存（可能抛出）的操作都放在锁外；持锁期间只有 memcpy 和
+        // 容量之内的 append，一个请求失败不会让 SHM 停在写了一半的状态
+        out->reserve(out->size() + data.size());
+        std::lock_guard<std::mutex> lock(mutex_);
+        for (size_t offset = 0; offset < data.size(); offset += chunk) {
+            const size_t len = std::min(chunk, data.size() - offset);
+            void* h2d = h2d_.region(0, len);
+            void* d2h = d2h_.region(0, len);
+            if (!h2d || !d2h) {
+                return grpc::Status(grpc::StatusCode::INTERNAL, "SHM region out of bounds");
+            }
+            std::memcpy(h2d, data.data() + offset, len);
+            h2d_.record_write(0, len);
+            std::memcpy(d2h, h2d, len);
+            d2h_.record_write(0, len);
+            const char* before = out->data();
+            out->append(static_cast<const char*>(d2h), len);
+            if (copied) {
+                *copied += len + (out->data() != before ? out->size() - len : 0);
+            }
+        }
+        return grpc::Status::OK;
+    }
+    
+    anyserve::ShmManager::RawShm& h2d_;
+    anyserve::ShmManager::RawShm& d2h_;
+    size_t shm_threshold_;
+    bool verify_shm_;
+    InferLimits limits_;
+    std::mutex mutex_;
+};
+
 } // anonymous namespace
 
 int main(int argc, char** argv) {
     // 解析命令行参数
     std::string app_target;
     int port = 8080;
+    std::string listen_path;  // --listen unix:PATH，非空时代替 --port
+    int health_port = 0;
+    int report_fd = -1;
+    anyserve::ShmManager::Options shm_options;
+    std::string shm_keep_prefix;  // 调试用，见 --shm-keep-name
+    anyserve::WorkerTransport worker_transport = anyserve::WorkerTransport::UDS;
+    std::string worker_addr;
+    std::string uds_dir;
+    std::map<std::string, std::string> models;  // model name -> APP_TARGET
+    std::map<std::string, std::string> worker_env;
+    std::vector<std::string> worker_args;
+    anyserve::WorkerOutput worker_output = anyserve::WorkerOutput::INHERIT;
+    std::string worker_log_path;
+    int max_message_mb = DEFAULT_MAX_MESSAGE_MB;
+    int max_concurrent_infers = DEFAULT_MAX_CONCURRENT_INFERS;
+    int max_queued_infers = DEFAULT_MAX_QUEUED_INFERS;
+    InferLimits infer_limits;
+    bool check_only = false;
+    bool cache_metadata = false;
+    bool echo_mode = false;
+    bool verify_shm = false;
+    std::optional<size_t> shm_threshold;
+    std::chrono::milliseconds shm_ack_timeout = DEFAULT_SHM_ACK_TIMEOUT;
+    grpc_compression_algorithm compression = GRPC_COMPRESS_NONE;
+    std::chrono::milliseconds worker_timeout = DEFAULT_WORKER_TIMEOUT;
+    if (const char* env = std::getenv("ANYSERVE_WORKER_TIMEOUT")) {
+        auto parsed = parse_duration(env);
+        if (!parsed) {
+            std::cerr << "[main] Invalid ANYSERVE_WORKER_TIMEOUT: " << env << std::endl;
+            return 1;
+        }
+        worker_timeout = *parsed;
+    }
     
     for (int i = 1; i < argc; ++i) {
         std::string arg = argv[i];
@@ -56,159 +1450,620 @@ int main(int argc, char** argv) {
             return 0;
         } else if (arg == "--port" && i + 1 < argc) {
             port = std::stoi(argv[++i]);
+        } else if (arg == "--listen" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value.rfind("unix:", 0) != 0 || value.size() == 5) {
+                std::cerr << "[main] --listen expects unix:PATH, got: " << value << std::endl;
+                return 1;
+            }
+            listen_path = value.substr(5);
+            if (listen_path.size() > MAX_UDS_PATH) {
+                std::cerr << "[main] --listen path is too long for a socket path (limit "
+                          << MAX_UDS_PATH << " bytes): " << listen_path << std::endl;
+                return 1;
+            }
+        } else if (arg == "--health-port" && i + 1 < argc) {
+            health_port = std::stoi(argv[++i]);
+        } else if (arg == "--report-fd" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value.empty() || !std::all_of(value.begin(), value.end(), ::isdigit)) {
+                std::cerr << "[main] --report-fd must be a file descriptor, got: " << value << std::endl;
+                return 1;
+            }
+            report_fd = std::stoi(value);
+            // 不让 Worker 继承：否则 Worker 持有写端，读端等不到 EOF
+            if (::fcntl(report_fd, F_SETFD, FD_CLOEXEC) < 0) {
+                std::cerr << "[main] --report-fd " << report_fd << " is not open" << std::endl;
+                return 1;
+            }
+        } else if (arg == "--worker-transport" && i + 1 < argc) {
+            std::string value = argv[++i];
+            if (value == "tcp") {
+                worker_transport = anyserve::WorkerTransport::TCP;
+            } else if (value == "uds") {
+                worker_transport = anyserve::WorkerTransport::UDS;
+            } else if (value == "uds-abstract") {
+#ifdef __linux__
+                worker_transport = anyserve::WorkerTransport::UDS_ABSTRACT;
+#else
+                std::cerr << "[main] uds-abstract is only supported on Linux" << std::endl;
+                return 1;
+#endif
+            } else {
+                std::cerr << "[main] Unknown worker transport: " << value << std::endl;
+                return 1;
+            }
+        } else if (arg == "--worker-addr" && i + 1 < argc) {
+            worker_addr = argv[++i];
+        } else if (arg == "--uds-dir" && i + 1 < argc) {
+            uds_dir = argv[++i];
+        } else if (arg == "--worker-timeout" && i + 1 < argc) {
+            std::string value = argv[++i];
+            auto parsed = parse_duration(value);
+            if (!parsed) {
+                std::cerr << "[main] Invalid --worker-timeout: " << value
+                          << " (expected e.g. 30s, 5m, 1h)" << std::endl;
+                return 1;
+            }
+            worker_timeout = *parsed;
+        } else if (arg == "--worker-env" && i + 1 < argc) {
+            std::string value = argv[++i];
+            auto eq = value.find('=');
+            const std::string key = value.substr(0, eq);
+            if (eq == std::string::npos || !is_env_name(key)) {
+                std::cerr << "[main] --worker-env expects KEY=VALUE with KEY matching"
+                          << " [A-Za-z_][A-Za-z0-9_]*, got: " << value << std::endl;
+                return 1;
+            }
+            if (key.rfind("ANSERVE_", 0) == 0) {
+                std::cerr << "[main] --worker-env cannot set reserved variable " << key << std::endl;
+                return 1;
+            }
+            worker_env[key] = value.substr(eq + 1);
+        } else if (arg == "--worker-arg" && i + 1 < argc) {
+            worker_args.push_back(argv[++i]);
+        } else if (arg == "--worker-output" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value == "inherit") {
+                worker_output = anyserve::WorkerOutput::INHERIT;
+            } else if (value == "prefix") {
+                worker_output = anyserve::WorkerOutput::PREFIX;
+            } else if (value.rfind("file:", 0) == 0 && value.size() > 5) {
+                worker_output = anyserve::WorkerOutput::FILE;
+                worker_log_path = value.substr(5);
+            } else {
+                std::cerr << "[main] --worker-output must be inherit, prefix or file:PATH, got: "
+                          << value << std::endl;
+                return 1;
+            }
+        } else if (arg == "--max-message-size" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            const auto parsed = parse_count(value);
+            // 以字节计算时不能超过 int 上限
+            if (!parsed || *parsed == 0 || *parsed > 2047) {
+                std::cerr << "[main] --max-message-size must be between 1 and 2047 MB, got: "
+                          << value << std::endl;
+                return 1;
+            }
+            max_message_mb = *parsed;
+        } else if (arg == "--compression" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value == "none") {
+                compression = GRPC_COMPRESS_NONE;
+            } else if (value == "gzip") {
+                compression = GRPC_COMPRESS_GZIP;
+            } else if (value == "deflate") {
+                compression = GRPC_COMPRESS_DEFLATE;
+            } else {
+                std::cerr << "[main] --compression must be none, gzip or deflate, got: " << value
+                          << std::endl;
+                return 1;
+            }
+        } else if (arg == "--max-concurrent-infers" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            const auto parsed = parse_count(value);
+            if (!parsed || *parsed == 0) {
+                std::cerr << "[main] --max-concurrent-infers must be a positive integer, got: "
+                          << value << std::endl;
+                return 1;
+            }
+            max_concurrent_infers = *parsed;
+        } else if (arg == "--max-queued-infers" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            const auto parsed = parse_count(value);
+            if (!parsed) {
+                std::cerr << "[main] --max-queued-infers must be a non-negative integer, got: "
+                          << value << std::endl;
+                return 1;
+            }
+            max_queued_infers = *parsed;
+        } else if ((arg == "--max-input-bytes" || arg == "--max-inputs") && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value.empty() || !std::all_of(value.begin(), value.end(), ::isdigit)) {
+                std::cerr << "[main] " << arg << " must be a non-negative integer, got: " << value
+                          << std::endl;
+                return 1;
+            }
+            if (arg == "--max-inputs") {
+                infer_limits.max_inputs = std::stoull(value);
+            } else {
+                infer_limits.max_input_bytes = std::stoull(value);
+            }
+        } else if (arg == "--model" && i + 1 < argc) {
+            std::string value = argv[++i];
+            auto eq = value.find('=');
+            if (eq == 0 || eq == std::string::npos || eq + 1 == value.size()) {
+                std::cerr << "[main] --model expects NAME=APP_TARGET, got: " << value << std::endl;
+                return 1;
+            }
+            if (!models.emplace(value.substr(0, eq), value.substr(eq + 1)).second) {
+                std::cerr << "[main] Duplicate --model: " << value.substr(0, eq) << std::endl;
+                return 1;
+            }
+        } else if (arg == "--shm-mlock") {
+            shm_options.lock_memory = true;
+        } else if (arg == "--shm-hugepages") {
+            shm_options.huge_pages = true;
+        } else if (arg == "--shm-keep-name" && i + 1 < argc) {
+            shm_keep_prefix = argv[++i];
+            // 加上 "/" 和 "_<i>_h2d" 后仍在 macOS 的 30 字节名称上限内
+            const bool valid = !shm_keep_prefix.empty() && shm_keep_prefix.size() <= 20 &&
+                std::all_of(shm_keep_prefix.begin(), shm_keep_prefix.end(), [](unsigned char c) {
+                    return std::isalnum(c) || c == '_' || c == '-' || c == '.';
+                });
+            if (!valid) {
+                std::cerr << "[main] --shm-keep-name expects 1-20 characters of [A-Za-z0-9_.-], got: "
+                          << shm_keep_prefix << std::endl;
+                return 1;
+            }
+        } else if (arg == "--cache-metadata") {
+            cache_metadata = true;
+        } else if (arg == "--check") {
+            check_only = true;
+        } else if (arg == "--echo") {
+            echo_mode = true;
+        } else if (arg == "--verify-shm") {
+            verify_shm = true;
+        } else if (arg == "--shm-threshold" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            if (value.empty() || !std::all_of(value.begin(), value.end(), ::isdigit)) {
+                std::cerr << "[main] --shm-threshold must be a byte count, got: " << value << std::endl;
+                return 1;
+            }
+            shm_threshold = std::stoull(value);
+        } else if (arg == "--shm-ack-timeout" && i + 1 < argc) {
+            const std::string value = argv[++i];
+            auto parsed = parse_duration(value);
+            if (!parsed) {
+                std::cerr << "[main] Invalid --shm-ack-timeout: " << value
+                          << " (expected e.g. 500ms, 30s)" << std::endl;
+                return 1;
+            }
+            shm_ack_timeout = *parsed;
         } else if (!arg.empty() && arg[0] != '-') {
             app_target = arg;
         }
     }
     
+    const bool use_tcp = worker_transport == anyserve::WorkerTransport::TCP;
+    const bool multi_model = !models.empty();
+    if (multi_model && (use_tcp || !app_target.empty())) {
+        std::cerr << "[main] --model cannot be combined with APP_TARGET or --worker-transport tcp"
+                  << std::endl;
+        return 1;
+    }
+    if (echo_mode && (multi_model || use_tcp || !app_target.empty() || check_only)) {
+        std::cerr << "[main] --echo cannot be combined with APP_TARGET, --model, --check"
+                  << " or --worker-transport tcp" << std::endl;
+        return 1;
+    }
+    if (use_tcp && worker_addr.empty()) {
+        std::cerr << "[main] --worker-transport tcp requires --worker-addr HOST:PORT" << std::endl;
+        return 1;
+    }
+    if (uds_dir.empty()) {
+        const char* tmpdir = std::getenv("TMPDIR");
+        uds_dir = tmpdir && *tmpdir ? tmpdir : "/tmp";
+    }
+    while (uds_dir.size() > 1 && uds_dir.back() == '/') {
+        uds_dir.pop_back();
+    }
+    if (worker_transport == anyserve::WorkerTransport::UDS && !echo_mode) {
+        struct stat st;
+        if (::stat(uds_dir.c_str(), &st) != 0 || !S_ISDIR(st.st_mode)) {
+            std::cerr << "[main] UDS directory " << uds_dir << " does not exist" << std::endl;
+            return 1;
+        }
+        // 提前报错，而不是让 Worker 以难以理解的 bind 错误退出
+        if (make_uds_path(uds_dir).empty()) {
+            std::cerr << "[main] UDS directory " << uds_dir << " is too long for a socket path (limit "
+                      << MAX_UDS_PATH << " bytes); pass a shorter --uds-dir or use"
+                      << " --worker-transport uds-abstract/tcp" << std::endl;
+            return 1;
+        }
+    }
+    
+    // 对外监听地址：--listen 指定的 UDS，或 --port 上的 TCP
+    const std::string server_address =
+        listen_path.empty() ? "0.0.0.0:" + std::to_string(port) : "unix:" + listen_path;
+    
     // 设置信号处理
     std::signal(SIGINT, signal_handler);
     std::signal(SIGTERM, signal_handler);
     
+    // 任一派生的 Worker 退出即置位（由 ProcessSupervisor 的退出监视线程设置），
+    // 健康检查、启动等待、转发中的请求和主循环都以此为准。
+    // 声明在 slots 之前，保证 Worker 被停止（回调触发）时它们仍然有效
+    std::atomic<bool> worker_dead{false};
+    std::mutex worker_exit_mutex;
+    std::condition_variable worker_exit_cv;
+    std::vector<std::unique_ptr<WorkerSlot>> slots;
+    auto cleanup_sockets = [&slots]() {
+        for (auto& slot : slots) {
+            if (!slot->uds_path.empty()) {
+                std::remove(slot->uds_path.c_str());
+                std::remove(slot->pid_marker.c_str());
+            }
+        }
+    };
+    
     try {
-        // 1. 创建 SHM
-        auto shm_h2d = anyserve::ShmManager::create(10 * 1024 * 1024);
-        auto shm_d2h = anyserve::ShmManager::create(10 * 1024 * 1024);
-        std::cout << "[main] Created SHM. H2D_FD=" << shm_h2d.fd 
-                  << ", D2H_FD=" << shm_d2h.fd << std::endl;
-        
-        // 2. 生成随机 UDS 路径
-        std::srand(static_cast<unsigned>(std::time(nullptr)));
-        std::string uds_path = "/tmp/anyserve_" + std::to_string(std::rand()) + ".sock";
-        std::cout << "[main] Using UDS path: " << uds_path << std::endl;
-        
-        // 3. 派生 Python Worker
         std::string python_path = std::getenv("PYTHON_PATH") ? std::getenv("PYTHON_PATH") : "python";
         std::string worker_module = "anyserve_worker.loader";
+        std::srand(static_cast<unsigned>(std::time(nullptr)));
         
-        anyserve::ProcessSupervisor supervisor(python_path, worker_module);
-        
-        std::vector<std::string> extra_args;
-        if (!app_target.empty()) {
-            extra_args.push_back(app_target);
+        // 1. 规划 Worker：多模型模式每个模型一个 Worker，否则一个
+        if (multi_model) {
+            for (const auto& [model, target] : models) {
+                auto slot = std::make_unique<WorkerSlot>();
+                slot->model = model;
+                slot->app_target = target;
+                slots.push_back(std::move(slot));
+            }
+        } else {
+            auto slot = std::make_unique<WorkerSlot>();
+            slot->app_target = app_target;
+            slots.push_back(std::move(slot));
         }
         
-        supervisor.spawn(uds_path, shm_h2d.fd, shm_d2h.fd, extra_args);
-        std::cout << "[main] Worker spawned. Waiting for ready..." << std::endl;
+        for (size_t slot_index = 0; slot_index < slots.size(); ++slot_index) {
+            auto& slot = slots[slot_index];
+            // TCP 且未指定 APP_TARGET 时连接外部 Worker（可能在其他主机/容器），不派生进程
+            slot->spawned = !echo_mode && (!use_tcp || !slot->app_target.empty());
+            slot->supervisor = std::make_unique<anyserve::ProcessSupervisor>(python_path, worker_module);
+            const std::string label = slot->model.empty() ? "" : " [" + slot->model + "]";
+            
+            // 2. 创建 SHM（fd 只能传给本机派生的 Worker，外部 Worker 回退为内联传输）
+            if (slot->spawned || echo_mode) {
+                auto h2d_options = shm_options;
+                auto d2h_options = shm_options;
+                if (!shm_keep_prefix.empty()) {
+                    const std::string base = shm_keep_prefix +
+                        (multi_model ? "_" + std::to_string(slot_index) : "");
+                    h2d_options.keep_name = base + "_h2d";
+                    d2h_options.keep_name = base + "_d2h";
+                }
+                slot->shm_h2d = anyserve::ShmManager::create(10 * 1024 * 1024, h2d_options);
+                slot->shm_d2h = anyserve::ShmManager::create(10 * 1024 * 1024, d2h_options);
+                std::cout << "[main]" << label << " Created SHM. H2D_FD=" << slot->shm_h2d.fd 
+                          << ", D2H_FD=" << slot->shm_d2h.fd << std::endl;
+                if (!shm_keep_prefix.empty()) {
+                    std::cerr << "[main]" << label << " Warning: debug --shm-keep-name, SHM kept at"
+                              << " /dev/shm" << slot->shm_h2d.name << " and /dev/shm"
+                              << slot->shm_d2h.name << " until exit" << std::endl;
+                }
+            } else {
+                std::cout << "[main] External worker, SHM offload disabled" << std::endl;
+            }
+            
+            // 3. 确定 Worker 地址（UDS 使用随机路径）
+            if (echo_mode) {
+                std::cout << "[main] Echo mode, no worker" << std::endl;
+            } else if (use_tcp) {
+                slot->address = worker_addr;
+                std::cout << "[main] Using TCP worker address: " << worker_addr << std::endl;
+            } else if (worker_transport == anyserve::WorkerTransport::UDS_ABSTRACT) {
+                // 名称带上 pid 避免与其他实例冲突；无文件，也就无需 pid 标记和清理
+                slot->worker_name = "anyserve_" + std::to_string(getpid()) + "_" +
+                                    std::to_string(std::rand());
+                slot->address = "unix-abstract:" + slot->worker_name;
+                std::cout << "[main]" << label << " Using abstract UDS: @" << slot->worker_name
+                          << std::endl;
+            } else {
+                slot->uds_path = make_uds_path(uds_dir);
+                slot->worker_name = slot->uds_path;
+                slot->address = "unix://" + slot->uds_path;
+                std::cout << "[main]" << label << " Using UDS path: " << slot->uds_path << std::endl;
+                
+                // pid 标记：进程崩溃后 reaper 据此判断 socket 是否可清理
+                slot->pid_marker = slot->uds_path + ".pid";
+                std::ofstream(slot->pid_marker) << getpid();
+            }
+        }
         
-        if (!supervisor.wait_for_ready(10)) {
-            std::cerr << "[main] Worker failed to start within timeout" << std::endl;
-            return 1;
+        // 代理自身的健康检查：所有 Worker 连接并且 gRPC 服务启动后才算就绪
+        std::atomic<bool> proxy_ready{false};
+        auto start_time = std::chrono::steady_clock::now();
+        auto workers_alive = [&worker_dead]() { return !worker_dead.load(); };
+        std::unique_ptr<anyserve::HealthServer> health_server;
+        if (health_port > 0 && !check_only) {
+            health_server = std::make_unique<anyserve::HealthServer>(health_port, [&]() {
+                anyserve::HealthServer::WorkerState state;
+                state.pid = slots.front()->supervisor->get_pid();
+                state.listen = server_address;
+                state.ready = proxy_ready.load() && workers_alive();
+                state.uptime_seconds = std::chrono::duration<double>(
+                    std::chrono::steady_clock::now() - start_time).count();
+                return state;
+            });
+            health_server->start();
         }
-        std::cout << "[main] Worker ready." << std::endl;
         
-        // 4. 连接到 Worker
-        std::string worker_address = "unix://" + uds_path;
-        auto channel = grpc::CreateChannel(worker_address, grpc::InsecureChannelCredentials());
-        auto stub = inference::GRPCInferenceService::NewStub(channel);
+        // 4. 派生所有 Worker，再等待全部就绪
+        for (auto& slot : slots) {
+            if (!slot->spawned) {
+                continue;
+            }
+            std::vector<std::string> extra_args;
+            if (!slot->app_target.empty()) {
+                extra_args.push_back(slot->app_target);
+            }
+            extra_args.insert(extra_args.end(), worker_args.begin(), worker_args.end());
+            slot->supervisor->set_extra_env(worker_env);
+            slot->supervisor->set_output(worker_output, worker_log_path);
+            if (!slot->model.empty()) {
+                slot->supervisor->set_output_prefix("[worker " + slot->model + "] ");
+            }
+            slot->supervisor->set_exit_callback([&]() {
+                {
+                    std::lock_guard<std::mutex> lock(worker_exit_mutex);
+                    worker_dead = true;
+                }
+                worker_exit_cv.notify_all();
+            });
+            slot->supervisor->spawn(worker_transport, use_tcp ? worker_addr : slot->worker_name,
+                                    slot->shm_h2d.fd, slot->shm_d2h.fd, extra_args);
+        }
+        if (!echo_mode) {
+            std::cout << "[main] Worker spawned. Waiting for ready..." << std::endl;
+        }
         
-        // 等待 channel 连接就绪
-        auto deadline = std::chrono::system_clock::now() + std::chrono::seconds(5);
-        if (!channel->WaitForConnected(deadline)) {
-            std::cerr << "[main] Failed to connect to worker" << std::endl;
-            return 1;
+        for (auto& slot : slots) {
+            if (slot->spawned && !slot->supervisor->wait_for_ready(worker_timeout)) {
+                std::cerr << "[main] Worker" << (slot->model.empty() ? "" : " for " + slot->model)
+                          << " failed to start (timeout " << worker_timeout.count() << "ms)"
+                          << std::endl;
+                cleanup_sockets();
+                return 1;
+            }
+            std::string shm_error;
+            if (slot->spawned && slot->shm_h2d.fd >= 0 && !check_shm_sizes(*slot, &shm_error)) {
+                std::cerr << "[main] Worker" << (slot->model.empty() ? "" : " for " + slot->model)
+                          << " failed to start: " << shm_error << std::endl;
+                cleanup_sockets();
+                return 1;
+            }
+            // 声明 shm_inputs=1 的 Worker 从 H2D 读取大输入（见 ProxyService::offload_inputs）
+            if (slot->spawned && slot->shm_h2d.fd >= 0 && slot->supervisor->reported("shm_inputs") == "1") {
+                slot->h2d_slots = std::make_unique<H2dSlots>(slot->shm_h2d, shm_ack_timeout);
+                std::cout << "[main] Worker" << (slot->model.empty() ? "" : " for " + slot->model)
+                          << " reads large inputs from H2D SHM" << std::endl;
+            }
+            // Worker 可在就绪消息中报告自己实际监听的地址，覆盖代理生成的地址
+            const std::string reported = slot->spawned ? slot->supervisor->reported_address() : "";
+            if (!reported.empty() && reported != slot->address) {
+                std::cout << "[main] Worker" << (slot->model.empty() ? "" : " for " + slot->model)
+                          << " reported address " << reported << std::endl;
+                slot->address = reported;
+            }
+        }
+        if (!echo_mode) {
+            std::cout << "[main] Worker ready." << std::endl;
         }
-        std::cout << "[main] Connected to Worker via UDS" << std::endl;
         
-        // 5. 启动代理 gRPC 服务器
-        std::string server_address = "0.0.0.0:" + std::to_string(port);
+        // 5. 连接到 Worker
+        const int max_message_bytes = max_message_mb * 1024 * 1024;
+        grpc::ChannelArguments channel_args;
+        channel_args.SetMaxReceiveMessageSize(max_message_bytes);
+        channel_args.SetMaxSendMessageSize(max_message_bytes);
         
-        // 简单代理服务实现
-        class ProxyService final : public inference::GRPCInferenceService::Service {
-        public:
-            explicit ProxyService(std::unique_ptr<inference::GRPCInferenceService::Stub> stub)
-                : stub_(std::move(stub)) {}
-            
-            grpc::Status ServerLive(
-                grpc::ServerContext* context,
-                const inference::ServerLiveRequest* request,
-                inference::ServerLiveResponse* response) override {
-                grpc::ClientContext client_ctx;
-                return stub_->ServerLive(&client_ctx, *request, response);
+        std::vector<Stub*> workers;
+        std::map<std::string, Stub*> routes;
+        std::map<Stub*, H2dSlots*> h2d_slots;
+        for (auto& slot : slots) {
+            if (echo_mode) {
+                break;
             }
+            slot->channel = grpc::CreateCustomChannel(slot->address, grpc::InsecureChannelCredentials(),
+                                                      channel_args);
+            slot->stub = inference::GRPCInferenceService::NewStub(slot->channel);
             
-            grpc::Status ServerReady(
-                grpc::ServerContext* context,
-                const inference::ServerReadyRequest* request,
-                inference::ServerReadyResponse* response) override {
-                grpc::ClientContext client_ctx;
-                return stub_->ServerReady(&client_ctx, *request, response);
+            // 等待 channel 连接就绪
+            auto deadline = std::chrono::system_clock::now() + std::chrono::seconds(5);
+            if (!slot->channel->WaitForConnected(deadline)) {
+                std::cerr << "[main] Failed to connect to worker " << slot->address << std::endl;
+                cleanup_sockets();
+                return 1;
             }
+            std::cout << "[main] Connected to Worker via " << (use_tcp ? "TCP" : "UDS")
+                      << (slot->model.empty() ? "" : " for model " + slot->model) << std::endl;
             
-            grpc::Status ModelReady(
-                grpc::ServerContext* context,
-                const inference::ModelReadyRequest* request,
-                inference::ModelReadyResponse* response) override {
-                grpc::ClientContext client_ctx;
-                return stub_->ModelReady(&client_ctx, *request, response);
+            workers.push_back(slot->stub.get());
+            if (slot->h2d_slots) {
+                h2d_slots[slot->stub.get()] = slot->h2d_slots.get();
             }
-            
-            grpc::Status ServerMetadata(
-                grpc::ServerContext* context,
-                const inference::ServerMetadataRequest* request,
-                inference::ServerMetadataResponse* response) override {
-                grpc::ClientContext client_ctx;
-                return stub_->ServerMetadata(&client_ctx, *request, response);
+            if (multi_model) {
+                routes[slot->model] = slot->stub.get();
             }
-            
-            grpc::Status ModelMetadata(
-                grpc::ServerContext* context,
-                const inference::ModelMetadataRequest* request,
-                inference::ModelMetadataResponse* response) override {
-                grpc::ClientContext client_ctx;
-                return stub_->ModelMetadata(&client_ctx, *request, response);
+        }
+        
+        // --check：逐个探测 Worker 是否就绪后退出，不启动对外服务
+        if (check_only) {
+            bool ok = true;
+            for (auto& slot : slots) {
+                std::string error;
+                if (!probe_worker_ready(*slot, &error)) {
+                    std::cerr << "[main] Check failed: " << error << std::endl;
+                    ok = false;
+                }
             }
-            
-            grpc::Status ModelInfer(
-                grpc::ServerContext* context,
-                const inference::ModelInferRequest* request,
-                inference::ModelInferResponse* response) override {
-                grpc::ClientContext client_ctx;
-                client_ctx.set_deadline(std::chrono::system_clock::now() + std::chrono::seconds(60));
-                return stub_->ModelInfer(&client_ctx, *request, response);
+            for (auto& slot : slots) {
+                slot->supervisor->stop();
             }
-            
-        private:
-            std::unique_ptr<inference::GRPCInferenceService::Stub> stub_;
-        };
+            cleanup_sockets();
+            std::cout << "[main] Check " << (ok ? "passed." : "failed.") << std::endl;
+            return ok ? 0 : 1;
+        }
+        
+        // Worker 可能在模型加载完成前就发出就绪信号：等到它（或其模型）报告就绪
+        // 再对外提供服务，超过 --worker-timeout 仍未就绪时照常启动，
+        // 此时 ServerReady 如实反映 Worker 状态
+        const auto gate_deadline = std::chrono::steady_clock::now() + worker_timeout;
+        for (auto& slot : slots) {
+            if (echo_mode) {
+                break;
+            }
+            std::string error;
+            while (!g_shutdown_requested && !probe_worker_ready(*slot, &error)) {
+                if (!workers_alive()) {
+                    std::cerr << "[main] Worker exited before becoming ready: " << error << std::endl;
+                    cleanup_sockets();
+                    return 1;
+                }
+                if (std::chrono::steady_clock::now() >= gate_deadline) {
+                    std::cerr << "[main] Warning: " << error << " after " << worker_timeout.count()
+                              << "ms, serving anyway" << std::endl;
+                    break;
+                }
+                std::this_thread::sleep_for(std::chrono::milliseconds(200));
+            }
+        }
         
-        ProxyService proxy_service(std::move(stub));
+        // 6. 启动代理 gRPC 服务器
+        std::unique_ptr<grpc::Service> service;
+        if (echo_mode) {
+            service = std::make_unique<EchoService>(slots.front()->shm_h2d, slots.front()->shm_d2h,
+                                                    shm_threshold.value_or(DEFAULT_SHM_THRESHOLD),
+                                                    verify_shm, infer_limits);
+        } else {
+            auto proxy = std::make_unique<ProxyService>(std::move(workers), std::move(routes),
+                                                        max_concurrent_infers, max_queued_infers,
+                                                        worker_dead, cache_metadata, infer_limits,
+                                                        std::move(h2d_slots),
+                                                        shm_threshold.value_or(DEFAULT_SHM_THRESHOLD),
+                                                        verify_shm);
+            if (cache_metadata) {
+                proxy->prefetch_metadata();
+            }
+            service = std::move(proxy);
+        }
         
         grpc::ServerBuilder builder;
-        builder.AddListeningPort(server_address, grpc::InsecureServerCredentials());
-        builder.RegisterService(&proxy_service);
+        int bound_port = 0;
+        builder.AddListeningPort(server_address, grpc::InsecureServerCredentials(), &bound_port);
+        builder.RegisterService(service.get());
+        builder.SetMaxReceiveMessageSize(max_message_bytes);
+        builder.SetMaxSendMessageSize(max_message_bytes);
+        if (compression != GRPC_COMPRESS_NONE) {
+            // 只对声明支持该算法的客户端压缩，其余照常发送
+            builder.SetDefaultCompressionAlgorithm(compression);
+        }
         
         auto server = builder.BuildAndStart();
         if (!server) {
             std::cerr << "[main] Failed to start gRPC server" << std::endl;
+            cleanup_sockets();
             return 1;
         }
         
-        std::cout << "[main] gRPC server listening on " << server_address << std::endl;
+        // UDS 没有端口号，报告中 port 为 null
+        if (!listen_path.empty()) {
+            bound_port = -1;
+        }
+        std::cout << "[main] gRPC server listening on " << server_address
+                  << " (max message " << max_message_mb << "MB)" << std::endl;
+        proxy_ready = true;
+        
+        // --report-fd：供上层进程解析实际端口等运行参数（--port 0 时由系统分配端口）。
+        // 多模型模式下顶层字段取第一个 Worker，models 中列出每个模型的 Worker
+        if (report_fd >= 0) {
+            auto worker_fields = [](const WorkerSlot& slot) {
+                std::ostringstream fields;
+                fields << "\"uds_path\": " << json_string_or_null(slot.uds_path)
+                       << ", \"h2d_fd\": " << json_int_or_null(slot.shm_h2d.fd)
+                       << ", \"d2h_fd\": " << json_int_or_null(slot.shm_d2h.fd)
+                       << ", \"worker_pid\": "
+                       << json_int_or_null(slot.spawned ? slot.supervisor->get_pid() : -1);
+                return fields.str();
+            };
+            std::ostringstream report;
+            report << "{\"port\": " << json_int_or_null(bound_port)
+                   << ", \"listen\": " << json_string_or_null(server_address)
+                   << ", " << worker_fields(*slots.front());
+            if (multi_model) {
+                report << ", \"models\": {";
+                for (size_t i = 0; i < slots.size(); ++i) {
+                    report << (i ? ", " : "") << json_string_or_null(slots[i]->model)
+                           << ": {" << worker_fields(*slots[i]) << "}";
+                }
+                report << "}";
+            }
+            report << "}";
+            if (!write_line_and_close(report_fd, report.str())) {
+                std::cerr << "[main] Warning: failed to write startup report to fd " << report_fd
+                          << ": " << std::strerror(errno) << std::endl;
+            }
+        }
         
-        // 6. 主循环
+        // 7. 主循环
         while (!g_shutdown_requested) {
-            // 检查 Worker 是否存活
-            if (!supervisor.is_alive()) {
+            // Worker 退出时立即唤醒（外部 Worker 不由本进程管理）；
+            // 信号处理函数不能通知条件变量，所以仍按 100ms 检查 g_shutdown_requested
+            std::unique_lock<std::mutex> lock(worker_exit_mutex);
+            if (worker_exit_cv.wait_for(lock, std::chrono::milliseconds(100),
+                                        [&] { return worker_dead.load(); })) {
                 std::cerr << "[main] Worker process exited unexpectedly" << std::endl;
                 break;
             }
-            std::this_thread::sleep_for(std::chrono::milliseconds(100));
         }
         
-        // 7. 清理
+        // 8. 清理
         std::cout << "[main] Shutting down..." << std::endl;
+        proxy_ready = false;
+        if (health_server) {
+            health_server->stop();
+        }
         server->Shutdown();
-        supervisor.stop();
+        if (!listen_path.empty()) {
+            std::remove(listen_path.c_str());
+        }
+        for (auto& slot : slots) {
+            slot->supervisor->stop();
+        }
+        
+        // 输出 SHM 使用情况，用于调整段大小
+        for (const auto& slot : slots) {
+            const std::string label = slot->model.empty() ? "" : " [" + slot->model + "]";
+            for (const auto* shm : {&slot->shm_h2d, &slot->shm_d2h}) {
+                if (shm->fd < 0) {
+                    continue;
+                }
+                std::cout << "[main]" << label << " SHM " << (shm == &slot->shm_h2d ? "H2D" : "D2H")
+                          << ": high water " << shm->high_water << "/" << shm->size
+                          << " bytes, " << shm->wraps << " wraps, "
+                          << shm->bytes_moved << " bytes moved" << std::endl;
+            }
+        }
         
         // 删除 UDS 文件
-        std::remove(uds_path.c_str());
+        cleanup_sockets();
         
         std::cout << "[main] Done." << std::endl;
         return 0;
         
     } catch (const std::exception& e) {
         std::cerr << "[main] Error: " << e.what() << std::endl;
+        cleanup_sockets();
         return 1;
     }
 }
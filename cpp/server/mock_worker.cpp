@@ -0,0 +1,324 @@
+/**
+ * mock_worker.cpp - 测试用的最小 Worker（不依赖 Python）
+ *
+ * 实现 anyserve_node 与 Worker 之间的全部约定：
+ * 1. 在 ANSERVE_WORKER_UDS / ANSERVE_WORKER_UDS_ABSTRACT / ANSERVE_WORKER_ADDR
+ *    指定的地址上提供 KServe gRPC 服务
+ * 2. 映射继承的 ANSERVE_H2D_FD / ANSERVE_D2H_FD
+ * 3. 服务启动后向 ANSERVE_READY_FD 写入就绪消息，带上实际映射的 SHM 大小和
+ *    shm_inputs=1（格式见 python/anyserve/worker/handshake.py）
+ * 4. 从请求参数 shm_raw（raw 输入）和 shm_contents（typed contents）指定的 H2D 槽
+ *    读取输入，读完后在响应参数 h2d_ack 中确认；
+ *    请求带 shm_checksum 时在响应参数 h2d_checksum 中回传读到的内容的校验和
+ *
+ * ModelInfer 把每个输入原样作为同名输出返回：raw 输入经 D2H 拷贝后作为 raw 输出，
+ * 经 D2H 的字节数写入响应参数 shm_bytes；typed contents 直接拷贝（经 H2D 的从 H2D 解析）。
+ * 供测试使用：响应参数 mock_h2d_offsets 列出读取的 H2D 偏移；请求参数
+ * mock_skip_ack（bool）为 true 时读取后不回 ACK；mock_corrupt_h2d（bool）为 true 时
+ * 翻转读到的每个 H2D 输入的首字节（模拟 SHM 数据损坏）。
+ *
+ * 代理以 "$PYTHON_PATH -m anyserve_worker.loader [APP_TARGET] ..." 启动 Worker，
+ * 因此用法为 PYTHON_PATH=anyserve_mock_worker anyserve_node ...，命令行参数被忽略。
+ */
+
+#include <grpcpp/grpcpp.h>
+
+#include <sys/mman.h>
+#include <sys/stat.h>
+#include <unistd.h>
+
+#include <algorithm>
+#include <cerrno>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <iostream>
+#include <map>
+#include <mutex>
+#include <sstream>
+#include <string>
+#include <vector>
+
+#include "grpc_predict_v2.grpc.pb.h"
+
+namespace {
+
+/**
+ * MappedShm - 映射一个继承的 SHM fd
+ */
+struct MappedShm {
+    void* ptr = nullptr;
+    size_t size = 0;
+
+    MappedShm() = default;
+    MappedShm(const MappedShm&) = delete;
+    MappedShm& operator=(const MappedShm&) = delete;
+    ~MappedShm() {
+        if (ptr) {
+            munmap(ptr, size);
+        }
+    }
+
+    /**
+     * 映射 fd 的全部内容
+     * @param error 失败时写入原因
+     */
+    bool map(int fd, std::string* error) {
+        struct stat st;
+        if (fstat(fd, &st) < 0) {
+            *error = "fstat(" + std::to_string(fd) + "): " + std::strerror(errno);
+            return false;
+        }
+        if (st.st_size <= 0) {
+            *error = "fd " + std::to_string(fd) + " is empty";
+            return false;
+        }
+        void* mapped = mmap(nullptr, static_cast<size_t>(st.st_size), PROT_READ | PROT_WRITE,
+                            MAP_SHARED, fd, 0);
+        if (mapped == MAP_FAILED) {
+            *error = "mmap(" + std::to_string(fd) + "): " + std::strerror(errno);
+            return false;
+        }
+        ptr = mapped;
+        size = static_cast<size_t>(st.st_size);
+        return true;
+    }
+};
+
+/**
+ * FNV-1a 64 位校验和，与 anyserve_node 的 --verify-shm 一致
+ */
+uint64_t fnv1a64(const std::string& data) {
+    uint64_t hash = 14695981039346656037ULL;
+    for (unsigned char byte : data) {
+        hash ^= byte;
+        hash *= 1099511628211ULL;
+    }
+    return hash;
+}
+
+/**
+ * 请求中的 bool 参数，缺省为 false
+ */
+bool bool_param(const inference::ModelInferRequest& request, const char* name) {
+    auto it = request.parameters().find(name);
+    return it != request.parameters().end() && it->second.bool_param();
+}
+
+/**
+ * MockWorkerService - 回显输入的 KServe 服务
+ */
+class MockWorkerService final : public inference::GRPCInferenceService::Service {
+public:
+    MockWorkerService(const MappedShm& h2d, const MappedShm& d2h) : h2d_(h2d), d2h_(d2h) {}
+
+    grpc::Status ServerLive(grpc::ServerContext*, const inference::ServerLiveRequest*,
+                            inference::ServerLiveResponse* response) override {
+        response->set_live(true);
+        return grpc::Status::OK;
+    }
+
+    grpc::Status ServerReady(grpc::ServerContext*, const inference::ServerReadyRequest*,
+                             inference::ServerReadyResponse* response) override {
+        response->set_ready(true);
+        return grpc::Status::OK;
+    }
+
+    grpc::Status ModelReady(grpc::ServerContext*, const inference::ModelReadyRequest*,
+                            inference::ModelReadyResponse* response) override {
+        response->set_ready(true);
+        return grpc::Status::OK;
+    }
+
+    grpc::Status ServerMetadata(grpc::ServerContext*, const inference::ServerMetadataRequest*,
+                                inference::ServerMetadataResponse* response) override {
+        response->set_name("anyserve-mock-worker");
+        return grpc::Status::OK;
+    }
+
+    grpc::Status ModelInfer(grpc::ServerContext*, const inference::ModelInferRequest* request,
+                            inference::ModelInferResponse* response) override {
+        // raw 输入；代理写入 H2D 的（shm_raw 中的下标）替换为从 H2D 读出的内容
+        std::vector<std::string> raw_inputs(request->raw_input_contents().begin(),
+                                            request->raw_input_contents().end());
+        // 代理写入 H2D 的 typed contents（shm_contents 中的下标），InferTensorContents 的 wire 格式
+        std::map<size_t, std::string> typed_inputs;
+        std::string acks;
+        std::string offsets;
+        std::string checksums;
+        const bool corrupt = bool_param(*request, "mock_corrupt_h2d");
+        // 读取 name 参数列出的 H2D 槽，read(下标) 返回存放位置，下标越界时为空
+        auto read_slots = [&](const char* name, auto&& read) {
+            auto param = request->parameters().find(name);
+            if (param == request->parameters().end()) {
+                return grpc::Status::OK;
+            }
+            std::istringstream entries(param->second.string_param());
+            std::string entry;
+            while (std::getline(entries, entry, ',')) {
+                size_t index = 0;
+                size_t offset = 0;
+                size_t len = 0;
+                std::string* target = nullptr;
+                if (std::sscanf(entry.c_str(), "%zu:%zu:%zu", &index, &offset, &len) != 3 ||
+                    !(target = read(index)) || !h2d_.ptr || offset > h2d_.size || len > h2d_.size - offset) {
+                    return grpc::Status(grpc::StatusCode::INVALID_ARGUMENT,
+                                        std::string("Bad ") + name + " entry: " + entry);
+                }
+                target->assign(static_cast<const char*>(h2d_.ptr) + offset, len);
+                if (corrupt && len > 0) {
+                    (*target)[0] = static_cast<char>(~(*target)[0]);
+                }
+                char checksum[17];
+                std::snprintf(checksum, sizeof(checksum), "%016llx",
+                              static_cast<unsigned long long>(fnv1a64(*target)));
+                checksums += (checksums.empty() ? "" : ",") + std::string(checksum);
+                acks += (acks.empty() ? "" : ",") + std::to_string(offset) + ":" + std::to_string(len);
+                offsets += (offsets.empty() ? "" : ",") + std::to_string(offset);
+            }
+            return grpc::Status::OK;
+        };
+        grpc::Status status = read_slots("shm_raw", [&](size_t index) {
+            return index < raw_inputs.size() ? &raw_inputs[index] : nullptr;
+        });
+        if (status.ok()) {
+            status = read_slots("shm_contents", [&](size_t index) {
+                return index < static_cast<size_t>(request->inputs_size()) ? &typed_inputs[index] : nullptr;
+            });
+        }
+        if (!status.ok()) {
+            return status;
+        }
+        if (!acks.empty() && !bool_param(*request, "mock_skip_ack")) {
+            (*response->mutable_parameters())["h2d_ack"].set_string_param(acks);
+            if (request->parameters().count("shm_checksum")) {
+                (*response->mutable_parameters())["h2d_checksum"].set_string_param(checksums);
+            }
+        }
+        (*response->mutable_parameters())["mock_h2d_offsets"].set_string_param(offsets);
+
+        response->set_model_name(request->model_name());
+        response->set_model_version(request->model_version());
+        response->set_id(request->id());
+
+        // InferTensorContents 在请求和响应中是不同的消息类型，字段定义一致，经 wire 格式互转
+        for (int i = 0; i < request->inputs_size(); ++i) {
+            const auto& input = request->inputs(i);
+            auto* output = response->add_outputs();
+            output->set_name(input.name());
+            output->set_datatype(input.datatype());
+            *output->mutable_shape() = input.shape();
+            auto typed = typed_inputs.find(static_cast<size_t>(i));
+            if (typed != typed_inputs.end()) {
+                if (!output->mutable_contents()->ParseFromString(typed->second)) {
+                    return grpc::Status(grpc::StatusCode::INTERNAL, "Failed to parse tensor contents from H2D");
+                }
+            } else if (input.has_contents() &&
+                       !output->mutable_contents()->ParseFromString(input.contents().SerializeAsString())) {
+                return grpc::Status(grpc::StatusCode::INTERNAL, "Failed to copy tensor contents");
+            }
+        }
+
+        int64_t shm_bytes = 0;
+        for (const auto& raw : raw_inputs) {
+            std::string* out = response->add_raw_output_contents();
+            if (!d2h_.ptr) {
+                *out = raw;
+                continue;
+            }
+            round_trip(raw, out);
+            shm_bytes += static_cast<int64_t>(raw.size());
+        }
+        (*response->mutable_parameters())["shm_bytes"].set_int64_param(shm_bytes);
+        return grpc::Status::OK;
+    }
+
+private:
+    /**
+     * 把 data 按段大小分块写入 D2H、再读出追加到 out
+     *
+     * H2D 由代理分配（见 shm_raw），Worker 只读不写
+     */
+    void round_trip(const std::string& data, std::string* out) {
+        out->reserve(data.size());
+        std::lock_guard<std::mutex> lock(mutex_);
+        for (size_t offset = 0; offset < data.size(); offset += d2h_.size) {
+            const size_t len = std::min(d2h_.size, data.size() - offset);
+            std::memcpy(d2h_.ptr, data.data() + offset, len);
+            out->append(static_cast<const char*>(d2h_.ptr), len);
+        }
+    }
+
+    const MappedShm& h2d_;
+    const MappedShm& d2h_;
+    std::mutex mutex_;
+};
+
+/**
+ * 按代理设置的传输变量确定监听地址，未设置时返回空
+ */
+std::string listen_address() {
+    if (const char* path = std::getenv("ANSERVE_WORKER_UDS")) {
+        return std::string("unix://") + path;
+    }
+    if (const char* name = std::getenv("ANSERVE_WORKER_UDS_ABSTRACT")) {
+        return std::string("unix-abstract:") + name;
+    }
+    if (const char* addr = std::getenv("ANSERVE_WORKER_ADDR")) {
+        return addr;
+    }
+    return "";
+}
+
+} // anonymous namespace
+
+int main() {
+    const std::string address = listen_address();
+    const char* ready_fd = std::getenv("ANSERVE_READY_FD");
+    if (address.empty() || !ready_fd) {
+        std::cerr << "[MockWorker] Must be started by anyserve_node"
+                  << " (ANSERVE_WORKER_* and ANSERVE_READY_FD not set)" << std::endl;
+        return 1;
+    }
+
+    MappedShm h2d;
+    MappedShm d2h;
+    const char* h2d_fd = std::getenv("ANSERVE_H2D_FD");
+    const char* d2h_fd = std::getenv("ANSERVE_D2H_FD");
+    if (h2d_fd && d2h_fd) {
+        std::string error;
+        if (!h2d.map(std::atoi(h2d_fd), &error) || !d2h.map(std::atoi(d2h_fd), &error)) {
+            std::cerr << "[MockWorker] Failed to map SHM: " << error << std::endl;
+            return 1;
+        }
+    }
+
+    MockWorkerService service(h2d, d2h);
+    grpc::ServerBuilder builder;
+    builder.AddListeningPort(address, grpc::InsecureServerCredentials());
+    builder.RegisterService(&service);
+    builder.SetMaxReceiveMessageSize(-1);
+    builder.SetMaxSendMessageSize(-1);
+    std::unique_ptr<grpc::Server> server = builder.BuildAndStart();
+    if (!server) {
+        std::cerr << "[MockWorker] Failed to listen on " << address << std::endl;
+        return 1;
+    }
+
+    std::string message = "ready";
+    if (h2d.ptr) {
+        message += " h2d_size=" + std::to_string(h2d.size) + " d2h_size=" + std::to_string(d2h.size) +
+                   " shm_inputs=1";
+    }
+    const int fd = std::atoi(ready_fd);
+    if (write(fd, message.data(), message.size()) != static_cast<ssize_t>(message.size())) {
+        std::cerr << "[MockWorker] Failed to signal ready: " << std::strerror(errno) << std::endl;
+        return 1;
+    }
+    close(fd);
+    std::cout << "[MockWorker] Serving on " << address << std::endl;
+
+    // 代理停止时以 SIGTERM 结束本进程
+    server->Wait();
+    return 0;
+}
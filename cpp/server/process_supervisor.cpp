@@ -1,15 +1,21 @@
 #include "process_supervisor.hpp"
 
 #include <iostream>
+#include <sstream>
+#include <algorithm>
 #include <vector>
 #include <cstring>
+#include <cerrno>
 #include <cstdlib>
 #include <stdexcept>
+#include <fcntl.h>
 #include <unistd.h>
 #include <sys/wait.h>
 #include <poll.h>
 #include <signal.h>
 
+extern char** environ;
+
 namespace anyserve {
 
 namespace {
@@ -21,6 +27,40 @@ void close_fd(int& fd) {
     }
 }
 
+/**
+ * 创建两端都带 FD_CLOEXEC 的 pipe（pipe2 在 macOS 上不可用）
+ */
+bool cloexec_pipe(int fds[2]) {
+    if (pipe(fds) < 0) {
+        return false;
+    }
+    if (fcntl(fds[0], F_SETFD, FD_CLOEXEC) < 0 || fcntl(fds[1], F_SETFD, FD_CLOEXEC) < 0) {
+        const int saved = errno;
+        close_fd(fds[0]);
+        close_fd(fds[1]);
+        errno = saved;
+        return false;
+    }
+    return true;
+}
+
+/**
+ * 设置 KEY=VALUE 形式的环境变量条目（语义同 setenv）
+ */
+void set_env_entry(std::vector<std::string>& env, const std::string& key,
+                   const std::string& value, bool overwrite) {
+    const std::string prefix = key + "=";
+    for (auto& entry : env) {
+        if (entry.compare(0, prefix.size(), prefix) == 0) {
+            if (overwrite) {
+                entry = prefix + value;
+            }
+            return;
+        }
+    }
+    env.push_back(prefix + value);
+}
+
 } // anonymous namespace
 
 ProcessSupervisor::ProcessSupervisor(const std::string& python_path, const std::string& worker_module)
@@ -37,117 +77,317 @@ void ProcessSupervisor::spawn(const std::string& uds_path, int h2d_fd, int d2h_f
 
 void ProcessSupervisor::spawn(const std::string& uds_path, int h2d_fd, int d2h_fd,
                                const std::vector<std::string>& extra_args) {
+    spawn(WorkerTransport::UDS, uds_path, h2d_fd, d2h_fd, extra_args);
+}
+
+void ProcessSupervisor::spawn(WorkerTransport transport, const std::string& address,
+                               int h2d_fd, int d2h_fd,
+                               const std::vector<std::string>& extra_args) {
+    // 准备 Worker 输出的去向（O_CLOEXEC：子进程 dup2 到 1/2 后其余副本在 exec 时关闭）
+    int out_fds[2] = {-1, -1};
+    int err_fds[2] = {-1, -1};
+    int log_fd = -1;
+    auto close_output_fds = [&]() {
+        close_fd(out_fds[0]);
+        close_fd(out_fds[1]);
+        close_fd(err_fds[0]);
+        close_fd(err_fds[1]);
+        close_fd(log_fd);
+    };
+    if (output_mode_ == WorkerOutput::PREFIX) {
+        if (!cloexec_pipe(out_fds) || !cloexec_pipe(err_fds)) {
+            const std::string error = strerror(errno);
+            close_output_fds();
+            throw std::runtime_error("Failed to create output pipe: " + error);
+        }
+    } else if (output_mode_ == WorkerOutput::FILE) {
+        log_fd = open(output_path_.c_str(), O_WRONLY | O_CREAT | O_APPEND | O_CLOEXEC, 0644);
+        if (log_fd < 0) {
+            throw std::runtime_error("Failed to open worker log " + output_path_ + ": " +
+                                     strerror(errno));
+        }
+    }
+
     // 创建 pipe 用于就绪信号
     int pipe_fds[2];
     if (pipe(pipe_fds) < 0) {
-        throw std::runtime_error("Failed to create pipe: " + std::string(strerror(errno)));
+        const std::string error = strerror(errno);
+        close_output_fds();
+        throw std::runtime_error("Failed to create pipe: " + error);
     }
     read_fd_ = pipe_fds[0];
     write_fd_ = pipe_fds[1];
 
+    // 环境变量和参数列表都在 fork 前构建：代理此时已有其他线程，
+    // 子进程在 exec 前只能调用 async-signal-safe 的函数（不能分配内存）
+    std::vector<std::string> env_entries;
+    for (char** entry = environ; *entry != nullptr; ++entry) {
+        env_entries.emplace_back(*entry);
+    }
+    if (output_mode_ != WorkerOutput::INHERIT) {
+        set_env_entry(env_entries, "PYTHONUNBUFFERED", "1", false);
+    }
+    // 额外变量先设置，传输变量始终以代理为准
+    for (const auto& [key, value] : extra_env_) {
+        set_env_entry(env_entries, key, value, true);
+    }
+    if (transport == WorkerTransport::TCP) {
+        set_env_entry(env_entries, "ANSERVE_WORKER_ADDR", address, true);
+    } else if (transport == WorkerTransport::UDS_ABSTRACT) {
+        // Worker 以 gRPC 地址 "unix-abstract:<name>" 监听
+        set_env_entry(env_entries, "ANSERVE_WORKER_UDS_ABSTRACT", address, true);
+    } else {
+        set_env_entry(env_entries, "ANSERVE_WORKER_UDS", address, true);
+    }
+    set_env_entry(env_entries, "ANSERVE_READY_FD", std::to_string(write_fd_), true);
+    // 未设置 SHM fd 时 Worker 回退为内联传输
+    if (h2d_fd >= 0 && d2h_fd >= 0) {
+        set_env_entry(env_entries, "ANSERVE_H2D_FD", std::to_string(h2d_fd), true);
+        set_env_entry(env_entries, "ANSERVE_D2H_FD", std::to_string(d2h_fd), true);
+    }
+    std::vector<char*> envp;
+    for (auto& entry : env_entries) {
+        envp.push_back(entry.data());
+    }
+    envp.push_back(nullptr);
+
+    // 构建参数列表: python -m <module> [extra_args...]
+    std::vector<char*> args;
+    args.push_back(const_cast<char*>(python_path_.c_str()));
+    args.push_back(const_cast<char*>("-m"));
+    args.push_back(const_cast<char*>(worker_module_.c_str()));
+    for (const auto& arg : extra_args) {
+        args.push_back(const_cast<char*>(arg.c_str()));
+    }
+    args.push_back(nullptr);
+
     pid_t pid = fork();
     if (pid < 0) {
+        const std::string error = strerror(errno);
         close_fd(read_fd_);
         close_fd(write_fd_);
-        throw std::runtime_error("Fork failed: " + std::string(strerror(errno)));
+        close_output_fds();
+        throw std::runtime_error("Fork failed: " + error);
     }
 
     if (pid == 0) {
         // ===== 子进程 =====
         close(read_fd_); // 子进程不读
 
-        // 设置环境变量
-        setenv("ANSERVE_WORKER_UDS", uds_path.c_str(), 1);
-        setenv("ANSERVE_READY_FD", std::to_string(write_fd_).c_str(), 1);
-        setenv("ANSERVE_H2D_FD", std::to_string(h2d_fd).c_str(), 1);
-        setenv("ANSERVE_D2H_FD", std::to_string(d2h_fd).c_str(), 1);
-
-        // 构建参数列表: python -m <module> [extra_args...]
-        std::vector<char*> args;
-        args.push_back(const_cast<char*>(python_path_.c_str()));
-        args.push_back(const_cast<char*>("-m"));
-        args.push_back(const_cast<char*>(worker_module_.c_str()));
-        
-        // 添加额外参数
-        for (const auto& arg : extra_args) {
-            args.push_back(const_cast<char*>(arg.c_str()));
+        if (output_mode_ == WorkerOutput::PREFIX) {
+            dup2(out_fds[1], STDOUT_FILENO);
+            dup2(err_fds[1], STDERR_FILENO);
+        } else if (output_mode_ == WorkerOutput::FILE) {
+            dup2(log_fd, STDOUT_FILENO);
+            dup2(log_fd, STDERR_FILENO);
         }
-        args.push_back(nullptr);
 
-        // 执行
+        // 执行（execvp 按 PATH 查找 python，并使用 environ 作为子进程环境）
+        environ = envp.data();
         execvp(python_path_.c_str(), args.data());
 
         // 如果 execvp 返回，说明失败了
-        std::cerr << "Failed to exec python worker: " << strerror(errno) << std::endl;
+        static const char message[] = "Failed to exec python worker\n";
+        ssize_t ignored = write(STDERR_FILENO, message, sizeof(message) - 1);
+        (void)ignored;
         _exit(1);
     } else {
         // ===== 父进程 =====
         worker_pid_ = pid;
         close(write_fd_); // 父进程不写
         write_fd_ = -1;
+        
+        if (exit_monitor_.joinable()) {
+            exit_monitor_.join();
+        }
+        for (auto& thread : output_threads_) {
+            thread.join();
+        }
+        output_threads_.clear();
+        exited_ = false;
+        exit_monitor_ = std::thread(&ProcessSupervisor::monitor_exit, this, pid);
+
+        if (output_mode_ == WorkerOutput::PREFIX) {
+            close_fd(out_fds[1]);
+            close_fd(err_fds[1]);
+            output_threads_.emplace_back(&ProcessSupervisor::forward_output, this,
+                                         out_fds[0], &std::cout, output_prefix_);
+            output_threads_.emplace_back(&ProcessSupervisor::forward_output, this,
+                                         err_fds[0], &std::cerr, output_prefix_);
+        }
+        close_fd(log_fd);
     }
 }
 
-bool ProcessSupervisor::wait_for_ready(int timeout_seconds) {
+void ProcessSupervisor::forward_output(int fd, std::ostream* stream, std::string prefix) {
+    constexpr int POLL_SLICE_MS = 200;
+    std::string pending;
+    char buf[4096];
+    struct pollfd pfd;
+    pfd.fd = fd;
+    pfd.events = POLLIN;
+
+    while (true) {
+        int ret = poll(&pfd, 1, POLL_SLICE_MS);
+        if (ret < 0 && errno == EINTR) {
+            continue;
+        }
+        if (ret == 0) {
+            if (exited_) {
+                break;
+            }
+            continue;
+        }
+        ssize_t n = ret > 0 ? read(fd, buf, sizeof(buf)) : -1;
+        if (n < 0 && errno == EINTR) {
+            continue;
+        }
+        if (n <= 0) {
+            break;
+        }
+        pending.append(buf, static_cast<size_t>(n));
+
+        // 每行一次写入，避免与代理自身的日志交错在同一行
+        size_t start = 0;
+        size_t newline;
+        while ((newline = pending.find('\n', start)) != std::string::npos) {
+            *stream << (prefix + pending.substr(start, newline - start + 1)) << std::flush;
+            start = newline + 1;
+        }
+        pending.erase(0, start);
+    }
+    if (!pending.empty()) {
+        *stream << (prefix + pending + "\n") << std::flush;
+    }
+    close(fd);
+}
+
+void ProcessSupervisor::monitor_exit(pid_t pid) {
+    // 先等待退出但不回收（WNOWAIT），置位 exited_ 之后再回收，
+    // 保证 stop() 不会向已被系统复用的 pid 发信号
+    siginfo_t info{};
+    while (waitid(P_PID, pid, &info, WEXITED | WNOWAIT) < 0 && errno == EINTR) {
+    }
+    {
+        std::lock_guard<std::mutex> lock(exit_mutex_);
+        exited_ = true;
+    }
+    int status = 0;
+    while (waitpid(pid, &status, 0) < 0 && errno == EINTR) {
+    }
+    exit_cv_.notify_all();
+
+    if (WIFSIGNALED(status)) {
+        std::cout << "[ProcessSupervisor] Worker " << pid << " killed by signal "
+                  << WTERMSIG(status) << std::endl;
+    } else {
+        std::cout << "[ProcessSupervisor] Worker " << pid << " exited with status "
+                  << WEXITSTATUS(status) << std::endl;
+    }
+    if (exit_callback_) {
+        exit_callback_();
+    }
+}
+
+bool ProcessSupervisor::wait_for_ready(std::chrono::milliseconds timeout) {
     if (read_fd_ < 0) {
         return false;
     }
+    ready_message_.clear();
+
+    // 分片 poll，每片之间检查 Worker 是否已退出
+    constexpr auto POLL_SLICE = std::chrono::milliseconds(200);
+    const auto deadline = std::chrono::steady_clock::now() + timeout;
 
     struct pollfd pfd;
     pfd.fd = read_fd_;
     pfd.events = POLLIN;
 
-    int ret = poll(&pfd, 1, timeout_seconds * 1000);
-    if (ret > 0) {
-        if (pfd.revents & POLLIN) {
-            char buf[128];
-            ssize_t n = read(read_fd_, buf, sizeof(buf) - 1);
-            if (n > 0) {
-                buf[n] = '\0';
-                std::cout << "[ProcessSupervisor] Worker signaled: " << buf << std::endl;
-                return true;
+    while (true) {
+        auto remaining = std::chrono::duration_cast<std::chrono::milliseconds>(
+            deadline - std::chrono::steady_clock::now());
+        if (remaining.count() <= 0) {
+            std::cerr << "[ProcessSupervisor] Timeout waiting for worker ready" << std::endl;
+            return false;
+        }
+
+        int ret = poll(&pfd, 1, static_cast<int>(std::min(remaining, POLL_SLICE).count()));
+        if (ret > 0) {
+            if (pfd.revents & POLLIN) {
+                char buf[512];
+                ssize_t n = read(read_fd_, buf, sizeof(buf) - 1);
+                if (n > 0) {
+                    buf[n] = '\0';
+                    ready_message_ = buf;
+                    std::cout << "[ProcessSupervisor] Worker signaled: " << buf << std::endl;
+                    return true;
+                }
             }
+            // 写端已关闭：Worker 在发出就绪信号前退出
+            std::cerr << "[ProcessSupervisor] Worker closed the ready pipe without signaling"
+                      << std::endl;
+            return false;
+        } else if (ret < 0 && errno != EINTR) {
+            std::cerr << "[ProcessSupervisor] Poll error: " << strerror(errno) << std::endl;
+            return false;
+        }
+
+        if (!is_alive()) {
+            std::cerr << "[ProcessSupervisor] Worker exited before signaling ready" << std::endl;
+            return false;
+        }
+    }
+}
+
+std::string ProcessSupervisor::reported_address() const {
+    return reported("addr");
+}
+
+std::string ProcessSupervisor::reported(const std::string& key) const {
+    const std::string prefix = key + "=";
+    std::istringstream tokens(ready_message_);
+    std::string token;
+    while (tokens >> token) {
+        if (token.rfind(prefix, 0) == 0) {
+            return token.substr(prefix.size());
         }
-    } else if (ret == 0) {
-        std::cerr << "[ProcessSupervisor] Timeout waiting for worker ready" << std::endl;
-    } else {
-        std::cerr << "[ProcessSupervisor] Poll error: " << strerror(errno) << std::endl;
     }
-    return false;
+    return "";
 }
 
 void ProcessSupervisor::stop() {
     if (worker_pid_ > 0) {
-        // 先发送 SIGTERM
-        kill(worker_pid_, SIGTERM);
-        
-        // 等待最多 5 秒
-        int status;
-        for (int i = 0; i < 50; ++i) {
-            pid_t result = waitpid(worker_pid_, &status, WNOHANG);
-            if (result == worker_pid_) {
-                worker_pid_ = -1;
-                return;
+        // 已退出的 pid 可能被系统复用，只在持锁确认未退出时发信号
+        auto signal_worker = [this](int sig) {
+            std::lock_guard<std::mutex> lock(exit_mutex_);
+            if (!exited_) {
+                kill(worker_pid_, sig);
             }
-            usleep(100000); // 100ms
-        }
+        };
         
-        // 超时则强制 SIGKILL
-        kill(worker_pid_, SIGKILL);
-        waitpid(worker_pid_, nullptr, 0);
+        // 先发送 SIGTERM，等待最多 5 秒，超时则强制 SIGKILL
+        signal_worker(SIGTERM);
+        if (!wait_for_exit(std::chrono::seconds(5))) {
+            signal_worker(SIGKILL);
+        }
         worker_pid_ = -1;
     }
+    if (exit_monitor_.joinable()) {
+        exit_monitor_.join();
+    }
+    for (auto& thread : output_threads_) {
+        thread.join();
+    }
+    output_threads_.clear();
 }
 
 bool ProcessSupervisor::is_alive() const {
-    if (worker_pid_ <= 0) {
-        return false;
-    }
-    
-    // 检查进程是否存在
-    int status;
-    pid_t result = waitpid(worker_pid_, &status, WNOHANG);
-    return result == 0; // 0 表示进程仍在运行
+    return worker_pid_ > 0 && !exited_;
+}
+
+bool ProcessSupervisor::wait_for_exit(std::chrono::milliseconds timeout) {
+    std::unique_lock<std::mutex> lock(exit_mutex_);
+    return exit_cv_.wait_for(lock, timeout, [this] { return worker_pid_ <= 0 || exited_.load(); });
 }
 
 } // namespace anyserve
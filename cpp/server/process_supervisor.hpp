@@ -1,18 +1,47 @@
 #pragma once
 
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <functional>
+#include <iosfwd>
+#include <map>
+#include <mutex>
 #include <string>
+#include <thread>
+#include <vector>
 #include <sys/types.h>
 
 namespace anyserve {
 
+/**
+ * WorkerTransport - 代理与 Worker 之间的传输方式
+ */
+enum class WorkerTransport {
+    UDS,           // Unix Domain Socket（默认，同机）
+    TCP,           // TCP（Worker 可位于其他容器/命名空间）
+    UDS_ABSTRACT   // Linux 抽象命名空间 UDS：没有文件系统条目，进程退出后自动消失
+};
+
+/**
+ * WorkerOutput - Worker stdout/stderr 的去向
+ */
+enum class WorkerOutput {
+    INHERIT,   // 直接继承代理的 stdout/stderr（默认）
+    PREFIX,    // 经 pipe 捕获，逐行加前缀后转发到代理的 stdout/stderr
+    FILE       // 两者都追加写入日志文件
+};
+
 /**
  * ProcessSupervisor - Python Worker 进程管理器
  * 
  * 负责：
  * 1. 派生 Python Worker 子进程
  * 2. 通过 pipe 接收就绪信号
- * 3. 传递环境变量（UDS 路径、SHM fd 等）
- * 4. 进程生命周期管理
+ * 3. 传递环境变量（UDS 路径或 TCP 地址、SHM fd 等）
+ * 4. 进程生命周期管理：spawn 后由专门的线程阻塞等待子进程退出，
+ *    退出状态立即可见，不依赖调用方轮询
+ * 5. 可选捕获 Worker 的 stdout/stderr（加前缀转发或写入日志文件）
  */
 class ProcessSupervisor {
 public:
@@ -29,6 +58,38 @@ public:
     ProcessSupervisor(const ProcessSupervisor&) = delete;
     ProcessSupervisor& operator=(const ProcessSupervisor&) = delete;
 
+    /**
+     * 设置传给 Worker 的额外环境变量（模型路径、设备等），在下次 spawn 时生效
+     *
+     * 在 ANSERVE_* 传输变量之前设置，不会覆盖它们。
+     */
+    void set_extra_env(std::map<std::string, std::string> env) { extra_env_ = std::move(env); }
+
+    /**
+     * 设置 Worker 退出时的回调，在下次 spawn 时生效
+     *
+     * 在退出监视线程中调用（包括 stop() 导致的退出），回调内不能调用 stop()。
+     */
+    void set_exit_callback(std::function<void()> callback) { exit_callback_ = std::move(callback); }
+
+    /**
+     * 设置 Worker 输出的去向，在下次 spawn 时生效
+     *
+     * 捕获（PREFIX/FILE）时为 Worker 设置 PYTHONUNBUFFERED=1（除非已显式设置），
+     * 否则 Python 向 pipe/文件的输出是块缓冲的，被 SIGTERM 结束时会丢失。
+     * @param mode 输出去向
+     * @param log_path FILE 时的日志文件路径（追加写入）
+     */
+    void set_output(WorkerOutput mode, std::string log_path = "") {
+        output_mode_ = mode;
+        output_path_ = std::move(log_path);
+    }
+
+    /**
+     * 设置 PREFIX 模式下每行的前缀（默认 "[worker] "），在下次 spawn 时生效
+     */
+    void set_output_prefix(std::string prefix) { output_prefix_ = std::move(prefix); }
+
     /**
      * 派生 Worker 进程
      * @param uds_path Unix Domain Socket 路径
@@ -49,12 +110,47 @@ public:
     void spawn(const std::string& uds_path, int h2d_fd, int d2h_fd, 
                const std::vector<std::string>& extra_args);
 
+    /**
+     * 派生 Worker 进程（指定传输方式）
+     * @param transport 传输方式
+     * @param address UDS 路径（UDS）、host:port（TCP）或抽象 socket 名（UDS_ABSTRACT，不含前导 \\0）
+     * @param h2d_fd Host-to-Device SHM fd，-1 表示不使用 SHM
+     * @param d2h_fd Device-to-Host SHM fd，-1 表示不使用 SHM
+     * @param extra_args 额外命令行参数
+     * @throws std::runtime_error 如果 fork 失败
+     */
+    void spawn(WorkerTransport transport, const std::string& address, int h2d_fd, int d2h_fd,
+               const std::vector<std::string>& extra_args);
+
     /**
      * 等待 Worker 就绪信号
-     * @param timeout_seconds 超时秒数
-     * @return true 如果收到就绪信号，false 如果超时
+     *
+     * 等待期间定期检查 Worker 是否已退出，崩溃时立即返回而不是等满超时。
+     * @param timeout 超时时间
+     * @return true 如果收到就绪信号，false 如果超时或 Worker 已退出
+     */
+    bool wait_for_ready(std::chrono::milliseconds timeout);
+    bool wait_for_ready(int timeout_seconds) { return wait_for_ready(std::chrono::seconds(timeout_seconds)); }
+
+    /**
+     * Worker 在就绪信号中报告的地址
+     *
+     * 就绪消息是空白分隔的 token，Worker 可以带上 "addr=<gRPC 地址>"
+     * （如 "ready addr=unix:///sandbox/w.sock"）表明实际监听的位置，
+     * 例如受限沙箱中无法绑定代理指定的路径时。
+     * @return 报告的地址；未报告时为空（使用代理指定的地址）
      */
-    bool wait_for_ready(int timeout_seconds);
+    std::string reported_address() const;
+
+    /**
+     * 就绪消息中 "<key>=<value>" token 的值
+     *
+     * 目前使用的 key：addr（见 reported_address）、h2d_size / d2h_size
+     * （Worker 实际映射的 SHM 字节数，代理据此校验 fd 是否正确继承）、
+     * shm_inputs（为 1 时代理经 H2D 传递大输入）。
+     * @return token 的值；消息中没有该 key 时为空
+     */
+    std::string reported(const std::string& key) const;
 
     /**
      * 停止 Worker 进程
@@ -62,10 +158,16 @@ public:
     void stop();
 
     /**
-     * 检查 Worker 是否存活
+     * 检查 Worker 是否存活（只读取退出监视线程维护的状态，可在任意线程调用）
      */
     bool is_alive() const;
 
+    /**
+     * 等待 Worker 退出
+     * @return true 如果 Worker 已退出（或从未启动）
+     */
+    bool wait_for_exit(std::chrono::milliseconds timeout);
+
     /**
      * 获取 Worker PID
      */
@@ -74,9 +176,28 @@ public:
 private:
     std::string python_path_;
     std::string worker_module_;
+    std::map<std::string, std::string> extra_env_;
+    std::function<void()> exit_callback_;
+    WorkerOutput output_mode_ = WorkerOutput::INHERIT;
+    std::string output_path_;
+    std::string output_prefix_ = "[worker] ";
     pid_t worker_pid_ = -1;
+    std::string ready_message_;  // 最近一次收到的就绪消息
     int read_fd_ = -1;
     int write_fd_ = -1;
+
+    // 退出监视：exited_ 在 exit_mutex_ 下置位，此后 pid 才会被回收，
+    // 因此持锁看到 exited_ 为 false 时向 pid 发信号是安全的
+    void monitor_exit(pid_t pid);
+    std::thread exit_monitor_;
+    std::mutex exit_mutex_;
+    std::condition_variable exit_cv_;
+    std::atomic<bool> exited_{false};
+
+    // PREFIX 模式的转发线程：读到 EOF，或 Worker 退出且 pipe 已读空时结束
+    // （Worker 派生的后台进程可能一直持有写端）
+    void forward_output(int fd, std::ostream* stream, std::string prefix);
+    std::vector<std::thread> output_threads_;
 };
 
 } // namespace anyserve
@@ -19,6 +19,8 @@ from typing import List, Optional
 import click
 import requests
 
+from anyserve.worker.reaper import Reaper, default_socket_dir, reap_stale_resources
+
 
 @click.command()
 @click.argument("app", required=True)
@@ -28,10 +30,17 @@ import requests
 @click.option("--reload", is_flag=True, help="Auto-reload on code changes (not implemented)")
 @click.option("--agent-bin", default=None, help="Path to anyserve_agent binary")
 @click.option("--api-server", default=None, help="API Server URL for capability registration")
-@click.option("--object-store", default="/tmp/anyserve-objects", help="Object store path")
+@click.option("--object-store", default="/tmp/anyserve-objects", help="Object store path, or memory:// for a per-worker in-memory store")
+@click.option("--object-max-bytes", type=int, default=None,
+              help="Evict least recently used objects beyond this many bytes (default: unbounded)")
+@click.option("--object-max-age", type=float, default=None,
+              help="Evict objects unused for this many seconds (default: never)")
 @click.option("--replica-id", default=None, help="Replica ID for API Server registration")
 @click.option("--factory", is_flag=True, help="Treat app as factory function")
-def run_command(app, host, port, workers, reload, agent_bin, api_server, object_store, replica_id, factory):
+@click.option("--reap-interval", type=float, default=0,
+              help="Seconds between sweeps for stale sockets/SHM (default: 0, startup only)")
+def run_command(app, host, port, workers, reload, agent_bin, api_server, object_store, object_max_bytes,
+                object_max_age, replica_id, factory, reap_interval):
     """Run an AnyServe application.
 
     Example:
@@ -54,8 +63,11 @@ def run_command(app, host, port, workers, reload, agent_bin, api_server, object_
         agent_bin=agent_bin,
         api_server=api_server,
         object_store=object_store,
+        object_max_bytes=object_max_bytes,
+        object_max_age=object_max_age,
         replica_id=replica_id,
         factory=factory,
+        reap_interval=reap_interval,
     )
 
     try:
@@ -86,6 +98,9 @@ class AnyServeServer:
         object_store: str = "/tmp/anyserve-objects",
         replica_id: Optional[str] = None,
         factory: bool = False,
+        reap_interval: float = 0,
+        object_max_bytes: Optional[int] = None,
+        object_max_age: Optional[float] = None,
     ):
         self.app = app
         self.host = host
@@ -95,8 +110,12 @@ class AnyServeServer:
         self.agent_bin = agent_bin or self._find_agent()
         self.api_server = api_server
         self.object_store = object_store
+        self.object_max_bytes = object_max_bytes
+        self.object_max_age = object_max_age
         self.replica_id = replica_id
         self.factory = factory
+        self.reap_interval = reap_interval
+        self.reaper: Optional[Reaper] = None
 
         self.management_port = port + 1000
 
@@ -257,6 +276,14 @@ class AnyServeServer:
             print(f"[AnyServe] API Server: {self.api_server}")
         print()
 
+        # Clean up sockets/SHM left behind by crashed instances, in the same
+        # directory the workers and anyserve_node put their sockets
+        socket_dir = default_socket_dir()
+        reap_stale_resources(socket_dir)
+        if self.reap_interval > 0:
+            self.reaper = Reaper(interval=self.reap_interval, socket_dir=socket_dir)
+            self.reaper.start()
+
         self._load_app_capabilities()
         self._start_ingress()
 
@@ -334,6 +361,11 @@ class AnyServeServer:
             if self.factory:
                 cmd.append("--factory")
 
+            if self.object_max_bytes is not None:
+                cmd.extend(["--object-max-bytes", str(self.object_max_bytes)])
+            if self.object_max_age is not None:
+                cmd.extend(["--object-max-age", str(self.object_max_age)])
+
             if self.replica_id:
                 cmd.extend(["--replica-id", self.replica_id])
 
@@ -380,6 +412,9 @@ class AnyServeServer:
 
         print("\n[AnyServe] Shutting down...")
 
+        if self.reaper:
+            self.reaper.stop()
+
         if self.keepalive_thread:
             print("[AnyServe] Stopping keepalive connection...")
 
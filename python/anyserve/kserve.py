@@ -10,11 +10,14 @@ Usage:
         ...
 """
 
-from typing import List, Optional, Callable, Dict, Any as PyAny, Union, Generator
+from typing import TYPE_CHECKING, List, Optional, Callable, Dict, Any as PyAny, Union, Generator
 from dataclasses import dataclass, field
 import queue
 import threading
 
+if TYPE_CHECKING:
+    from anyserve.objects import ObjectBackend
+
 # =============================================================================
 # Capability Definition
 # =============================================================================
@@ -256,7 +259,7 @@ class Context:
     Context object passed to capability handlers.
 
     Provides access to:
-    - objects: ObjectStore instance for creating/reading objects
+    - objects: ObjectBackend (ObjectStore or InMemoryObjectStore) for creating/reading objects
     - call: Function to call other capabilities/services
     - replica_id: ID of the current replica
     - capability: The capability that matched this handler
@@ -275,8 +278,8 @@ class Context:
         self.capability = capability
 
     @property
-    def objects(self):
-        """Access the ObjectStore for creating/reading objects."""
+    def objects(self) -> "ObjectBackend":
+        """Access the object backend for creating/reading objects."""
         if self._objects is None:
             raise RuntimeError(
                 "ObjectStore not available. Make sure --object-store is configured."
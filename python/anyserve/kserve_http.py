@@ -0,0 +1,423 @@
+"""
+KServe v2 REST framing, including the binary tensor data extension.
+
+A REST body is a JSON header, optionally followed by raw tensor bytes. When
+the binary extension is used, the `Inference-Header-Content-Length` HTTP
+header gives the JSON length, and each binary tensor carries
+`"parameters": {"binary_data_size": N}` in place of `"data"`. Its bytes
+follow in tensor order.
+
+decode_infer_request() turns such a body into a ModelInferRequest whose
+tensors are in raw_input_contents, the same form a gRPC client sends. Large
+inputs therefore take the normal SHM offload path. encode_infer_response()
+does the reverse for responses. The client-side pair (encode_infer_request,
+decode_infer_response) is provided for gateways and tests.
+
+Usage:
+    request = decode_infer_request(body, headers.get(HEADER_CONTENT_LENGTH), "llama")
+    response = handler(request)
+    body, header_length = encode_infer_response(response, request)
+"""
+
+import json
+import struct
+from typing import Any as PyAny, Dict, List, Optional, Tuple, Union
+
+from .kserve import (
+    InferInputTensor,
+    InferOutputTensor,
+    InferTensorContents,
+    ModelInferRequest,
+    ModelInferResponse,
+)
+
+HEADER_CONTENT_LENGTH = "Inference-Header-Content-Length"
+BINARY_DATA_SIZE = "binary_data_size"
+BINARY_DATA = "binary_data"
+BINARY_DATA_OUTPUT = "binary_data_output"
+
+# Little-endian element formats for the fixed-size datatypes
+_STRUCT_FORMATS = {
+    "BOOL": "?",
+    "UINT8": "B", "UINT16": "H", "UINT32": "I", "UINT64": "Q",
+    "INT8": "b", "INT16": "h", "INT32": "i", "INT64": "q",
+    "FP16": "e", "FP32": "f", "FP64": "d",
+}
+
+# InferTensorContents field for each datatype (FP16 has none and is always raw)
+_CONTENTS_FIELDS = {
+    "BOOL": "bool_contents",
+    "UINT8": "uint_contents", "UINT16": "uint_contents", "UINT32": "uint_contents",
+    "UINT64": "uint64_contents",
+    "INT8": "int_contents", "INT16": "int_contents", "INT32": "int_contents",
+    "INT64": "int64_contents",
+    "FP32": "fp32_contents", "FP64": "fp64_contents",
+    "BYTES": "bytes_contents",
+}
+
+
+def _flatten(data: PyAny) -> List[PyAny]:
+    if not isinstance(data, list):
+        return [data]
+    flat: List[PyAny] = []
+    for item in data:
+        flat.extend(_flatten(item))
+    return flat
+
+
+def _to_bytes(value: PyAny) -> bytes:
+    return value if isinstance(value, bytes) else str(value).encode("utf-8")
+
+
+def _pack(datatype: str, values: List[PyAny]) -> bytes:
+    """Serialize flattened tensor elements to raw bytes (KServe raw layout)."""
+    if datatype == "BYTES":
+        # Each element is a 4-byte little-endian length followed by its bytes
+        out = bytearray()
+        for value in values:
+            item = _to_bytes(value)
+            out += struct.pack("<I", len(item)) + item
+        return bytes(out)
+    fmt = _STRUCT_FORMATS.get(datatype)
+    if fmt is None:
+        raise ValueError(f"Unsupported datatype: {datatype}")
+    try:
+        return struct.pack(f"<{len(values)}{fmt}", *values)
+    except struct.error as e:
+        raise ValueError(f"Invalid {datatype} data: {e}") from None
+
+
+def _unpack(datatype: str, raw: bytes) -> List[PyAny]:
+    """Parse raw tensor bytes back into a flat list of elements."""
+    if datatype == "BYTES":
+        values: List[PyAny] = []
+        offset = 0
+        while offset < len(raw):
+            if offset + 4 > len(raw):
+                raise ValueError("Truncated BYTES element length")
+            (length,) = struct.unpack_from("<I", raw, offset)
+            offset += 4
+            if offset + length > len(raw):
+                raise ValueError("Truncated BYTES element")
+            values.append(raw[offset:offset + length])
+            offset += length
+        return values
+    fmt = _STRUCT_FORMATS.get(datatype)
+    if fmt is None:
+        raise ValueError(f"Unsupported datatype: {datatype}")
+    size = struct.calcsize(fmt)
+    if len(raw) % size:
+        raise ValueError(f"{len(raw)} bytes is not a whole number of {datatype} elements")
+    return list(struct.unpack(f"<{len(raw) // size}{fmt}", raw))
+
+
+def _json_value(datatype: str, values: List[PyAny]) -> List[PyAny]:
+    """Make elements JSON-serializable (BYTES as UTF-8 strings where possible)."""
+    if datatype != "BYTES":
+        return values
+    out = []
+    for value in values:
+        try:
+            out.append(value.decode("utf-8"))
+        except UnicodeDecodeError:
+            raise ValueError("BYTES output is not valid UTF-8; request it as binary data") from None
+    return out
+
+
+def _split_body(body: bytes, header_length: Optional[Union[int, str]]) -> Tuple[dict, memoryview]:
+    if header_length is None:
+        return json.loads(body), memoryview(b"")
+    length = int(header_length)
+    if length < 0 or length > len(body):
+        raise ValueError(f"{HEADER_CONTENT_LENGTH} {length} exceeds body size {len(body)}")
+    return json.loads(bytes(body[:length])), memoryview(body)[length:]
+
+
+def _take_binary(tensor: dict, binary: memoryview, offset: int) -> Tuple[Optional[bytes], int]:
+    """Slice this tensor's bytes off the binary section, if it has any."""
+    params = tensor.get("parameters") or {}
+    if BINARY_DATA_SIZE not in params:
+        return None, offset
+    size = int(params[BINARY_DATA_SIZE])
+    if size < 0 or offset + size > len(binary):
+        raise ValueError(f"Tensor {tensor.get('name')!r} binary data runs past the end of the body")
+    return bytes(binary[offset:offset + size]), offset + size
+
+
+def _check_input(tensor: PyAny, index: int) -> int:
+    """Reject an input without a name, datatype or valid shape. Returns its element count."""
+    if not isinstance(tensor, dict):
+        raise ValueError(f"Input {index} is not a JSON object")
+    for key in ("name", "datatype"):
+        if not isinstance(tensor.get(key), str):
+            raise ValueError(f"Input {index} has no {key!r}")
+    shape = tensor.get("shape", [])
+    if not isinstance(shape, list) or not all(
+            isinstance(dim, int) and not isinstance(dim, bool) and dim >= 0 for dim in shape):
+        raise ValueError(f"Tensor {tensor['name']!r} has an invalid shape: {shape!r}")
+    count = 1
+    for dim in shape:
+        count *= dim
+    return count
+
+
+def _tensor_data(tensor: dict, count: int) -> List[PyAny]:
+    """The tensor's flattened JSON data, which must hold one element per shape entry."""
+    values = _flatten(tensor.get("data", []))
+    if len(values) != count:
+        raise ValueError(f"Tensor {tensor['name']!r} has {len(values)} elements of data "
+                         f"but shape {tensor.get('shape', [])} needs {count}")
+    return values
+
+
+def _tensor_parameters(tensor: dict) -> Dict[str, PyAny]:
+    params = dict(tensor.get("parameters") or {})
+    params.pop(BINARY_DATA_SIZE, None)
+    return params
+
+
+def decode_infer_request(body: bytes, header_length: Optional[Union[int, str]],
+                         model_name: str, model_version: str = "") -> ModelInferRequest:
+    """
+    Parse a REST inference request body.
+
+    Args:
+        body: Full HTTP body
+        header_length: Value of the Inference-Header-Content-Length header,
+            or None when the body is plain JSON
+        model_name: Model from the request URL
+        model_version: Version from the request URL, if any
+
+    Returns:
+        The request. If any input is binary, every input is put in
+        raw_input_contents (gRPC requires all or none), and JSON data is
+        serialized to the raw layout. Otherwise inputs keep typed contents.
+
+    Raises:
+        ValueError: Malformed JSON, an input without a name, datatype or
+            valid shape, JSON data whose element count doesn't match the
+            shape or can't be packed as the datatype, a binary section that
+            doesn't match the declared sizes, or an unsupported datatype
+    """
+    header, binary = _split_body(body, header_length)
+    if not isinstance(header, dict):
+        raise ValueError("Inference header is not a JSON object")
+    request = ModelInferRequest(
+        model_name=model_name,
+        model_version=model_version,
+        id=header.get("id", ""),
+        parameters=header.get("parameters") or {},
+    )
+
+    tensors = header.get("inputs") or []
+    counts = [_check_input(tensor, i) for i, tensor in enumerate(tensors)]
+    offset = 0
+    raws: List[Optional[bytes]] = []
+    for tensor in tensors:
+        raw, offset = _take_binary(tensor, binary, offset)
+        raws.append(raw)
+    if offset != len(binary):
+        raise ValueError(f"{len(binary) - offset} unclaimed bytes after the binary tensor data")
+
+    use_raw = any(raw is not None for raw in raws) or any(
+        tensor.get("datatype") not in _CONTENTS_FIELDS for tensor in tensors)
+    for tensor, raw, count in zip(tensors, raws, counts):
+        datatype = tensor["datatype"]
+        contents = InferTensorContents()
+        if use_raw:
+            if raw is None:
+                raw = _pack(datatype, _tensor_data(tensor, count))
+            request.raw_input_contents.append(raw)
+        else:
+            values = _tensor_data(tensor, count)
+            if datatype == "BYTES":
+                values = [_to_bytes(v) for v in values]
+            setattr(contents, _CONTENTS_FIELDS[datatype], values)
+        request.inputs.append(InferInputTensor(
+            name=tensor["name"],
+            datatype=datatype,
+            shape=list(tensor.get("shape", [])),
+            contents=contents,
+            parameters=_tensor_parameters(tensor),
+        ))
+
+    for index, output in enumerate(header.get("outputs") or []):
+        if not isinstance(output, dict) or not isinstance(output.get("name"), str):
+            raise ValueError(f"Requested output {index} has no 'name'")
+        request.outputs.append(InferOutputTensor(
+            name=output["name"], datatype="", shape=[],
+            parameters=dict(output.get("parameters") or {}),
+        ))
+    return request
+
+
+def _wants_binary(request: Optional[ModelInferRequest], name: str, raw: Optional[bytes]) -> bool:
+    """
+    Whether to send an output as binary data.
+
+    Without a request, or when the client expressed no preference, raw outputs
+    go as binary and typed outputs as JSON. Otherwise the output's own
+    "binary_data" parameter wins, then the request's "binary_data_output".
+    """
+    default = None if request is None else request.parameters.get(BINARY_DATA_OUTPUT)
+    for output in [] if request is None else request.outputs:
+        if output.name == name and BINARY_DATA in output.parameters:
+            return bool(output.parameters[BINARY_DATA])
+    if default is None:
+        return raw is not None
+    return bool(default)
+
+
+def _typed_values(tensor: Union[InferInputTensor, InferOutputTensor]) -> List[PyAny]:
+    field_name = _CONTENTS_FIELDS.get(tensor.datatype)
+    if field_name is None:
+        raise ValueError(f"Tensor {tensor.name!r} has datatype {tensor.datatype} without typed contents")
+    return list(getattr(tensor.contents, field_name))
+
+
+def encode_infer_response(response: ModelInferResponse,
+                          request: Optional[ModelInferRequest] = None) -> Tuple[bytes, Optional[int]]:
+    """
+    Serialize a response as a REST body.
+
+    Args:
+        response: Handler response; outputs may use typed contents or
+            raw_output_contents (index-aligned with outputs)
+        request: The decoded request, whose "binary_data" / "binary_data_output"
+            parameters choose which outputs are sent as binary data
+
+    Returns:
+        (body, header_length). header_length is the value for the
+        Inference-Header-Content-Length header, or None when the body is plain
+        JSON and the header should be omitted.
+    """
+    raws = response.raw_output_contents
+    outputs = []
+    chunks: List[bytes] = []
+    for i, output in enumerate(response.outputs):
+        raw = raws[i] if i < len(raws) else None
+        as_binary = _wants_binary(request, output.name, raw)
+        entry: Dict[str, PyAny] = {
+            "name": output.name,
+            "datatype": output.datatype,
+            "shape": list(output.shape),
+        }
+        params = dict(output.parameters)
+        if as_binary:
+            data = raw if raw is not None else _pack(output.datatype, _typed_values(output))
+            params[BINARY_DATA_SIZE] = len(data)
+            chunks.append(data)
+        else:
+            values = _unpack(output.datatype, raw) if raw is not None else _typed_values(output)
+            entry["data"] = _json_value(output.datatype, values)
+        if params:
+            entry["parameters"] = params
+        outputs.append(entry)
+
+    header: Dict[str, PyAny] = {"model_name": response.model_name, "outputs": outputs}
+    if response.model_version:
+        header["model_version"] = response.model_version
+    if response.id:
+        header["id"] = response.id
+    if response.parameters:
+        header["parameters"] = response.parameters
+    if response.error:
+        header["error"] = response.error
+
+    header_bytes = json.dumps(header).encode("utf-8")
+    if not chunks:
+        return header_bytes, None
+    return header_bytes + b"".join(chunks), len(header_bytes)
+
+
+def encode_infer_request(request: ModelInferRequest,
+                         binary_inputs: bool = True) -> Tuple[bytes, Optional[int]]:
+    """
+    Serialize a request as a REST body (client side).
+
+    Inputs in raw_input_contents are sent as binary data when binary_inputs is
+    set, otherwise as JSON. Typed inputs are always sent as JSON.
+    """
+    raws = request.raw_input_contents
+    inputs = []
+    chunks: List[bytes] = []
+    for i, tensor in enumerate(request.inputs):
+        raw = raws[i] if i < len(raws) else None
+        entry: Dict[str, PyAny] = {
+            "name": tensor.name,
+            "datatype": tensor.datatype,
+            "shape": list(tensor.shape),
+        }
+        params = dict(tensor.parameters)
+        if raw is not None and binary_inputs:
+            params[BINARY_DATA_SIZE] = len(raw)
+            chunks.append(raw)
+        else:
+            values = _unpack(tensor.datatype, raw) if raw is not None else _typed_values(tensor)
+            entry["data"] = _json_value(tensor.datatype, values)
+        if params:
+            entry["parameters"] = params
+        inputs.append(entry)
+
+    header: Dict[str, PyAny] = {"inputs": inputs}
+    if request.id:
+        header["id"] = request.id
+    if request.parameters:
+        header["parameters"] = request.parameters
+    if request.outputs:
+        header["outputs"] = [
+            {"name": o.name, **({"parameters": o.parameters} if o.parameters else {})}
+            for o in request.outputs
+        ]
+
+    header_bytes = json.dumps(header).encode("utf-8")
+    if not chunks:
+        return header_bytes, None
+    return header_bytes + b"".join(chunks), len(header_bytes)
+
+
+def decode_infer_response(body: bytes, header_length: Optional[Union[int, str]]) -> ModelInferResponse:
+    """
+    Parse a REST inference response body (client side).
+
+    Binary outputs land in raw_output_contents; if any output is binary,
+    JSON outputs are serialized to raw as well so the list stays index-aligned.
+    """
+    header, binary = _split_body(body, header_length)
+    response = ModelInferResponse(
+        model_name=header.get("model_name", ""),
+        model_version=header.get("model_version", ""),
+        id=header.get("id", ""),
+        parameters=header.get("parameters") or {},
+        error=header.get("error"),
+    )
+
+    tensors = header.get("outputs") or []
+    offset = 0
+    raws: List[Optional[bytes]] = []
+    for tensor in tensors:
+        raw, offset = _take_binary(tensor, binary, offset)
+        raws.append(raw)
+    if offset != len(binary):
+        raise ValueError(f"{len(binary) - offset} unclaimed bytes after the binary tensor data")
+
+    use_raw = any(raw is not None for raw in raws)
+    for tensor, raw in zip(tensors, raws):
+        datatype = tensor["datatype"]
+        contents = InferTensorContents()
+        if use_raw:
+            response.raw_output_contents.append(
+                raw if raw is not None else _pack(datatype, _flatten(tensor.get("data", []))))
+        else:
+            values = _flatten(tensor.get("data", []))
+            if datatype == "BYTES":
+                values = [_to_bytes(v) for v in values]
+            setattr(contents, _CONTENTS_FIELDS[datatype], values)
+        response.outputs.append(InferOutputTensor(
+            name=tensor["name"],
+            datatype=datatype,
+            shape=list(tensor.get("shape", [])),
+            contents=contents,
+            parameters=_tensor_parameters(tensor),
+        ))
+    return response
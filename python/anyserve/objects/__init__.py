@@ -2,12 +2,26 @@
 AnyServe Object System - MVP Version
 
 This module provides a simplified Object System based on shared filesystem.
-Objects are stored as files in a shared directory.
+Objects are stored as files in a shared directory. Code that only creates
+and reads objects can take any ObjectBackend, e.g. InMemoryObjectStore in tests.
 """
 
-from .store import ObjectStore, ObjRef
+from .backend import ObjectBackend, open_object_store
+from .memory import InMemoryObjectStore
+from .store import (
+    ObjectStore, ObjectStoreFullError, ObjectStoreQuotaError, ObjectReplicationError, ObjectWriter, ObjRef,
+)
+from .audit import AuditLog
 
 __all__ = [
+    "ObjectBackend",
     "ObjectStore",
+    "InMemoryObjectStore",
+    "open_object_store",
+    "ObjectStoreFullError",
+    "ObjectStoreQuotaError",
+    "ObjectReplicationError",
+    "ObjectWriter",
     "ObjRef",
+    "AuditLog",
 ]
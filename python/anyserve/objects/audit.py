@@ -0,0 +1,125 @@
+"""
+AuditLog - append-only JSONL record of object accesses.
+
+Each line is one operation:
+
+    {"ts": "2026-01-01T00:00:00.000000+00:00", "op": "get", "key": "obj-...",
+     "size": 1024, "peer": "instance-b"}
+
+peer is set when the object was read from or written to another instance's
+store (federated reads, redirects, replication) and null for local access.
+get records also carry "found" and "latency_ms", the time spent locating and
+reading the object. Reads of missing objects are only recorded when
+log_not_found is set; they have "found": false and a null size.
+Records are buffered in memory and appended by a background thread, so
+callers only pay for formatting one line.
+"""
+
+import atexit
+import json
+import os
+import threading
+from datetime import datetime, timezone
+from typing import Collection, List, Optional
+
+
+class AuditLog:
+    """
+    Buffered, append-only JSONL audit log.
+
+    Usage:
+        audit = AuditLog("/var/log/anyserve/objects.jsonl")
+        store = ObjectStore("/tmp/anyserve-objects", audit_log=audit)
+        ...
+        audit.close()
+
+    Records still buffered at interpreter exit are flushed then; a crash can
+    lose at most flush_interval seconds of records.
+    """
+
+    def __init__(
+        self,
+        path: str,
+        flush_interval: float = 1.0,
+        file_mode: Optional[int] = 0o600,
+        ops: Optional[Collection[str]] = None,
+        log_not_found: bool = False,
+    ):
+        """
+        Args:
+            path: Log file; created if missing, never truncated
+            flush_interval: Seconds between background flushes
+            file_mode: Mode for a newly created log file. None leaves it to
+                       the process umask.
+            ops: Operations to record, e.g. {"get"} for a read access log.
+                 None records every operation.
+            log_not_found: Also record reads of objects that do not exist
+        """
+        self.path = path
+        self.flush_interval = flush_interval
+        self.ops = None if ops is None else frozenset(ops)
+        self.log_not_found = log_not_found
+        fd = os.open(path, os.O_WRONLY | os.O_APPEND | os.O_CREAT, 0o666 if file_mode is None else file_mode)
+        self._file = os.fdopen(fd, "a", encoding="utf-8")
+        self._lock = threading.Lock()
+        self._write_lock = threading.Lock()
+        self._pending: List[str] = []
+        self._stop = threading.Event()
+        self._thread = threading.Thread(target=self._run, daemon=True)
+        self._thread.start()
+        atexit.register(self.close)
+
+    def record(
+        self,
+        op: str,
+        key: str,
+        size: Optional[int] = None,
+        peer: Optional[str] = None,
+        found: Optional[bool] = None,
+        latency_ms: Optional[float] = None,
+    ) -> None:
+        """Queue one record, unless ops or log_not_found filter it out. Never blocks on I/O."""
+        if self.ops is not None and op not in self.ops:
+            return
+        if found is False and not self.log_not_found:
+            return
+        entry = {
+            "ts": datetime.now(timezone.utc).isoformat(),
+            "op": op,
+            "key": key,
+            "size": size,
+            "peer": peer,
+        }
+        if found is not None:
+            entry["found"] = found
+        if latency_ms is not None:
+            entry["latency_ms"] = round(latency_ms, 3)
+        line = json.dumps(entry)
+        with self._lock:
+            self._pending.append(line)
+
+    def flush(self) -> None:
+        """Append all queued records to the file."""
+        with self._write_lock:
+            with self._lock:
+                lines, self._pending = self._pending, []
+            if lines and not self._file.closed:
+                self._file.write("\n".join(lines) + "\n")
+                self._file.flush()
+
+    def close(self) -> None:
+        """Stop the background thread and flush what's left. Safe to call twice."""
+        self._stop.set()
+        if self._thread.is_alive() and self._thread is not threading.current_thread():
+            self._thread.join(timeout=self.flush_interval + 1)
+        self.flush()
+        with self._write_lock:
+            self._file.close()
+        atexit.unregister(self.close)
+
+    def _run(self):
+        while not self._stop.wait(self.flush_interval):
+            try:
+                self.flush()
+            except Exception as e:
+                print(f"[AuditLog] Flush to {self.path} failed: {e}")
@@ -0,0 +1,126 @@
+"""
+ObjectBackend - the object API handlers and workers program against.
+
+ObjectStore (files in a shared directory) is the default implementation;
+InMemoryObjectStore keeps objects in the worker process, for tests and
+ephemeral setups that should not touch disk. Workers pick one from their
+--object-store option through open_object_store(), and handlers reach it
+as context.objects.
+
+Filesystem-only features (mmap buffers, federation, replication, quotas,
+eviction) stay on ObjectStore.
+"""
+
+from abc import ABC, abstractmethod
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+from .ref import ObjRef, key_of
+
+MEMORY_URL = "memory://"
+
+
+class ObjectBackend(ABC):
+    """
+    Create, read and delete objects by ObjRef.
+
+    Every method that takes an object accepts an ObjRef, its dict or JSON
+    string form, or the path string of its ObjRef.
+    """
+
+    DEFAULT_MEDIA_TYPE = "application/octet-stream"
+
+    @abstractmethod
+    def create(
+        self,
+        data: Any,
+        key: Optional[str] = None,
+        content_type: Optional[str] = None,
+        durable: bool = True,
+        overwrite: bool = True,
+        media_type: Optional[str] = None,
+        tenant: Optional[str] = None,
+    ) -> ObjRef:
+        """
+        Store data and return a reference to it.
+
+        Args:
+            data: The data to store (any picklable object, bytes, or JSON-serializable)
+            key: Optional key; a unique one is generated if None
+            content_type: Storage format ("pickle", "bytes", "json"). Auto-detected if None.
+            durable: Survive a crash once this returns (where the backend can crash-proof writes)
+            overwrite: Replace an existing object with the same key
+            media_type: Optional caller-supplied type, returned by stat()
+            tenant: Optional tenant the object is charged to
+
+        Raises:
+            ValueError: If key is not a valid object name
+            FileExistsError: If overwrite is False and key exists
+        """
+
+    @abstractmethod
+    def get(self, obj_ref: Union[ObjRef, str, dict]) -> Any:
+        """Read an object. Raises FileNotFoundError if it does not exist."""
+
+    @abstractmethod
+    def stat(self, obj_ref: Union[ObjRef, str, dict]) -> ObjRef:
+        """Read an object's metadata without loading its payload."""
+
+    @abstractmethod
+    def delete(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
+        """Delete an object. Returns True if deleted, False if not found."""
+
+    @abstractmethod
+    def exists(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
+        """Check if an object exists."""
+
+    @abstractmethod
+    def list_objects(self) -> List[ObjRef]:
+        """List all objects."""
+
+    def get_with_media_type(self, obj_ref: Union[ObjRef, str, dict]) -> Tuple[Any, str]:
+        """
+        Read an object together with the media type it was stored with, so
+        the caller knows how to decode mixed payloads.
+
+        Returns:
+            (data, media_type), where data is as returned by get() and
+            media_type is DEFAULT_MEDIA_TYPE if none was given on create
+        """
+        media_type = self.stat(obj_ref).media_type or self.DEFAULT_MEDIA_TYPE
+        return self.get(obj_ref), media_type
+
+    def get_many(self, obj_refs: List[Union[ObjRef, str, dict]]) -> Dict[str, Any]:
+        """
+        Read several objects in one call.
+
+        Returns:
+            Dict mapping object key to data. Objects that don't exist are omitted.
+        """
+        results = {}
+        for obj_ref in obj_refs:
+            try:
+                data = self.get(obj_ref)
+            except FileNotFoundError:
+                continue
+            results[key_of(obj_ref)] = data
+        return results
+
+
+def open_object_store(location: str, **options) -> ObjectBackend:
+    """
+    Open the backend a worker's --object-store names.
+
+    Args:
+        location: memory:// for an InMemoryObjectStore, otherwise the
+                  directory of an ObjectStore
+        **options: ObjectStore arguments (max_bytes, max_age, ...). Options
+                   left at None are ignored; an in-memory store accepts none.
+    """
+    options = {name: value for name, value in options.items() if value is not None}
+    if location == MEMORY_URL:
+        if options:
+            raise ValueError(f"{MEMORY_URL} object store does not support {', '.join(sorted(options))}")
+        from .memory import InMemoryObjectStore
+        return InMemoryObjectStore()
+    from .store import ObjectStore
+    return ObjectStore(location, **options)
@@ -0,0 +1,133 @@
+"""
+ReadCache - in-memory copies of object files for ObjectStore reads.
+
+Entries are keyed by (owner, file name), owner being None for the local
+store, and remember the size and mtime the content was read at: a hit is
+only served while the file still has them, so a rewritten object is read
+again. Pinned entries are kept outside the LRU budget until unpinned.
+"""
+
+import os
+import threading
+from collections import OrderedDict
+from pathlib import Path
+from typing import Dict, Optional, Tuple
+
+CacheKey = Tuple[Optional[str], str]
+
+
+class ReadCache:
+    """
+    LRU cache of object contents, bounded by max_bytes, plus pinned objects.
+
+    Usage:
+        cache = ReadCache(256 << 20)
+        content = cache.read(("instance-a", "obj-1.bin"), path, path.stat())
+    """
+
+    def __init__(self, max_bytes: int):
+        """
+        Args:
+            max_bytes: Budget of the LRU part; 0 disables it. Pinned objects
+                       are held in addition to, not within, this budget.
+        """
+        if max_bytes < 0:
+            raise ValueError("cache_bytes must not be negative")
+        self.max_bytes = max_bytes
+        # key -> (size, mtime_ns, content), least recently used first
+        self._entries: "OrderedDict[CacheKey, Tuple[int, int, bytes]]" = OrderedDict()
+        self._size = 0
+        self._pinned: Dict[CacheKey, Tuple[int, int, bytes]] = {}
+        self._hits = 0
+        self._misses = 0
+        self._lock = threading.Lock()
+
+    @property
+    def active(self) -> bool:
+        """Whether anything can be cached, i.e. writes must invalidate."""
+        return bool(self.max_bytes or self._pinned)
+
+    @property
+    def has_pinned(self) -> bool:
+        return bool(self._pinned)
+
+    def read(self, key: CacheKey, path: Path, st: os.stat_result) -> bytes:
+        """Content of path, from the LRU if it is unchanged since cached (st is its current stat)."""
+        with self._lock:
+            entry = self._entries.get(key)
+            if entry is not None and entry[:2] == (st.st_size, st.st_mtime_ns):
+                self._entries.move_to_end(key)
+                self._hits += 1
+                return entry[2]
+            self._misses += 1
+
+        content = path.read_bytes()
+        if len(content) <= self.max_bytes:
+            with self._lock:
+                old = self._entries.pop(key, None)
+                if old is not None:
+                    self._size -= len(old[2])
+                self._entries[key] = (st.st_size, st.st_mtime_ns, content)
+                self._size += len(content)
+                while self._size > self.max_bytes:
+                    _, (_, _, evicted) = self._entries.popitem(last=False)
+                    self._size -= len(evicted)
+        return content
+
+    def read_pinned(self, key: CacheKey, path: Path) -> Optional[bytes]:
+        """Content of a pinned object file, reloaded if it changed; None if not pinned."""
+        with self._lock:
+            entry = self._pinned.get(key)
+        if entry is None:
+            return None
+        st = path.stat()
+        with self._lock:
+            if entry[:2] == (st.st_size, st.st_mtime_ns):
+                self._hits += 1
+                return entry[2]
+            self._misses += 1
+
+        content = path.read_bytes()
+        with self._lock:
+            if key in self._pinned:
+                self._pinned[key] = (st.st_size, st.st_mtime_ns, content)
+        return content
+
+    def pin(self, key: CacheKey, path: Path) -> None:
+        """Load path into memory and keep it until unpin(); pinning twice is a no-op."""
+        st = path.stat()
+        content = path.read_bytes()
+        with self._lock:
+            old = self._entries.pop(key, None)
+            if old is not None:
+                self._size -= len(old[2])
+            self._pinned[key] = (st.st_size, st.st_mtime_ns, content)
+
+    def unpin(self, key: CacheKey) -> bool:
+        """Release a pinned object. Returns whether it was pinned."""
+        with self._lock:
+            return self._pinned.pop(key, None) is not None
+
+    def invalidate(self, name: str) -> None:
+        """Drop cached and pinned copies of an object file, from any owner."""
+        with self._lock:
+            for key in [key for key in self._entries if key[1] == name]:
+                self._size -= len(self._entries.pop(key)[2])
+            for key in [key for key in self._pinned if key[1] == name]:
+                del self._pinned[key]
+
+    def clear(self) -> None:
+        """Drop every cached object; pinned ones stay. Hit and miss counters are kept."""
+        with self._lock:
+            self._entries.clear()
+            self._size = 0
+
+    def stats(self) -> Dict[str, int]:
+        """Hits, misses, and entries and bytes currently held by the LRU."""
+        with self._lock:
+            return {
+                "hits": self._hits,
+                "misses": self._misses,
+                "entries": len(self._entries),
+                "bytes": self._size,
+            }
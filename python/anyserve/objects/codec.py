@@ -0,0 +1,82 @@
+"""
+Storage formats and key generation for ObjectStore.
+
+An object's content type ("pickle", "bytes", "json") decides how it is
+encoded on disk and is recorded in its file extension, so a file can be
+decoded without its metadata sidecar.
+"""
+
+import hashlib
+import json
+import pickle
+import uuid
+from pathlib import Path
+from typing import Any, Union
+
+CONTENT_TYPE_EXTS = {
+    "pickle": ".pkl",
+    "bytes": ".bin",
+    "json": ".json",
+}
+EXT_CONTENT_TYPES = {ext: content_type for content_type, ext in CONTENT_TYPE_EXTS.items()}
+
+
+def detect_content_type(data: Any) -> str:
+    """Storage format create() uses when none is given."""
+    if isinstance(data, bytes):
+        return "bytes"
+    if isinstance(data, (dict, list, str, int, float, bool, type(None))):
+        return "json"
+    return "pickle"
+
+
+def extension_of(content_type: str) -> str:
+    """File extension for a content type; unknown types are stored as bytes."""
+    return CONTENT_TYPE_EXTS.get(content_type, ".bin")
+
+
+def content_type_of(path: Path) -> str:
+    """Content type recorded in an object file's extension."""
+    return EXT_CONTENT_TYPES.get(path.suffix, "bytes")
+
+
+def serialize(data: Any, content_type: str) -> bytes:
+    """Encode data in a storage format ("pickle", "bytes", "json")."""
+    if content_type == "bytes":
+        return data if isinstance(data, bytes) else pickle.dumps(data)
+    elif content_type == "json":
+        return json.dumps(data).encode()
+    else:  # pickle
+        return pickle.dumps(data)
+
+
+def deserialize(data: Union[bytes, memoryview], content_type: str) -> Any:
+    """Decode stored bytes; "bytes" objects are returned as read."""
+    if content_type == "bytes":
+        return data
+    elif content_type == "json":
+        return json.loads(data)
+    else:  # pickle
+        return pickle.loads(data)
+
+
+def generate_key(data: Any = None) -> str:
+    """Generate a unique key for an object."""
+    # Use UUID + optional content hash for uniqueness
+    unique_id = str(uuid.uuid4())[:12]
+
+    if data is not None:
+        try:
+            # Try to create a content-based prefix
+            content_bytes = pickle.dumps(data)
+            content_hash = hashlib.md5(content_bytes).hexdigest()[:8]
+            return f"obj-{content_hash}-{unique_id}"
+        except Exception:
+            pass
+
+    return f"obj-{unique_id}"
+
+
+def content_key(content: bytes) -> str:
+    """Generate a stable key from the stored bytes (dedup mode)."""
+    return f"sha256-{hashlib.sha256(content).hexdigest()}"
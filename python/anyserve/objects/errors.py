@@ -0,0 +1,73 @@
+"""
+Errors raised by ObjectStore.
+
+ObjectStoreFullError and ObjectStoreQuotaError subclass OSError with the
+errno of the underlying condition (ENOSPC / EDQUOT), so code that already
+handles a full disk handles them too.
+"""
+
+import errno
+from pathlib import Path
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from .ref import ObjRef
+
+# EDQUOT is not defined on every platform
+EDQUOT = getattr(errno, "EDQUOT", errno.ENOSPC)
+DISK_FULL_ERRNOS = {errno.ENOSPC, EDQUOT}
+
+
+class ObjectStoreFullError(OSError):
+    """
+    A write failed because the store's filesystem is full (ENOSPC) or the
+    user's quota is exhausted (EDQUOT).
+
+    Subclasses OSError and keeps the original errno, so existing handlers
+    still catch it.
+    """
+
+    def __init__(self, base_path: Path, usage: int, cause: OSError):
+        super().__init__(
+            cause.errno,
+            f"Object store {base_path} is out of space "
+            f"({usage} bytes stored): {cause.strerror or cause}",
+        )
+        self.base_path = base_path
+        self.usage = usage
+
+
+class ObjectStoreQuotaError(ObjectStoreFullError):
+    """
+    A write was rejected because it would take a tenant past its quota
+    (see ObjectStore quotas). Nothing was written.
+
+    Carries errno EDQUOT, so it is handled wherever a full store is, and
+    a dispatcher that lets it escape is answered with RESOURCE_EXHAUSTED.
+    """
+
+    def __init__(self, base_path: Path, tenant: str, usage: int, quota: int, size: int):
+        OSError.__init__(
+            self,
+            EDQUOT,
+            f"Tenant {tenant!r} is over its quota in {base_path}: "
+            f"{usage} bytes stored + {size} new > {quota}",
+        )
+        self.base_path = base_path
+        self.usage = usage
+        self.tenant = tenant
+        self.quota = quota
+
+
+class ObjectReplicationError(RuntimeError):
+    """
+    create_replicated() could not reach min_acks peer copies.
+
+    The local object (obj_ref) and any peer copies that did succeed are kept.
+    """
+
+    def __init__(self, obj_ref: "ObjRef", acks: int, min_acks: int):
+        super().__init__(f"Replicated {obj_ref.key} to {acks} peer(s), needed {min_acks}")
+        self.obj_ref = obj_ref
+        self.acks = acks
+        self.min_acks = min_acks
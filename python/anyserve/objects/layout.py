@@ -0,0 +1,160 @@
+"""
+On-disk layout of an ObjectStore directory.
+
+Objects are files named <key><ext>, either directly in the store directory
+(flat layout) or in a subdirectory named after the first SHARD_CHARS hex
+digits of the md5 of the key (sharded layout). Next to each object file may
+be hidden companions, all skipped when listing objects:
+
+    .<file>.meta.json        metadata sidecar (media type, tenant, ...)
+    .<file>.moved            owner of an object held by another store
+    .<file>.<random>.tmp     write in flight (see temp_path)
+    .<upload_id>.partial     resumable upload (in the store directory)
+"""
+
+import hashlib
+import os
+import threading
+import time
+import uuid
+from pathlib import Path
+from typing import Dict, Iterable, List, Tuple
+
+SHARD_CHARS = 2
+
+
+def shard_of(key: str) -> str:
+    """Subdirectory holding key in the sharded layout."""
+    return hashlib.md5(key.encode()).hexdigest()[:SHARD_CHARS]
+
+
+def is_shard_dir(name: str) -> bool:
+    return len(name) == SHARD_CHARS and all(c in "0123456789abcdef" for c in name)
+
+
+def meta_path(file_path: Path) -> Path:
+    """Hidden metadata sidecar for an object file."""
+    return file_path.with_name(f".{file_path.name}.meta.json")
+
+
+def moved_path(file_path: Path) -> Path:
+    """Hidden hint recording which owner's store now holds an object."""
+    return file_path.with_name(f".{file_path.name}.moved")
+
+
+def temp_path(file_path: Path) -> Path:
+    """Hidden temp file next to file_path; list_objects() and cleanup() skip it."""
+    return file_path.with_name(f".{file_path.name}.{uuid.uuid4().hex[:8]}.tmp")
+
+
+def fsync_dir(path: Path) -> None:
+    """fsync a directory so renames into it survive a crash."""
+    dir_fd = os.open(path, os.O_RDONLY)
+    try:
+        os.fsync(dir_fd)
+    finally:
+        os.close(dir_fd)
+
+
+def validate_name(name: str) -> None:
+    """Reject names that could escape the store directory or clash with hidden files."""
+    if not name or name in (".", "..") or name.startswith("."):
+        raise ValueError(f"Invalid object name: {name!r}")
+    if any(c in name for c in ("/", "\\", "\0")):
+        raise ValueError(f"Invalid object name: {name!r}")
+
+
+def find(store_dir: Path, name: str) -> Path:
+    """
+    Where the object file name lives in store_dir: the sharded location
+    if a file is there, otherwise the flat one (which may not exist).
+    """
+    sharded = store_dir / shard_of(Path(name).stem) / name
+    if sharded.is_file():
+        return sharded
+    return store_dir / name
+
+
+def scan(root: Path, include_hidden: bool = False) -> Iterable[os.DirEntry]:
+    """
+    Files in the store directory root under both layouts: root itself and
+    its shard subdirectories. Hidden files (sidecars, hints, in-flight
+    writes) are skipped unless include_hidden.
+    """
+    dirs = [root]
+    while dirs:
+        directory = dirs.pop()
+        try:
+            entries = list(os.scandir(directory))
+        except FileNotFoundError:
+            continue
+        for entry in entries:
+            try:
+                if entry.is_dir(follow_symlinks=False):
+                    if directory == root and is_shard_dir(entry.name):
+                        dirs.append(Path(entry.path))
+                    continue
+                if not entry.is_file():
+                    continue
+            except FileNotFoundError:
+                continue
+            if include_hidden or not entry.name.startswith("."):
+                yield entry
+
+
+class SortedListings:
+    """
+    Sorted directory listings for paging through a store, cached per
+    directory until its mtime changes.
+    """
+
+    # A directory's listing is cached once its mtime is this old
+    SETTLE_NS = 1_000_000_000
+
+    def __init__(self, root: Path):
+        """
+        Args:
+            root: The store directory; only its listing includes shard subdirectories
+        """
+        self.root = root
+        self._listings: Dict[Path, Tuple[int, List[str], List[str]]] = {}
+        self._lock = threading.Lock()
+
+    def get(self, directory: Path) -> Tuple[List[str], List[str]]:
+        """
+        (files, shard_dirs) of one directory, each in name order, hidden files
+        skipped. Cached until the directory's mtime changes, so paging through
+        a store doesn't re-read and re-sort it for every page.
+        """
+        try:
+            mtime = directory.stat().st_mtime_ns
+        except FileNotFoundError:
+            return [], []
+        with self._lock:
+            cached = self._listings.get(directory)
+        if cached is not None and cached[0] == mtime:
+            return cached[1], cached[2]
+
+        files, shard_dirs = [], []
+        try:
+            entries = list(os.scandir(directory))
+        except FileNotFoundError:
+            return [], []
+        for entry in entries:
+            try:
+                if entry.is_dir(follow_symlinks=False):
+                    if directory == self.root and is_shard_dir(entry.name):
+                        shard_dirs.append(entry.name)
+                elif entry.is_file() and not entry.name.startswith("."):
+                    files.append(entry.name)
+            except FileNotFoundError:
+                continue
+        files.sort()
+        shard_dirs.sort()
+
+        # mtimes are coarse: a change within the same tick would leave the
+        # mtime as is, so only listings of directories that have settled are kept
+        if time.time_ns() - mtime > self.SETTLE_NS:
+            with self._lock:
+                self._listings[directory] = (mtime, files, shard_dirs)
+        return files, shard_dirs
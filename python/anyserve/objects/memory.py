@@ -0,0 +1,117 @@
+"""
+InMemoryObjectStore - ObjectBackend held in the process, for tests and
+ephemeral setups.
+
+Objects are serialized exactly as ObjectStore would store them, so get()
+returns fresh copies and the same content types round-trip. ObjRef paths
+are memory://<key><ext>; they mean nothing to other processes.
+"""
+
+import threading
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+from .backend import MEMORY_URL, ObjectBackend
+from .codec import deserialize, detect_content_type, extension_of, generate_key, serialize
+from .layout import validate_name
+from .ref import ObjRef
+
+
+class InMemoryObjectStore(ObjectBackend):
+    """
+    ObjectBackend over a dict.
+
+    Usage:
+        store = InMemoryObjectStore()
+        obj_ref = store.create({"key": "value"})
+        data = store.get(obj_ref)
+
+    Workers use one when started with --object-store memory://.
+    """
+
+    def __init__(self):
+        # key -> (ObjRef, stored bytes)
+        self._objects: Dict[str, Tuple[ObjRef, bytes]] = {}
+        self._lock = threading.Lock()
+
+    @staticmethod
+    def _key(obj_ref: Union[ObjRef, str, dict]) -> str:
+        """Key of any supported reference form, including memory:// paths."""
+        if isinstance(obj_ref, ObjRef):
+            return obj_ref.key
+        if isinstance(obj_ref, dict):
+            return obj_ref["key"]
+        if obj_ref.startswith("{"):
+            return ObjRef.from_string(obj_ref).key
+        # Path strings end in <key><ext>, like ObjectStore file names
+        return Path(obj_ref.removeprefix(MEMORY_URL)).stem
+
+    def create(
+        self,
+        data: Any,
+        key: Optional[str] = None,
+        content_type: Optional[str] = None,
+        durable: bool = True,
+        overwrite: bool = True,
+        media_type: Optional[str] = None,
+        tenant: Optional[str] = None,
+    ) -> ObjRef:
+        """Store data; durable has no effect. See ObjectBackend.create()."""
+        if content_type is None:
+            content_type = detect_content_type(data)
+        content = serialize(data, content_type)
+        if key is None:
+            key = generate_key(data)
+        validate_name(key)
+
+        obj_ref = ObjRef(
+            path=f"{MEMORY_URL}{key}{extension_of(content_type)}",
+            key=key,
+            size=len(content),
+            content_type=content_type,
+            media_type=media_type,
+            tenant=tenant,
+        )
+        with self._lock:
+            if not overwrite and key in self._objects:
+                raise FileExistsError(f"Object already exists: {key}")
+            self._objects[key] = (obj_ref, content)
+        return obj_ref
+
+    def _entry(self, obj_ref: Union[ObjRef, str, dict]) -> Tuple[ObjRef, bytes]:
+        key = self._key(obj_ref)
+        with self._lock:
+            entry = self._objects.get(key)
+        if entry is None:
+            raise FileNotFoundError(f"Object not found: {key}")
+        return entry
+
+    def get(self, obj_ref: Union[ObjRef, str, dict]) -> Any:
+        stored, content = self._entry(obj_ref)
+        return deserialize(content, stored.content_type)
+
+    def stat(self, obj_ref: Union[ObjRef, str, dict]) -> ObjRef:
+        stored, _ = self._entry(obj_ref)
+        return ObjRef(**stored.to_dict())
+
+    def delete(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
+        key = self._key(obj_ref)
+        with self._lock:
+            return self._objects.pop(key, None) is not None
+
+    def exists(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
+        key = self._key(obj_ref)
+        with self._lock:
+            return key in self._objects
+
+    def list_objects(self) -> List[ObjRef]:
+        with self._lock:
+            stored = [obj_ref for obj_ref, _ in self._objects.values()]
+        return [ObjRef(**obj_ref.to_dict()) for obj_ref in stored]
+
+    def clear(self) -> int:
+        """Delete all objects. Returns the number deleted."""
+        with self._lock:
+            deleted = len(self._objects)
+            self._objects.clear()
+        return deleted
@@ -0,0 +1,122 @@
+"""
+Per-tenant byte quotas for ObjectStore.
+
+An object's tenant is recorded in its metadata sidecar, so usage is
+recomputed from the sidecars rather than persisted: on startup, and after
+deletes or evictions, which only mark the running totals stale.
+"""
+
+import json
+import os
+import threading
+from contextlib import contextmanager
+from pathlib import Path
+from typing import Callable, Dict, Iterable, Optional
+
+from .errors import ObjectStoreQuotaError
+
+
+def scan_tenant_usage(entries: Iterable[os.DirEntry]) -> Dict[str, int]:
+    """Sum object sizes per tenant from the metadata sidecars among entries."""
+    usage: Dict[str, int] = {}
+    for entry in entries:
+        if not entry.name.endswith(".meta.json"):
+            continue
+        try:
+            tenant = json.loads(Path(entry.path).read_text()).get("tenant")
+            if tenant is None:
+                continue
+            # The object's own size, not the (possibly stale) recorded one
+            size = os.stat(Path(entry.path).with_name(entry.name[1:-len(".meta.json")])).st_size
+        except (OSError, ValueError):
+            # Object or sidecar removed while scanning, or a half-written sidecar
+            continue
+        usage[tenant] = usage.get(tenant, 0) + size
+    return usage
+
+
+class TenantQuotas:
+    """
+    Running per-tenant usage checked against quotas.
+
+    Tenants without a quota are unlimited and not tracked.
+    """
+
+    def __init__(self, quotas: Optional[Dict[str, int]], scan: Callable[[Optional[Path]], Dict[str, int]]):
+        """
+        Args:
+            quotas: Byte quota per tenant
+            scan: Computes usage from the sidecars of a store directory
+                  (None for the local store)
+        """
+        if quotas is not None and any(q < 0 for q in quotas.values()):
+            raise ValueError("quotas must not be negative")
+        self.quotas = dict(quotas or {})
+        self._scan = scan
+        # tenant -> bytes stored; None until (re)computed from the sidecars
+        self._usage: Optional[Dict[str, int]] = None
+        self._lock = threading.Lock()
+        if self.quotas:
+            self._usage = scan(None)
+
+    def _usage_locked(self) -> Dict[str, int]:
+        """Per-tenant usage, rescanned if a delete or eviction made it stale."""
+        if self._usage is None:
+            self._usage = self._scan(None)
+        return self._usage
+
+    @contextmanager
+    def reserve(self, tenant: Optional[str], size: int, old_tenant: Optional[str], old: int,
+                store_dir: Path, local: bool):
+        """
+        Reserve size bytes of tenant's quota for a write that replaces old
+        bytes charged to old_tenant (0 and None for a new object).
+
+        The old bytes are credited back first, to old_tenant, which need not
+        be the writer. Raises ObjectStoreQuotaError, before anything is
+        written, if the result would exceed the quota; if the write itself
+        fails the reservation and the credit are undone. Writes into another
+        instance's store (local False) are checked against that store's
+        current usage, with these quotas.
+        """
+        charged = tenant is not None and tenant in self.quotas
+        credited = old_tenant is not None and old_tenant in self.quotas
+        if not charged and not credited:
+            yield
+            return
+        with self._lock:
+            # A sibling store's usage is cached by its own instance, not ours
+            usage = self._usage_locked() if local else self._scan(store_dir)
+            if charged:
+                used = usage.get(tenant, 0)
+                freed = old if old_tenant == tenant else 0
+                quota = self.quotas[tenant]
+                if used - freed + size > quota:
+                    raise ObjectStoreQuotaError(store_dir, tenant, used, quota, size)
+            if credited:
+                usage[old_tenant] = usage.get(old_tenant, 0) - old
+            if charged:
+                usage[tenant] = usage.get(tenant, 0) + size
+        try:
+            yield
+        except BaseException:
+            with self._lock:
+                if local and self._usage is not None:
+                    if credited:
+                        self._usage[old_tenant] = self._usage.get(old_tenant, 0) + old
+                    if charged:
+                        self._usage[tenant] = self._usage.get(tenant, 0) - size
+            raise
+
+    def stale(self) -> None:
+        """Objects were removed; recount usage on the next quota check."""
+        if self.quotas:
+            with self._lock:
+                self._usage = None
+
+    def usage(self, tenant: str) -> int:
+        """Bytes of objects currently charged to tenant."""
+        with self._lock:
+            if not self.quotas:
+                return self._scan(None).get(tenant, 0)
+            return self._usage_locked().get(tenant, 0)
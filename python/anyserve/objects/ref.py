@@ -0,0 +1,78 @@
+"""
+ObjRef - serializable reference to an object in an ObjectStore.
+"""
+
+import json
+from dataclasses import dataclass, field
+from datetime import datetime
+from pathlib import Path
+from typing import Optional, Union
+
+
+@dataclass
+class ObjRef:
+    """
+    Reference to an Object in the ObjectStore.
+
+    ObjRef can be serialized and passed between Replicas.
+    The receiving Replica can use the path to read the object.
+    """
+    path: str
+    key: str
+    size: int = 0
+    created_at: str = field(default_factory=lambda: datetime.now().isoformat())
+    content_type: str = "pickle"  # "pickle", "bytes", "json"
+    media_type: Optional[str] = None  # caller-supplied, e.g. "image/png"
+    tenant: Optional[str] = None  # whose quota the object counts against
+
+    def to_dict(self) -> dict:
+        return {
+            "path": self.path,
+            "key": self.key,
+            "size": self.size,
+            "created_at": self.created_at,
+            "content_type": self.content_type,
+            "media_type": self.media_type,
+            "tenant": self.tenant,
+        }
+
+    @classmethod
+    def from_dict(cls, data: dict) -> "ObjRef":
+        return cls(**data)
+
+    def to_string(self) -> str:
+        """Serialize ObjRef to a string for passing in requests."""
+        return json.dumps(self.to_dict())
+
+    @classmethod
+    def from_string(cls, s: str) -> "ObjRef":
+        """Deserialize ObjRef from a string."""
+        return cls.from_dict(json.loads(s))
+
+    def __str__(self):
+        return self.path
+
+    def __repr__(self):
+        return f"ObjRef(key={self.key!r}, size={self.size}, type={self.content_type})"
+
+
+def path_of(obj_ref: Union[ObjRef, str, dict]) -> Path:
+    """Get the object file path from any supported reference form."""
+    if isinstance(obj_ref, ObjRef):
+        return Path(obj_ref.path)
+    if isinstance(obj_ref, dict):
+        return Path(obj_ref["path"])
+    if obj_ref.startswith("{"):
+        return Path(ObjRef.from_string(obj_ref).path)
+    return Path(obj_ref)
+
+
+def key_of(obj_ref: Union[ObjRef, str, dict]) -> str:
+    """Get the object key from any supported reference form."""
+    if isinstance(obj_ref, ObjRef):
+        return obj_ref.key
+    if isinstance(obj_ref, dict):
+        return obj_ref["key"]
+    if obj_ref.startswith("{"):
+        return ObjRef.from_string(obj_ref).key
+    return Path(obj_ref).stem
@@ -3,63 +3,55 @@ ObjectStore - File-based Object Storage for MVP.
 
 Objects are stored as files in a shared directory (e.g., /tmp/anyserve-objects/).
 This allows objects to be passed between Replicas on the same machine or via NFS.
+
+The store is split across this package: ref (ObjRef), codec (storage formats
+and keys), layout (file naming, sharding and listing), cache (in-memory
+reads), quota (per-tenant accounting), writer (streamed writes) and errors.
 """
 
 import os
-import uuid
+import bisect
+import heapq
+import itertools
+import mmap
 import json
-import pickle
-import hashlib
-from dataclasses import dataclass, field
+import threading
+import time
+from contextlib import contextmanager
+from concurrent.futures import ThreadPoolExecutor, as_completed
 from datetime import datetime
-from typing import Any, Optional, Union
+from typing import Any, Dict, Iterable, List, Optional, Tuple, Union
 from pathlib import Path
 
+from .audit import AuditLog
+from .backend import ObjectBackend
+from .cache import ReadCache
+from .codec import (
+    EXT_CONTENT_TYPES, content_key, content_type_of, deserialize, detect_content_type, extension_of,
+    generate_key, serialize,
+)
+from .errors import (
+    DISK_FULL_ERRNOS, ObjectReplicationError, ObjectStoreFullError, ObjectStoreQuotaError,
+)
+from .layout import (
+    SHARD_CHARS, SortedListings, find, fsync_dir, is_shard_dir, meta_path, moved_path, scan, shard_of,
+    temp_path, validate_name,
+)
+from .quota import TenantQuotas, scan_tenant_usage
+from .ref import ObjRef, path_of
+from .writer import ObjectWriter
 
-@dataclass
-class ObjRef:
-    """
-    Reference to an Object in the ObjectStore.
+__all__ = [
+    "ObjectStore",
+    "ObjectStoreFullError",
+    "ObjectStoreQuotaError",
+    "ObjectReplicationError",
+    "ObjectWriter",
+    "ObjRef",
+]
 
-    ObjRef can be serialized and passed between Replicas.
-    The receiving Replica can use the path to read the object.
-    """
-    path: str
-    key: str
-    size: int = 0
-    created_at: str = field(default_factory=lambda: datetime.now().isoformat())
-    content_type: str = "pickle"  # "pickle", "bytes", "json"
-
-    def to_dict(self) -> dict:
-        return {
-            "path": self.path,
-            "key": self.key,
-            "size": self.size,
-            "created_at": self.created_at,
-            "content_type": self.content_type,
-        }
-
-    @classmethod
-    def from_dict(cls, data: dict) -> "ObjRef":
-        return cls(**data)
-
-    def to_string(self) -> str:
-        """Serialize ObjRef to a string for passing in requests."""
-        return json.dumps(self.to_dict())
-
-    @classmethod
-    def from_string(cls, s: str) -> "ObjRef":
-        """Deserialize ObjRef from a string."""
-        return cls.from_dict(json.loads(s))
-
-    def __str__(self):
-        return self.path
-
-    def __repr__(self):
-        return f"ObjRef(key={self.key!r}, size={self.size}, type={self.content_type})"
-
-
-class ObjectStore:
+
+class ObjectStore(ObjectBackend):
     """
     File-based Object Store.
 
@@ -80,95 +72,278 @@ class ObjectStore:
 
         # Create with specific key
         obj_ref = store.create(data, key="my-object")
+
+        # Create under a validated, stable name (fails if taken)
+        obj_ref = store.create_named("model-v1", data)
+
+        # Content-addressed store: identical payloads share one file
+        store = ObjectStore("/tmp/anyserve-objects", dedup=True)
+
+        # Bounded store: evict least recently used objects past 10GB or 1 day
+        store = ObjectStore("/tmp/anyserve-objects", max_bytes=10 << 30, max_age=86400)
+
+        # Large store: spread files over 256 subdirectories (objects/3f/obj-...)
+        store = ObjectStore("/tmp/anyserve-objects", shard=True)
+
+        # Keep up to 256MB of objects read from other instances' stores in memory
+        store = ObjectStore("/tmp/anyserve-objects", federated=True, cache_bytes=256 << 20)
+        store.cache_pin(obj_ref)  # always served from memory, never evicted
+
+        # Copy an object from instance-a's store into this one
+        obj_ref = store.fetch(obj_ref, owner="instance-a")
+
+        # Shared store: each tenant may hold at most 1GB
+        store = ObjectStore("/tmp/anyserve-objects", quotas={"team-a": 1 << 30})
+        obj_ref = store.create(data, tenant="team-a")  # ObjectStoreQuotaError past 1GB
     """
 
-    def __init__(self, base_path: str = "/tmp/anyserve-objects"):
+    DEFAULT_MMAP_THRESHOLD = 1024 * 1024  # 1MB
+    DEFAULT_DIR_MODE = 0o700
+    DEFAULT_FILE_MODE = 0o600
+    DEFAULT_GC_INTERVAL = 60.0
+    SHARD_CHARS = SHARD_CHARS
+
+    def __init__(
+        self,
+        base_path: str = "/tmp/anyserve-objects",
+        dedup: bool = False,
+        mmap_threshold: int = DEFAULT_MMAP_THRESHOLD,
+        federated: bool = False,
+        dir_mode: Optional[int] = DEFAULT_DIR_MODE,
+        file_mode: Optional[int] = DEFAULT_FILE_MODE,
+        check_permissions: bool = True,
+        audit_log: Optional[Union[AuditLog, str]] = None,
+        sync_interval: Optional[float] = None,
+        max_bytes: Optional[int] = None,
+        max_age: Optional[float] = None,
+        gc_interval: float = DEFAULT_GC_INTERVAL,
+        shard: bool = False,
+        cache_bytes: int = 0,
+        quotas: Optional[Dict[str, int]] = None,
+    ):
         """
         Initialize ObjectStore.
 
         Args:
             base_path: Directory to store objects
+            dedup: Content-addressed mode. Generated keys become the sha256 of the
+                   stored bytes, so identical payloads share one file.
+            mmap_threshold: Objects at least this many bytes are memory-mapped by
+                            get_buffer() instead of read into a new buffer.
+            federated: Node-local federation. Reads that miss locally fall back to
+                       sibling stores laid out as <root>/*/<base_path.name>, e.g.
+                       other instances' instances/<id>/objects directories.
+            dir_mode: Mode for directories the store creates. None leaves it
+                      to the process umask.
+            file_mode: Mode for object and metadata files. None leaves it to
+                       the process umask.
+            check_permissions: Warn if an existing base_path grants more
+                               access than dir_mode.
+            audit_log: AuditLog, or a path to open one at, that records every
+                       put, get and delete, including reads served by and
+                       copies sent to other instances' stores. Reads are
+                       recorded with their latency, and misses too if the
+                       log has log_not_found set.
+            sync_interval: Background fsync for durable=False writes. Such
+                           objects are fsync'd (file and directory) by a
+                           background thread within about this many seconds,
+                           which bounds what a crash can lose without putting
+                           fsync on the write path. None (the default) never
+                           syncs them; see sync() and close().
+            max_bytes: Evict least recently used objects while the objects
+                       in base_path total more than this many bytes.
+            max_age: Evict objects not read or written for this many seconds.
+            gc_interval: Seconds between background gc() runs. The GC thread
+                         only runs when max_bytes or max_age is set; pinned
+                         objects (see pin()) are never evicted.
+            shard: Write new objects to base_path/<xx>/<file>, where xx is
+                   the first SHARD_CHARS hex digits of the md5 of the key, so
+                   no single directory holds millions of entries. Reads,
+                   listing, deletes and GC understand both layouts either
+                   way, so a store can be switched over without moving the
+                   objects it already holds.
+            cache_bytes: Size of an in-memory LRU cache for objects read from
+                         other instances' stores (federated reads and
+                         mark_moved redirects), keyed by (owner, file name).
+                         Hits still stat the remote file and are only served
+                         if its size and mtime are unchanged. Objects of at
+                         least mmap_threshold bytes are mapped, not cached.
+                         0 disables it. Objects pinned with cache_pin() are
+                         held in addition to, not within, this budget.
+            quotas: Byte quota per tenant. Objects created with tenant=
+                    count against that tenant's entry; a write that would
+                    take it past the quota raises ObjectStoreQuotaError.
+                    Tenants without an entry are unlimited. The tenant is
+                    recorded in the object's metadata sidecar, and usage is
+                    recomputed from the sidecars when the store is opened,
+                    so it survives restarts.
         """
         self.base_path = Path(base_path)
-        self._ensure_directory()
+        self.dedup = dedup
+        self.mmap_threshold = mmap_threshold
+        self.federated = federated
+        self.shard = shard
+        self.dir_mode = dir_mode
+        self.file_mode = file_mode
+        # Per-thread set of directories whose fsync create_many() defers
+        self._batch = threading.local()
+        self._ensure_directory(check_permissions)
+        self._owns_audit_log = isinstance(audit_log, str)
+        self.audit_log = AuditLog(audit_log, file_mode=file_mode) if self._owns_audit_log else audit_log
 
-    def _ensure_directory(self):
-        """Create the storage directory if it doesn't exist."""
-        self.base_path.mkdir(parents=True, exist_ok=True)
+        # Files written with durable=False, waiting for the background sync
+        self.sync_interval = sync_interval
+        self._unsynced = set()
+        self._unsynced_lock = threading.Lock()
+        self._sync_stop = threading.Event()
+        self._sync_thread: Optional[threading.Thread] = None
+        if sync_interval is not None:
+            if sync_interval <= 0:
+                raise ValueError("sync_interval must be positive")
+            self._sync_thread = threading.Thread(target=self._sync_loop, daemon=True)
+            self._sync_thread.start()
 
-    def _generate_key(self, data: Any = None) -> str:
-        """Generate a unique key for an object."""
-        # Use UUID + optional content hash for uniqueness
-        unique_id = str(uuid.uuid4())[:12]
+        # In-flight reads and explicit pins, by object file path; gc() skips these
+        self.max_bytes = max_bytes
+        self.max_age = max_age
+        self.gc_interval = gc_interval
+        self._pins: Dict[Path, int] = {}
+        self._pins_lock = threading.Lock()
+        # Sorted directory listings for list_objects_page
+        self._listings = SortedListings(self.base_path)
+        self._gc_stop = threading.Event()
+        self._gc_thread: Optional[threading.Thread] = None
+        if max_bytes is not None and max_bytes < 0:
+            raise ValueError("max_bytes must not be negative")
+        if max_age is not None and max_age < 0:
+            raise ValueError("max_age must not be negative")
+        if max_bytes is not None or max_age is not None:
+            if gc_interval <= 0:
+                raise ValueError("gc_interval must be positive")
+            self._gc_thread = threading.Thread(target=self._gc_loop, daemon=True)
+            self._gc_thread.start()
 
-        if data is not None:
-            try:
-                # Try to create a content-based prefix
-                content_bytes = pickle.dumps(data)
-                content_hash = hashlib.md5(content_bytes).hexdigest()[:8]
-                return f"obj-{content_hash}-{unique_id}"
-            except Exception:
-                pass
+        self._cache = ReadCache(cache_bytes)
+        self.cache_bytes = cache_bytes
 
-        return f"obj-{unique_id}"
+        self._quotas = TenantQuotas(quotas, self._scan_tenant_usage)
+        self.quotas = self._quotas.quotas
+
+    def _ensure_directory(self, check_permissions: bool = False):
+        """Create the storage directory if it doesn't exist."""
+        if self.base_path.is_dir():
+            if check_permissions and self.dir_mode is not None:
+                mode = self.base_path.stat().st_mode & 0o777
+                if mode & ~self.dir_mode:
+                    print(f"[ObjectStore] Warning: {self.base_path} has mode {mode:o}, "
+                          f"more permissive than {self.dir_mode:o}")
+            return
+
+        missing = []
+        path = self.base_path
+        while not path.exists():
+            missing.append(path)
+            path = path.parent
+        self.base_path.mkdir(parents=True, exist_ok=True)
+        if self.dir_mode is not None:
+            # mkdir(mode=) is masked by the umask; chmod sets it exactly
+            for created in missing:
+                os.chmod(created, self.dir_mode)
+
+    def _open_temp(self, tmp_path: Path):
+        """Create a temp file for writing with file_mode applied."""
+        f = open(tmp_path, "xb")
+        if self.file_mode is not None:
+            os.fchmod(f.fileno(), self.file_mode)
+        return f
 
     def _get_file_path(self, key: str, content_type: str = "pickle") -> Path:
         """Get the file path for an object key."""
-        ext_map = {
-            "pickle": ".pkl",
-            "bytes": ".bin",
-            "json": ".json",
-        }
-        ext = ext_map.get(content_type, ".bin")
-        return self.base_path / f"{key}{ext}"
+        ext = extension_of(content_type)
+        if not self.shard:
+            return self.base_path / f"{key}{ext}"
+        shard_dir = self.base_path / shard_of(key)
+        if not shard_dir.is_dir():
+            shard_dir.mkdir(exist_ok=True)
+            if self.dir_mode is not None:
+                os.chmod(shard_dir, self.dir_mode)
+        return shard_dir / f"{key}{ext}"
+
+    def _store_dir_of(self, path: Path) -> Path:
+        """The store directory an object file is in, under either layout."""
+        parent = path.parent
+        if is_shard_dir(parent.name) and parent.parent.name == self.base_path.name:
+            return parent.parent
+        return parent
+
+    def _scan(self, include_hidden: bool = False, store_dir: Optional[Path] = None) -> Iterable[os.DirEntry]:
+        """Files in this store under both layouts (see layout.scan); store_dir scans a sibling store instead."""
+        return scan(self.base_path if store_dir is None else store_dir, include_hidden)
 
     def create(
         self,
         data: Any,
         key: Optional[str] = None,
         content_type: Optional[str] = None,
+        durable: bool = True,
+        overwrite: bool = True,
+        media_type: Optional[str] = None,
+        tenant: Optional[str] = None,
     ) -> ObjRef:
         """
         Create a new object in the store.
 
         Args:
             data: The data to store (any picklable object, bytes, or JSON-serializable)
-            key: Optional key for the object. If None, a unique key is generated.
+            key: Optional key for the object. If None, a unique key is generated
+                 (or the content hash when dedup is enabled).
             content_type: Storage format ("pickle", "bytes", "json"). Auto-detected if None.
+            durable: fsync the object and its directory entry before
+                     returning, so it survives power loss once the ObjRef is
+                     handed out. This costs two disk flushes per object:
+                     around a millisecond on local SSDs, far more on network
+                     filesystems. With durable=False, writes are still atomic
+                     (a crash leaves the old object or none, never a partial
+                     one), but recent objects can be lost. sync_interval
+                     bounds how recent.
+            overwrite: Replace an existing file with the same key. If False,
+                       raises FileExistsError instead.
+            media_type: Optional caller-supplied type, recorded in a metadata
+                        sidecar and returned by stat().
+            tenant: Optional tenant the object is charged to (see quotas),
+                    recorded in the metadata sidecar.
 
         Returns:
             ObjRef pointing to the created object
-        """
-        # Generate key if not provided
-        if key is None:
-            key = self._generate_key(data)
 
+        Raises:
+            ValueError: If key could escape the store directory
+            ObjectStoreQuotaError: If the object would take tenant past its quota
+        """
         # Auto-detect content type
         if content_type is None:
-            if isinstance(data, bytes):
-                content_type = "bytes"
-            elif isinstance(data, (dict, list, str, int, float, bool, type(None))):
-                content_type = "json"
-            else:
-                content_type = "pickle"
+            content_type = detect_content_type(data)
+
+        content = serialize(data, content_type)
+        size = len(content)
+
+        # Generate key if not provided
+        content_addressed = key is None and self.dedup
+        if key is None:
+            key = content_key(content) if self.dedup else generate_key(data)
+        validate_name(key)
 
         # Get file path
         file_path = self._get_file_path(key, content_type)
 
-        # Write data
-        if content_type == "bytes":
-            if not isinstance(data, bytes):
-                data = pickle.dumps(data)
-            file_path.write_bytes(data)
-            size = len(data)
-        elif content_type == "json":
-            json_str = json.dumps(data)
-            file_path.write_text(json_str)
-            size = len(json_str)
-        else:  # pickle
-            content = pickle.dumps(data)
-            file_path.write_bytes(content)
-            size = len(content)
+        # A content-addressed file that exists already holds these bytes. It stays
+        # owned by, and charged to, whoever stored them first.
+        if content_addressed and file_path.exists():
+            self._audit("put", file_path, size)
+            return self.stat(str(file_path))
+
+        with self._charged(tenant, file_path, size):
+            self._write_atomic(file_path, content, durable, overwrite)
 
         # Create ObjRef
         obj_ref = ObjRef(
@@ -176,10 +351,518 @@ class ObjectStore:
             key=key,
             size=size,
             content_type=content_type,
+            media_type=media_type,
+            tenant=tenant,
+        )
+
+        # Only objects with extra metadata need a sidecar; stat() falls back to the file
+        if media_type is not None or tenant is not None:
+            meta = obj_ref.to_dict()
+            del meta["path"]
+            self._write_atomic(meta_path(file_path), json.dumps(meta).encode(), durable)
+        elif overwrite:
+            # A replaced object's sidecar would keep charging its old tenant
+            meta_path(file_path).unlink(missing_ok=True)
+
+        self._audit("put", file_path, size)
+        return obj_ref
+
+    def create_many(
+        self,
+        datas: Iterable[Any],
+        content_type: Optional[str] = None,
+        durable: bool = True,
+        tenant: Optional[str] = None,
+    ) -> List[ObjRef]:
+        """
+        Create a batch of objects with generated keys.
+
+        Each object is still its own atomically written file, but with
+        durable set the directory is fsync'd once for the whole batch rather
+        than once per object, which dominates the cost for many small
+        objects.
+
+        Args:
+            datas: Objects to store, as for create()
+            content_type: Storage format for all of them. Auto-detected per
+                          object if None.
+            durable: fsync the objects before returning
+            tenant: Tenant all of them are charged to, as for create().
+                    Objects created before one is rejected for quota are kept.
+
+        Returns:
+            ObjRefs in the same order as datas
+        """
+        self._batch.dirs = set()
+        try:
+            return [self.create(data, content_type=content_type, durable=durable, tenant=tenant)
+                    for data in datas]
+        finally:
+            # Objects committed before a failure are synced too
+            dirs, self._batch.dirs = self._batch.dirs, None
+            for path in sorted(dirs):
+                self._fsync_dir(path)
+
+    def create_named(
+        self,
+        name: str,
+        data: Any,
+        overwrite: bool = False,
+        content_type: Optional[str] = None,
+        durable: bool = True,
+        media_type: Optional[str] = None,
+        tenant: Optional[str] = None,
+    ) -> ObjRef:
+        """
+        Create an object under a caller-chosen name.
+
+        Args:
+            name: Object key. Must be a plain file name (no path separators,
+                  no leading dot, not "." or "..").
+            data: The data to store
+            overwrite: Replace an existing object with the same name
+            content_type: Storage format ("pickle", "bytes", "json"). Auto-detected if None.
+            durable: fsync the object before returning
+            media_type: Optional caller-supplied type, returned by stat()
+            tenant: Optional tenant the object is charged to (see quotas)
+
+        Returns:
+            ObjRef pointing to the created object
+
+        Raises:
+            ValueError: If name is not a valid object name
+            FileExistsError: If the name is taken and overwrite is False
+            ObjectStoreQuotaError: If the object would take tenant past its quota
+        """
+        validate_name(name)
+
+        if not overwrite and any(find(self.base_path, f"{name}{ext}").exists()
+                                 for ext in EXT_CONTENT_TYPES):
+            raise FileExistsError(f"Object already exists: {name}")
+
+        return self.create(data, key=name, content_type=content_type, durable=durable,
+                           overwrite=overwrite, media_type=media_type, tenant=tenant)
+
+    def replace(self, obj_ref: Union[ObjRef, str, dict], data: Any, durable: bool = True) -> ObjRef:
+        """
+        Atomically replace the content of an existing object.
+
+        The new content is written to a temp file and renamed over the
+        object, so a concurrent get() sees either the whole old or the whole
+        new version, never a mix. Readers that already opened or mapped the
+        old version (e.g. a get_buffer() memoryview) keep reading it until
+        they drop it. The object keeps its key and content type; a metadata
+        sidecar, if any, keeps its media_type and tenant and is updated with
+        the new size. Growing an object counts against its tenant's quota.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation
+            data: The new data, serialized with the object's content type
+            durable: As for create()
+
+        Returns:
+            ObjRef of the replaced object
+
+        Raises:
+            FileNotFoundError: If the object does not exist
+            ValueError: If the object is content-addressed (a sha256-* key in
+                        a dedup store), whose key must match its bytes
+            ObjectStoreQuotaError: If the new content would take the object's
+                                   tenant past its quota
+        """
+        path = self._locate(path_of(obj_ref))
+        if not path.exists():
+            raise FileNotFoundError(f"Object not found: {path}")
+        old = self.stat(str(path))
+        if self.dedup and old.key.startswith("sha256-"):
+            # Other creators of the same bytes share this file and its key
+            raise ValueError(f"Cannot replace content-addressed object: {old.key}")
+        content = serialize(data, old.content_type)
+
+        with self._charged(old.tenant, path, len(content)):
+            self._write_atomic(path, content, durable)
+        if self._cache.active:
+            self._cache.invalidate(path.name)
+        obj_ref = ObjRef(
+            path=str(path),
+            key=old.key,
+            size=len(content),
+            content_type=old.content_type,
+            media_type=old.media_type,
+            tenant=old.tenant,
         )
+        if meta_path(path).exists():
+            meta = obj_ref.to_dict()
+            del meta["path"]
+            self._write_atomic(meta_path(path), json.dumps(meta).encode(), durable)
 
+        self._audit("put", path, len(content))
         return obj_ref
 
+    @contextmanager
+    def _charged(self, tenant: Optional[str], file_path: Path, size: int):
+        """
+        Reserve size bytes of tenant's quota for a write to file_path (see
+        TenantQuotas.reserve). An existing file at file_path is being
+        overwritten, so its size is credited back to the tenant its sidecar
+        names. Writes into another instance's store (replication) are checked
+        against that store's current usage.
+        """
+        try:
+            old = file_path.stat().st_size
+        except FileNotFoundError:
+            old = 0
+        old_tenant = self._tenant_of(file_path) if old else None
+        store_dir = self._store_dir_of(file_path)
+        with self._quotas.reserve(tenant, size, old_tenant, old, store_dir, local=store_dir == self.base_path):
+            yield
+
+    def _tenant_of(self, file_path: Path) -> Optional[str]:
+        """The tenant recorded in file_path's sidecar, if any."""
+        try:
+            return json.loads(meta_path(file_path).read_text()).get("tenant")
+        except (OSError, ValueError):
+            return None
+
+    def _scan_tenant_usage(self, store_dir: Optional[Path] = None) -> Dict[str, int]:
+        """Sum object sizes per tenant from the metadata sidecars."""
+        return scan_tenant_usage(self._scan(include_hidden=True, store_dir=store_dir))
+
+    def tenant_usage(self, tenant: str) -> int:
+        """Bytes of objects currently charged to tenant."""
+        return self._quotas.usage(tenant)
+
+    def _write_atomic(self, file_path: Path, content: bytes, durable: bool, overwrite: bool = True) -> None:
+        """
+        Write content to a hidden temp file, then rename it into place.
+
+        Readers only ever see complete objects under the final name. When
+        durable is set, the file and directory are fsync'd so the object
+        survives a crash once this returns. With overwrite=False the final
+        name is claimed with link(), which fails if it already exists.
+        """
+        tmp_path = temp_path(file_path)
+        try:
+            with self._open_temp(tmp_path) as f:
+                f.write(content)
+                if durable:
+                    f.flush()
+                    os.fsync(f.fileno())
+        except BaseException as e:
+            tmp_path.unlink(missing_ok=True)
+            self._raise_if_full(e)
+            raise
+        self._commit_temp(tmp_path, file_path, durable, overwrite)
+
+    def _raise_if_full(self, error: BaseException) -> None:
+        """Re-raise an out-of-space write failure as ObjectStoreFullError."""
+        if isinstance(error, OSError) and error.errno in DISK_FULL_ERRNOS:
+            raise ObjectStoreFullError(self.base_path, self.storage_usage(), error) from error
+
+    def _commit_temp(self, tmp_path: Path, file_path: Path, durable: bool, overwrite: bool) -> None:
+        """Move a fully written temp file to its final name (see _write_atomic)."""
+        try:
+            if overwrite:
+                os.replace(tmp_path, file_path)
+            else:
+                os.link(tmp_path, file_path)
+                tmp_path.unlink()
+        except BaseException:
+            tmp_path.unlink(missing_ok=True)
+            raise
+
+        if durable:
+            pending = getattr(self._batch, "dirs", None)
+            if pending is not None:
+                pending.add(file_path.parent)
+            else:
+                self._fsync_dir(file_path.parent)
+        elif self._sync_thread is not None:
+            with self._unsynced_lock:
+                self._unsynced.add(file_path)
+
+    _fsync_dir = staticmethod(fsync_dir)
+
+    def sync(self) -> int:
+        """
+        fsync every object written with durable=False since the last sync.
+
+        Only tracked when sync_interval is set. Objects deleted in the
+        meantime are skipped.
+
+        Returns:
+            Number of files synced
+        """
+        with self._unsynced_lock:
+            paths, self._unsynced = self._unsynced, set()
+        synced = 0
+        for path in paths:
+            try:
+                fd = os.open(path, os.O_RDONLY)
+            except FileNotFoundError:
+                continue
+            try:
+                os.fsync(fd)
+            finally:
+                os.close(fd)
+            synced += 1
+        for directory in {path.parent for path in paths}:
+            try:
+                self._fsync_dir(directory)
+            except FileNotFoundError:
+                continue
+        return synced
+
+    def _sync_loop(self):
+        while not self._sync_stop.wait(self.sync_interval):
+            try:
+                self.sync()
+            except Exception as e:
+                print(f"[ObjectStore] Background sync failed: {e}")
+
+    def close(self) -> None:
+        """
+        Stop the background sync after a final sync(), stop the background GC,
+        and close an audit log the store opened itself. Reads and writes still
+        work afterwards, but durable=False objects are no longer synced and
+        the limits are no longer enforced.
+        """
+        if self._gc_thread is not None:
+            self._gc_stop.set()
+            self._gc_thread.join(timeout=self.gc_interval + 1)
+            self._gc_thread = None
+        if self._sync_thread is not None:
+            self._sync_stop.set()
+            self._sync_thread.join(timeout=self.sync_interval + 1)
+            self._sync_thread = None
+            self.sync()
+        if self._owns_audit_log and self.audit_log is not None:
+            self.audit_log.close()
+
+    def open_writer(
+        self,
+        key: Optional[str] = None,
+        durable: bool = True,
+        overwrite: bool = True,
+        media_type: Optional[str] = None,
+        upload_id: Optional[str] = None,
+        offset: Optional[int] = None,
+        tenant: Optional[str] = None,
+    ) -> "ObjectWriter":
+        """
+        Open a writer that stores a bytes object from chunks.
+
+        Only the current chunk is held in memory, so large payloads can be
+        streamed in. The object becomes visible under its final name when
+        finish() returns; until then it lives in a hidden temp file.
+
+        With upload_id the upload is resumable: data goes to a hidden
+        ".<upload_id>.partial" file that outlives the writer. If the writer
+        is left without finish() (dropped connection, exception), the
+        partial file is kept, and a later open_writer() with the same
+        upload_id continues from upload_offset(). abort() discards it.
+
+        Args:
+            key: Optional key. If None, a unique key is generated (or the
+                 content hash, computed while streaming, when dedup is enabled).
+            durable, overwrite, media_type: As for create().
+            upload_id: Resumable upload session name (a plain file name, as
+                       for create_named())
+            offset: Byte offset to resume at; data past it is dropped. Must
+                    not exceed upload_offset(). Defaults to upload_offset().
+            tenant: As for create(). The quota is checked by finish(), once
+                    the size is known.
+
+        Returns:
+            ObjectWriter with write(chunk) and finish() -> ObjRef
+        """
+        if offset is not None and upload_id is None:
+            raise ValueError("offset requires upload_id")
+        return ObjectWriter(self, key, durable, overwrite, media_type, upload_id, offset, tenant)
+
+    def _partial_path(self, upload_id: str) -> Path:
+        """Hidden file holding an in-progress resumable upload."""
+        validate_name(upload_id)
+        return self.base_path / f".{upload_id}.partial"
+
+    def upload_offset(self, upload_id: str) -> int:
+        """
+        Bytes already received for a resumable upload (0 if there is none).
+
+        A client resumes by sending its data from this offset.
+        """
+        try:
+            return self._partial_path(upload_id).stat().st_size
+        except FileNotFoundError:
+            return 0
+
+    def discard_upload(self, upload_id: str) -> bool:
+        """Delete a resumable upload's partial data. Returns True if it existed."""
+        path = self._partial_path(upload_id)
+        existed = path.exists()
+        path.unlink(missing_ok=True)
+        return existed
+
+    def create_from_chunks(self, chunks: Iterable[bytes], key: Optional[str] = None, **kwargs) -> ObjRef:
+        """
+        Store a bytes object from an iterable of chunks.
+
+        Convenience wrapper around open_writer(); kwargs are passed through.
+        """
+        with self.open_writer(key, **kwargs) as writer:
+            for chunk in chunks:
+                writer.write(chunk)
+            return writer.finish()
+
+    def create_replicated(self, data: Any, peers: List[str], min_acks: int, **kwargs) -> ObjRef:
+        """
+        Create an object here and copy it to peer instances' stores.
+
+        Copies go to <root>/<peer>/<base_path.name> (see federated) in parallel
+        and this returns as soon as min_acks of them have landed; the rest
+        finish in the background. Copy failures are logged, and only fatal
+        when they make min_acks unreachable.
+
+        Args:
+            data: Object data, as for create()
+            peers: Instance ids whose stores receive a copy
+            min_acks: Peer copies required before returning (0 to len(peers))
+            **kwargs: Passed through to create()
+
+        Returns:
+            ObjRef of the local object
+
+        Raises:
+            ObjectReplicationError: If fewer than min_acks copies succeed
+        """
+        if not 0 <= min_acks <= len(peers):
+            raise ValueError(f"min_acks must be between 0 and {len(peers)}, got {min_acks}")
+        for peer in peers:
+            validate_name(peer)
+
+        obj_ref = self.create(data, **kwargs)
+        if not peers:
+            return obj_ref
+
+        durable = kwargs.get("durable", True)
+        executor = ThreadPoolExecutor(max_workers=len(peers))
+        try:
+            futures = {}
+            for peer in peers:
+                future = executor.submit(self._replicate_to, obj_ref, peer, durable)
+                future.add_done_callback(lambda f, peer=peer: self._log_replication(f, obj_ref, peer))
+                futures[future] = peer
+            if min_acks == 0:
+                return obj_ref
+
+            acks = failures = 0
+            for future in as_completed(futures):
+                if future.exception() is None:
+                    acks += 1
+                    if acks >= min_acks:
+                        return obj_ref
+                else:
+                    failures += 1
+                    if len(peers) - failures < min_acks:
+                        break
+            raise ObjectReplicationError(obj_ref, acks, min_acks)
+        finally:
+            executor.shutdown(wait=False)
+
+    def _replicate_to(self, obj_ref: ObjRef, peer: str, durable: bool) -> None:
+        """Copy an object (and its metadata sidecar) into a peer's store."""
+        src = Path(obj_ref.path)
+        dest = self._owner_path(peer, src)
+        peer_dir = self._store_dir_of(dest)
+        if not peer_dir.is_dir():
+            raise FileNotFoundError(f"No object store for {peer} at {peer_dir}")
+        if self.shard and dest.parent == peer_dir:
+            # Copy into the peer using our layout; its reads accept either one
+            dest = peer_dir / shard_of(src.stem) / src.name
+            dest.parent.mkdir(exist_ok=True)
+            if self.dir_mode is not None:
+                os.chmod(dest.parent, self.dir_mode)
+        with self._charged(obj_ref.tenant, dest, obj_ref.size):
+            self._copy_atomic(src, dest, durable)
+        meta = meta_path(src)
+        if meta.exists():
+            self._write_atomic(meta_path(dest), meta.read_bytes(), durable)
+        self._audit("put", dest, obj_ref.size)
+
+    @staticmethod
+    def _log_replication(future, obj_ref: ObjRef, peer: str) -> None:
+        error = future.exception()
+        if error is not None:
+            print(f"[ObjectStore] Replicating {obj_ref.key} to {peer} failed: {error}")
+
+    def fetch(self, obj_ref: Union[ObjRef, str, dict], owner: str, durable: bool = True) -> ObjRef:
+        """
+        Copy an object from another instance's store into this one.
+
+        The file is copied by the kernel (sendfile), so the payload never
+        passes through Python however large it is. The copy keeps the
+        object's file name, and so its key, and its metadata sidecar. An
+        object already held here is returned as is.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation (only the
+                     file name is used)
+            owner: Instance whose store <root>/<owner>/<base_path.name>
+                   holds the object
+            durable: As for create()
+
+        Returns:
+            ObjRef of the local copy
+
+        Raises:
+            FileNotFoundError: If the owner's store has no such object
+            ObjectStoreQuotaError: If the copy would take the object's tenant
+                                   past its quota here
+        """
+        validate_name(owner)
+        name = path_of(obj_ref).name
+        local = find(self.base_path, name)
+        if local.exists():
+            return self.stat(str(local))
+
+        src = self._owner_path(owner, Path(name))
+        if not src.is_file():
+            raise FileNotFoundError(f"Object not found in {owner}'s store: {src}")
+        dest = self._get_file_path(src.stem, content_type_of(src)).with_name(name)
+
+        meta = meta_path(src)
+        meta_bytes = meta.read_bytes() if meta.exists() else None
+        tenant = json.loads(meta_bytes).get("tenant") if meta_bytes is not None else None
+
+        self._audit("get", src)
+        with self._charged(tenant, dest, src.stat().st_size):
+            self._copy_atomic(src, dest, durable)
+        if meta_bytes is not None:
+            self._write_atomic(meta_path(dest), meta_bytes, durable)
+        self._audit("put", dest)
+        return self.stat(str(dest))
+
+    def _copy_atomic(self, src: Path, file_path: Path, durable: bool) -> None:
+        """Like _write_atomic, with the content copied from src in the kernel."""
+        tmp_path = temp_path(file_path)
+        try:
+            with open(src, "rb") as fin, self._open_temp(tmp_path) as fout:
+                remaining = os.fstat(fin.fileno()).st_size
+                offset = 0
+                while remaining > 0:
+                    sent = os.sendfile(fout.fileno(), fin.fileno(), offset, remaining)
+                    if sent == 0:
+                        break
+                    offset += sent
+                    remaining -= sent
+                if durable:
+                    os.fsync(fout.fileno())
+        except BaseException as e:
+            tmp_path.unlink(missing_ok=True)
+            self._raise_if_full(e)
+            raise
+        self._commit_temp(tmp_path, file_path, durable, overwrite=True)
+
     def get(self, obj_ref: Union[ObjRef, str, dict]) -> Any:
         """
         Read an object from the store.
@@ -197,34 +880,193 @@ class ObjectStore:
                 obj_ref = ObjRef.from_string(obj_ref)
             else:
                 # Assume it's a path
-                path = Path(obj_ref)
+                started = time.monotonic()
+                path = self._locate(Path(obj_ref))
                 if not path.exists():
+                    self._audit_read(path, started)
                     raise FileNotFoundError(f"Object not found: {obj_ref}")
 
+                with self._pinned(path):
+                    data = self._read_cached(path)
+                self._audit_read(path, started, len(data))
+
                 # Detect content type from extension
-                if path.suffix == ".json":
-                    return json.loads(path.read_text())
-                elif path.suffix == ".pkl":
-                    return pickle.loads(path.read_bytes())
-                else:
-                    return path.read_bytes()
+                return deserialize(data, content_type_of(path))
 
         elif isinstance(obj_ref, dict):
             obj_ref = ObjRef.from_dict(obj_ref)
 
         # Read from file
-        path = Path(obj_ref.path)
+        started = time.monotonic()
+        path = self._locate(Path(obj_ref.path))
         if not path.exists():
+            self._audit_read(path, started)
             raise FileNotFoundError(f"Object not found: {obj_ref.path}")
 
         content_type = obj_ref.content_type
 
-        if content_type == "bytes":
-            return path.read_bytes()
-        elif content_type == "json":
-            return json.loads(path.read_text())
-        else:  # pickle
-            return pickle.loads(path.read_bytes())
+        with self._pinned(path):
+            data = self._read_cached(path, mmap_local=content_type not in ("bytes", "json"))
+        self._audit_read(path, started, len(data))
+        return deserialize(data, content_type)
+
+    def stat(self, obj_ref: Union[ObjRef, str, dict]) -> ObjRef:
+        """
+        Read an object's metadata without loading its payload.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation
+
+        Returns:
+            ObjRef with size, created_at, content_type and media_type taken from
+            the metadata sidecar, or from the file itself if there is none.
+        """
+        path = self._locate(path_of(obj_ref))
+        if not path.exists():
+            raise FileNotFoundError(f"Object not found: {path}")
+
+        sidecar = meta_path(path)
+        if sidecar.exists():
+            return ObjRef(path=str(path), **json.loads(sidecar.read_text()))
+
+        st = path.stat()
+        return ObjRef(
+            path=str(path),
+            key=path.stem,
+            size=st.st_size,
+            created_at=datetime.fromtimestamp(st.st_mtime).isoformat(),
+            content_type=content_type_of(path),
+        )
+
+    def get_buffer(self, obj_ref: Union[ObjRef, str, dict]) -> Union[bytes, memoryview]:
+        """
+        Read the raw stored bytes of an object without deserializing.
+
+        Objects of at least mmap_threshold bytes are memory-mapped and returned
+        as a read-only memoryview, avoiding a heap copy of the file. The mapping
+        stays valid for as long as the view is referenced. Smaller objects are
+        returned as bytes.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation
+
+        Returns:
+            bytes or memoryview over the stored content
+        """
+        started = time.monotonic()
+        path = self._locate(path_of(obj_ref))
+        if not path.exists():
+            self._audit_read(path, started)
+            raise FileNotFoundError(f"Object not found: {path}")
+        # A mapping stays readable if the file is evicted later, so the pin
+        # only has to cover opening it
+        with self._pinned(path):
+            data = self._read_cached(path, mmap_local=True)
+        self._audit_read(path, started, len(data))
+        return data
+
+    def _locate(self, path: Path) -> Path:
+        """
+        Resolve where an object file lives.
+
+        Returns path itself if it exists. Otherwise follows a moved hint (see
+        mark_moved) one hop to the owner's store; the owner's own hints are
+        not consulted, so redirects cannot loop. Failing that, and only when
+        federated, looks for the same file name in sibling stores. Falls back
+        to path so callers still report it as missing.
+        """
+        if path.exists():
+            return path
+        if self._store_dir_of(path) == self.base_path:
+            # Same store, other layout
+            local = find(self.base_path, path.name)
+            if local.exists():
+                return local
+        owner = self._read_moved_hint(path)
+        if owner is not None:
+            candidate = self._owner_path(owner, path)
+            if candidate.is_file():
+                return candidate
+        if not self.federated:
+            return path
+        for sibling in sorted(self.base_path.parent.parent.glob(f"*/{self.base_path.name}")):
+            candidate = find(sibling, path.name)
+            if sibling != self.base_path and candidate.is_file():
+                return candidate
+        return path
+
+    def _read_buffer(self, path: Path) -> Union[bytes, memoryview]:
+        """Read a file, memory-mapping it if it is at least mmap_threshold bytes."""
+        with open(path, "rb") as f:
+            size = os.fstat(f.fileno()).st_size
+            if size == 0 or size < self.mmap_threshold:
+                return f.read()
+            # The mapping holds its own reference to the file, so f can be closed
+            return memoryview(mmap.mmap(f.fileno(), 0, access=mmap.ACCESS_READ))
+
+    def _read_cached(self, path: Path, mmap_local: bool = False) -> Union[bytes, memoryview]:
+        """
+        Read an object file's bytes. Files in other instances' stores go
+        through the cache when cache_bytes is set; local files are read
+        directly. With mmap_local, files of at least mmap_threshold bytes are
+        memory-mapped rather than copied, whichever store they are in, and
+        never cached: the page cache already holds them. Objects pinned with
+        cache_pin() are served from memory before any of that.
+        """
+        if self._cache.has_pinned:
+            content = self._cache.read_pinned((self._peer_of(path), path.name), path)
+            if content is not None:
+                return content
+
+        peer = self._peer_of(path) if self.cache_bytes else None
+        if peer is None:
+            return self._read_buffer(path) if mmap_local else path.read_bytes()
+
+        st = path.stat()
+        if mmap_local and st.st_size >= self.mmap_threshold:
+            return self._read_buffer(path)
+        return self._cache.read((peer, path.name), path, st)
+
+    def cache_pin(self, obj_ref: Union[ObjRef, str, dict]) -> None:
+        """
+        Load an object into memory and keep it there until cache_unpin(), so
+        every read of it is served without touching the file. Works for local
+        objects and ones in other instances' stores, with or without
+        cache_bytes; pinned objects are never evicted and do not count toward
+        cache_bytes. Like cached entries, a pinned copy is reloaded if the
+        file's size or mtime changes, and dropped if the object is deleted.
+        Pinning an object twice is the same as pinning it once.
+
+        Raises:
+            FileNotFoundError: The object does not exist
+        """
+        path = self._locate(path_of(obj_ref))
+        if not path.exists():
+            raise FileNotFoundError(f"Object not found: {path}")
+        self._cache.pin((self._peer_of(path), path.name), path)
+
+    def cache_unpin(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
+        """
+        Release an object pinned with cache_pin(). Its memory is freed; later
+        reads go through the LRU cache as usual.
+
+        Returns:
+            Whether the object was pinned
+        """
+        path = self._locate(path_of(obj_ref))
+        return self._cache.unpin((self._peer_of(path), path.name))
+
+    def cache_clear(self) -> None:
+        """Drop every cached object; pinned ones stay. Hit and miss counters are kept."""
+        self._cache.clear()
+
+    def cache_stats(self) -> Dict[str, int]:
+        """
+        Cache counters: hits, misses, entries and bytes currently held by the
+        LRU. Reads of pinned objects count as hits (or misses when reloaded),
+        but pinned objects themselves are not in entries or bytes.
+        """
+        return self._cache.stats()
 
     def delete(self, obj_ref: Union[ObjRef, str, dict]) -> bool:
         """
@@ -248,8 +1090,19 @@ class ObjectStore:
         if isinstance(obj_ref, ObjRef):
             path = Path(obj_ref.path)
 
+        if not path.exists() and self._store_dir_of(path) == self.base_path:
+            # Same store, other layout
+            path = find(self.base_path, path.name)
+
+        if self._cache.active:
+            self._cache.invalidate(path.name)
+
         if path.exists():
+            size = path.stat().st_size if self.audit_log is not None else None
             path.unlink()
+            meta_path(path).unlink(missing_ok=True)
+            self._quotas.stale()
+            self._audit("delete", path, size)
             return True
         return False
 
@@ -259,32 +1112,304 @@ class ObjectStore:
             if obj_ref.startswith("{"):
                 obj_ref = ObjRef.from_string(obj_ref)
             else:
-                return Path(obj_ref).exists()
+                return self._locate(Path(obj_ref)).exists()
         elif isinstance(obj_ref, dict):
             obj_ref = ObjRef.from_dict(obj_ref)
 
-        return Path(obj_ref.path).exists()
+        return self._locate(Path(obj_ref.path)).exists()
+
+    def peek(self, obj_ref: Union[ObjRef, str, dict], owner: Optional[str] = None) -> Optional[int]:
+        """
+        Check whether an object exists and get its size, without reading it.
+
+        Only the file is stat'd, so this is cheap enough for schedulers to
+        probe locality before deciding where to run or whether to fetch.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation
+            owner: Look only in this instance's store, laid out as a sibling
+                   <root>/<owner>/<base_path.name> (see federated). If None,
+                   look here and, when federated, in sibling stores.
+
+        Returns:
+            Size in bytes, or None if the object isn't there
+        """
+        path = path_of(obj_ref)
+        if owner is None:
+            path = self._locate(path)
+        else:
+            path = self._owner_path(owner, path)
+        try:
+            st = path.stat()
+        except (FileNotFoundError, NotADirectoryError):
+            return None
+        return st.st_size
+
+    def _audit(self, op: str, path: Path, size: Optional[int] = None) -> None:
+        """Record an access in the audit log, if one is configured."""
+        if self.audit_log is None:
+            return
+        if size is None:
+            try:
+                size = path.stat().st_size
+            except OSError:
+                pass
+        self.audit_log.record(op, path.stem, size, self._peer_of(path))
+
+    def _audit_read(self, path: Path, started: float, size: Optional[int] = None) -> None:
+        """Record a get with its latency; size None means the object was not found."""
+        if self.audit_log is None:
+            return
+        latency_ms = (time.monotonic() - started) * 1000
+        self.audit_log.record("get", path.stem, size, self._peer_of(path),
+                              found=size is not None, latency_ms=latency_ms)
+
+    def _peer_of(self, path: Path) -> Optional[str]:
+        """Instance id if path is in another instance's store <root>/<peer>/<base_path.name>."""
+        store_dir = self._store_dir_of(path)
+        if store_dir != self.base_path and store_dir.name == self.base_path.name \
+                and store_dir.parent.parent == self.base_path.parent.parent:
+            return store_dir.parent.name
+        return None
+
+    def _owner_path(self, owner: str, path: Path) -> Path:
+        """Where path's object lives in a sibling store <root>/<owner>/<base_path.name>."""
+        return find(self.base_path.parent.parent / owner / self.base_path.name, path.name)
+
+    def _read_moved_hint(self, path: Path) -> Optional[str]:
+        try:
+            owner = moved_path(self.base_path / path.name).read_text().strip()
+        except (FileNotFoundError, NotADirectoryError):
+            return None
+        return owner or None
+
+    def mark_moved(self, obj_ref: Union[ObjRef, str, dict], owner: str) -> None:
+        """
+        Record that an object is held by another instance's store.
+
+        Reads here that miss locally then follow the hint one hop to
+        <root>/<owner>/<base_path.name>, so any instance can be asked for any
+        object as long as it knows the owner.
+
+        Args:
+            obj_ref: ObjRef, path string, or dict representation (only the
+                     file name is used)
+            owner: Instance whose store holds the object
+        """
+        validate_name(owner)
+        hint = moved_path(self.base_path / path_of(obj_ref).name)
+        self._write_atomic(hint, owner.encode(), durable=False)
+
+    def redirect_of(self, obj_ref: Union[ObjRef, str, dict]) -> Optional[str]:
+        """
+        Owner recorded by mark_moved() for an object not held here.
+
+        Returns:
+            The owner, or None if the object is stored locally or has no hint
+        """
+        path = find(self.base_path, path_of(obj_ref).name)
+        if path.exists():
+            return None
+        return self._read_moved_hint(path)
+
+    def storage_usage(self) -> int:
+        """Total bytes held in the store directory, including in-flight temp files."""
+        total = 0
+        for entry in self._scan(include_hidden=True):
+            try:
+                total += entry.stat().st_size
+            except OSError:
+                # Removed while scanning
+                continue
+        return total
+
+    def store_stats(self) -> Dict[str, Any]:
+        """
+        Summary of this store's objects, from one stat() per file (payloads
+        are never read): "objects" (count), "bytes" (their total size) and
+        "oldest" / "newest" (modification times as Unix timestamps, None
+        for an empty store). Sidecars and in-flight temp files are not
+        counted; see storage_usage() for the bytes on disk.
+        """
+        count = 0
+        total = 0
+        oldest: Optional[float] = None
+        newest: Optional[float] = None
+        for entry in self._scan():
+            try:
+                st = entry.stat()
+            except FileNotFoundError:
+                # Removed while scanning
+                continue
+            count += 1
+            total += st.st_size
+            oldest = st.st_mtime if oldest is None else min(oldest, st.st_mtime)
+            newest = st.st_mtime if newest is None else max(newest, st.st_mtime)
+        return {"objects": count, "bytes": total, "oldest": oldest, "newest": newest}
 
     def list_objects(self) -> list:
         """List all objects in the store."""
         objects = []
-        for file_path in self.base_path.iterdir():
-            if file_path.is_file():
-                key = file_path.stem
-                content_type = {
-                    ".pkl": "pickle",
-                    ".bin": "bytes",
-                    ".json": "json",
-                }.get(file_path.suffix, "bytes")
-
-                objects.append(ObjRef(
-                    path=str(file_path),
-                    key=key,
-                    size=file_path.stat().st_size,
-                    content_type=content_type,
-                ))
+        for entry in self._scan():
+            file_path = Path(entry.path)
+            key = file_path.stem
+            content_type = content_type_of(file_path)
+
+            objects.append(ObjRef(
+                path=str(file_path),
+                key=key,
+                size=file_path.stat().st_size,
+                content_type=content_type,
+            ))
         return objects
 
+    def list_objects_page(
+        self,
+        page_size: int = 1000,
+        page_token: Optional[str] = None,
+    ) -> Tuple[List[ObjRef], Optional[str]]:
+        """
+        List objects one page at a time, in file name order.
+
+        The token is the last file name of the previous page, so pagination
+        stays stable while objects are added or removed concurrently: every
+        object that exists for the whole walk is returned exactly once.
+
+        Args:
+            page_size: Maximum number of objects per page
+            page_token: Token from the previous call, or None for the first page
+
+        Returns:
+            (objects, next_page_token); next_page_token is None on the last page
+        """
+        if page_size <= 0:
+            raise ValueError("page_size must be positive")
+
+        # Both layouts are merged by name; the token stays a plain file name.
+        # Each directory's sorted listing is walked from the token, and the
+        # walk stops once the page (plus one entry, for the next token) is full
+        files, shard_dirs = self._listings.get(self.base_path)
+        listings = [(self.base_path, files)] + [
+            (self.base_path / shard, self._listings.get(self.base_path / shard)[0])
+            for shard in shard_dirs
+        ]
+
+        def walk(directory: Path, names: List[str]):
+            start = 0 if page_token is None else bisect.bisect_right(names, page_token)
+            return ((name, directory) for name in itertools.islice(names, start, None))
+
+        page: List[Tuple[str, Path]] = []
+        for name, directory in heapq.merge(*(walk(d, names) for d, names in listings)):
+            if page and page[-1][0] == name:
+                continue
+            page.append((name, directory))
+            if len(page) > page_size:
+                break
+
+        objects = []
+        for name, directory in page[:page_size]:
+            file_path = directory / name
+            try:
+                size = file_path.stat().st_size
+            except FileNotFoundError:
+                # Deleted since the directory scan
+                continue
+            objects.append(ObjRef(
+                path=str(file_path),
+                key=file_path.stem,
+                size=size,
+                content_type=content_type_of(file_path),
+            ))
+
+        next_token = page[page_size - 1][0] if len(page) > page_size else None
+        return objects, next_token
+
+    @contextmanager
+    def pin(self, obj_ref: Union[ObjRef, str, dict]):
+        """
+        Keep an object from being evicted by gc() for the duration of a with
+        block, e.g. while it is being streamed to a client. Pins nest.
+
+        Usage:
+            with store.pin(obj_ref):
+                send(store.get_buffer(obj_ref))
+        """
+        with self._pinned(path_of(obj_ref)):
+            yield
+
+    @contextmanager
+    def _pinned(self, path: Path):
+        path = Path(os.path.abspath(path))
+        with self._pins_lock:
+            self._pins[path] = self._pins.get(path, 0) + 1
+        try:
+            yield
+        finally:
+            with self._pins_lock:
+                if self._pins[path] == 1:
+                    del self._pins[path]
+                else:
+                    self._pins[path] -= 1
+
+    def gc(self) -> int:
+        """
+        Enforce max_age and max_bytes once. Objects are evicted least recently
+        used first (the later of atime and mtime), skipping pinned ones, until
+        none is older than max_age and the rest fit in max_bytes. Only this
+        store's base_path is considered, never federated siblings.
+
+        Returns:
+            Number of objects evicted
+        """
+        if self.max_bytes is None and self.max_age is None:
+            return 0
+
+        candidates = []
+        total = 0
+        for entry in self._scan():
+            try:
+                st = entry.stat()
+            except FileNotFoundError:
+                continue
+            candidates.append((max(st.st_atime, st.st_mtime), st.st_size,
+                               Path(os.path.abspath(entry.path))))
+            total += st.st_size
+        candidates.sort(key=lambda c: c[0])
+
+        now = time.time()
+        evicted = 0
+        for last_used, size, path in candidates:
+            expired = self.max_age is not None and now - last_used > self.max_age
+            over = self.max_bytes is not None and total > self.max_bytes
+            if not expired and not over:
+                # Everything after this one is newer
+                break
+            # Held across the unlink so a read can't pin the file in between
+            with self._pins_lock:
+                if path in self._pins:
+                    continue
+                try:
+                    path.unlink()
+                except FileNotFoundError:
+                    total -= size
+                    continue
+            meta_path(path).unlink(missing_ok=True)
+            self._audit("evict", path, size)
+            total -= size
+            evicted += 1
+        if evicted:
+            self._quotas.stale()
+        return evicted
+
+    def _gc_loop(self):
+        while not self._gc_stop.wait(self.gc_interval):
+            try:
+                evicted = self.gc()
+                if evicted:
+                    print(f"[ObjectStore] Evicted {evicted} objects from {self.base_path}")
+            except Exception as e:
+                print(f"[ObjectStore] Background GC failed: {e}")
+
     def cleanup(self, max_age_seconds: int = 3600) -> int:
         """
         Clean up old objects.
@@ -295,17 +1420,20 @@ class ObjectStore:
         Returns:
             Number of objects deleted
         """
-        import time
         now = time.time()
         deleted = 0
 
-        for file_path in self.base_path.iterdir():
-            if file_path.is_file():
-                age = now - file_path.stat().st_mtime
-                if age > max_age_seconds:
-                    file_path.unlink()
+        for entry in self._scan(include_hidden=True):
+            file_path = Path(entry.path)
+            age = now - file_path.stat().st_mtime
+            if age > max_age_seconds:
+                file_path.unlink()
+                # Hidden files are sidecars or abandoned temp files, not objects
+                if not file_path.name.startswith("."):
                     deleted += 1
 
+        if deleted:
+            self._quotas.stale()
         return deleted
 
     def clear(self) -> int:
@@ -316,8 +1444,10 @@ class ObjectStore:
             Number of objects deleted
         """
         deleted = 0
-        for file_path in self.base_path.iterdir():
-            if file_path.is_file():
-                file_path.unlink()
+        for entry in self._scan(include_hidden=True):
+            os.unlink(entry.path)
+            if not entry.name.startswith("."):
                 deleted += 1
+        self._quotas.stale()
         return deleted
+
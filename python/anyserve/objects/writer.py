@@ -0,0 +1,197 @@
+"""
+ObjectWriter - streams a bytes object into an ObjectStore chunk by chunk.
+"""
+
+import hashlib
+import json
+import os
+from typing import TYPE_CHECKING, Optional, Union
+
+from .codec import generate_key
+from .layout import meta_path, temp_path
+from .ref import ObjRef
+
+if TYPE_CHECKING:
+    from .store import ObjectStore
+
+
+class ObjectWriter:
+    """
+    Streaming writer returned by ObjectStore.open_writer().
+
+    Usage:
+        with store.open_writer() as writer:
+            for chunk in source:
+                writer.write(chunk)
+            obj_ref = writer.finish()
+
+    Leaving the with-block without calling finish() (e.g. on an exception)
+    discards the partial object, unless the writer is resumable (opened with
+    an upload_id), in which case the data written so far is kept for
+    resuming.
+    """
+
+    def __init__(
+        self,
+        store: "ObjectStore",
+        key: Optional[str],
+        durable: bool,
+        overwrite: bool,
+        media_type: Optional[str],
+        upload_id: Optional[str] = None,
+        offset: Optional[int] = None,
+        tenant: Optional[str] = None,
+    ):
+        if key is None and not store.dedup:
+            key = generate_key()
+        self._store = store
+        self._key = key
+        self._durable = durable
+        self._overwrite = overwrite
+        self._media_type = media_type
+        self._tenant = tenant
+        self._hasher = hashlib.sha256() if key is None else None
+        self._size = 0
+        self._resumable = upload_id is not None
+        if self._resumable:
+            self._tmp_path = store._partial_path(upload_id)
+            self._file = self._open_partial(offset)
+        else:
+            # With a content-addressed key the final name is only known at finish()
+            self._tmp_path = temp_path(store._get_file_path(key or "stream", "bytes"))
+            self._file = store._open_temp(self._tmp_path)
+        self._result: Optional[ObjRef] = None
+
+    def _open_partial(self, offset: Optional[int]):
+        """Open (or create) the partial file and position it at offset."""
+        mode = self._store.file_mode
+        created = not self._tmp_path.exists()
+        fd = os.open(self._tmp_path, os.O_RDWR | os.O_CREAT, 0o666 if mode is None else mode)
+        f = os.fdopen(fd, "r+b")
+        try:
+            available = os.fstat(fd).st_size
+            if offset is None:
+                offset = available
+            if not 0 <= offset <= available:
+                raise ValueError(f"Cannot resume at offset {offset}: "
+                                 f"{available} bytes have been uploaded")
+            f.truncate(offset)
+            if self._hasher is not None:
+                # Content-addressed: the key covers the bytes from earlier attempts too
+                while f.tell() < offset:
+                    self._hasher.update(f.read(min(1024 * 1024, offset - f.tell())))
+            f.seek(offset)
+        except BaseException:
+            f.close()
+            if created:
+                self._tmp_path.unlink(missing_ok=True)
+            raise
+        self._size = offset
+        return f
+
+    @property
+    def size(self) -> int:
+        """Bytes written so far."""
+        return self._size
+
+    @property
+    def closed(self) -> bool:
+        return self._file.closed
+
+    def write(self, chunk: Union[bytes, bytearray, memoryview]) -> int:
+        """Append a chunk. Returns the number of bytes written."""
+        if self._file.closed:
+            raise ValueError("write to a finished or aborted ObjectWriter")
+        try:
+            n = self._file.write(chunk)
+        except OSError as e:
+            self._fail()
+            self._store._raise_if_full(e)
+            raise
+        if self._hasher is not None:
+            self._hasher.update(chunk)
+        self._size += n
+        return n
+
+    def finish(self) -> ObjRef:
+        """Flush the data, move it under its final name and return its ObjRef."""
+        if self._result is not None:
+            return self._result
+        if self._file.closed:
+            raise ValueError("finish on an aborted ObjectWriter")
+
+        try:
+            if self._durable:
+                self._file.flush()
+                os.fsync(self._file.fileno())
+            self._file.close()
+        except BaseException as e:
+            self._fail()
+            self._store._raise_if_full(e)
+            raise
+
+        store = self._store
+        content_addressed = self._hasher is not None
+        key = f"sha256-{self._hasher.hexdigest()}" if content_addressed else self._key
+        file_path = store._get_file_path(key, "bytes")
+
+        if content_addressed and file_path.exists():
+            # Already stored, and owned by whoever stored it first (see create())
+            self._tmp_path.unlink(missing_ok=True)
+            store._audit("put", file_path, self._size)
+            self._result = store.stat(str(file_path))
+            return self._result
+
+        try:
+            with store._charged(self._tenant, file_path, self._size):
+                store._commit_temp(self._tmp_path, file_path, self._durable, self._overwrite)
+        except BaseException:
+            self._fail()
+            raise
+
+        obj_ref = ObjRef(
+            path=str(file_path),
+            key=key,
+            size=self._size,
+            content_type="bytes",
+            media_type=self._media_type,
+            tenant=self._tenant,
+        )
+        if self._media_type is not None or self._tenant is not None:
+            meta = obj_ref.to_dict()
+            del meta["path"]
+            store._write_atomic(meta_path(file_path), json.dumps(meta).encode(), self._durable)
+        elif self._overwrite:
+            meta_path(file_path).unlink(missing_ok=True)
+
+        store._audit("put", file_path, self._size)
+        self._result = obj_ref
+        return obj_ref
+
+    def abort(self) -> None:
+        """Discard everything written so far (for resumable writers, the whole upload)."""
+        self._close_quietly()
+        self._tmp_path.unlink(missing_ok=True)
+
+    def _fail(self) -> None:
+        """Give up after an error: resumable writers keep their data, others discard it."""
+        if self._resumable:
+            self._close_quietly()
+        else:
+            self.abort()
+
+    def _close_quietly(self) -> None:
+        if not self._file.closed:
+            try:
+                self._file.close()
+            except OSError:
+                # Flushing buffered data can fail again (e.g. disk still full)
+                pass
+
+    def __enter__(self) -> "ObjectWriter":
+        return self
+
+    def __exit__(self, exc_type, exc, tb) -> None:
+        if self._result is None:
+            # Resumable writers keep what arrived, to continue from upload_offset()
+            self._fail()
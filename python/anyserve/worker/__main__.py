@@ -26,6 +26,7 @@ from concurrent import futures
 
 # 导入模型类型
 from anyserve.kserve import ModelInferRequest, ModelInferResponse, Context, Capability, Stream
+from anyserve.worker.reaper import default_socket_dir, write_pid_marker, remove_pid_marker
 
 
 def main():
@@ -35,7 +36,11 @@ def main():
     parser.add_argument('--worker-id', required=True, help='Worker ID')
     parser.add_argument('--worker-port', type=int, default=None, help='Worker port for Unix socket')
     parser.add_argument('--api-server', default=None, help='API Server URL (e.g., http://localhost:8080)')
-    parser.add_argument('--object-store', default='/tmp/anyserve-objects', help='Object store path')
+    parser.add_argument('--object-store', default='/tmp/anyserve-objects', help='Object store path, or memory:// for a per-worker in-memory store')
+    parser.add_argument('--object-max-bytes', type=int, default=None,
+                        help='Evict least recently used objects beyond this many bytes (default: unbounded)')
+    parser.add_argument('--object-max-age', type=float, default=None,
+                        help='Evict objects unused for this many seconds (default: never)')
     parser.add_argument('--replica-id', default=None, help='Replica ID for registration')
     parser.add_argument('--grpc-port', type=int, default=None, help='gRPC port for streaming (default: ingress_port + 100)')
     parser.add_argument('--factory', action='store_true', help='Treat app as factory function')
@@ -66,6 +71,8 @@ def main():
         worker_port=args.worker_port,
         api_server=args.api_server,
         object_store_path=args.object_store,
+        object_max_bytes=args.object_max_bytes,
+        object_max_age=args.object_max_age,
         replica_id=args.replica_id or args.worker_id,
         grpc_port=grpc_port,
     )
@@ -94,6 +101,8 @@ class Worker:
         object_store_path: str = '/tmp/anyserve-objects',
         replica_id: str = None,
         grpc_port: int = None,
+        object_max_bytes: int = None,
+        object_max_age: float = None,
     ):
         self.app = app
         self.worker_id = worker_id
@@ -102,18 +111,20 @@ class Worker:
         self.replica_id = replica_id or worker_id
         self.grpc_port = grpc_port
 
-        # Unix Socket 路径
+        # Unix Socket 路径（与 anyserve_node 的 --uds-dir 默认目录一致，reaper 也清理这里）
         if worker_port:
-            self.socket_path = f"/tmp/anyserve-worker-{worker_id}-{worker_port}.sock"
+            socket_name = f"anyserve-worker-{worker_id}-{worker_port}.sock"
         else:
-            self.socket_path = f"/tmp/anyserve-worker-{worker_id}.sock"
+            socket_name = f"anyserve-worker-{worker_id}.sock"
+        self.socket_path = os.path.join(default_socket_dir(), socket_name)
 
         self.running = True
         self.grpc_server = None
 
-        # Initialize ObjectStore
-        from anyserve.objects import ObjectStore
-        self.object_store = ObjectStore(object_store_path)
+        # Initialize the object backend (ObjectStore, or InMemoryObjectStore for memory://)
+        from anyserve.objects import open_object_store
+        self.object_store = open_object_store(object_store_path, max_bytes=object_max_bytes,
+                                              max_age=object_max_age)
 
         # 设置信号处理
         signal.signal(signal.SIGINT, self._signal_handler)
@@ -273,6 +284,7 @@ class Worker:
         # 创建 Unix Socket
         sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
         sock.bind(self.socket_path)
+        write_pid_marker(self.socket_path)
         sock.listen(5)
         sock.settimeout(1.0)  # 设置超时以便能响应关闭信号
 
@@ -294,6 +306,7 @@ class Worker:
             sock.close()
             if os.path.exists(self.socket_path):
                 os.remove(self.socket_path)
+            remove_pid_marker(self.socket_path)
             self._stop_grpc_server()
             print(f"[Worker-{self.worker_id}] Stopped")
 
@@ -0,0 +1,75 @@
+"""
+Handshake - the ready message a spawned worker sends back to anyserve_node.
+
+anyserve_node passes the worker ANSERVE_READY_FD, and for SHM offload
+ANSERVE_H2D_FD / ANSERVE_D2H_FD. Once serving, the worker writes one message
+of whitespace-separated tokens to the ready fd:
+
+    ready [addr=<grpc address>] [h2d_size=<bytes> d2h_size=<bytes>] [shm_inputs=1]
+
+addr overrides the address the proxy connects to. h2d_size / d2h_size are the
+sizes the worker actually mapped; the proxy refuses to start if they differ
+from the segments it created, since that means the fds were not inherited
+correctly and tensors would be read from the wrong memory. A plain "ready" is
+still accepted with a warning, but workers should use signal_ready() so the
+check can run.
+
+shm_inputs=1 tells the proxy the worker reads large inputs out of H2D. The
+proxy then writes them into H2D slots and lists each one as
+"<index>:<offset>:<length>" in a request parameter. Raw inputs go in shm_raw,
+indexed into raw_input_contents, and that entry is left empty. Typed contents
+(fp32_contents, ...) go in shm_contents, indexed into inputs. They are written
+as a serialized InferTensorContents, whose field numbers carry the datatype,
+and that input is sent without contents. Once it has read them, the worker
+returns "<offset>:<length>" for each one in the response parameter h2d_ack;
+until then the proxy won't reuse the slot. Under --verify-shm the request also
+carries shm_checksum, the FNV-1a 64 checksum of each input (16 hex digits,
+comma-separated), shm_raw entries first and then shm_contents. The worker must
+return the checksums of what it read in h2d_checksum, in h2d_ack order; the
+proxy fails the call with DATA_LOSS on a mismatch. The Python worker doesn't
+send shm_inputs, so both encodings always arrive inline.
+"""
+
+import mmap
+import os
+from typing import Optional, Tuple
+
+
+def shm_sizes() -> Optional[Tuple[int, int]]:
+    """
+    Map the inherited H2D / D2H fds and return their sizes in bytes.
+
+    Returns:
+        (h2d_size, d2h_size), or None when the proxy passed no SHM fds
+        (external workers, inline transport)
+
+    Raises:
+        OSError: If an fd is set but can't be mapped
+    """
+    h2d_fd = os.environ.get("ANSERVE_H2D_FD")
+    d2h_fd = os.environ.get("ANSERVE_D2H_FD")
+    if h2d_fd is None or d2h_fd is None:
+        return None
+    sizes = []
+    for fd in (int(h2d_fd), int(d2h_fd)):
+        with mmap.mmap(fd, 0) as region:
+            sizes.append(len(region))
+    return sizes[0], sizes[1]
+
+
+def ready_message(addr: Optional[str] = None) -> bytes:
+    """Build the ready message, including the mapped SHM sizes if any."""
+    tokens = ["ready"]
+    if addr:
+        tokens.append(f"addr={addr}")
+    sizes = shm_sizes()
+    if sizes is not None:
+        tokens += [f"h2d_size={sizes[0]}", f"d2h_size={sizes[1]}"]
+    return " ".join(tokens).encode()
+
+
+def signal_ready(addr: Optional[str] = None) -> None:
+    """Send the ready message to anyserve_node and close the ready fd."""
+    fd = int(os.environ["ANSERVE_READY_FD"])
+    os.write(fd, ready_message(addr))
+    os.close(fd)
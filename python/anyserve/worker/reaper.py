@@ -0,0 +1,162 @@
+"""
+Reaper - remove UDS sockets and SHM segments left behind by crashed processes.
+
+Each worker socket has a sibling "<socket>.pid" marker holding the owner's pid,
+and SHM segments are named "as_<pid>_<hex>". A resource is only removed when
+its owning pid is known and no longer alive; anything without a pid is left
+alone so a live instance's resources are never touched.
+"""
+
+import os
+import re
+import threading
+from pathlib import Path
+from typing import Optional
+
+SOCKET_PATTERNS = ("anyserve-worker-*.sock", "anyserve_*.sock")
+SHM_NAME_RE = re.compile(r"^as_(\d+)_[0-9a-f]+$")
+
+
+def default_socket_dir() -> str:
+    """
+    Where worker sockets go by default: $TMPDIR, or /tmp.
+
+    This is also anyserve_node's --uds-dir default, so the reaper sweeps the
+    directory the node's sockets are actually in.
+    """
+    socket_dir = os.environ.get("TMPDIR") or "/tmp"
+    return socket_dir.rstrip("/") or "/"
+
+
+def pid_marker_path(socket_path: str) -> str:
+    """Path of the pid marker that records which process owns a socket."""
+    return f"{socket_path}.pid"
+
+
+def write_pid_marker(socket_path: str) -> None:
+    """Record the current process as the owner of socket_path."""
+    Path(pid_marker_path(socket_path)).write_text(str(os.getpid()))
+
+
+def remove_pid_marker(socket_path: str) -> None:
+    """Remove the pid marker for socket_path, if any."""
+    Path(pid_marker_path(socket_path)).unlink(missing_ok=True)
+
+
+def pid_alive(pid: int) -> bool:
+    """Check whether a process with this pid exists."""
+    if pid <= 0:
+        return False
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except PermissionError:
+        # Exists but owned by another user
+        return True
+    return True
+
+
+def _read_pid(marker: Path) -> Optional[int]:
+    try:
+        return int(marker.read_text().strip())
+    except (OSError, ValueError):
+        return None
+
+
+def reap_stale_sockets(socket_dir: Optional[str] = None) -> int:
+    """
+    Remove worker sockets whose owning process has exited.
+
+    Args:
+        socket_dir: Directory containing the sockets (default: default_socket_dir())
+
+    Returns:
+        Number of sockets removed
+    """
+    removed = 0
+    base = Path(socket_dir or default_socket_dir())
+    for pattern in SOCKET_PATTERNS:
+        for sock_path in base.glob(pattern):
+            marker = Path(pid_marker_path(str(sock_path)))
+            pid = _read_pid(marker)
+            if pid is None or pid_alive(pid):
+                continue
+            try:
+                sock_path.unlink(missing_ok=True)
+                marker.unlink(missing_ok=True)
+                removed += 1
+                print(f"[Reaper] Removed stale socket {sock_path} (pid {pid} is gone)")
+            except OSError as e:
+                print(f"[Reaper] Failed to remove {sock_path}: {e}")
+    return removed
+
+
+def reap_stale_shm(shm_dir: str = "/dev/shm") -> int:
+    """
+    Remove named SHM segments whose creating process has exited (Linux only).
+
+    Args:
+        shm_dir: Directory where POSIX SHM segments are visible
+
+    Returns:
+        Number of segments removed
+    """
+    base = Path(shm_dir)
+    if not base.is_dir():
+        return 0
+
+    removed = 0
+    for entry in base.iterdir():
+        match = SHM_NAME_RE.match(entry.name)
+        if not match or pid_alive(int(match.group(1))):
+            continue
+        try:
+            entry.unlink()
+            removed += 1
+            print(f"[Reaper] Removed stale SHM segment {entry}")
+        except OSError as e:
+            print(f"[Reaper] Failed to remove {entry}: {e}")
+    return removed
+
+
+def reap_stale_resources(socket_dir: Optional[str] = None, shm_dir: str = "/dev/shm") -> int:
+    """Sweep both stale sockets and stale SHM segments. Returns the total removed."""
+    return reap_stale_sockets(socket_dir) + reap_stale_shm(shm_dir)
+
+
+class Reaper:
+    """
+    Background thread that periodically calls reap_stale_resources().
+
+    Usage:
+        reaper = Reaper(interval=60)
+        reaper.start()
+        ...
+        reaper.stop()
+    """
+
+    def __init__(self, interval: float = 60.0, socket_dir: Optional[str] = None,
+                 shm_dir: str = "/dev/shm"):
+        self.interval = interval
+        self.socket_dir = socket_dir or default_socket_dir()
+        self.shm_dir = shm_dir
+        self._stop = threading.Event()
+        self._thread: Optional[threading.Thread] = None
+
+    def start(self):
+        self._thread = threading.Thread(target=self._run, daemon=True)
+        self._thread.start()
+
+    def stop(self):
+        self._stop.set()
+        if self._thread:
+            self._thread.join(timeout=self.interval)
+            self._thread = None
+
+    def _run(self):
+        while not self._stop.wait(self.interval):
+            try:
+                reap_stale_resources(self.socket_dir, self.shm_dir)
+            except Exception as e:
+                print(f"[Reaper] Sweep failed: {e}")
@@ -0,0 +1,171 @@
+"""
+Helpers shared by the anyserve_node integration tests.
+
+The tests run the built binaries (NODE_BIN, and MOCK_WORKER_BIN for the
+Python-free worker) and skip when they are missing. make_worker() writes a
+throwaway `anyserve_worker.loader` package that the node spawns in place of a
+real app, so the real spawn -> ready handshake -> connect path is exercised.
+node() starts a node and yields a gRPC stub once it serves.
+"""
+
+import contextlib
+import json
+import os
+import socket
+import subprocess
+import sys
+import time
+from pathlib import Path
+
+import pytest
+
+grpc = pytest.importorskip("grpc")
+pytest.importorskip("anyserve._proto.grpc_predict_v2_pb2")
+
+from anyserve._proto import grpc_predict_v2_pb2, grpc_predict_v2_pb2_grpc  # noqa: E402
+
+REPO_ROOT = Path(__file__).resolve().parents[2]
+
+
+def _find_binary(name: str, env_var: str):
+    candidates = [os.environ.get(env_var, "")]
+    candidates += [str(REPO_ROOT / d / name) for d in ("build", "cpp/build")]
+    for path in candidates:
+        if path and os.access(path, os.X_OK):
+            return path
+    return None
+
+
+NODE_BIN = _find_binary("anyserve_node", "ANYSERVE_NODE_BIN")
+MOCK_WORKER_BIN = _find_binary("anyserve_mock_worker", "ANYSERVE_MOCK_WORKER_BIN")
+requires_node = pytest.mark.skipif(NODE_BIN is None, reason="anyserve_node not built")
+requires_mock_worker = pytest.mark.skipif(MOCK_WORKER_BIN is None,
+                                          reason="anyserve_mock_worker not built")
+
+# Channel options for payloads above gRPC's 4MB default
+LARGE_MESSAGES = [
+    ("grpc.max_receive_message_length", 64 * 1024 * 1024),
+    ("grpc.max_send_message_length", 64 * 1024 * 1024),
+]
+
+TRIVIAL_WORKER = '''
+import os, time
+from concurrent import futures
+import grpc
+from anyserve._proto import grpc_predict_v2_pb2, grpc_predict_v2_pb2_grpc
+from anyserve.worker.handshake import ready_message
+
+class Servicer(grpc_predict_v2_pb2_grpc.GRPCInferenceServiceServicer):
+    def ServerReady(self, request, context):
+        return grpc_predict_v2_pb2.ServerReadyResponse(ready=__READY__)
+
+server = grpc.server(futures.ThreadPoolExecutor(max_workers=2))
+grpc_predict_v2_pb2_grpc.add_GRPCInferenceServiceServicer_to_server(Servicer(), server)
+if "ANSERVE_WORKER_ADDR" in os.environ:
+    server.add_insecure_port(os.environ["ANSERVE_WORKER_ADDR"])
+elif "ANSERVE_WORKER_UDS_ABSTRACT" in os.environ:
+    server.add_insecure_port("unix-abstract:" + os.environ["ANSERVE_WORKER_UDS_ABSTRACT"])
+else:
+    server.add_insecure_port("unix://" + os.environ["ANSERVE_WORKER_UDS"])
+server.start()
+os.write(int(os.environ["ANSERVE_READY_FD"]), ready_message())
+while True:
+    time.sleep(1)
+'''
+
+# ModelInfer blocks while the request's "slow" parameter is set, touching
+# $SLOW_MARKER.started on entry and $SLOW_MARKER.cancelled once the call is cancelled
+SLOW_WORKER = TRIVIAL_WORKER.replace("__READY__", "True").replace('''
+server = grpc.server''', '''
+    def ModelInfer(self, request, context):
+        if request.parameters["slow"].bool_param:
+            marker = os.environ["SLOW_MARKER"]
+            open(marker + ".started", "w").close()
+            while context.is_active():
+                time.sleep(0.05)
+            open(marker + ".cancelled", "w").close()
+        return grpc_predict_v2_pb2.ModelInferResponse(model_name=request.model_name, id=request.id)
+
+server = grpc.server''')
+
+
+def make_worker(root: Path, body: str) -> dict:
+    """Write an anyserve_worker.loader package under root and return the node's env."""
+    pkg = root / "anyserve_worker"
+    pkg.mkdir()
+    (pkg / "__init__.py").write_text("")
+    (pkg / "loader.py").write_text(body)
+
+    env = dict(os.environ)
+    env["PYTHON_PATH"] = sys.executable
+    env["PYTHONPATH"] = os.pathsep.join([str(root), str(REPO_ROOT / "python"), env.get("PYTHONPATH", "")])
+    return env
+
+
+def run_check(env: dict, *args: str) -> subprocess.CompletedProcess:
+    return subprocess.run([NODE_BIN, "--check", *args], env=env, capture_output=True,
+                          text=True, timeout=60)
+
+
+def free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+def wait_serving(stub, proc: subprocess.Popen, timeout: float = 20):
+    """Poll ServerReady until the node answers, failing if it exits or times out."""
+    deadline = time.time() + timeout
+    while True:
+        try:
+            stub.ServerReady(grpc_predict_v2_pb2.ServerReadyRequest(), timeout=1)
+            return
+        except grpc.RpcError:
+            if time.time() > deadline or proc.poll() is not None:
+                raise
+            time.sleep(0.1)
+
+
+def wait_for(path: Path, timeout: float = 10):
+    """Wait for a marker file written by a test worker."""
+    deadline = time.time() + timeout
+    while not path.exists():
+        assert time.time() < deadline, f"{path.name} never appeared"
+        time.sleep(0.05)
+
+
+@contextlib.contextmanager
+def node(*args: str, env: dict = None, options=(), stderr=subprocess.DEVNULL):
+    """An anyserve_node process on a free port, and a gRPC stub connected once it serves."""
+    port = free_port()
+    proc = subprocess.Popen([NODE_BIN, "--port", str(port), *args], env=env,
+                            stdout=subprocess.DEVNULL, stderr=stderr)
+    channel = grpc.insecure_channel(f"127.0.0.1:{port}", options=list(options))
+    stub = grpc_predict_v2_pb2_grpc.GRPCInferenceServiceStub(channel)
+    try:
+        wait_serving(stub, proc)
+        yield stub
+    finally:
+        channel.close()
+        proc.terminate()
+        proc.wait(timeout=10)
+
+
+def echo_node(*args: str, stderr=subprocess.DEVNULL):
+    """An `anyserve_node --echo` process (no worker) and a gRPC stub connected to it."""
+    return node("--echo", *args, options=LARGE_MESSAGES, stderr=stderr)
+
+
+def mock_worker_node(*args: str, stderr=subprocess.DEVNULL):
+    """An anyserve_node process spawning anyserve_mock_worker, and a gRPC stub connected to it."""
+    # The node runs "$PYTHON_PATH -m anyserve_worker.loader ..."; the mock ignores its arguments
+    env = dict(os.environ, PYTHON_PATH=MOCK_WORKER_BIN)
+    return node(*args, env=env, options=LARGE_MESSAGES, stderr=stderr)
+
+
+def read_report(proc: subprocess.Popen, read_fd: int) -> dict:
+    """Read the --report-fd JSON line, failing if the node exits first."""
+    with os.fdopen(read_fd) as reader:
+        line = reader.readline()
+    assert line, f"node exited with {proc.poll()} before reporting"
+    return json.loads(line)
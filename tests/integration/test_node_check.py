@@ -0,0 +1,209 @@
+"""
+Integration tests for `anyserve_node --check`.
+
+The node is pointed at a throwaway `anyserve_worker.loader` package that
+serves the KServe gRPC API on the socket it is given, so the check exercises
+the real spawn -> ready handshake -> connect -> ServerReady path. Also covers
+the worker options the check goes through: --uds-dir, --worker-env,
+--worker-arg, --worker-timeout and the SHM size handshake.
+"""
+
+import os
+import socket
+import sys
+import time
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    TRIVIAL_WORKER,
+    make_worker,
+    requires_node,
+    run_check,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeCheck:
+    """Tests for the --check smoke-test mode."""
+
+    @pytest.mark.p1
+    def test_ready_worker_passes(self, temp_dir):
+        """Test that a worker answering ServerReady makes --check exit 0."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert "Check passed." in result.stdout
+
+    @pytest.mark.p1
+    def test_does_not_bind_port(self, temp_dir):
+        """Test that --check succeeds even when --port is already taken."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        with socket.socket() as busy:
+            busy.bind(("0.0.0.0", 0))
+            busy.listen()
+            result = run_check(env, "--port", str(busy.getsockname()[1]))
+        assert result.returncode == 0, result.stderr
+
+    @pytest.mark.p1
+    def test_not_ready_worker_fails(self, temp_dir):
+        """Test that a worker reporting not ready makes --check exit 1."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "False"))
+        result = run_check(env)
+        assert result.returncode == 1
+        assert "reports not ready" in result.stderr
+
+    @pytest.mark.p2
+    def test_crashing_worker_fails(self, temp_dir):
+        """Test that a worker exiting before the handshake makes --check exit 1."""
+        env = make_worker(Path(temp_dir), "raise SystemExit(3)\n")
+        result = run_check(env)
+        assert result.returncode == 1
+        assert "failed to start" in result.stderr
+
+    @pytest.mark.p1
+    def test_worker_env_and_args(self, temp_dir):
+        """Test that --worker-env and --worker-arg reach the spawned worker."""
+        ready = 'os.environ.get("MODEL_DEVICE") == "cuda:1" and "--batch-size=8" in __import__("sys").argv'
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", f"({ready})"))
+        result = run_check(env, "--worker-env", "MODEL_DEVICE=cuda:1", "--worker-arg", "--batch-size=8")
+        assert result.returncode == 0, result.stderr
+
+    @pytest.mark.p2
+    def test_worker_env_rejects_malformed(self, temp_dir):
+        """Test that malformed or reserved --worker-env entries are rejected."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        for entry in ("NOEQUALS", "=value", "1BAD=x", "ANSERVE_WORKER_UDS=/tmp/x"):
+            result = run_check(env, "--worker-env", entry)
+            assert result.returncode == 1, entry
+            assert "--worker-env" in result.stderr
+
+    @pytest.mark.p1
+    def test_uds_dir(self, temp_dir):
+        """Test that the worker socket goes in --uds-dir, or $TMPDIR without it."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        for name in ("flag", "tmpdir"):
+            os.mkdir(os.path.join(temp_dir, name))
+        env["TMPDIR"] = os.path.join(temp_dir, "tmpdir")
+
+        result = run_check(env, "--uds-dir", os.path.join(temp_dir, "flag") + "/")
+        assert result.returncode == 0, result.stderr
+        assert f"Using UDS path: {temp_dir}/flag/anyserve_" in result.stdout
+
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert f"Using UDS path: {temp_dir}/tmpdir/anyserve_" in result.stdout
+
+    @pytest.mark.p2
+    def test_uds_dir_too_long(self, temp_dir):
+        """Test that a directory too long for a socket path fails with a clear message."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        long_dir = Path(temp_dir, *["d" * 50] * 3)
+        long_dir.mkdir(parents=True)
+
+        result = run_check(env, "--uds-dir", str(long_dir))
+        assert result.returncode == 1
+        assert "too long for a socket path" in result.stderr
+
+        result = run_check(env, "--uds-dir", os.path.join(temp_dir, "missing"))
+        assert result.returncode == 1
+        assert "does not exist" in result.stderr
+
+    @pytest.mark.p1
+    def test_worker_reported_address(self, temp_dir):
+        """Test that the proxy connects to the address the worker reports in its ready message."""
+        own_path = os.path.join(temp_dir, "own.sock")
+        body = (TRIVIAL_WORKER.replace("__READY__", "True")
+                .replace('os.environ["ANSERVE_WORKER_UDS"]', repr(own_path))
+                .replace("ready_message()", f"ready_message({f'unix://{own_path}'!r})"))
+        env = make_worker(Path(temp_dir), body)
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert f"reported address unix://{own_path}" in result.stdout
+
+    @pytest.mark.p1
+    def test_slow_worker_within_timeout(self, temp_dir):
+        """Test that a worker slower than the default 10s passes with a longer --worker-timeout."""
+        body = "import time; time.sleep(12)\n" + TRIVIAL_WORKER.replace("__READY__", "True")
+        env = make_worker(Path(temp_dir), body)
+        result = run_check(env, "--worker-timeout", "1m")
+        assert result.returncode == 0, result.stderr
+
+    @pytest.mark.p2
+    def test_slow_worker_times_out(self, temp_dir):
+        """Test that a worker slower than --worker-timeout fails."""
+        body = "import time; time.sleep(5)\n" + TRIVIAL_WORKER.replace("__READY__", "True")
+        env = make_worker(Path(temp_dir), body)
+        env["ANYSERVE_WORKER_TIMEOUT"] = "500ms"
+        result = run_check(env)
+        assert result.returncode == 1
+        assert "Timeout waiting for worker ready" in result.stderr
+
+    @pytest.mark.p1
+    def test_early_exit_fails_fast(self, temp_dir):
+        """Test that a crashing worker fails immediately rather than after the timeout."""
+        env = make_worker(Path(temp_dir), "import time; time.sleep(0.5); raise SystemExit(3)\n")
+        start = time.monotonic()
+        result = run_check(env, "--worker-timeout", "5m")
+        assert result.returncode == 1
+        assert time.monotonic() - start < 10
+
+    @pytest.mark.p2
+    def test_invalid_worker_timeout(self, temp_dir):
+        """Test that a malformed duration is rejected."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        result = run_check(env, "--worker-timeout", "5 minutes")
+        assert result.returncode == 1
+        assert "Invalid --worker-timeout" in result.stderr
+
+    @pytest.mark.p1
+    @pytest.mark.skipif(not sys.platform.startswith("linux"), reason="abstract sockets are Linux-only")
+    def test_abstract_socket(self, temp_dir):
+        """Test that the proxy reaches the worker over an abstract-namespace socket."""
+        before = set(Path("/tmp").glob("anyserve_*.sock*"))
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        result = run_check(env, "--worker-transport", "uds-abstract")
+        assert result.returncode == 0, result.stderr
+        assert "abstract UDS" in result.stdout
+        # No filesystem entry is created at all
+        assert set(Path("/tmp").glob("anyserve_*.sock*")) <= before
+
+    @pytest.mark.p2
+    def test_leaves_no_sockets(self, temp_dir):
+        """Test that the worker socket and pid marker are removed afterwards."""
+        before = set(Path("/tmp").glob("anyserve_*.sock*"))
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        assert run_check(env).returncode == 0
+        assert set(Path("/tmp").glob("anyserve_*.sock*")) <= before
+
+    @pytest.mark.p1
+    def test_shm_sizes_verified(self, temp_dir):
+        """Test that a worker reporting the sizes it actually mapped passes the SHM check."""
+        body = (TRIVIAL_WORKER.replace("__READY__", "True")
+                .replace('os.write(int(os.environ["ANSERVE_READY_FD"]), ready_message())',
+                         'from anyserve.worker.handshake import signal_ready; signal_ready()'))
+        env = make_worker(Path(temp_dir), body)
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert "did not report its SHM sizes" not in result.stderr
+
+    @pytest.mark.p1
+    def test_shm_sizes_missing_warns(self, temp_dir):
+        """Test that a worker sending a plain "ready" still starts, with a warning."""
+        body = TRIVIAL_WORKER.replace("__READY__", "True").replace("ready_message()", 'b"ready"')
+        env = make_worker(Path(temp_dir), body)
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert "did not report its SHM sizes" in result.stderr
+
+    @pytest.mark.p1
+    def test_shm_size_mismatch_fails(self, temp_dir):
+        """Test that a worker whose mapping doesn't match the proxy's segments stops startup."""
+        body = (TRIVIAL_WORKER.replace("__READY__", "True")
+                .replace("ready_message()", 'b"ready h2d_size=4096 d2h_size=4096"'))
+        env = make_worker(Path(temp_dir), body)
+        result = run_check(env)
+        assert result.returncode == 1
+        assert "SHM size mismatch: worker mapped H2D=4096 D2H=4096" in result.stderr
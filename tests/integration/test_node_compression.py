@@ -0,0 +1,48 @@
+"""
+Integration tests for `anyserve_node --compression`.
+"""
+
+import subprocess
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    echo_node,
+    grpc,
+    grpc_predict_v2_pb2,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeCompression:
+    """Tests for gRPC transport compression on the proxy server."""
+
+    @staticmethod
+    def _request(data):
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        tensor = request.inputs.add(name="in0", datatype="UINT8")
+        tensor.shape.append(len(data))
+        request.raw_input_contents.append(data)
+        return request
+
+    @pytest.mark.p1
+    def test_gzip_round_trip(self):
+        """Test that a large compressible tensor survives gzip in both directions."""
+        data = b"anyserve " * (1 << 17)
+        with echo_node("--compression", "gzip") as stub:
+            response = stub.ModelInfer(self._request(data), timeout=10,
+                                       compression=grpc.Compression.Gzip)
+            assert response.raw_output_contents == [data]
+            # Clients that don't compress are served as before
+            assert stub.ModelInfer(self._request(b"plain"), timeout=10).raw_output_contents == [b"plain"]
+
+    @pytest.mark.p2
+    def test_unknown_algorithm_rejected(self):
+        """Test that an unsupported algorithm fails at startup."""
+        result = subprocess.run([NODE_BIN, "--echo", "--compression", "zstd"],
+                                capture_output=True, text=True, timeout=30)
+        assert result.returncode == 1
+        assert "--compression must be none, gzip or deflate" in result.stderr
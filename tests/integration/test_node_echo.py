@@ -0,0 +1,225 @@
+"""
+Integration tests for `anyserve_node --echo`, which spawns no worker and
+echoes inputs back through the H2D/D2H SHM segments: the inline/SHM threshold
+and --verify-shm checksums.
+"""
+
+import os
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+
+from .node_helpers import (
+    echo_node,
+    grpc,
+    grpc_predict_v2_pb2,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+@pytest.fixture
+def echo_stub():
+    with echo_node() as stub:
+        yield stub
+
+
+@pytest.fixture
+def verify_stub():
+    with echo_node("--verify-shm") as stub:
+        yield stub
+
+
+def _fnv1a64(data: bytes) -> int:
+    h = 0xcbf29ce484222325
+    for b in data:
+        h = ((h ^ b) * 0x100000001b3) & 0xFFFFFFFFFFFFFFFF
+    return h
+
+
+class TestNodeEcho:
+    """Tests for the --echo mode (no worker, SHM round trip)."""
+
+    @pytest.mark.p1
+    def test_echoes_raw_inputs(self, echo_stub):
+        """Test that raw inputs come back unchanged, including ones larger than SHM."""
+        small = b"hello"
+        large = os.urandom(25 * 1024 * 1024)  # spans three 10MB SHM chunks
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo", id="req-1")
+        for name, data in (("small", small), ("large", large)):
+            tensor = request.inputs.add(name=name, datatype="BYTES")
+            tensor.shape.append(len(data))
+            request.raw_input_contents.append(data)
+
+        response = echo_stub.ModelInfer(request, timeout=30)
+
+        assert response.model_name == "echo"
+        assert response.id == "req-1"
+        assert [o.name for o in response.outputs] == ["small", "large"]
+        assert list(response.outputs[1].shape) == [len(large)]
+        assert list(response.raw_output_contents) == [small, large]
+
+    @pytest.mark.p2
+    def test_echoes_typed_contents(self, echo_stub):
+        """Test that typed tensor contents are copied to the outputs."""
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        tensor = request.inputs.add(name="x", datatype="INT64")
+        tensor.shape.append(3)
+        tensor.contents.int64_contents.extend([1, 2, 3])
+
+        response = echo_stub.ModelInfer(request, timeout=10)
+        assert list(response.outputs[0].contents.int64_contents) == [1, 2, 3]
+
+    @pytest.mark.p2
+    def test_no_checksum_without_verify(self, echo_stub):
+        """Test that plain --echo does not add a checksum parameter."""
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.raw_input_contents.append(b"abc")
+
+        response = echo_stub.ModelInfer(request, timeout=10)
+        assert "shm_checksum" not in response.parameters
+
+    @pytest.mark.p1
+    def test_failed_requests_do_not_wedge_shm(self, echo_stub):
+        """Test that concurrent SHM round trips stay isolated while other requests fail."""
+        def call(i):
+            request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+            data = bytes([i]) * (3 * 1024 * 1024 + i)
+            request.raw_input_contents.extend([data, data[::2]])
+            request.parameters["__force_shm__"].bool_param = True
+            # Every third request also asks for inline transfer and is rejected
+            request.parameters["__inline__"].bool_param = i % 3 == 0
+            try:
+                response = echo_stub.ModelInfer(request, timeout=30)
+            except grpc.RpcError as e:
+                return i, e.code()
+            return i, list(response.raw_output_contents) == [data, data[::2]]
+
+        with ThreadPoolExecutor(max_workers=8) as pool:
+            results = dict(pool.map(call, range(24)))
+
+        for i, result in results.items():
+            assert result == (grpc.StatusCode.INVALID_ARGUMENT if i % 3 == 0 else True)
+
+
+class TestNodeShmThreshold:
+    """Tests for choosing inline vs SHM transfer in --echo mode."""
+
+    @staticmethod
+    def _request(data, **hints):
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.raw_input_contents.append(data)
+        for name, value in hints.items():
+            request.parameters[name].bool_param = value
+        return request
+
+    @pytest.mark.p1
+    def test_default_threshold(self, echo_stub):
+        """Test that small inputs go inline and large ones through SHM."""
+        small, large = b"s" * 1024, b"L" * (1024 * 1024)
+
+        for data, expected in ((small, 0), (large, len(large))):
+            response = echo_stub.ModelInfer(self._request(data), timeout=10)
+            assert response.raw_output_contents[0] == data
+            assert response.parameters["shm_bytes"].int64_param == expected
+
+    @pytest.mark.p1
+    def test_hints_override_threshold(self, echo_stub):
+        """Test that __inline__ and __force_shm__ pick the path for the same tensor."""
+        data = b"x" * (1024 * 1024)
+
+        inline = echo_stub.ModelInfer(self._request(data, __inline__=True), timeout=10)
+        forced = echo_stub.ModelInfer(self._request(data, __force_shm__=True), timeout=10)
+        small = echo_stub.ModelInfer(self._request(b"tiny", __force_shm__=True), timeout=10)
+
+        assert inline.raw_output_contents[0] == data
+        assert inline.parameters["shm_bytes"].int64_param == 0
+        assert forced.raw_output_contents[0] == data
+        assert forced.parameters["shm_bytes"].int64_param == len(data)
+        assert small.parameters["shm_bytes"].int64_param == len(b"tiny")
+
+    @pytest.mark.p2
+    def test_conflicting_hints(self, echo_stub):
+        """Test that asking for both paths is INVALID_ARGUMENT."""
+        with pytest.raises(grpc.RpcError) as exc_info:
+            echo_stub.ModelInfer(self._request(b"x", __inline__=True, __force_shm__=True), timeout=10)
+        assert exc_info.value.code() == grpc.StatusCode.INVALID_ARGUMENT
+
+    @pytest.mark.p2
+    def test_custom_threshold(self):
+        """Test that --shm-threshold moves the cut-off."""
+        with echo_node("--shm-threshold", "4") as stub:
+            response = stub.ModelInfer(self._request(b"12345"), timeout=10)
+            assert response.parameters["shm_bytes"].int64_param == 5
+
+    @pytest.mark.p1
+    def test_typed_contents_use_shm(self, verify_stub):
+        """Test that typed contents past the threshold go through SHM and come back typed."""
+        values = [i * 0.5 for i in range(64 * 1024)]
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        large = request.inputs.add(name="large", datatype="FP32")
+        large.shape.append(len(values))
+        large.contents.fp32_contents.extend(values)
+        small = request.inputs.add(name="small", datatype="INT64")
+        small.shape.append(2)
+        small.contents.int64_contents.extend([7, 8])
+
+        response = verify_stub.ModelInfer(request, timeout=30)
+
+        assert list(response.outputs[0].contents.fp32_contents) == values
+        assert list(response.outputs[1].contents.int64_contents) == [7, 8]
+        assert not response.raw_output_contents
+        # Only the large tensor's serialized contents crossed SHM
+        assert response.parameters["shm_bytes"].int64_param == large.contents.ByteSize()
+
+
+class TestNodeVerifyShm:
+    """Tests for --verify-shm checksumming of the echo SHM round trip."""
+
+    @pytest.mark.p1
+    def test_reports_checksums(self, verify_stub):
+        """Test that verified echoes succeed and report one checksum per raw input."""
+        # Small inputs are copied inline but still get a checksum; the second spans two SHM chunks
+        payloads = [b"hello", bytes(range(256)) * (45 * 1024)]
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.raw_input_contents.extend(payloads)
+
+        response = verify_stub.ModelInfer(request, timeout=30)
+
+        assert list(response.raw_output_contents) == payloads
+        expected = ",".join(f"{_fnv1a64(p):016x}" for p in payloads)
+        assert response.parameters["shm_checksum"].string_param == expected
+
+    @pytest.mark.p1
+    def test_input_larger_than_segment(self, verify_stub):
+        """Test that an input bigger than a whole SHM segment is chunked, not written past its end."""
+        segment = 10 * 1024 * 1024
+        payload = os.urandom(segment + 1)
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.raw_input_contents.append(payload)
+        request.parameters["__force_shm__"].bool_param = True
+        checksum = f"{_fnv1a64(payload):016x}"
+
+        # The second call finds the segments as the first left them
+        for _ in range(2):
+            response = verify_stub.ModelInfer(request, timeout=30)
+            assert response.raw_output_contents[0] == payload
+            assert response.parameters["shm_bytes"].int64_param == segment + 1
+            assert response.parameters["shm_checksum"].string_param == checksum
+
+    @pytest.mark.p1
+    def test_output_copied_once(self, verify_stub):
+        """Test that each raw output byte is copied out of D2H exactly once, with no reallocation."""
+        segment = 10 * 1024 * 1024
+        # One chunk, several chunks, and an inline input that must not count
+        payloads = [os.urandom(1024 * 1024), os.urandom(2 * segment + 3), b"inline"]
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.raw_input_contents.extend(payloads)
+
+        response = verify_stub.ModelInfer(request, timeout=60)
+
+        assert list(response.raw_output_contents) == payloads
+        shm_bytes = response.parameters["shm_bytes"].int64_param
+        assert shm_bytes == len(payloads[0]) + len(payloads[1])
+        assert response.parameters["shm_output_copy_bytes"].int64_param == shm_bytes
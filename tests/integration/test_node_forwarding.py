@@ -0,0 +1,93 @@
+"""
+Integration tests for how the proxy forwards ModelInfer to its worker:
+worker status codes and client cancellation.
+"""
+
+import time
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    SLOW_WORKER,
+    TRIVIAL_WORKER,
+    grpc,
+    grpc_predict_v2_pb2,
+    make_worker,
+    node,
+    requires_node,
+    wait_for,
+)
+
+pytestmark = requires_node
+
+
+ERROR_WORKER = TRIVIAL_WORKER.replace("__READY__", "True").replace('''
+server = grpc.server''', '''
+    def ModelInfer(self, request, context):
+        code = request.parameters["fail_with"].string_param
+        if code:
+            context.abort(getattr(grpc.StatusCode, code), f"worker says {code}")
+        return grpc_predict_v2_pb2.ModelInferResponse(model_name=request.model_name, id=request.id)
+
+server = grpc.server''')
+
+
+class TestNodeStatusPropagation:
+    """Tests that worker errors reach the client with their own status code."""
+
+    @pytest.mark.p1
+    def test_worker_codes_forwarded(self, temp_dir):
+        """Test that each worker status comes back unchanged and never leaks a concurrency slot."""
+        env = make_worker(Path(temp_dir), ERROR_WORKER)
+        with node("--max-concurrent-infers", "1", "--max-queued-infers", "0", env=env) as stub:
+            def infer(fail_with=""):
+                request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m", id="req")
+                request.parameters["fail_with"].string_param = fail_with
+                return stub.ModelInfer(request, timeout=5)
+
+            for code in ("INVALID_ARGUMENT", "NOT_FOUND", "FAILED_PRECONDITION", "INTERNAL"):
+                with pytest.raises(grpc.RpcError) as exc:
+                    infer(code)
+                assert exc.value.code() == getattr(grpc.StatusCode, code)
+                assert exc.value.details() == f"worker says {code}"
+
+            # With one slot and no queue, a leaked slot would make this RESOURCE_EXHAUSTED
+            assert infer().id == "req"
+
+
+def _slow_request(slow):
+    request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m", id="req")
+    request.parameters["slow"].bool_param = slow
+    return request
+
+
+class TestNodeCancellation:
+    """Tests that a client cancelling ModelInfer cancels the forwarded worker call."""
+
+    @pytest.mark.p1
+    def test_cancel_releases_slot(self, temp_dir):
+        """Test that cancelling a slow infer reaches the worker and frees the concurrency slot."""
+        env = make_worker(Path(temp_dir), SLOW_WORKER)
+        marker = Path(temp_dir) / "slow"
+        env["SLOW_MARKER"] = str(marker)
+        with node("--max-concurrent-infers", "1", "--max-queued-infers", "0", env=env) as stub:
+            call = stub.ModelInfer.future(_slow_request(True), timeout=30)
+            wait_for(marker.with_suffix(".started"))
+            # The only slot is taken while the slow call is in flight
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ModelInfer(_slow_request(False), timeout=5)
+            assert exc.value.code() == grpc.StatusCode.RESOURCE_EXHAUSTED
+
+            call.cancel()
+            wait_for(marker.with_suffix(".cancelled"))
+            # Without propagation the slot would stay taken until the 30s deadline
+            deadline = time.time() + 5
+            while True:
+                try:
+                    assert stub.ModelInfer(_slow_request(False), timeout=5).id == "req"
+                    break
+                except grpc.RpcError as e:
+                    if e.code() != grpc.StatusCode.RESOURCE_EXHAUSTED or time.time() > deadline:
+                        raise
+                    time.sleep(0.05)
@@ -0,0 +1,96 @@
+"""
+Integration tests for the proxy's own HTTP /healthz and /readyz endpoints
+(`anyserve_node --health-port`).
+"""
+
+import json
+import socket
+import subprocess
+import time
+import urllib.error
+import urllib.request
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    TRIVIAL_WORKER,
+    free_port,
+    make_worker,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+def _get(port: int, path: str):
+    """Return (status, body) for GET path, including non-2xx answers."""
+    try:
+        with urllib.request.urlopen(f"http://127.0.0.1:{port}{path}", timeout=2) as resp:
+            return resp.status, json.loads(resp.read())
+    except urllib.error.HTTPError as e:
+        return e.code, json.loads(e.read())
+
+
+def _wait_health(proc: subprocess.Popen, port: int, timeout: float = 20):
+    """Wait until the health port answers /healthz."""
+    deadline = time.monotonic() + timeout
+    while True:
+        try:
+            return _get(port, "/healthz")
+        except (urllib.error.URLError, ConnectionError):
+            assert time.monotonic() < deadline and proc.poll() is None
+            time.sleep(0.05)
+
+
+class TestNodeHealthServer:
+    """Tests for --health-port while the worker starts up and under idle clients."""
+
+    @pytest.mark.p1
+    def test_readyz_503_until_worker_ready(self, temp_dir):
+        """Test that /readyz is 503 while the worker loads and 200 once it is ready."""
+        # Signals the handshake right away but reports not ready for 2s, as if loading weights
+        body = "import time; _t0 = time.time()\n" + TRIVIAL_WORKER.replace(
+            "__READY__", "(time.time() - _t0 > 2)")
+        env = make_worker(Path(temp_dir), body)
+        health_port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--port", str(free_port()), "--worker-timeout", "30s",
+                                 "--health-port", str(health_port)],
+                                env=env, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL)
+        try:
+            status, state = _wait_health(proc, health_port)
+            assert status == 200 and not state["ready"]
+
+            status, state = _get(health_port, "/readyz")
+            assert status == 503 and not state["ready"]
+
+            deadline = time.monotonic() + 20
+            while status != 200:
+                assert time.monotonic() < deadline and proc.poll() is None
+                time.sleep(0.1)
+                status, state = _get(health_port, "/readyz")
+            assert state["ready"] and state["worker_pid"] > 0
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+
+    @pytest.mark.p1
+    def test_idle_client_does_not_block(self, temp_dir):
+        """Test that a connection that never sends a request doesn't stall other probes."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        health_port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--port", str(free_port()),
+                                 "--health-port", str(health_port)],
+                                env=env, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL)
+        try:
+            _wait_health(proc, health_port)
+            with socket.create_connection(("127.0.0.1", health_port)):
+                # The idle connection is accepted first; the probe must still be answered
+                start = time.monotonic()
+                status, _ = _get(health_port, "/healthz")
+                assert status == 200
+                assert time.monotonic() - start < 2
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
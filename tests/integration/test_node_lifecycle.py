@@ -0,0 +1,100 @@
+"""
+Integration tests for when the node starts serving and how it reacts to its
+worker exiting.
+"""
+
+import json
+import os
+import signal
+import socket
+import subprocess
+import time
+import urllib.error
+import urllib.request
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    TRIVIAL_WORKER,
+    free_port,
+    grpc,
+    grpc_predict_v2_pb2,
+    grpc_predict_v2_pb2_grpc,
+    make_worker,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeReadyGate:
+    """Tests that the proxy waits for the worker to report ready before serving."""
+
+    @pytest.mark.p1
+    def test_port_closed_until_worker_ready(self, temp_dir):
+        """Test that the external port only opens once the worker reports ready."""
+        # Signals the handshake right away but reports not ready for 2s, as if loading weights
+        body = "import time; _t0 = time.time()\n" + TRIVIAL_WORKER.replace(
+            "__READY__", "(time.time() - _t0 > 2)")
+        env = make_worker(Path(temp_dir), body)
+        port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--port", str(port), "--worker-timeout", "30s"],
+                                env=env, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL)
+        try:
+            opened_at = None
+            start = time.monotonic()
+            while time.monotonic() - start < 20 and proc.poll() is None:
+                with socket.socket() as probe:
+                    if probe.connect_ex(("127.0.0.1", port)) == 0:
+                        opened_at = time.monotonic() - start
+                        break
+                time.sleep(0.05)
+
+            assert opened_at is not None
+            assert opened_at >= 1.5
+
+            with grpc.insecure_channel(f"127.0.0.1:{port}") as channel:
+                stub = grpc_predict_v2_pb2_grpc.GRPCInferenceServiceStub(channel)
+                response = stub.ServerReady(grpc_predict_v2_pb2.ServerReadyRequest(), timeout=5)
+                assert response.ready
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+
+
+class TestNodeWorkerExit:
+    """Tests that a worker dying while the node is idle is noticed right away."""
+
+    @pytest.mark.p1
+    def test_killed_worker_noticed_promptly(self, temp_dir):
+        """Test that killing the worker externally shuts the node down within a bounded time."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        health_port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--port", str(free_port()),
+                                 "--health-port", str(health_port)],
+                                env=env, stdout=subprocess.PIPE, stderr=subprocess.PIPE, text=True)
+        try:
+            state = None
+            deadline = time.monotonic() + 20
+            while time.monotonic() < deadline and proc.poll() is None:
+                try:
+                    with urllib.request.urlopen(f"http://127.0.0.1:{health_port}/readyz",
+                                                timeout=1) as resp:
+                        state = json.loads(resp.read())
+                        break
+                except (urllib.error.URLError, ConnectionError):
+                    time.sleep(0.1)
+            assert state is not None and state["ready"]
+
+            os.kill(state["worker_pid"], signal.SIGKILL)
+            killed_at = time.monotonic()
+            proc.wait(timeout=10)
+
+            assert time.monotonic() - killed_at < 2
+            assert "Worker process exited unexpectedly" in proc.stderr.read()
+        finally:
+            if proc.poll() is None:
+                proc.kill()
+                proc.wait(timeout=10)
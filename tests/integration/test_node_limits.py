@@ -0,0 +1,162 @@
+"""
+Integration tests for the limits the node applies to ModelInfer requests.
+"""
+
+import subprocess
+import time
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    SLOW_WORKER,
+    echo_node,
+    grpc,
+    grpc_predict_v2_pb2,
+    make_worker,
+    node,
+    requires_node,
+    wait_for,
+)
+
+pytestmark = requires_node
+
+
+@pytest.fixture
+def echo_stub():
+    with echo_node() as stub:
+        yield stub
+
+
+class TestNodeInferLimits:
+    """Tests for validating ModelInfer requests before they reach SHM or a worker."""
+
+    @staticmethod
+    def _request(*tensors):
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        for i, (datatype, shape, raw) in enumerate(tensors):
+            tensor = request.inputs.add(name=f"in{i}", datatype=datatype)
+            tensor.shape.extend(shape)
+            request.raw_input_contents.append(raw)
+        return request
+
+    @staticmethod
+    def _rejected(stub, request):
+        with pytest.raises(grpc.RpcError) as exc_info:
+            stub.ModelInfer(request, timeout=10)
+        assert exc_info.value.code() == grpc.StatusCode.INVALID_ARGUMENT
+        return exc_info.value.details()
+
+    @pytest.mark.p0
+    def test_shape_must_match_raw_size(self, echo_stub):
+        """Test that fixed-size tensors must carry exactly shape x itemsize raw bytes."""
+        details = self._rejected(echo_stub, self._request(("FP32", [2, 2], b"\0" * 12)))
+        assert "shape [2,2] of FP32 but 12 raw bytes" in details
+        self._rejected(echo_stub, self._request(("INT64", [1 << 62, 4], b"\0" * 8)))
+        self._rejected(echo_stub, self._request(("INT8", [-1], b"")))
+
+        ok = echo_stub.ModelInfer(self._request(("FP32", [2, 2], b"\0" * 16),
+                                                ("BYTES", [1], b"any length"),
+                                                ("UINT8", [0, 3], b"")), timeout=10)
+        assert len(ok.raw_output_contents) == 3
+
+    @pytest.mark.p1
+    def test_raw_count_must_match_inputs(self, echo_stub):
+        """Test that declared inputs and raw contents must pair up."""
+        request = self._request(("UINT8", [1], b"a"))
+        request.raw_input_contents.append(b"b")
+        assert "2 entries for 1 inputs" in self._rejected(echo_stub, request)
+
+    @pytest.mark.p1
+    def test_configured_limits(self):
+        """Test that --max-inputs and --max-input-bytes bound each request."""
+        with echo_node("--max-inputs", "2", "--max-input-bytes", "1024") as stub:
+            assert "limit is 2" in self._rejected(stub, self._request(*[("UINT8", [1], b"x")] * 3))
+            assert "limit is 1024" in self._rejected(stub, self._request(("UINT8", [1025], b"x" * 1025)))
+
+            ok = stub.ModelInfer(self._request(("UINT8", [512], b"x" * 512),
+                                               ("UINT8", [512], b"y" * 512)), timeout=10)
+            assert ok.raw_output_contents == [b"x" * 512, b"y" * 512]
+
+    @pytest.mark.p2
+    def test_invalid_limit_rejected(self):
+        """Test that a non-numeric limit fails at startup."""
+        result = subprocess.run([NODE_BIN, "--echo", "--max-inputs", "many"],
+                                capture_output=True, text=True, timeout=30)
+        assert result.returncode == 1
+        assert "--max-inputs must be a non-negative integer" in result.stderr
+
+
+class TestNodeMaxMessageSize:
+    """Tests for --max-message-size on client requests."""
+
+    @staticmethod
+    def _request(size):
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+        request.inputs.add(name="in0", datatype="UINT8", shape=[size])
+        request.raw_input_contents.append(b"m" * size)
+        return request
+
+    @pytest.mark.p1
+    def test_cap_bounds_client_messages(self):
+        """Test that a request just over 4MB needs a cap above 4MB and round-trips under one."""
+        size = 4 * 1024 * 1024 + 1024
+        with echo_node("--max-message-size", "4") as stub:
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ModelInfer(self._request(size), timeout=30)
+            assert exc.value.code() == grpc.StatusCode.RESOURCE_EXHAUSTED
+
+        with echo_node("--max-message-size", "8") as stub:
+            response = stub.ModelInfer(self._request(size), timeout=30)
+            assert response.raw_output_contents == [b"m" * size]
+
+    @pytest.mark.p2
+    def test_invalid_values_rejected(self):
+        """Test that non-numeric or out-of-range sizes fail at startup instead of aborting."""
+        for value in ("abc", "0", "2048", "99999999999"):
+            result = subprocess.run([NODE_BIN, "--echo", "--max-message-size", value],
+                                    capture_output=True, text=True, timeout=30)
+            assert result.returncode == 1, value
+            assert "--max-message-size must be between 1 and 2047 MB" in result.stderr
+
+
+class TestNodeConcurrencyLimit:
+    """Tests for --max-concurrent-infers and --max-queued-infers."""
+
+    @staticmethod
+    def _request(slow):
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m", id="req")
+        request.parameters["slow"].bool_param = slow
+        return request
+
+    @pytest.mark.p1
+    def test_excess_infers_queued_then_rejected(self, temp_dir):
+        """Test that with one slot and one queue place, the second infer waits and the third is refused."""
+        env = make_worker(Path(temp_dir), SLOW_WORKER)
+        marker = Path(temp_dir) / "slow"
+        env["SLOW_MARKER"] = str(marker)
+        with node("--max-concurrent-infers", "1", "--max-queued-infers", "1", env=env) as stub:
+            first = stub.ModelInfer.future(self._request(True), timeout=30)
+            wait_for(marker.with_suffix(".started"))
+
+            second = stub.ModelInfer.future(self._request(False), timeout=30)
+            time.sleep(0.5)
+            assert not second.done()
+
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ModelInfer(self._request(False), timeout=5)
+            assert exc.value.code() == grpc.StatusCode.RESOURCE_EXHAUSTED
+
+            first.cancel()
+            assert second.result(timeout=10).id == "req"
+
+    @pytest.mark.p2
+    def test_invalid_values_rejected(self):
+        """Test that non-numeric or out-of-range limits fail at startup instead of aborting."""
+        for flag, value in [("--max-concurrent-infers", "abc"), ("--max-concurrent-infers", "0"),
+                            ("--max-queued-infers", "-1"), ("--max-queued-infers", "99999999999")]:
+            result = subprocess.run([NODE_BIN, "--echo", flag, value],
+                                    capture_output=True, text=True, timeout=30)
+            assert result.returncode == 1, (flag, value)
+            assert f"{flag} must be a" in result.stderr
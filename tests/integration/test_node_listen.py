@@ -0,0 +1,87 @@
+"""
+Integration tests for serving the node on a Unix socket with `--listen`.
+"""
+
+import json
+import os
+import subprocess
+import time
+import urllib.error
+import urllib.request
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    free_port,
+    grpc,
+    grpc_predict_v2_pb2,
+    grpc_predict_v2_pb2_grpc,
+    read_report,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeListenUnix:
+    """Tests for serving the proxy's gRPC API on a Unix socket with --listen."""
+
+    @pytest.mark.p1
+    def test_server_live_over_uds(self, temp_dir):
+        """Test that a client reaches the node over the socket and the report names it."""
+        sock = Path(temp_dir) / "node.sock"
+        read_fd, write_fd = os.pipe()
+        proc = subprocess.Popen([NODE_BIN, "--echo", "--listen", f"unix:{sock}",
+                                 "--report-fd", str(write_fd)],
+                                pass_fds=(write_fd,), stdout=subprocess.DEVNULL,
+                                stderr=subprocess.DEVNULL)
+        os.close(write_fd)
+        try:
+            report = read_report(proc, read_fd)
+            assert report["port"] is None
+            assert report["listen"] == f"unix:{sock}"
+            assert sock.is_socket()
+
+            with grpc.insecure_channel(f"unix:{sock}") as channel:
+                stub = grpc_predict_v2_pb2_grpc.GRPCInferenceServiceStub(channel)
+                assert stub.ServerLive(grpc_predict_v2_pb2.ServerLiveRequest(), timeout=5).live
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+        assert not sock.exists()
+
+    @pytest.mark.p2
+    def test_readyz_reports_listener(self, temp_dir):
+        """Test that /readyz names the Unix socket once the node is serving on it."""
+        sock = Path(temp_dir) / "node.sock"
+        health_port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--echo", "--listen", f"unix:{sock}",
+                                 "--health-port", str(health_port)],
+                                stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL)
+        try:
+            state = None
+            deadline = time.monotonic() + 10
+            while time.monotonic() < deadline and proc.poll() is None:
+                try:
+                    with urllib.request.urlopen(f"http://127.0.0.1:{health_port}/readyz",
+                                                timeout=1) as resp:
+                        state = json.loads(resp.read())
+                        break
+                except (urllib.error.URLError, ConnectionError):
+                    time.sleep(0.1)
+            assert state is not None and state["ready"]
+            assert state["listen"] == f"unix:{sock}"
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+
+    @pytest.mark.p2
+    def test_rejects_non_unix(self):
+        """Test that --listen only accepts unix:PATH."""
+        for value in ("tcp:0.0.0.0:1234", "unix:", "unix:/" + "x" * 200):
+            result = subprocess.run([NODE_BIN, "--echo", "--listen", value],
+                                    capture_output=True, text=True, timeout=30)
+            assert result.returncode == 1
+            assert "--listen" in result.stderr
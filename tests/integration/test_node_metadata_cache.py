@@ -0,0 +1,126 @@
+"""
+Integration tests for `anyserve_node --cache-metadata` with an external TCP
+worker that restarts.
+"""
+
+import contextlib
+import os
+import subprocess
+import sys
+import time
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    REPO_ROOT,
+    free_port,
+    grpc,
+    grpc_predict_v2_pb2,
+    node,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+METADATA_WORKER = '''
+import sys
+from concurrent import futures
+import grpc
+from anyserve._proto import grpc_predict_v2_pb2 as pb, grpc_predict_v2_pb2_grpc as pb_grpc
+
+port, version = sys.argv[1], sys.argv[2]
+
+class Servicer(pb_grpc.GRPCInferenceServiceServicer):
+    def ServerReady(self, request, context):
+        return pb.ServerReadyResponse(ready=True)
+
+    def ServerMetadata(self, request, context):
+        return pb.ServerMetadataResponse(name="metadata-worker", version=version)
+
+    def ModelMetadata(self, request, context):
+        tensor = pb.ModelMetadataResponse.TensorMetadata(name="x", datatype="FP32", shape=[-1])
+        return pb.ModelMetadataResponse(name=request.name, versions=[version], inputs=[tensor])
+
+server = grpc.server(futures.ThreadPoolExecutor(max_workers=2))
+pb_grpc.add_GRPCInferenceServiceServicer_to_server(Servicer(), server)
+server.add_insecure_port("127.0.0.1:" + port)
+server.start()
+print("ready", flush=True)
+server.wait_for_termination()
+'''
+
+
+class TestNodeMetadataCache:
+    """Tests for --cache-metadata with an external worker that restarts."""
+
+    @staticmethod
+    def _start_worker(script: Path, port: int, version: str) -> subprocess.Popen:
+        env = dict(os.environ)
+        env["PYTHONPATH"] = os.pathsep.join([str(REPO_ROOT / "python"), env.get("PYTHONPATH", "")])
+        worker = subprocess.Popen([sys.executable, str(script), str(port), version], env=env,
+                                  stdout=subprocess.PIPE, stderr=subprocess.DEVNULL, text=True)
+        assert worker.stdout.readline().strip() == "ready"
+        return worker
+
+    @contextlib.contextmanager
+    def _node(self, temp_dir, *args: str):
+        """Yields (stub, worker_port, worker_script) with a v1 worker running behind the node."""
+        script = Path(temp_dir) / "metadata_worker.py"
+        script.write_text(METADATA_WORKER)
+        worker_port = free_port()
+        self.worker = self._start_worker(script, worker_port, "v1")
+        try:
+            with node("--worker-transport", "tcp", "--worker-addr", f"127.0.0.1:{worker_port}",
+                      *args) as stub:
+                yield stub, worker_port, script
+        finally:
+            self.worker.kill()
+            self.worker.wait(timeout=10)
+
+    def _stop_worker(self):
+        self.worker.kill()
+        self.worker.wait(timeout=10)
+
+    @pytest.mark.p1
+    def test_served_during_restart_then_refreshed(self, temp_dir):
+        """Test that cached metadata is served while the worker is down and refreshed once it returns."""
+        server_request = grpc_predict_v2_pb2.ServerMetadataRequest()
+        model_request = grpc_predict_v2_pb2.ModelMetadataRequest(name="m")
+        with self._node(temp_dir, "--cache-metadata") as (stub, worker_port, script):
+            assert stub.ServerMetadata(server_request, timeout=5).version == "v1"
+            assert list(stub.ModelMetadata(model_request, timeout=5).versions) == ["v1"]
+
+            # Restart window: the worker is gone, the proxy answers from its cache
+            self._stop_worker()
+            assert stub.ServerMetadata(server_request, timeout=5).version == "v1"
+            cached = stub.ModelMetadata(model_request, timeout=5)
+            assert list(cached.versions) == ["v1"]
+            assert cached.inputs[0].datatype == "FP32"
+
+            # The restarted worker reports different metadata, which replaces the cache
+            self.worker = self._start_worker(script, worker_port, "v2")
+            deadline = time.time() + 20
+            while stub.ServerMetadata(server_request, timeout=5).version != "v2":
+                assert time.time() < deadline
+                time.sleep(0.2)
+            assert list(stub.ModelMetadata(model_request, timeout=5).versions) == ["v2"]
+
+            # Unknown models aren't invented from the cache
+            self._stop_worker()
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ModelMetadata(grpc_predict_v2_pb2.ModelMetadataRequest(name="other"), timeout=5)
+            assert exc.value.code() == grpc.StatusCode.UNAVAILABLE
+
+    @pytest.mark.p2
+    def test_not_cached_by_default(self, temp_dir):
+        """Test that without --cache-metadata an unreachable worker surfaces UNAVAILABLE."""
+        with self._node(temp_dir) as (stub, _, _):
+            request = grpc_predict_v2_pb2.ServerMetadataRequest()
+            assert stub.ServerMetadata(request, timeout=5).version == "v1"
+
+            self._stop_worker()
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ServerMetadata(request, timeout=5)
+            assert exc.value.code() == grpc.StatusCode.UNAVAILABLE
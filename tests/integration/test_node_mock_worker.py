@@ -0,0 +1,205 @@
+"""
+End-to-end tests of the proxy against anyserve_mock_worker, a C++ worker that
+implements the spawn contract (UDS, ready fd, SHM fds) without Python, so the
+proxy's startup and infer path can be tested on their own.
+"""
+
+import os
+import time
+from pathlib import Path
+
+import grpc
+import pytest
+
+from .node_helpers import (
+    MOCK_WORKER_BIN,
+    grpc_predict_v2_pb2,
+    mock_worker_node,
+    requires_mock_worker,
+    requires_node,
+    run_check,
+)
+
+pytestmark = requires_node
+
+
+@requires_mock_worker
+class TestNodeMockWorker:
+    """End-to-end tests of the proxy against the Python-free mock worker."""
+
+    @pytest.mark.p0
+    def test_check_handshake(self):
+        """Test that --check passes with the mock's ready message and SHM sizes."""
+        result = run_check(dict(os.environ, PYTHON_PATH=MOCK_WORKER_BIN))
+        assert result.returncode == 0, result.stdout + result.stderr
+        assert "SHM size mismatch" not in result.stderr
+
+    @pytest.mark.p0
+    def test_infer_round_trip(self):
+        """Test that raw and typed inputs come back through proxy and worker SHM in order."""
+        small = b"hello"
+        large = os.urandom(15 * 1024 * 1024)  # larger than one 10MB SHM segment
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m", model_version="1", id="req-1")
+        for name, data in (("small", small), ("large", large)):
+            tensor = request.inputs.add(name=name, datatype="BYTES")
+            tensor.shape.append(len(data))
+            request.raw_input_contents.append(data)
+
+        with mock_worker_node() as stub:
+            response = stub.ModelInfer(request, timeout=30)
+
+        assert (response.model_name, response.model_version, response.id) == ("m", "1", "req-1")
+        assert [o.name for o in response.outputs] == ["small", "large"]
+        assert [list(o.shape) for o in response.outputs] == [[len(small)], [len(large)]]
+        assert list(response.raw_output_contents) == [small, large]
+        assert response.parameters["shm_bytes"].int64_param == len(small) + len(large)
+
+    @pytest.mark.p1
+    def test_typed_contents(self):
+        """Test that typed contents are copied to the outputs unchanged."""
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m")
+        tensor = request.inputs.add(name="x", datatype="FP32", shape=[3])
+        tensor.contents.fp32_contents.extend([1.0, 2.5, -4.0])
+
+        with mock_worker_node("--worker-transport", "uds-abstract") as stub:
+            response = stub.ModelInfer(request, timeout=10)
+
+        assert list(response.outputs[0].contents.fp32_contents) == [1.0, 2.5, -4.0]
+        assert response.parameters["shm_bytes"].int64_param == 0
+
+
+def _offsets(response):
+    """The H2D offsets the mock read its inputs from."""
+    return [int(o) for o in response.parameters["mock_h2d_offsets"].string_param.split(",") if o]
+
+
+def _infer(stub, *payloads, **params):
+    """Echo raw payloads through the mock and check they come back intact."""
+    request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m")
+    request.raw_input_contents.extend(payloads)
+    for name, value in params.items():
+        request.parameters[name].bool_param = value
+    response = stub.ModelInfer(request, timeout=10)
+    assert list(response.raw_output_contents) == list(payloads)
+    return response
+
+
+@requires_mock_worker
+class TestNodeMockWorkerH2dAck:
+    """Tests that the proxy frees a worker's H2D slots only once the worker acknowledges them."""
+
+    @pytest.mark.p0
+    def test_slot_held_until_ack(self):
+        """Test that an unacknowledged slot isn't handed out again while an acknowledged one is."""
+        mb = 1024 * 1024
+        with mock_worker_node() as stub:
+            unacked = _infer(stub, os.urandom(mb), mock_skip_ack=True)
+            second = _infer(stub, os.urandom(mb))
+            third = _infer(stub, os.urandom(mb), b"small")
+
+        assert unacked.parameters["h2d_bytes"].int64_param == mb
+        assert "h2d_ack" not in second.parameters
+        assert _offsets(second) != _offsets(unacked)
+        assert _offsets(third) == _offsets(second)
+        # The small input stayed inline
+        assert third.parameters["h2d_bytes"].int64_param == mb
+
+    @pytest.mark.p1
+    def test_missing_ack_force_freed(self, temp_dir):
+        """Test that a slot never acknowledged is freed after --shm-ack-timeout, with a warning."""
+        log = Path(temp_dir) / "node.log"
+        payload = os.urandom(1024 * 1024)
+        with open(log, "w") as stderr, \
+                mock_worker_node("--shm-ack-timeout", "500ms", stderr=stderr) as stub:
+            unacked = _infer(stub, payload, mock_skip_ack=True)
+            time.sleep(1)
+            reused = _infer(stub, payload)
+
+        assert _offsets(reused) == _offsets(unacked)
+        assert "never acknowledged H2D slot at offset" in log.read_text()
+
+
+@requires_mock_worker
+class TestNodeMockWorkerShmThreshold:
+    """Tests for the inline-vs-H2D choice the proxy makes for each raw input."""
+
+    @pytest.mark.p1
+    def test_hints_override_threshold(self):
+        """Test that __inline__ and __force_shm__ pick the path for the same tensor."""
+        data = os.urandom(1024 * 1024)
+        with mock_worker_node() as stub:
+            default = _infer(stub, data)
+            inline = _infer(stub, data, __inline__=True)
+            forced = _infer(stub, b"tiny", __force_shm__=True)
+
+        assert default.parameters["h2d_bytes"].int64_param == len(data)
+        assert inline.parameters["h2d_bytes"].int64_param == 0
+        assert _offsets(inline) == []
+        assert forced.parameters["h2d_bytes"].int64_param == len(b"tiny")
+        assert len(_offsets(forced)) == 1
+
+    @pytest.mark.p2
+    def test_conflicting_hints(self):
+        """Test that asking for both paths is INVALID_ARGUMENT before reaching the worker."""
+        with mock_worker_node() as stub:
+            with pytest.raises(grpc.RpcError) as exc_info:
+                _infer(stub, b"x", __inline__=True, __force_shm__=True)
+        assert exc_info.value.code() == grpc.StatusCode.INVALID_ARGUMENT
+
+    @pytest.mark.p2
+    def test_custom_threshold(self):
+        """Test that --shm-threshold moves the cut-off without --echo."""
+        with mock_worker_node("--shm-threshold", "4") as stub:
+            response = _infer(stub, b"12345", b"123")
+        assert response.parameters["h2d_bytes"].int64_param == 5
+
+
+@requires_mock_worker
+class TestNodeMockWorkerVerifyShm:
+    """Tests for --verify-shm checksumming of the inputs the proxy writes to a worker's H2D slots."""
+
+    @pytest.mark.p1
+    def test_verified_inputs(self):
+        """Test that matching checksums pass and the checksum parameters stay internal."""
+        payloads = [os.urandom(1024 * 1024), b"small", os.urandom(2 * 1024 * 1024)]
+        with mock_worker_node("--verify-shm") as stub:
+            response = _infer(stub, *payloads)
+
+        assert response.parameters["h2d_bytes"].int64_param == len(payloads[0]) + len(payloads[2])
+        assert "h2d_checksum" not in response.parameters
+        assert "h2d_ack" not in response.parameters
+
+    @pytest.mark.p1
+    def test_corrupt_input_is_data_loss(self):
+        """Test that a worker reading different bytes than were written fails with DATA_LOSS."""
+        with mock_worker_node("--verify-shm") as stub:
+            with pytest.raises(grpc.RpcError) as exc_info:
+                _infer(stub, os.urandom(1024 * 1024), mock_corrupt_h2d=True)
+            # The slot was still acknowledged, so the next call reuses it
+            _infer(stub, os.urandom(1024 * 1024))
+
+        assert exc_info.value.code() == grpc.StatusCode.DATA_LOSS
+        assert "checksum mismatch on raw input 0" in exc_info.value.details()
+
+
+@requires_mock_worker
+class TestNodeMockWorkerTypedShm:
+    """Tests that typed contents reach a worker through H2D like raw inputs."""
+
+    @pytest.mark.p1
+    def test_large_typed_contents_via_h2d(self):
+        """Test that a large fp32 tensor goes through H2D and keeps its values and encoding."""
+        values = [float(i) for i in range(64 * 1024)]
+        request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m")
+        small = request.inputs.add(name="small", datatype="FP32", shape=[2])
+        small.contents.fp32_contents.extend([1.0, 2.0])
+        large = request.inputs.add(name="large", datatype="FP32", shape=[len(values)])
+        large.contents.fp32_contents.extend(values)
+
+        with mock_worker_node("--verify-shm") as stub:
+            response = stub.ModelInfer(request, timeout=10)
+
+        assert list(response.outputs[0].contents.fp32_contents) == [1.0, 2.0]
+        assert list(response.outputs[1].contents.fp32_contents) == values
+        assert len(_offsets(response)) == 1
+        assert response.parameters["h2d_bytes"].int64_param == large.contents.ByteSize()
@@ -0,0 +1,51 @@
+"""
+Integration tests for multi-model mode (`anyserve_node --model NAME=APP ...`),
+where each model gets its own worker and requests are routed by model_name.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    TRIVIAL_WORKER,
+    grpc,
+    grpc_predict_v2_pb2,
+    make_worker,
+    node,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+# Answers ModelInfer with the app target it was started for (its first argument)
+APP_WORKER = "import sys\n" + TRIVIAL_WORKER.replace("__READY__", "True").replace('''
+server = grpc.server''', '''
+    def ModelInfer(self, request, context):
+        response = grpc_predict_v2_pb2.ModelInferResponse(model_name=request.model_name, id=request.id)
+        response.parameters["app"].string_param = sys.argv[1]
+        return response
+
+server = grpc.server''')
+
+
+class TestNodeModels:
+    """Tests for routing ModelInfer between per-model workers."""
+
+    @pytest.mark.p1
+    def test_requests_routed_by_model_name(self, temp_dir):
+        """Test that each model's requests reach its own worker and unknown models are NOT_FOUND."""
+        env = make_worker(Path(temp_dir), APP_WORKER)
+        with node("--model", "alpha=apps:alpha", "--model", "beta=apps:beta", env=env) as stub:
+            for _ in range(3):
+                for model in ("alpha", "beta"):
+                    request = grpc_predict_v2_pb2.ModelInferRequest(model_name=model, id=model)
+                    response = stub.ModelInfer(request, timeout=5)
+                    assert response.model_name == model
+                    assert response.parameters["app"].string_param == f"apps:{model}"
+
+            with pytest.raises(grpc.RpcError) as exc:
+                stub.ModelInfer(grpc_predict_v2_pb2.ModelInferRequest(model_name="gamma"), timeout=5)
+            assert exc.value.code() == grpc.StatusCode.NOT_FOUND
+            assert "gamma" in exc.value.details()
@@ -0,0 +1,78 @@
+"""
+Integration tests for the `anyserve_node --report-fd` startup report.
+"""
+
+import os
+import subprocess
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    TRIVIAL_WORKER,
+    free_port,
+    grpc,
+    grpc_predict_v2_pb2,
+    grpc_predict_v2_pb2_grpc,
+    make_worker,
+    read_report,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeReportFd:
+    """Tests for the --report-fd startup report."""
+
+    @pytest.mark.p1
+    def test_echo_reports_bound_port(self):
+        """Test that --port 0 reports the port actually bound, and it serves."""
+        read_fd, write_fd = os.pipe()
+        proc = subprocess.Popen([NODE_BIN, "--echo", "--port", "0", "--report-fd", str(write_fd)],
+                                pass_fds=(write_fd,), stdout=subprocess.DEVNULL,
+                                stderr=subprocess.DEVNULL)
+        os.close(write_fd)
+        try:
+            report = read_report(proc, read_fd)
+            assert report["port"] > 0
+            assert report["uds_path"] is None
+            assert report["worker_pid"] is None
+            assert report["h2d_fd"] >= 0 and report["d2h_fd"] >= 0
+
+            with grpc.insecure_channel(f"127.0.0.1:{report['port']}") as channel:
+                stub = grpc_predict_v2_pb2_grpc.GRPCInferenceServiceStub(channel)
+                assert stub.ServerReady(grpc_predict_v2_pb2.ServerReadyRequest(), timeout=5).ready
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+
+    @pytest.mark.p1
+    def test_worker_fields(self, temp_dir):
+        """Test that a spawned worker's pid and UDS path are reported."""
+        env = make_worker(Path(temp_dir), TRIVIAL_WORKER.replace("__READY__", "True"))
+        read_fd, write_fd = os.pipe()
+        port = free_port()
+        proc = subprocess.Popen([NODE_BIN, "--port", str(port), "--report-fd", str(write_fd)],
+                                env=env, pass_fds=(write_fd,), stdout=subprocess.DEVNULL,
+                                stderr=subprocess.DEVNULL)
+        os.close(write_fd)
+        try:
+            report = read_report(proc, read_fd)
+            assert report["port"] == port
+            assert report["uds_path"].startswith("/tmp/anyserve_")
+            assert os.path.exists(report["uds_path"])
+            assert report["worker_pid"] > 0
+            os.kill(report["worker_pid"], 0)
+        finally:
+            proc.terminate()
+            proc.wait(timeout=10)
+
+    @pytest.mark.p2
+    def test_closed_fd_rejected(self):
+        """Test that a descriptor that isn't open fails fast."""
+        result = subprocess.run([NODE_BIN, "--echo", "--port", "0", "--report-fd", "987"],
+                                capture_output=True, text=True, timeout=30)
+        assert result.returncode == 1
+        assert "--report-fd 987 is not open" in result.stderr
@@ -0,0 +1,100 @@
+"""
+Integration tests for the node's SHM segment options.
+"""
+
+import os
+import resource
+import subprocess
+import sys
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    NODE_BIN,
+    echo_node,
+    grpc_predict_v2_pb2,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+SEGMENT_SIZE = 10 * 1024 * 1024
+
+
+def _shm_round_trip(stub):
+    """Echo one tensor through SHM and check it comes back intact."""
+    payload = os.urandom(1024 * 1024)
+    request = grpc_predict_v2_pb2.ModelInferRequest(model_name="echo")
+    request.raw_input_contents.append(payload)
+    request.parameters["__force_shm__"].bool_param = True
+    response = stub.ModelInfer(request, timeout=10)
+    assert response.raw_output_contents[0] == payload
+    assert response.parameters["shm_bytes"].int64_param == len(payload)
+
+
+class TestNodeShmMlock:
+    """Tests for --shm-mlock, which needs RLIMIT_MEMLOCK room or CAP_IPC_LOCK."""
+
+    @pytest.mark.p2
+    def test_round_trip(self, temp_dir):
+        """Test that SHM works with --shm-mlock, locked or with the fallback warning."""
+        log = Path(temp_dir) / "node.log"
+        with open(log, "w") as stderr, echo_node("--shm-mlock", stderr=stderr) as stub:
+            _shm_round_trip(stub)
+
+        warnings = [line for line in log.read_text().splitlines() if "mlock(" in line]
+        soft, _ = resource.getrlimit(resource.RLIMIT_MEMLOCK)
+        if soft == resource.RLIM_INFINITY or soft >= 4 * SEGMENT_SIZE:
+            assert warnings == []
+        else:
+            # Without room to lock, both segments stay swappable and say why
+            assert all("RLIMIT_MEMLOCK" in line for line in warnings)
+
+
+@pytest.mark.skipif(not sys.platform.startswith("linux"), reason="huge page SHM is Linux-only")
+class TestNodeShmHugePages:
+    """Tests for --shm-hugepages, which falls back to normal pages when none are reserved."""
+
+    @pytest.mark.p2
+    def test_round_trip(self, temp_dir):
+        """Test that SHM works with --shm-hugepages, on huge pages or after the fallback warning."""
+        log = Path(temp_dir) / "node.log"
+        with open(log, "w") as stderr, echo_node("--shm-hugepages", stderr=stderr) as stub:
+            _shm_round_trip(stub)
+
+        # The only acceptable complaint is the fallback, with the reason it was needed
+        warnings = [line for line in log.read_text().splitlines() if "huge page" in line]
+        assert all("using normal pages" in line for line in warnings)
+        assert not any("(Success)" in line for line in warnings)
+
+
+@pytest.mark.skipif(not Path("/dev/shm").is_dir(), reason="POSIX SHM not visible under /dev/shm")
+class TestNodeShmKeepName:
+    """Tests for the debug-only --shm-keep-name option."""
+
+    @pytest.mark.p2
+    def test_segments_visible_then_removed(self):
+        """Test that named segments appear in /dev/shm while serving and are unlinked on exit."""
+        prefix = f"anyserve_test_{os.getpid()}"
+        segments = [Path("/dev/shm") / f"{prefix}_{d}" for d in ("h2d", "d2h")]
+        with echo_node("--shm-keep-name", prefix) as stub:
+            assert stub.ServerLive(grpc_predict_v2_pb2.ServerLiveRequest(), timeout=5).live
+            for segment in segments:
+                assert segment.exists()
+                assert segment.stat().st_size == SEGMENT_SIZE
+        assert not any(segment.exists() for segment in segments)
+
+    @pytest.mark.p2
+    def test_stale_segment_fails(self):
+        """Test that a leftover segment with the same name is reported instead of reused."""
+        prefix = f"anyserve_stale_{os.getpid()}"
+        stale = Path("/dev/shm") / f"{prefix}_h2d"
+        stale.write_bytes(b"")
+        try:
+            result = subprocess.run([NODE_BIN, "--echo", "--port", "0", "--shm-keep-name", prefix],
+                                    capture_output=True, text=True, timeout=30)
+            assert result.returncode == 1
+            assert f"remove /dev/shm/{prefix}_h2d" in result.stderr
+        finally:
+            stale.unlink()
@@ -0,0 +1,38 @@
+"""
+Integration tests for the proxy <-> worker transport
+(`--worker-transport uds|tcp`) with a spawned worker.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    SLOW_WORKER,
+    free_port,
+    grpc_predict_v2_pb2,
+    make_worker,
+    node,
+    requires_node,
+)
+
+pytestmark = requires_node
+
+
+class TestNodeWorkerTransport:
+    """Tests that ModelInfer reaches a spawned worker over each transport."""
+
+    @pytest.mark.p1
+    @pytest.mark.parametrize("transport", ["uds", "tcp"])
+    def test_infer_round_trip(self, temp_dir, transport):
+        """Test that a simple infer is forwarded to the worker and answered."""
+        env = make_worker(Path(temp_dir), SLOW_WORKER)
+        args = ["--worker-transport", transport]
+        if transport == "tcp":
+            # The loader ignores its APP_TARGET; with one, the TCP worker is spawned, not external
+            args += ["--worker-addr", f"127.0.0.1:{free_port()}", "app:target"]
+        with node(*args, env=env) as stub:
+            for i in range(3):
+                request = grpc_predict_v2_pb2.ModelInferRequest(model_name="m", id=f"req-{i}")
+                response = stub.ModelInfer(request, timeout=10)
+                assert (response.model_name, response.id) == ("m", f"req-{i}")
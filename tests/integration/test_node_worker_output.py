@@ -0,0 +1,67 @@
+"""
+Integration tests for `anyserve_node --worker-output`.
+"""
+
+from pathlib import Path
+
+import pytest
+
+from .node_helpers import (
+    TRIVIAL_WORKER,
+    make_worker,
+    requires_node,
+    run_check,
+)
+
+pytestmark = requires_node
+
+
+NOISY_WORKER = """
+import sys
+print("out-marker", flush=True)
+print("err-marker", file=sys.stderr)
+""" + TRIVIAL_WORKER.replace("__READY__", "True")
+
+
+class TestNodeWorkerOutput:
+    """Tests for --worker-output."""
+
+    @pytest.mark.p1
+    def test_inherit_by_default(self, temp_dir):
+        """Test that worker output reaches the node's stdout/stderr unchanged by default."""
+        env = make_worker(Path(temp_dir), NOISY_WORKER)
+        result = run_check(env)
+        assert result.returncode == 0, result.stderr
+        assert "out-marker" in result.stdout.splitlines()
+        assert "err-marker" in result.stderr.splitlines()
+
+    @pytest.mark.p0
+    def test_prefix(self, temp_dir):
+        """Test that captured lines are forwarded with a [worker] prefix to the same stream."""
+        env = make_worker(Path(temp_dir), NOISY_WORKER)
+        result = run_check(env, "--worker-output", "prefix")
+        assert result.returncode == 0, result.stderr
+        assert "[worker] out-marker" in result.stdout.splitlines()
+        assert "[worker] err-marker" in result.stderr.splitlines()
+        assert "out-marker" not in result.stdout.splitlines()
+
+    @pytest.mark.p1
+    def test_file(self, temp_dir):
+        """Test that file:PATH appends both streams to the log file instead of the node's output."""
+        log = Path(temp_dir) / "worker.log"
+        log.write_text("earlier\n")
+        env = make_worker(Path(temp_dir), NOISY_WORKER)
+        result = run_check(env, "--worker-output", f"file:{log}")
+        assert result.returncode == 0, result.stderr
+        assert log.read_text().splitlines()[:1] == ["earlier"]
+        assert {"out-marker", "err-marker"} <= set(log.read_text().splitlines())
+        assert "out-marker" not in result.stdout
+
+    @pytest.mark.p2
+    def test_rejects_unknown(self, temp_dir):
+        """Test that an unknown mode or an empty file path is rejected."""
+        env = make_worker(Path(temp_dir), NOISY_WORKER)
+        for value in ("pipe", "file:"):
+            result = run_check(env, "--worker-output", value)
+            assert result.returncode == 1
+            assert "--worker-output must be" in result.stderr
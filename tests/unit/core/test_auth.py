@@ -0,0 +1,83 @@
+"""
+Unit tests for bearer-token authentication between AnyserveCore instances.
+"""
+
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def make_core(temp_dir):
+    """Factory for AnyserveCore instances that are stopped after the test."""
+    cores = []
+
+    def _make(instance_id, dispatcher=None, **kwargs):
+        core = _core.AnyserveCore(temp_dir, instance_id, _free_port(), dispatcher, **kwargs)
+        cores.append(core)
+        return core
+
+    yield _make
+    for core in cores:
+        core.stop()
+
+
+class TestAuthTokens:
+    """Tests for the optional auth_tokens check."""
+
+    @pytest.mark.p1
+    def test_off_by_default(self, make_core):
+        """Test that a peer without auth_tokens accepts unauthenticated calls."""
+        peer = make_core("peer", _Dispatcher())
+        client = make_core("client")
+        assert client.remote_call(peer.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p0
+    def test_missing_token_rejected(self, make_core):
+        """Test that a call without a token is rejected."""
+        peer = make_core("peer", _Dispatcher(), auth_tokens=["s3cret"])
+        client = make_core("client")
+        with pytest.raises(_core.TransportError, match="bearer token"):
+            client.remote_call(peer.get_address(), "echo", b"hi", False)
+
+    @pytest.mark.p0
+    def test_matching_token_accepted(self, make_core):
+        """Test that the client's token is attached and accepted."""
+        peer = make_core("peer", _Dispatcher(), auth_tokens=["s3cret"])
+        client = make_core("client", auth_tokens=["s3cret"])
+        assert client.remote_call(peer.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p1
+    def test_wrong_token_rejected(self, make_core):
+        """Test that a token the peer doesn't know is rejected."""
+        peer = make_core("peer", _Dispatcher(), auth_tokens=["s3cret"])
+        client = make_core("client", auth_tokens=["guess"])
+        with pytest.raises(_core.TransportError):
+            client.remote_call(peer.get_address(), "echo", b"hi", False)
+
+    @pytest.mark.p2
+    def test_any_listed_token_accepted(self, make_core):
+        """Test that every token in the peer's list is accepted, for rotation."""
+        peer = make_core("peer", _Dispatcher(), auth_tokens=["new", "old"])
+        client = make_core("client", auth_tokens=["old"])
+        assert client.remote_call(peer.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p2
+    def test_empty_token_invalid(self, temp_dir):
+        """Test that an empty token is rejected at construction."""
+        with pytest.raises(ValueError):
+            _core.AnyserveCore(temp_dir, "bad", _free_port(), None, auth_tokens=[""])
@@ -0,0 +1,60 @@
+"""
+Unit tests for AnyserveCore with an OS-assigned port (port=0).
+"""
+
+import pytest
+from pathlib import Path
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+@pytest.fixture
+def core(temp_dir):
+    core = _core.AnyserveCore(temp_dir, "port-zero", 0, _Dispatcher())
+    yield core
+    core.stop()
+
+
+class TestBoundPort:
+    """Tests that port=0 reports the port the server actually bound."""
+
+    @pytest.mark.p0
+    def test_port_is_assigned(self, core):
+        """Test that port and get_address reflect the assigned port."""
+        assert core.port > 0
+        assert core.get_address() == f"localhost:{core.port}"
+
+    @pytest.mark.p1
+    def test_registry_uses_assigned_port(self, core, temp_dir):
+        """Test that the instance and capability registry record the assigned port."""
+        core.register_capability("echo")
+
+        instance_file = Path(temp_dir) / "instances" / "port-zero" / "address"
+        assert instance_file.read_text() == core.get_address()
+        assert core.lookup_capability("echo") == [core.get_address()]
+
+    @pytest.mark.p1
+    def test_reachable_on_assigned_port(self, core, temp_dir):
+        """Test that another instance can call this one at the reported address."""
+        client = _core.AnyserveCore(temp_dir, "client", 0, None)
+        try:
+            assert client.remote_call(core.get_address(), "echo", b"hi", False) == b"hi"
+        finally:
+            client.stop()
+
+    @pytest.mark.p2
+    def test_distinct_instances_get_distinct_ports(self, core, temp_dir):
+        """Test that two port=0 instances don't collide."""
+        other = _core.AnyserveCore(temp_dir, "other", 0, None)
+        try:
+            assert other.port > 0
+            assert other.port != core.port
+        finally:
+            other.stop()
@@ -0,0 +1,117 @@
+"""
+Unit tests for the per-address circuit breaker in remote_call.
+"""
+
+import socket
+import time
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Answers every capability with b"ok"."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return b"ok"
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def client(temp_dir):
+    """An AnyserveCore that opens its breaker after 2 failures, for 0.5s."""
+    core = _core.AnyserveCore(temp_dir, "client", _free_port(), None)
+    core.set_circuit_breaker(failure_threshold=2, cooldown_secs=0.5)
+    yield core
+    core.stop()
+
+
+def _fail_twice(client, address):
+    for _ in range(2):
+        with pytest.raises(_core.PeerUnreachableError) as exc_info:
+            client.remote_call(address, "echo", b"", False, timeout_secs=1.0)
+        assert not isinstance(exc_info.value, _core.PeerUnavailableError)
+
+
+class TestCircuitBreaker:
+    """Tests for opening, cooling down and recovering the breaker."""
+
+    @pytest.mark.p1
+    def test_hierarchy(self):
+        """Test that PeerUnavailableError is caught as PeerUnreachableError."""
+        assert issubclass(_core.PeerUnavailableError, _core.PeerUnreachableError)
+        assert issubclass(_core.PeerUnavailableError, ConnectionError)
+
+    @pytest.mark.p0
+    def test_opens_after_consecutive_failures(self, client):
+        """Test that calls short-circuit once the failure threshold is reached."""
+        address = f"127.0.0.1:{_free_port()}"
+        _fail_twice(client, address)
+
+        start = time.monotonic()
+        with pytest.raises(_core.PeerUnavailableError, match="2 consecutive failures"):
+            client.remote_call(address, "echo", b"", False, timeout_secs=30.0)
+        assert time.monotonic() - start < 0.1
+
+    @pytest.mark.p1
+    def test_breaker_is_per_address(self, client):
+        """Test that an open breaker only affects its own address."""
+        _fail_twice(client, f"127.0.0.1:{_free_port()}")
+
+        with pytest.raises(_core.PeerUnreachableError) as exc_info:
+            client.remote_call(f"127.0.0.1:{_free_port()}", "echo", b"", False, timeout_secs=1.0)
+        assert not isinstance(exc_info.value, _core.PeerUnavailableError)
+
+    @pytest.mark.p1
+    def test_failed_probe_reopens(self, client):
+        """Test that after the cooldown one probe goes out, and its failure reopens the breaker."""
+        address = f"127.0.0.1:{_free_port()}"
+        _fail_twice(client, address)
+        time.sleep(0.6)
+
+        with pytest.raises(_core.PeerUnreachableError) as exc_info:
+            client.remote_call(address, "echo", b"", False, timeout_secs=1.0)
+        assert not isinstance(exc_info.value, _core.PeerUnavailableError)
+        with pytest.raises(_core.PeerUnavailableError):
+            client.remote_call(address, "echo", b"", False, timeout_secs=1.0)
+
+    @pytest.mark.p0
+    def test_successful_probe_closes(self, client, temp_dir):
+        """Test that a peer coming back is reached by the probe and the breaker closes."""
+        port = _free_port()
+        address = f"127.0.0.1:{port}"
+        _fail_twice(client, address)
+
+        peer = _core.AnyserveCore(temp_dir, "peer", port, _Dispatcher())
+        try:
+            with pytest.raises(_core.PeerUnavailableError):
+                client.remote_call(address, "echo", b"", False, timeout_secs=5.0)
+            time.sleep(0.6)
+
+            assert client.remote_call(address, "echo", b"", False, timeout_secs=5.0) == b"ok"
+            assert client.remote_call(address, "echo", b"", False, timeout_secs=5.0) == b"ok"
+        finally:
+            peer.stop()
+
+    @pytest.mark.p2
+    def test_disabled(self, client):
+        """Test that failure_threshold=0 never short-circuits."""
+        client.set_circuit_breaker(failure_threshold=0, cooldown_secs=0.5)
+        address = f"127.0.0.1:{_free_port()}"
+        for _ in range(4):
+            with pytest.raises(_core.PeerUnreachableError) as exc_info:
+                client.remote_call(address, "echo", b"", False, timeout_secs=1.0)
+            assert not isinstance(exc_info.value, _core.PeerUnavailableError)
+
+    @pytest.mark.p2
+    def test_invalid_settings(self, client):
+        """Test that a negative threshold or non-positive cooldown is rejected."""
+        with pytest.raises(ValueError):
+            client.set_circuit_breaker(failure_threshold=-1, cooldown_secs=1.0)
+        with pytest.raises(ValueError):
+            client.set_circuit_breaker(failure_threshold=2, cooldown_secs=0)
@@ -0,0 +1,50 @@
+"""
+Unit tests for gRPC transport compression between AnyserveCore instances.
+"""
+
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+class TestCompression:
+    """Tests for the compression property."""
+
+    @pytest.mark.p0
+    def test_gzip_round_trip(self, temp_dir):
+        """Test that a large payload round-trips when both sides compress."""
+        payload = b"tensor " * (1 << 18)
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher()) as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            server.compression = "gzip"
+            client.compression = "gzip"
+            assert client.remote_call(server.get_address(), "echo", payload, False) == payload
+
+    @pytest.mark.p1
+    def test_mixed_settings(self, temp_dir):
+        """Test that a compressing client and a plain server, and vice versa, interoperate."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher()) as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            client.compression = "deflate"
+            assert client.remote_call(server.get_address(), "echo", b"x" * 4096, False) == b"x" * 4096
+
+            client.compression = "none"
+            server.compression = "gzip"
+            assert client.remote_call(server.get_address(), "echo", b"y" * 4096, False) == b"y" * 4096
+
+    @pytest.mark.p2
+    def test_default_and_invalid(self, temp_dir):
+        """Test that compression is off by default and unknown algorithms are rejected."""
+        with _core.AnyserveCore(temp_dir, "server", 0, None) as core:
+            assert core.compression == "none"
+            with pytest.raises(ValueError, match="Unsupported compression"):
+                core.compression = "zstd"
+            core.compression = "gzip"
+            assert core.compression == "gzip"
@@ -0,0 +1,159 @@
+"""
+Unit tests for AnyserveCore.get_object_local and named object stores.
+"""
+
+import socket
+from pathlib import Path
+
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _StoreDispatcher:
+    """Answers every call with the store_dir parameter the server handed it."""
+
+    def dispatch(self, capability, request_bytes, is_delegated):
+        from anyserve._proto import grpc_predict_v2_pb2
+
+        request = grpc_predict_v2_pb2.ModelInferRequest()
+        request.ParseFromString(request_bytes)
+        response = grpc_predict_v2_pb2.ModelInferResponse(model_name=capability)
+        store_dir = request.parameters["store_dir"].string_param if "store_dir" in request.parameters else ""
+        response.raw_output_contents.append(store_dir.encode())
+        return response.SerializeToString()
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+class TestGetObjectLocal:
+    """Tests for reading this instance's objects without going over gRPC."""
+
+    @pytest.mark.p0
+    def test_reads_by_file_name_and_key(self, temp_dir):
+        """Test that an ObjectStore object is found by its file name or its key."""
+        from anyserve.objects import ObjectStore
+
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            ref = ObjectStore(core.objects_dir).create(b"payload", key="weights")
+            assert core.get_object_local(Path(ref.path).name) == b"payload"
+            assert core.get_object_local("weights") == b"payload"
+
+    @pytest.mark.p1
+    def test_sharded_layout(self, temp_dir):
+        """Test that objects in shard subdirectories are found too."""
+        from anyserve.objects import ObjectStore
+
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            ref = ObjectStore(core.objects_dir, shard=True).create({"a": 1})
+            assert Path(ref.path).parent.name != "objects"
+            assert core.get_object_local(Path(ref.path).name) == b'{"a": 1}'
+            assert core.get_object_local(Path(ref.path).stem) == b'{"a": 1}'
+
+    @pytest.mark.p1
+    def test_dotted_key(self, temp_dir):
+        """Test that a key containing a dot is still found without its suffix."""
+        from anyserve.objects import ObjectStore
+
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            ObjectStore(core.objects_dir).create(b"flat", key="model.v2")
+            ObjectStore(core.objects_dir, shard=True).create(b"sharded", key="model.v3")
+            assert core.get_object_local("model.v2") == b"flat"
+            assert core.get_object_local("model.v3") == b"sharded"
+
+    @pytest.mark.p1
+    def test_missing_returns_none(self, temp_dir):
+        """Test that an absent object yields None, even with no objects directory."""
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            assert core.get_object_local("missing.bin") is None
+            assert core.get_object_local("missing") is None
+
+    @pytest.mark.p1
+    def test_works_without_server(self, temp_dir):
+        """Test that a stopped instance can still read its own objects."""
+        from anyserve.objects import ObjectStore
+
+        core = _core.AnyserveCore(temp_dir, "node", 0, None)
+        core.stop()
+        ObjectStore(core.objects_dir).create(b"offline", key="obj")
+        assert core.get_object_local("obj.bin") == b"offline"
+
+    @pytest.mark.p2
+    @pytest.mark.parametrize("object_id", ["", ".hidden", "../other/objects/x.bin", "a/b"])
+    def test_invalid_id(self, temp_dir, object_id):
+        """Test that ids that could escape the objects directory are rejected."""
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            with pytest.raises(ValueError, match="Invalid object id"):
+                core.get_object_local(object_id)
+
+
+class TestNamedStores:
+    """Tests for selecting among object roots registered with add_store."""
+
+    @pytest.mark.p0
+    def test_reads_from_selected_store(self, temp_dir):
+        """Test that store= reads from the registered root and not from objects_dir."""
+        from anyserve.objects import ObjectStore
+
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            core.add_store("tenant-a", str(Path(temp_dir) / "volumes" / "a"))
+            core.add_store("tenant-b", str(Path(temp_dir) / "volumes" / "b"))
+            ObjectStore(core.store_dir("tenant-a")).create(b"from a", key="weights")
+            ObjectStore(core.store_dir("tenant-b")).create(b"from b", key="weights")
+
+            assert core.get_object_local("weights", store="tenant-a") == b"from a"
+            assert core.get_object_local("weights", store="tenant-b") == b"from b"
+            assert core.get_object_local("weights") is None
+            assert core.store_dir("") == core.objects_dir
+
+    @pytest.mark.p0
+    def test_store_resolved_by_server(self, temp_dir):
+        """Test that remote_call(store=) reaches the peer's dispatcher as that store's root."""
+        peer = _core.AnyserveCore(str(Path(temp_dir) / "peer"), "peer", _free_port(), _StoreDispatcher())
+        client = _core.AnyserveCore(str(Path(temp_dir) / "client"), "client", _free_port(), None)
+        try:
+            volume = str(Path(temp_dir) / "volumes" / "a")
+            peer.add_store("tenant-a", volume)
+            address = peer.get_address()
+
+            assert client.remote_call(address, "echo", b"", False, timeout_secs=5.0,
+                                      store="tenant-a") == volume.encode()
+            assert client.remote_call(address, "echo", b"", False, timeout_secs=5.0) == b""
+            # The store is resolved on the peer, so names only the client knows are rejected there
+            client.add_store("tenant-b", volume)
+            with pytest.raises(_core.TransportError, match="Unknown store"):
+                client.remote_call(address, "echo", b"", False, timeout_secs=5.0, store="tenant-b")
+            # The local fast path resolves against this instance's stores
+            assert peer.remote_call(address, "echo", b"", False, store="tenant-a") == volume.encode()
+            with pytest.raises(ValueError, match="Unknown store"):
+                peer.remote_call(address, "echo", b"", False, store="tenant-b")
+        finally:
+            client.stop()
+            peer.stop()
+
+    @pytest.mark.p1
+    def test_unknown_store(self, temp_dir):
+        """Test that an unregistered store name is rejected."""
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            with pytest.raises(ValueError, match="Unknown store"):
+                core.get_object_local("weights", store="missing")
+            with pytest.raises(ValueError, match="Unknown store"):
+                core.store_dir("missing")
+
+    @pytest.mark.p2
+    def test_add_store_validation(self, temp_dir):
+        """Test that bad names and re-registering a name elsewhere are rejected."""
+        with _core.AnyserveCore(temp_dir, "node", 0, None) as core:
+            core.add_store("a", temp_dir)
+            core.add_store("a", temp_dir)  # same path: no-op
+            with pytest.raises(ValueError, match="already registered"):
+                core.add_store("a", str(Path(temp_dir) / "other"))
+            for name in ("", ".hidden", "a/b"):
+                with pytest.raises(ValueError, match="Invalid store name"):
+                    core.add_store(name, temp_dir)
+            with pytest.raises(ValueError, match="needs a path"):
+                core.add_store("b", "")
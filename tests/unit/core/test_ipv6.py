@@ -0,0 +1,78 @@
+"""
+Unit tests for serving and calling over IPv6 with bind_host.
+"""
+
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+def _has_ipv6_loopback():
+    if not socket.has_ipv6:
+        return False
+    try:
+        with socket.socket(socket.AF_INET6) as s:
+            s.bind(("::1", 0))
+        return True
+    except OSError:
+        return False
+
+
+pytestmark = pytest.mark.skipif(not _has_ipv6_loopback(), reason="no IPv6 loopback")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+class TestBindHost:
+    """Tests for the bind_host constructor argument."""
+
+    @pytest.mark.p0
+    def test_dual_stack_advertises_ipv6_loopback(self, temp_dir):
+        """Test that an instance bound to :: registers [::1] and answers calls there."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher(), bind_host="::") as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            server.register_capability("echo")
+            assert server.bind_host == "[::]"
+            assert server.get_address() == f"[::1]:{server.port}"
+            assert [e["grpc"] for e in client.lookup_capability_endpoints("echo")] == [server.get_address()]
+            assert client.remote_call(server.get_address(), "echo", b"hi", False) == b"hi"
+            assert client.remote_call(f"http://[::1]:{server.port}", "echo", b"v6", False) == b"v6"
+
+    @pytest.mark.p1
+    @pytest.mark.parametrize("bind_host", ["::1", "[::1]"])
+    def test_specific_address(self, temp_dir, bind_host):
+        """Test that an IPv6 literal is bracketed, with or without brackets given."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher(), bind_host=bind_host) as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            server.wait_ready(timeout_secs=5.0)
+            assert server.get_address() == f"[::1]:{server.port}"
+            assert client.remote_call(server.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p1
+    def test_ipv4_loopback_not_self_when_unbound(self, temp_dir):
+        """Test that 127.0.0.1:<port> isn't treated as self when only [::1] is bound."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher(), bind_host="::1") as server:
+            server.wait_ready(timeout_secs=5.0)
+            assert server.remote_call(server.get_address(), "echo", b"hi", False) == b"hi"
+            with pytest.raises(_core.PeerUnreachableError):
+                server.remote_call(f"127.0.0.1:{server.port}", "echo", b"hi", False, timeout_secs=1.0)
+
+    @pytest.mark.p2
+    def test_default_is_ipv4(self, temp_dir):
+        """Test that the default still binds 0.0.0.0 and advertises localhost."""
+        with _core.AnyserveCore(temp_dir, "server", 0, None) as core:
+            assert core.bind_host == "0.0.0.0"
+            assert core.get_address() == f"localhost:{core.port}"
+
+    @pytest.mark.p2
+    @pytest.mark.parametrize("bind_host", ["", "[::1", "::1]", "[]"])
+    def test_invalid(self, temp_dir, bind_host):
+        """Test that an empty or malformed bind_host is rejected."""
+        with pytest.raises(ValueError, match="bind_host"):
+            _core.AnyserveCore(temp_dir, "server", 0, None, bind_host=bind_host)
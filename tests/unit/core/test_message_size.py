@@ -0,0 +1,68 @@
+"""
+Unit tests for the configurable gRPC max message size on AnyserveCore.
+"""
+
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def make_core(temp_dir):
+    """Factory for AnyserveCore instances that are stopped after the test."""
+    cores = []
+
+    def _make(instance_id, dispatcher=None, **kwargs):
+        core = _core.AnyserveCore(temp_dir, instance_id, _free_port(), dispatcher, **kwargs)
+        cores.append(core)
+        return core
+
+    yield _make
+    for core in cores:
+        core.stop()
+
+
+class TestMaxMessageSize:
+    """Tests for max_message_size on the server and remote_call client."""
+
+    @pytest.mark.p1
+    def test_default(self, make_core):
+        """Test that the default limit is 64MB."""
+        core = make_core("a")
+        assert core.max_message_size == 64 * 1024 * 1024
+
+    @pytest.mark.p1
+    def test_payload_above_grpc_default(self, make_core):
+        """Test that a payload larger than gRPC's 4MB default round-trips."""
+        peer = make_core("peer", _Dispatcher())
+        client = make_core("client")
+        payload = b"x" * (8 * 1024 * 1024)
+        assert client.remote_call(peer.get_address(), "echo", payload, False) == payload
+
+    @pytest.mark.p2
+    def test_payload_above_limit(self, make_core):
+        """Test that exceeding a configured limit raises TransportError."""
+        peer = make_core("peer", _Dispatcher(), max_message_size=1024 * 1024)
+        client = make_core("client")
+        with pytest.raises(_core.TransportError):
+            client.remote_call(peer.get_address(), "echo", b"x" * (2 * 1024 * 1024), False)
+
+    @pytest.mark.p2
+    def test_invalid(self, temp_dir):
+        """Test that a non-positive limit is rejected."""
+        with pytest.raises(ValueError):
+            _core.AnyserveCore(temp_dir, "bad", _free_port(), None, max_message_size=0)
@@ -0,0 +1,136 @@
+"""
+Unit tests for AnyserveCore.pick_instance().
+"""
+
+import socket
+import pytest
+from pathlib import Path
+
+_core = pytest.importorskip("anyserve._core")
+
+ENDPOINTS = ["10.0.0.1:8000", "10.0.0.2:8000", "10.0.0.3:8000"]
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def core(temp_dir):
+    """An AnyserveCore whose registry lists three instances of 'decode'."""
+    cap_dir = Path(temp_dir) / "names" / "decode"
+    cap_dir.mkdir(parents=True)
+    for i, address in enumerate(ENDPOINTS):
+        (cap_dir / f"inst-{i}").write_text(address)
+
+    core = _core.AnyserveCore(temp_dir, "picker", _free_port(), None)
+    yield core
+    core.stop()
+
+
+class TestPickInstance:
+    """Tests for pick_instance() selection strategies."""
+
+    @pytest.mark.p1
+    def test_round_robin_cycles(self, core):
+        """Test that round_robin visits every endpoint before repeating."""
+        picks = [core.pick_instance("decode", "round_robin") for _ in range(6)]
+
+        assert sorted(picks[:3]) == ENDPOINTS
+        assert picks[3:] == picks[:3]
+
+    @pytest.mark.p1
+    def test_least_recent(self, core):
+        """Test that least_recent prefers the endpoint picked longest ago."""
+        first = [core.pick_instance("decode", "least_recent") for _ in range(3)]
+        assert sorted(first) == ENDPOINTS
+
+        # The oldest pick comes around again
+        assert core.pick_instance("decode", "least_recent") == first[0]
+
+    @pytest.mark.p1
+    def test_least_recent_forgets_departed(self, core, temp_dir):
+        """Test that an endpoint that leaves and comes back counts as never picked."""
+        first = [core.pick_instance("decode", "least_recent") for _ in range(3)]
+        departed = Path(temp_dir) / "names" / "decode" / f"inst-{ENDPOINTS.index(first[2])}"
+        departed.unlink()
+        assert core.pick_instance("decode", "least_recent") == first[0]
+
+        departed.write_text(first[2])
+        assert core.pick_instance("decode", "least_recent") == first[2]
+
+    @pytest.mark.p1
+    def test_pick_counts_drop_departed(self, core, temp_dir):
+        """Test that pick_counts forgets endpoints that left the registry."""
+        for _ in range(3):
+            core.pick_instance("decode", "round_robin")
+        assert set(core.pick_counts("decode")) == set(ENDPOINTS)
+
+        (Path(temp_dir) / "names" / "decode" / "inst-1").unlink()
+        core.pick_instance("decode", "round_robin")
+        assert set(core.pick_counts("decode")) == {ENDPOINTS[0], ENDPOINTS[2]}
+
+        for path in (Path(temp_dir) / "names" / "decode").iterdir():
+            path.unlink()
+        assert core.pick_instance("decode", "round_robin") is None
+        assert core.pick_counts("decode") == {}
+
+    @pytest.mark.p2
+    def test_random_returns_registered_endpoint(self, core):
+        """Test that random only returns registered endpoints."""
+        for _ in range(10):
+            assert core.pick_instance("decode", "random") in ENDPOINTS
+
+    @pytest.mark.p1
+    def test_unknown_capability_returns_none(self, core):
+        """Test that a capability with no instances yields None."""
+        assert core.pick_instance("missing", "round_robin") is None
+
+    @pytest.mark.p2
+    def test_unknown_strategy(self, core):
+        """Test that an unknown strategy raises ValueError."""
+        with pytest.raises(ValueError):
+            core.pick_instance("decode", "fastest")
+
+    @pytest.mark.p2
+    def test_pick_counts(self, core):
+        """Test that every pick is counted per endpoint, whatever the strategy."""
+        assert core.pick_counts("decode") == {}
+        for _ in range(6):
+            core.pick_instance("decode", "round_robin")
+        core.pick_instance("decode", "random")
+
+        counts = core.pick_counts("decode")
+        assert sum(counts.values()) == 7
+        assert set(counts) <= set(ENDPOINTS)
+        assert all(counts[address] >= 2 for address in ENDPOINTS)
+
+
+class TestPickInstanceLive:
+    """Tests for round_robin over instances that register and stop at runtime."""
+
+    @pytest.mark.p1
+    def test_round_robin_over_live_instances(self, temp_dir):
+        """Test that round_robin cycles every live instance and drops stopped ones."""
+        peers = [_core.AnyserveCore(temp_dir, f"live-{i}", _free_port(), None) for i in range(3)]
+        client = _core.AnyserveCore(temp_dir, "client", _free_port(), None)
+        try:
+            for peer in peers:
+                peer.register_capability("decode")
+            live = sorted(peer.get_address() for peer in peers)
+
+            picks = [client.pick_instance("decode", "round_robin") for _ in range(6)]
+            assert sorted(picks[:3]) == live
+            assert picks[3:] == picks[:3]
+
+            live.remove(peers[1].get_address())
+            peers[1].stop()
+            picks = [client.pick_instance("decode", "round_robin") for _ in range(4)]
+            assert sorted(set(picks)) == live
+            assert picks[2:] == picks[:2]
+        finally:
+            client.stop()
+            for peer in peers:
+                peer.stop()
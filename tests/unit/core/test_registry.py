@@ -0,0 +1,316 @@
+"""
+Unit tests for the filesystem capability registry in the C++ core.
+"""
+
+import os
+import socket
+import subprocess
+import pytest
+from pathlib import Path
+
+_core = pytest.importorskip("anyserve._core")
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def core(temp_dir):
+    core = _core.AnyserveCore(temp_dir, "registry-test", _free_port(), None)
+    yield core
+    core.stop()
+
+
+class TestRegistry:
+    """Tests for register_capability() / lookup_capability()."""
+
+    @pytest.mark.p0
+    def test_register_then_lookup(self, core):
+        """Test that a registered capability resolves to this instance."""
+        core.register_capability("decode")
+
+        assert core.lookup_capability("decode") == [core.get_address()]
+
+    @pytest.mark.p1
+    def test_register_leaves_no_temp_files(self, core, temp_dir):
+        """Test that registration renames its temp file into place."""
+        core.register_capability("decode")
+
+        entries = [p.name for p in (Path(temp_dir) / "names" / "decode").iterdir()]
+        assert entries == ["registry-test"]
+
+    @pytest.mark.p0
+    def test_lookup_skips_empty_entry(self, core, temp_dir):
+        """Test that a zero-byte entry (crash mid-write) is ignored."""
+        core.register_capability("decode")
+        (Path(temp_dir) / "names" / "decode" / "crashed-instance").write_bytes(b"")
+
+        assert core.lookup_capability("decode") == [core.get_address()]
+
+    @pytest.mark.p1
+    def test_stop_removes_registrations(self, temp_dir):
+        """Test that stop() removes the instance record and every capability entry."""
+        core = _core.AnyserveCore(temp_dir, "leaving", _free_port(), None)
+        core.register_capability("decode")
+        core.register_capability("embed")
+        assert (Path(temp_dir) / "instances" / "leaving" / "address").read_text() == core.get_address()
+
+        core.stop()
+
+        assert not (Path(temp_dir) / "instances" / "leaving").exists()
+        assert core.lookup_capability("decode") == []
+        assert core.lookup_capability("embed") == []
+
+    @pytest.mark.p1
+    def test_lookup_skips_in_flight_temp_entry(self, core, temp_dir):
+        """Test that hidden temp entries are never returned."""
+        cap_dir = Path(temp_dir) / "names" / "embed"
+        cap_dir.mkdir(parents=True)
+        (cap_dir / ".other.tmp.123").write_text("10.0.0.9:8000")
+
+        assert core.lookup_capability("embed") == []
+
+
+class TestRegistryEndpoints:
+    """Tests for lookup_capability_endpoints()."""
+
+    @pytest.mark.p0
+    def test_endpoints_without_uds(self, core):
+        """Test that an instance without a Unix socket reports an empty uds."""
+        core.register_capability("decode")
+
+        assert core.lookup_capability_endpoints("decode") == [
+            {"grpc": core.get_address(), "uds": ""}
+        ]
+
+    @pytest.mark.p0
+    def test_endpoints_include_uds(self, temp_dir):
+        """Test that both data-plane addresses round-trip through the registry."""
+        path = str(Path(temp_dir) / "core.sock")
+        core = _core.AnyserveCore(temp_dir, "with-uds", _free_port(), None, uds_path=path)
+        try:
+            core.register_capability("decode")
+
+            assert core.lookup_capability_endpoints("decode") == [
+                {"grpc": core.get_address(), "uds": f"unix:{path}"}
+            ]
+            # Readers that only take the first line still see the gRPC address
+            assert core.lookup_capability("decode") == [core.get_address()]
+        finally:
+            core.stop()
+
+    @pytest.mark.p1
+    def test_endpoints_accept_legacy_entry(self, core, temp_dir):
+        """Test that a single-line entry written by an older instance still resolves."""
+        cap_dir = Path(temp_dir) / "names" / "embed"
+        cap_dir.mkdir(parents=True)
+        (cap_dir / "old-instance").write_text("10.0.0.9:8000")
+
+        assert core.lookup_capability_endpoints("embed") == [
+            {"grpc": "10.0.0.9:8000", "uds": ""}
+        ]
+
+
+@pytest.mark.skipif(os.name != "posix", reason="Unix permission bits")
+class TestRegistryPermissions:
+    """Tests for the modes of registry paths the core creates."""
+
+    @staticmethod
+    def _mode(path):
+        return os.stat(path).st_mode & 0o777
+
+    @pytest.mark.p0
+    def test_default_modes(self, temp_dir):
+        """Test that the registry is private to the current user by default."""
+        root = os.path.join(temp_dir, "root")
+        core = _core.AnyserveCore(root, "perm-test", _free_port(), None)
+        try:
+            core.register_capability("decode")
+
+            assert self._mode(root) == 0o700
+            assert self._mode(os.path.join(root, "names", "decode")) == 0o700
+            assert self._mode(os.path.join(root, "names", "decode", "perm-test")) == 0o600
+        finally:
+            core.stop()
+
+    @pytest.mark.p1
+    def test_modes_from_env(self, temp_dir, monkeypatch):
+        """Test that ANYSERVE_DIR_MODE / ANYSERVE_FILE_MODE override the defaults."""
+        monkeypatch.setenv("ANYSERVE_DIR_MODE", "0750")
+        monkeypatch.setenv("ANYSERVE_FILE_MODE", "0640")
+        root = os.path.join(temp_dir, "root")
+        core = _core.AnyserveCore(root, "perm-test", _free_port(), None)
+        try:
+            core.register_capability("decode")
+
+            assert self._mode(os.path.join(root, "names")) == 0o750
+            assert self._mode(os.path.join(root, "names", "decode", "perm-test")) == 0o640
+        finally:
+            core.stop()
+
+    @pytest.mark.p1
+    def test_invalid_mode_rejected(self, temp_dir, monkeypatch):
+        """Test that a non-octal mode fails construction."""
+        monkeypatch.setenv("ANYSERVE_DIR_MODE", "rwx")
+
+        with pytest.raises(ValueError, match="ANYSERVE_DIR_MODE"):
+            _core.AnyserveCore(temp_dir, "perm-test", _free_port(), None)
+
+
+class TestRegistryNamespace:
+    """Tests for tenant namespaces sharing one root_dir."""
+
+    @pytest.mark.p0
+    def test_namespaces_are_isolated(self, temp_dir):
+        """Test that the same capability in two namespaces resolves separately."""
+        a = _core.AnyserveCore(temp_dir, "same-id", _free_port(), None, namespace="team-a")
+        b = _core.AnyserveCore(temp_dir, "same-id", _free_port(), None, namespace="team-b")
+        try:
+            a.register_capability("decode")
+            b.register_capability("decode")
+
+            assert a.lookup_capability("decode") == [a.get_address()]
+            assert b.lookup_capability("decode") == [b.get_address()]
+            assert (Path(temp_dir) / "ns" / "team-a" / "names" / "decode" / "same-id").is_file()
+        finally:
+            a.stop()
+            b.stop()
+
+    @pytest.mark.p0
+    def test_default_layout_unchanged(self, core, temp_dir):
+        """Test that without a namespace the registry stays at root_dir/names."""
+        core.register_capability("decode")
+
+        assert core.namespace == ""
+        assert (Path(temp_dir) / "names" / "decode" / "registry-test").is_file()
+        assert core.objects_dir == str(Path(temp_dir) / "instances" / "registry-test" / "objects")
+
+    @pytest.mark.p1
+    def test_object_federation_scoped(self, temp_dir):
+        """Test that federated object stores only see instances in their namespace."""
+        from anyserve.objects import ObjectStore
+
+        a = _core.AnyserveCore(temp_dir, "writer", _free_port(), None, namespace="team-a")
+        b = _core.AnyserveCore(temp_dir, "reader", _free_port(), None, namespace="team-b")
+        try:
+            obj_ref = ObjectStore(a.objects_dir).create(b"secret", key="shared-key")
+            reader = ObjectStore(b.objects_dir, federated=True)
+
+            assert not reader.exists(str(Path(b.objects_dir) / Path(obj_ref.path).name))
+        finally:
+            a.stop()
+            b.stop()
+
+    @pytest.mark.p1
+    def test_invalid_namespace(self, temp_dir):
+        """Test that namespaces that could escape root_dir are rejected."""
+        for ns in ("../other", "a/b", ".hidden"):
+            with pytest.raises(ValueError):
+                _core.AnyserveCore(temp_dir, "bad", _free_port(), None, namespace=ns)
+
+
+
+class TestRegistryDuplicateInstance:
+    """Tests that a live instance_id can't be registered twice."""
+
+    @pytest.mark.p0
+    def test_duplicate_rejected(self, core, temp_dir):
+        """Test that a second core with a live instance_id fails and leaves the first intact."""
+        with pytest.raises(_core.DuplicateInstanceError, match="registry-test"):
+            _core.AnyserveCore(temp_dir, "registry-test", _free_port(), None)
+
+        address_file = Path(temp_dir) / "instances" / "registry-test" / "address"
+        assert address_file.read_text() == core.get_address()
+
+    @pytest.mark.p1
+    def test_force_takes_over(self, core, temp_dir):
+        """Test that force=True starts anyway and overwrites the registration."""
+        other = _core.AnyserveCore(temp_dir, "registry-test", _free_port(), None, force=True)
+        try:
+            address_file = Path(temp_dir) / "instances" / "registry-test" / "address"
+            assert address_file.read_text() == other.get_address()
+        finally:
+            other.stop()
+
+    @pytest.mark.p1
+    def test_stale_registration_ignored(self, temp_dir):
+        """Test that a record left by a dead process doesn't block a restart."""
+        proc = subprocess.Popen(["true"])
+        proc.wait()
+        instance_dir = Path(temp_dir) / "instances" / "crashed"
+        instance_dir.mkdir(parents=True)
+        (instance_dir / "address").write_text("localhost:1")
+        (instance_dir / "owner").write_text(f"host={socket.gethostname()}\npid={proc.pid}\n")
+
+        core = _core.AnyserveCore(temp_dir, "crashed", _free_port(), None)
+        try:
+            assert (instance_dir / "address").read_text() == core.get_address()
+        finally:
+            core.stop()
+
+    @pytest.mark.p2
+    def test_other_host_treated_as_live(self, temp_dir):
+        """Test that a record from another host blocks, since its pid can't be checked."""
+        instance_dir = Path(temp_dir) / "instances" / "remote"
+        instance_dir.mkdir(parents=True)
+        (instance_dir / "address").write_text("otherhost:50051")
+        (instance_dir / "owner").write_text("host=not-this-host.invalid\npid=1\n")
+
+        with pytest.raises(_core.DuplicateInstanceError, match="not-this-host.invalid"):
+            _core.AnyserveCore(temp_dir, "remote", _free_port(), None)
+
+BAD_NAMES = ("", ".", "..", "../escape", "a/b", "/abs", "..\\up", ".hidden", "tab\tname",
+             "x" * 256)
+
+
+def _tree(root):
+    return sorted(str(p.relative_to(root)) for p in Path(root).rglob("*"))
+
+
+class TestRegistryNameValidation:
+    """Tests that capability names can't escape the names/ directory."""
+
+    @pytest.mark.p0
+    def test_register_rejects_bad_names(self, core, temp_dir):
+        """Test that malicious names are refused before anything is written."""
+        before = _tree(temp_dir)
+        for name in BAD_NAMES:
+            with pytest.raises(ValueError):
+                core.register_capability(name)
+
+        assert _tree(temp_dir) == before
+        assert not (Path(temp_dir) / "names").exists()
+        assert not (Path(temp_dir) / "escape").exists()
+
+    @pytest.mark.p1
+    def test_lookup_rejects_bad_names(self, core):
+        """Test that lookup and pick_instance refuse the same names."""
+        for name in BAD_NAMES:
+            with pytest.raises(ValueError):
+                core.lookup_capability(name)
+            with pytest.raises(ValueError):
+                core.pick_instance(name)
+
+    @pytest.mark.p1
+    def test_ordinary_names_accepted(self, core):
+        """Test that dotted, dashed and unicode names still register."""
+        for name in ("decode.v2", "llm-chat_1", "trailing..", "模型"):
+            core.register_capability(name)
+            assert core.lookup_capability(name) == [core.get_address()]
+
+    @pytest.mark.p2
+    def test_rejected_name_not_unregistered(self, temp_dir):
+        """Test that a refused name isn't remembered and touched again on stop()."""
+        root = Path(temp_dir) / "root"
+        victim = Path(temp_dir) / "victim"
+        victim.mkdir()
+        (victim / "registry-test").write_text("keep")
+        core = _core.AnyserveCore(str(root), "registry-test", _free_port(), None)
+        with pytest.raises(ValueError):
+            core.register_capability("../../victim")
+        core.stop()
+
+        assert (victim / "registry-test").read_text() == "keep"
@@ -0,0 +1,128 @@
+"""
+Unit tests for remote_call exception mapping in the C++ core.
+"""
+
+import errno
+import socket
+import time
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Minimal dispatcher: echoes for 'echo', sleeps for 'slow', raises KeyError for unknown capabilities."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        if capability == "echo":
+            return args_pickle
+        if capability == "slow":
+            time.sleep(2)
+            return b""
+        if capability == "broken":
+            raise ValueError("boom")
+        if capability == "full":
+            raise OSError(errno.EDQUOT, "tenant over quota")
+        raise KeyError(capability)
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def peer(temp_dir):
+    """A live AnyserveCore peer."""
+    core = _core.AnyserveCore(temp_dir, "peer", _free_port(), _Dispatcher())
+    yield core
+    core.stop()
+
+
+@pytest.fixture
+def client(temp_dir):
+    """An AnyserveCore used only to issue remote calls."""
+    core = _core.AnyserveCore(temp_dir, "client", _free_port(), None)
+    yield core
+    core.stop()
+
+
+class TestRemoteCallErrors:
+    """Tests that each remote_call failure mode raises its own exception type."""
+
+    @pytest.mark.p1
+    def test_hierarchy(self):
+        """Test that every error derives from AnyserveError and its builtin."""
+        assert issubclass(_core.ObjectNotFoundError, _core.AnyserveError)
+        assert issubclass(_core.ObjectNotFoundError, KeyError)
+        assert issubclass(_core.PeerUnreachableError, ConnectionError)
+        assert issubclass(_core.RemoteTimeoutError, TimeoutError)
+        assert issubclass(_core.TransportError, _core.AnyserveError)
+
+    @pytest.mark.p1
+    def test_peer_down_raises_unreachable(self, client):
+        """Test that calling a closed port raises PeerUnreachableError."""
+        with pytest.raises(_core.PeerUnreachableError):
+            client.remote_call(f"127.0.0.1:{_free_port()}", "echo", b"", False, timeout_secs=1.0)
+
+    @pytest.mark.p1
+    def test_missing_capability_raises_not_found(self, client, peer):
+        """Test that a capability the peer doesn't serve raises ObjectNotFoundError."""
+        with pytest.raises(_core.ObjectNotFoundError):
+            client.remote_call(peer.get_address(), "missing", b"", False, timeout_secs=5.0)
+
+    @pytest.mark.p1
+    def test_self_call_after_stop_unreachable(self, peer):
+        """Test that a stopped instance doesn't keep answering calls to its own address."""
+        address = peer.get_address()
+        assert isinstance(peer.remote_call(address, "echo", b"", False, timeout_secs=5.0), bytes)
+        peer.stop()
+        with pytest.raises(_core.PeerUnreachableError):
+            peer.remote_call(address, "echo", b"", False, timeout_secs=1.0)
+
+    @pytest.mark.p1
+    def test_slow_peer_raises_timeout(self, client, peer):
+        """Test that a live but slow peer raises RemoteTimeoutError, not unreachable."""
+        # Warm the connection so the failure is attributed to the call itself
+        with pytest.raises(_core.ObjectNotFoundError):
+            client.remote_call(peer.get_address(), "missing", b"", False, timeout_secs=5.0)
+
+        with pytest.raises(_core.RemoteTimeoutError):
+            client.remote_call(peer.get_address(), "slow", b"", False, timeout_secs=0.5)
+        # The connection itself is fine, so it stays pooled for the next call
+        assert len(client.connected_peers()) == 1
+
+    @pytest.mark.p2
+    def test_peer_error_raises_transport(self, client, peer):
+        """Test that other server-side failures raise TransportError."""
+        with pytest.raises(_core.TransportError):
+            client.remote_call(peer.get_address(), "broken", b"", False, timeout_secs=5.0)
+
+    @pytest.mark.p2
+    def test_out_of_space_is_resource_exhausted(self, client, peer):
+        """Test that a full store or exhausted quota on the peer comes back as RESOURCE_EXHAUSTED."""
+        with pytest.raises(_core.TransportError, match=r"code 8\).*over quota"):
+            client.remote_call(peer.get_address(), "full", b"", False, timeout_secs=5.0)
+
+
+class TestTryRemoteCall:
+    """Tests for try_remote_call, which reports not-found as None."""
+
+    @pytest.mark.p0
+    def test_missing_returns_none(self, client, peer):
+        """Test that a capability the peer doesn't serve yields None instead of raising."""
+        assert client.try_remote_call(peer.get_address(), "missing", b"", False, timeout_secs=5.0) is None
+
+    @pytest.mark.p1
+    def test_found_returns_bytes(self, client, peer):
+        """Test that a successful call returns the result like remote_call."""
+        assert client.try_remote_call(peer.get_address(), "echo", b"hi", False, timeout_secs=5.0) == b"hi"
+
+    @pytest.mark.p1
+    def test_other_failures_still_raise(self, client, peer):
+        """Test that unreachable peers and server-side errors are not turned into None."""
+        with pytest.raises(_core.PeerUnreachableError):
+            client.try_remote_call(f"127.0.0.1:{_free_port()}", "echo", b"", False, timeout_secs=1.0)
+        with pytest.raises(_core.TransportError):
+            client.try_remote_call(peer.get_address(), "broken", b"", False, timeout_secs=5.0)
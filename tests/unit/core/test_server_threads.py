@@ -0,0 +1,62 @@
+"""
+Unit tests for capping the gRPC server's threads with server_threads.
+"""
+
+import os
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+def _thread_count():
+    return len(os.listdir("/proc/self/task"))
+
+
+class TestServerThreads:
+    """Tests for the server_threads constructor argument."""
+
+    @pytest.mark.p0
+    def test_capped_instance_serves_calls(self, temp_dir):
+        """Test that an instance with a thread cap still answers remote calls."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher(), server_threads=2) as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            assert server.server_threads == 2
+            for _ in range(5):
+                assert client.remote_call(server.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p1
+    @pytest.mark.skipif(not os.path.isdir("/proc/self/task"), reason="needs /proc")
+    def test_many_instances_bounded(self, temp_dir):
+        """Test that each capped instance adds only a few threads to the process."""
+        # gRPC's process-wide threads start with the first server
+        first = _core.AnyserveCore(temp_dir, "first", 0, None, server_threads=2)
+        cores = [first]
+        try:
+            before = _thread_count()
+            for i in range(8):
+                cores.append(_core.AnyserveCore(temp_dir, f"core-{i}", 0, None, server_threads=2))
+            # server_threads gRPC threads plus the thread blocked in Server::Wait
+            assert _thread_count() - before <= 8 * 3
+        finally:
+            for core in cores:
+                core.stop()
+
+    @pytest.mark.p2
+    def test_default_is_unlimited(self, temp_dir):
+        """Test that server_threads defaults to 0 (gRPC's own sizing)."""
+        with _core.AnyserveCore(temp_dir, "server", 0, None) as core:
+            assert core.server_threads == 0
+
+    @pytest.mark.p2
+    @pytest.mark.parametrize("server_threads", [-1, 1])
+    def test_invalid(self, temp_dir, server_threads):
+        """Test that a negative cap, or one too small to both poll and serve, is rejected."""
+        with pytest.raises(ValueError, match="server_threads"):
+            _core.AnyserveCore(temp_dir, "server", 0, None, server_threads=server_threads)
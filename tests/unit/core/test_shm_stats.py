@@ -0,0 +1,51 @@
+"""
+Unit tests for AnyserveCore.shm_stats().
+"""
+
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+class TestShmStats:
+    """Tests for SHM segment introspection."""
+
+    @pytest.mark.p1
+    def test_reports_both_segments(self, temp_dir):
+        """Test that both H2D and D2H segments are reported with their size."""
+        core = _core.AnyserveCore(temp_dir, "shm", _free_port(), None)
+        try:
+            stats = core.shm_stats()
+            assert set(stats) == {"h2d", "d2h"}
+            for segment in stats.values():
+                assert segment["size"] == 10 * 1024 * 1024
+                assert segment["fd"] >= 0
+                assert segment["locked"] is False
+                # Nothing in the core writes to SHM yet
+                assert segment["high_water"] == 0
+                assert segment["wraps"] == 0
+                assert segment["bytes_moved"] == 0
+            assert stats["h2d"]["name"] != stats["d2h"]["name"]
+        finally:
+            core.stop()
+
+    @pytest.mark.p2
+    def test_segment_names_are_wide(self, temp_dir):
+        """Test that names carry the pid and 64 random bits within the macOS name limit."""
+        import os
+        import re
+
+        core = _core.AnyserveCore(temp_dir, "shm", _free_port(), None)
+        try:
+            for segment in core.shm_stats().values():
+                assert re.fullmatch(rf"/as_{os.getpid()}_[0-9a-f]{{16}}", segment["name"])
+                assert len(segment["name"]) <= 30
+        finally:
+            core.stop()
@@ -0,0 +1,74 @@
+"""
+Unit tests for stopping AnyserveCore and releasing its resources.
+"""
+
+import os
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+def _thread_count():
+    return len(os.listdir("/proc/self/task"))
+
+
+class TestShutdown:
+    """Tests for stop() and the context-manager form."""
+
+    @pytest.mark.p0
+    def test_stop_releases_port(self, temp_dir):
+        """Test that the port can be bound again right after stop()."""
+        port = _free_port()
+        core = _core.AnyserveCore(temp_dir, "stopper", port, None)
+        core.stop()
+
+        assert not core.is_running
+        with socket.socket() as s:
+            s.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+            s.bind(("0.0.0.0", port))
+
+    @pytest.mark.p0
+    def test_with_block_stops(self, temp_dir):
+        """Test that leaving a with-block stops the core, also on error."""
+        with _core.AnyserveCore(temp_dir, "ctx", 0, None) as core:
+            assert core.is_running
+        assert not core.is_running
+
+        with pytest.raises(RuntimeError):
+            with _core.AnyserveCore(temp_dir, "ctx-err", 0, None) as failing:
+                raise RuntimeError("boom")
+        assert not failing.is_running
+
+    @pytest.mark.p1
+    def test_many_cores_do_not_leak(self, temp_dir):
+        """Test that creating and stopping many cores on one port leaks no threads or ports."""
+        if not os.path.isdir("/proc/self/task"):
+            pytest.skip("needs /proc to count threads")
+        port = _free_port()
+
+        # Warm up gRPC's own process-wide threads before taking the baseline
+        with _core.AnyserveCore(temp_dir, "warmup", port, None):
+            pass
+        baseline = _thread_count()
+
+        for i in range(20):
+            with _core.AnyserveCore(temp_dir, f"core-{i}", port, None) as core:
+                assert core.port == port
+
+        # gRPC may keep a few pooled threads around; per-core threads must not accumulate
+        assert _thread_count() <= baseline + 4
+
+    @pytest.mark.p2
+    def test_stop_is_idempotent(self, temp_dir):
+        """Test that stop() can be called repeatedly."""
+        core = _core.AnyserveCore(temp_dir, "twice", 0, None)
+        core.stop()
+        core.stop()
+        assert not core.is_running
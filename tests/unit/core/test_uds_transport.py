@@ -0,0 +1,83 @@
+"""
+Unit tests for the optional Unix domain socket listener on AnyserveCore.
+"""
+
+import os
+import socket
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back for 'echo', KeyError otherwise."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        if capability == "echo":
+            return args_pickle
+        raise KeyError(capability)
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+@pytest.fixture
+def uds_peer(temp_dir):
+    """A peer listening on both TCP and a Unix socket."""
+    path = os.path.join(temp_dir, "peer.sock")
+    core = _core.AnyserveCore(temp_dir, "peer", _free_port(), _Dispatcher(), uds_path=path)
+    yield core, path
+    core.stop()
+
+
+@pytest.fixture
+def client(temp_dir):
+    """An AnyserveCore used only to issue remote calls."""
+    core = _core.AnyserveCore(temp_dir, "client", _free_port(), None)
+    yield core
+    core.stop()
+
+
+class TestUdsTransport:
+    """Tests for serving and calling over unix: addresses."""
+
+    @pytest.mark.p1
+    def test_uds_address(self, uds_peer):
+        """Test that uds_address reports the unix: address and the socket exists."""
+        core, path = uds_peer
+        assert core.uds_address == f"unix:{path}"
+        assert os.path.exists(path)
+
+    @pytest.mark.p1
+    def test_remote_call_over_uds(self, client, uds_peer):
+        """Test that a remote call over unix: reaches the peer."""
+        _, path = uds_peer
+        assert client.remote_call(f"unix:{path}", "echo", b"hello", False) == b"hello"
+
+    @pytest.mark.p2
+    def test_triple_slash_scheme(self, client, uds_peer):
+        """Test that unix:///path is accepted as well."""
+        _, path = uds_peer
+        assert client.remote_call(f"unix://{path}", "echo", b"x", False) == b"x"
+
+    @pytest.mark.p2
+    def test_relative_path_rejected(self, client):
+        """Test that a relative unix: path raises ValueError."""
+        with pytest.raises(ValueError):
+            client.remote_call("unix:relative.sock", "echo", b"", False)
+
+    @pytest.mark.p2
+    def test_disabled_by_default(self, client):
+        """Test that no Unix socket is served unless uds_path is given."""
+        assert client.uds_address == ""
+
+    @pytest.mark.p2
+    def test_socket_removed_on_stop(self, temp_dir):
+        """Test that stop() removes the socket file."""
+        path = os.path.join(temp_dir, "gone.sock")
+        core = _core.AnyserveCore(temp_dir, "gone", _free_port(), _Dispatcher(), uds_path=path)
+        core.stop()
+        assert not os.path.exists(path)
@@ -0,0 +1,49 @@
+"""
+Unit tests for AnyserveCore.wait_ready.
+"""
+
+import pytest
+
+_core = pytest.importorskip("anyserve._core")
+
+
+class _Dispatcher:
+    """Echoes the argument bytes back."""
+
+    def dispatch(self, capability, args_pickle, is_delegated):
+        return args_pickle
+
+
+class TestWaitReady:
+    """Tests for waiting on the gRPC server before the first call."""
+
+    @pytest.mark.p0
+    def test_call_right_after_ready(self, temp_dir):
+        """Test that a fresh instance answers as soon as wait_ready returns."""
+        with _core.AnyserveCore(temp_dir, "server", 0, _Dispatcher()) as server, \
+                _core.AnyserveCore(temp_dir, "client", 0, None) as client:
+            server.wait_ready(timeout_secs=5.0)
+            assert client.remote_call(server.get_address(), "echo", b"hi", False) == b"hi"
+
+    @pytest.mark.p1
+    def test_repeated_calls(self, temp_dir):
+        """Test that wait_ready returns immediately once the server is up."""
+        with _core.AnyserveCore(temp_dir, "server", 0, None) as core:
+            core.wait_ready()
+            core.wait_ready(timeout_secs=0.5)
+            assert core.connected_peers() == []
+
+    @pytest.mark.p1
+    def test_stopped_instance(self, temp_dir):
+        """Test that waiting on a stopped instance raises instead of timing out."""
+        core = _core.AnyserveCore(temp_dir, "server", 0, None)
+        core.stop()
+        with pytest.raises(RuntimeError, match="not running"):
+            core.wait_ready(timeout_secs=0.5)
+
+    @pytest.mark.p2
+    def test_negative_timeout(self, temp_dir):
+        """Test that a negative timeout is rejected."""
+        with _core.AnyserveCore(temp_dir, "server", 0, None) as core:
+            with pytest.raises(ValueError):
+                core.wait_ready(timeout_secs=-1)
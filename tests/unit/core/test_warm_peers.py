@@ -0,0 +1,140 @@
+"""
+Unit tests for pre-connecting to known peers at startup (warm_peers) and to
+a capability's instances on demand (warm_capability), and the default port
+used to normalize host-only addresses.
+"""
+
+import socket
+import time
+import pytest
+from pathlib import Path
+
+_core = pytest.importorskip("anyserve._core")
+
+
+def _free_port():
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        return s.getsockname()[1]
+
+
+def _wait_for(predicate, timeout=5.0):
+    deadline = time.monotonic() + timeout
+    while time.monotonic() < deadline:
+        if predicate():
+            return True
+        time.sleep(0.05)
+    return predicate()
+
+
+@pytest.fixture
+def peer(temp_dir):
+    """A live AnyserveCore peer."""
+    core = _core.AnyserveCore(temp_dir, "peer", _free_port(), None)
+    yield core
+    core.stop()
+
+
+class TestWarmPeers:
+    """Tests for the warm_peers constructor argument."""
+
+    @pytest.mark.p1
+    def test_live_peer_connected(self, temp_dir, peer):
+        """Test that a live peer shows up in connected_peers() without any call."""
+        peer_port = peer.get_address().rsplit(":", 1)[1]
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None,
+                                warm_peers=[f"127.0.0.1:{peer_port}"]) as client:
+            assert _wait_for(lambda: f"127.0.0.1:{peer_port}" in client.connected_peers())
+
+    @pytest.mark.p1
+    def test_dead_peer_does_not_block_start(self, temp_dir, peer):
+        """Test that an unreachable peer is skipped and calls to it still fail normally."""
+        peer_port = peer.get_address().rsplit(":", 1)[1]
+        dead = f"127.0.0.1:{_free_port()}"
+        start = time.monotonic()
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None,
+                                warm_peers=[dead, f"127.0.0.1:{peer_port}"]) as client:
+            # Startup doesn't wait for pre-connection
+            assert time.monotonic() - start < 2.0
+            assert _wait_for(lambda: f"127.0.0.1:{peer_port}" in client.connected_peers())
+            assert dead not in client.connected_peers()
+
+            with pytest.raises(_core.PeerUnreachableError):
+                client.remote_call(dead, "echo", b"", False, timeout_secs=1.0)
+
+    @pytest.mark.p2
+    def test_invalid_address_rejected(self, temp_dir):
+        """Test that a malformed peer address fails construction."""
+        with pytest.raises(ValueError):
+            _core.AnyserveCore(temp_dir, "client", _free_port(), None, warm_peers=["host:notaport"])
+
+    @pytest.mark.p2
+    def test_no_peers_by_default(self, temp_dir):
+        """Test that nothing is connected before the first call when warm_peers is unset."""
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None) as client:
+            assert client.connected_peers() == []
+
+
+class TestWarmCapability:
+    """Tests for warm_capability()."""
+
+    @pytest.mark.p0
+    def test_prunes_unreachable_instances(self, temp_dir, peer):
+        """Test that of one live and one dead instance only the live one is returned and pooled."""
+        peer.register_capability("decode")
+        dead = f"127.0.0.1:{_free_port()}"
+        (Path(temp_dir) / "names" / "decode" / "dead-instance").write_text(dead)
+
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None) as client:
+            assert sorted(client.lookup_capability("decode")) == sorted([peer.get_address(), dead])
+
+            start = time.monotonic()
+            assert client.warm_capability("decode", timeout_secs=1.0) == [peer.get_address()]
+            assert time.monotonic() - start < 3.0
+
+            peer_port = peer.get_address().rsplit(":", 1)[1]
+            assert client.connected_peers() == [f"127.0.0.1:{peer_port}"]
+
+    @pytest.mark.p1
+    def test_self_is_reachable(self, temp_dir):
+        """Test that the calling instance's own registration counts as reachable."""
+        with _core.AnyserveCore(temp_dir, "self", _free_port(), None) as core:
+            core.register_capability("decode")
+            assert core.warm_capability("decode") == [core.get_address()]
+            assert core.connected_peers() == []
+
+    @pytest.mark.p2
+    def test_unknown_capability_and_bad_timeout(self, temp_dir):
+        """Test that a capability nobody serves yields [] and a negative timeout is rejected."""
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None) as client:
+            assert client.warm_capability("nobody") == []
+            with pytest.raises(ValueError):
+                client.warm_capability("nobody", timeout_secs=-1)
+
+
+class TestDefaultRemotePort:
+    """Tests for the default_remote_port constructor argument and property."""
+
+    @pytest.mark.p1
+    def test_applies_to_warm_peers(self, temp_dir, peer):
+        """Test that a host-only warm peer is normalized with the constructor's default port."""
+        peer_port = int(peer.get_address().rsplit(":", 1)[1])
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None, warm_peers=["127.0.0.1"],
+                                default_remote_port=peer_port) as client:
+            assert client.default_remote_port == peer_port
+            assert _wait_for(lambda: f"127.0.0.1:{peer_port}" in client.connected_peers())
+
+    @pytest.mark.p2
+    @pytest.mark.parametrize("port", [0, -1, 65536])
+    def test_out_of_range_rejected(self, temp_dir, port):
+        """Test that ports outside 1-65535 fail construction and leave the property unchanged."""
+        with pytest.raises(ValueError, match="1-65535"):
+            _core.AnyserveCore(temp_dir, "client", _free_port(), None, default_remote_port=port)
+
+        with _core.AnyserveCore(temp_dir, "client", _free_port(), None) as client:
+            assert client.default_remote_port == 8000
+            with pytest.raises(ValueError, match="1-65535"):
+                client.default_remote_port = port
+            assert client.default_remote_port == 8000
+            client.default_remote_port = 65535
+            assert client.default_remote_port == 65535
@@ -21,6 +21,17 @@ class TestContextObjects:
         data = ctx.objects.get(obj_ref)
         assert data == {"test": "data"}
 
+    @pytest.mark.p1
+    def test_context_objects_in_memory(self):
+        """Test handlers work the same with an in-memory backend."""
+        from anyserve.objects import open_object_store
+        ctx = Context(objects=open_object_store("memory://"))
+
+        obj_ref = ctx.objects.create({"test": "data"})
+
+        assert ctx.objects.get(obj_ref.to_string()) == {"test": "data"}
+        assert [o.key for o in ctx.objects.list_objects()] == [obj_ref.key]
+
     @pytest.mark.p1
     def test_context_objects_not_configured(self):
         """Test accessing objects when not configured raises error."""
@@ -0,0 +1,225 @@
+"""
+Unit tests for KServe REST framing and the binary tensor data extension.
+"""
+
+import json
+import struct
+
+import pytest
+
+from anyserve.kserve import ModelInferRequest, ModelInferResponse
+from anyserve.kserve_http import (
+    decode_infer_request,
+    decode_infer_response,
+    encode_infer_request,
+    encode_infer_response,
+)
+
+
+def _binary_body(header: dict, *chunks: bytes):
+    header_bytes = json.dumps(header).encode()
+    return header_bytes + b"".join(chunks), len(header_bytes)
+
+
+class TestDecodeRequest:
+    """Tests for decode_infer_request()."""
+
+    @pytest.mark.p0
+    def test_binary_inputs_become_raw_contents(self):
+        """Test that binary tensors land in raw_input_contents, in order."""
+        image = bytes(range(256)) * 4
+        ids = struct.pack("<3q", 1, 2, 3)
+        body, length = _binary_body({
+            "id": "r1",
+            "inputs": [
+                {"name": "image", "datatype": "UINT8", "shape": [1024],
+                 "parameters": {"binary_data_size": len(image)}},
+                {"name": "ids", "datatype": "INT64", "shape": [3],
+                 "parameters": {"binary_data_size": len(ids)}},
+            ],
+        }, image, ids)
+
+        request = decode_infer_request(body, str(length), "vision")
+
+        assert request.model_name == "vision"
+        assert request.id == "r1"
+        assert [t.name for t in request.inputs] == ["image", "ids"]
+        assert request.raw_input_contents == [image, ids]
+        assert request.inputs[0].parameters == {}
+
+    @pytest.mark.p1
+    def test_json_input_mixed_with_binary_is_serialized(self):
+        """Test that JSON data next to binary data is converted to the raw layout."""
+        blob = b"\x00" * 8
+        body, length = _binary_body({
+            "inputs": [
+                {"name": "scale", "datatype": "FP32", "shape": [2], "data": [0.5, 2.0]},
+                {"name": "prompt", "datatype": "BYTES", "shape": [1], "data": ["hi"]},
+                {"name": "blob", "datatype": "UINT8", "shape": [8],
+                 "parameters": {"binary_data_size": 8}},
+            ],
+        }, blob)
+
+        request = decode_infer_request(body, length, "m")
+
+        assert request.raw_input_contents == [
+            struct.pack("<2f", 0.5, 2.0),
+            struct.pack("<I", 2) + b"hi",
+            blob,
+        ]
+
+    @pytest.mark.p1
+    def test_plain_json_keeps_typed_contents(self):
+        """Test that a request without binary data keeps typed contents."""
+        body = json.dumps({"inputs": [
+            {"name": "x", "datatype": "INT32", "shape": [2, 2], "data": [[1, 2], [3, 4]]},
+            {"name": "s", "datatype": "BYTES", "shape": [1], "data": ["abc"]},
+        ]}).encode()
+
+        request = decode_infer_request(body, None, "m")
+
+        assert request.raw_input_contents == []
+        assert request.inputs[0].int_contents == [1, 2, 3, 4]
+        assert request.inputs[1].bytes_contents == [b"abc"]
+
+    @pytest.mark.p1
+    def test_size_mismatch_rejected(self):
+        """Test that binary sizes that don't add up to the body are refused."""
+        header = {"inputs": [{"name": "x", "datatype": "UINT8", "shape": [4],
+                              "parameters": {"binary_data_size": 4}}]}
+        short, length = _binary_body(header, b"abc")
+        extra, _ = _binary_body(header, b"abcde")
+
+        with pytest.raises(ValueError):
+            decode_infer_request(short, length, "m")
+        with pytest.raises(ValueError):
+            decode_infer_request(extra, length, "m")
+        with pytest.raises(ValueError):
+            decode_infer_request(short, len(short) + 1, "m")
+
+    @pytest.mark.p1
+    def test_malformed_inputs_rejected(self):
+        """Test that missing fields and unpackable data raise ValueError, not KeyError or struct.error."""
+        blob = b"\x00" * 4
+        bad_inputs = [
+            {"datatype": "INT32", "shape": [1], "data": [1]},
+            {"name": "x", "shape": [1], "data": [1]},
+            {"name": "x", "datatype": "INT32", "shape": [-1], "data": [1]},
+            {"name": "x", "datatype": "INT32", "shape": "2", "data": [1, 2]},
+            # Packed next to the binary tensor below, so these hit struct.pack
+            {"name": "x", "datatype": "INT32", "shape": [1], "data": ["one"]},
+            {"name": "x", "datatype": "UINT8", "shape": [1], "data": [300]},
+            "x",
+        ]
+        for bad in bad_inputs:
+            body, length = _binary_body({"inputs": [
+                bad,
+                {"name": "blob", "datatype": "UINT8", "shape": [4],
+                 "parameters": {"binary_data_size": 4}},
+            ]}, blob)
+            with pytest.raises(ValueError):
+                decode_infer_request(body, length, "m")
+
+        for header in ([], {"inputs": [], "outputs": [{}]}):
+            with pytest.raises(ValueError):
+                decode_infer_request(json.dumps(header).encode(), None, "m")
+
+    @pytest.mark.p1
+    def test_element_count_must_match_shape(self):
+        """Test that JSON data must have exactly one element per shape entry, typed or packed."""
+        for datatype, data in (("INT32", [[1, 2], [3]]), ("FP16", [1.0, 2.0, 3.0])):
+            body = json.dumps({"inputs": [
+                {"name": "x", "datatype": datatype, "shape": [2, 2], "data": data},
+            ]}).encode()
+            with pytest.raises(ValueError, match="needs 4"):
+                decode_infer_request(body, None, "m")
+
+        ok = json.dumps({"inputs": [
+            {"name": "e", "datatype": "FP32", "shape": [0], "data": []},
+        ]}).encode()
+        assert decode_infer_request(ok, None, "m").inputs[0].shape == [0]
+
+
+class TestEncodeResponse:
+    """Tests for encode_infer_response()."""
+
+    @pytest.mark.p0
+    def test_raw_outputs_sent_as_binary(self):
+        """Test that raw outputs are appended after the JSON header."""
+        response = ModelInferResponse(model_name="m", id="r1")
+        response.add_output("out", "UINT8", [3])
+        response.raw_output_contents = [b"\x01\x02\x03"]
+
+        body, length = encode_infer_response(response)
+
+        header = json.loads(body[:length])
+        assert header["outputs"][0]["parameters"] == {"binary_data_size": 3}
+        assert "data" not in header["outputs"][0]
+        assert body[length:] == b"\x01\x02\x03"
+
+    @pytest.mark.p1
+    def test_typed_outputs_stay_json(self):
+        """Test that a response without raw outputs is plain JSON."""
+        response = ModelInferResponse(model_name="m")
+        response.add_output("y", "FP32", [2], fp32_contents=[1.5, 2.5])
+
+        body, length = encode_infer_response(response)
+
+        assert length is None
+        assert json.loads(body)["outputs"][0]["data"] == [1.5, 2.5]
+
+    @pytest.mark.p1
+    def test_client_preference_respected(self):
+        """Test that binary_data on a requested output overrides the default."""
+        request = decode_infer_request(json.dumps({
+            "inputs": [],
+            "outputs": [{"name": "raw", "parameters": {"binary_data": False}},
+                        {"name": "typed", "parameters": {"binary_data": True}}],
+        }).encode(), None, "m")
+        response = ModelInferResponse(model_name="m")
+        response.add_output("raw", "INT32", [2])
+        response.add_output("typed", "INT32", [1], int_contents=[7])
+        response.raw_output_contents = [struct.pack("<2i", 4, 5)]
+
+        body, length = encode_infer_response(response, request)
+
+        header = json.loads(body[:length])
+        assert header["outputs"][0]["data"] == [4, 5]
+        assert header["outputs"][1]["parameters"] == {"binary_data_size": 4}
+        assert body[length:] == struct.pack("<i", 7)
+
+
+class TestRoundTrip:
+    """Tests that client and server helpers agree on the framing."""
+
+    @pytest.mark.p0
+    def test_request_round_trip(self):
+        """Test that a binary-extension request survives encode -> decode."""
+        payload = bytes(range(256)) * 1024
+        request = ModelInferRequest(model_name="m", id="abc")
+        request.add_input("tensor", "UINT8", [len(payload)])
+        request.add_input("lengths", "INT32", [2])
+        request.raw_input_contents = [payload, struct.pack("<2i", 10, 20)]
+
+        body, length = encode_infer_request(request)
+        decoded = decode_infer_request(body, length, "m")
+
+        assert decoded.id == "abc"
+        assert [t.shape for t in decoded.inputs] == [[len(payload)], [2]]
+        assert decoded.raw_input_contents == request.raw_input_contents
+
+    @pytest.mark.p1
+    def test_response_round_trip(self):
+        """Test that a binary-extension response survives encode -> decode."""
+        response = ModelInferResponse(model_name="m", id="abc")
+        response.add_output("a", "FP64", [2])
+        response.add_output("b", "BYTES", [2])
+        response.raw_output_contents = [struct.pack("<2d", 1.0, -1.0),
+                                        struct.pack("<I", 1) + b"x" + struct.pack("<I", 0)]
+
+        body, length = encode_infer_response(response)
+        decoded = decode_infer_response(body, length)
+
+        assert decoded.id == "abc"
+        assert [o.name for o in decoded.outputs] == ["a", "b"]
+        assert decoded.raw_output_contents == response.raw_output_contents
@@ -0,0 +1,215 @@
+"""
+Unit tests for the ObjectBackend interface.
+
+The same cases run against ObjectStore and InMemoryObjectStore, since
+workers hand either one to handlers as context.objects.
+"""
+
+import pytest
+from anyserve.objects import (
+    InMemoryObjectStore, ObjectBackend, ObjectStore, ObjRef, open_object_store,
+)
+
+
+@pytest.fixture(params=["fs", "memory"])
+def backend(request, temp_dir):
+    """Each ObjectBackend implementation, empty."""
+    if request.param == "fs":
+        return ObjectStore(temp_dir)
+    return InMemoryObjectStore()
+
+
+class TestObjectBackendCreateGet:
+    """Tests for create() and get() on every backend."""
+
+    @pytest.mark.p0
+    def test_is_object_backend(self, backend):
+        """Test both implementations share the interface."""
+        assert isinstance(backend, ObjectBackend)
+
+    @pytest.mark.p0
+    def test_roundtrip_content_types(self, backend):
+        """Test pickle, json and bytes objects read back as stored."""
+        cases = [
+            ({"nested": [1, 2, {"a": (3, 4)}]}, "pickle"),
+            ({"key": "value", "list": [1, 2]}, "json"),
+            (b"\x00raw bytes\xff", "bytes"),
+        ]
+        for data, content_type in cases:
+            obj_ref = backend.create(data, content_type=content_type)
+            assert obj_ref.content_type == content_type
+            assert obj_ref.size > 0
+            assert backend.get(obj_ref) == data
+
+    @pytest.mark.p0
+    def test_auto_detects_content_type(self, backend):
+        """Test bytes are stored as bytes without a content_type."""
+        obj_ref = backend.create(b"payload")
+
+        assert obj_ref.content_type == "bytes"
+        assert obj_ref.size == len(b"payload")
+
+    @pytest.mark.p0
+    def test_create_with_key(self, backend):
+        """Test a caller-chosen key is kept."""
+        obj_ref = backend.create({"n": 1}, key="my-object")
+
+        assert obj_ref.key == "my-object"
+        assert backend.get(obj_ref) == {"n": 1}
+
+    @pytest.mark.p1
+    def test_overwrite(self, backend):
+        """Test creating an existing key replaces it unless overwrite=False."""
+        backend.create(b"first", key="obj")
+        obj_ref = backend.create(b"second", key="obj")
+        assert backend.get(obj_ref) == b"second"
+
+        with pytest.raises(FileExistsError):
+            backend.create(b"third", key="obj", overwrite=False)
+        assert backend.get(obj_ref) == b"second"
+
+    @pytest.mark.p1
+    def test_rejects_invalid_key(self, backend):
+        """Test keys that could escape the store are refused."""
+        for key in ("../escape", ".hidden", "a/b"):
+            with pytest.raises(ValueError):
+                backend.create(b"data", key=key)
+
+    @pytest.mark.p1
+    def test_get_returns_copy(self, backend):
+        """Test mutating a read object does not change the stored one."""
+        obj_ref = backend.create({"items": [1]})
+
+        backend.get(obj_ref)["items"].append(2)
+
+        assert backend.get(obj_ref) == {"items": [1]}
+
+    @pytest.mark.p0
+    def test_get_missing(self, backend):
+        """Test reading a missing object raises FileNotFoundError."""
+        obj_ref = backend.create(b"data", key="gone")
+        backend.delete(obj_ref)
+
+        with pytest.raises(FileNotFoundError):
+            backend.get(obj_ref)
+
+    @pytest.mark.p1
+    def test_reference_forms(self, backend):
+        """Test ObjRef, dict, JSON string and path string all name the object."""
+        obj_ref = backend.create({"x": 1}, key="ref-forms")
+
+        for ref in (obj_ref, obj_ref.to_dict(), obj_ref.to_string(), obj_ref.path):
+            assert backend.get(ref) == {"x": 1}
+            assert backend.exists(ref)
+            assert backend.stat(ref).key == "ref-forms"
+
+
+class TestObjectBackendMetadata:
+    """Tests for stat() and media types on every backend."""
+
+    @pytest.mark.p1
+    def test_stat(self, backend):
+        """Test stat() returns the metadata given on create."""
+        obj_ref = backend.create(b"data", key="meta", media_type="image/png", tenant="team-a")
+
+        stat = backend.stat(obj_ref)
+
+        assert isinstance(stat, ObjRef)
+        assert stat.key == "meta"
+        assert stat.size == obj_ref.size
+        assert stat.content_type == "bytes"
+        assert stat.media_type == "image/png"
+        assert stat.tenant == "team-a"
+
+    @pytest.mark.p1
+    def test_get_with_media_type(self, backend):
+        """Test get_with_media_type() falls back to the default media type."""
+        typed = backend.create(b"png", media_type="image/png")
+        untyped = backend.create(b"raw")
+
+        assert backend.get_with_media_type(typed) == (b"png", "image/png")
+        assert backend.get_with_media_type(untyped) == (b"raw", ObjectBackend.DEFAULT_MEDIA_TYPE)
+
+    @pytest.mark.p1
+    def test_stat_missing(self, backend):
+        """Test stat() of a missing object raises FileNotFoundError."""
+        obj_ref = backend.create(b"data")
+        backend.delete(obj_ref)
+
+        with pytest.raises(FileNotFoundError):
+            backend.stat(obj_ref)
+
+
+class TestObjectBackendDeleteList:
+    """Tests for delete(), exists(), list_objects() and get_many() on every backend."""
+
+    @pytest.mark.p0
+    def test_delete(self, backend):
+        """Test delete() removes the object and reports whether it existed."""
+        obj_ref = backend.create(b"data")
+        assert backend.exists(obj_ref)
+
+        assert backend.delete(obj_ref) is True
+        assert not backend.exists(obj_ref)
+        assert backend.delete(obj_ref) is False
+
+    @pytest.mark.p1
+    def test_list_objects(self, backend):
+        """Test list_objects() returns every object."""
+        assert backend.list_objects() == []
+
+        backend.create(b"a", key="obj-a")
+        backend.create({"b": 2}, key="obj-b")
+
+        listed = backend.list_objects()
+        assert all(isinstance(obj_ref, ObjRef) for obj_ref in listed)
+        assert sorted(obj_ref.key for obj_ref in listed) == ["obj-a", "obj-b"]
+
+    @pytest.mark.p1
+    def test_get_many(self, backend):
+        """Test get_many() maps keys to data and omits missing objects."""
+        ref_a = backend.create(b"a", key="many-a")
+        ref_b = backend.create({"b": 2}, key="many-b")
+        ref_gone = backend.create(b"c", key="many-gone")
+        backend.delete(ref_gone)
+
+        result = backend.get_many([ref_a, ref_b.to_dict(), ref_gone])
+
+        assert result == {"many-a": b"a", "many-b": {"b": 2}}
+
+
+class TestOpenObjectStore:
+    """Tests for open_object_store(), which workers use for --object-store."""
+
+    @pytest.mark.p0
+    def test_memory(self):
+        """Test memory:// opens an InMemoryObjectStore."""
+        store = open_object_store("memory://", max_bytes=None, max_age=None)
+
+        assert isinstance(store, InMemoryObjectStore)
+        obj_ref = store.create(b"data")
+        assert obj_ref.path.startswith("memory://")
+
+    @pytest.mark.p0
+    def test_directory(self, temp_dir):
+        """Test a path opens an ObjectStore with the given options."""
+        store = open_object_store(temp_dir, max_bytes=1024, max_age=None)
+
+        assert isinstance(store, ObjectStore)
+        assert store.max_bytes == 1024
+
+    @pytest.mark.p1
+    def test_memory_rejects_options(self):
+        """Test ObjectStore-only options are refused for memory://."""
+        with pytest.raises(ValueError, match="max_bytes"):
+            open_object_store("memory://", max_bytes=1024)
+
+    @pytest.mark.p1
+    def test_stores_are_separate(self):
+        """Test each memory:// store holds its own objects."""
+        first = open_object_store("memory://")
+        second = open_object_store("memory://")
+
+        obj_ref = first.create(b"data")
+
+        assert not second.exists(obj_ref)
@@ -113,6 +113,18 @@ class TestObjectStoreGet:
         with pytest.raises(FileNotFoundError):
             object_store.get("/nonexistent/path/obj.pkl")
 
+    @pytest.mark.p1
+    def test_get_many_mixed_present_and_absent(self, object_store):
+        """Test batch read returns present objects and omits missing ones."""
+        ref1 = object_store.create(b"first")
+        ref2 = object_store.create({"second": 2})
+        missing = object_store.create(b"gone")
+        object_store.delete(missing)
+
+        results = object_store.get_many([ref1, ref2.path, missing, "/nonexistent/obj.bin"])
+
+        assert results == {ref1.key: b"first", ref2.key: {"second": 2}}
+
 
 class TestObjectStoreDelete:
     """Tests for ObjectStore.delete()"""
@@ -188,6 +200,21 @@ class TestObjectStoreOperations:
         assert deleted_count == 5
         assert len(object_store.list_objects()) == 0
 
+    @pytest.mark.p1
+    def test_store_stats(self, temp_dir):
+        """Test that store_stats counts objects in both layouts, without sidecars."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir)
+        assert store.store_stats() == {"objects": 0, "bytes": 0, "oldest": None, "newest": None}
+
+        old = store.create(b"a" * 10, media_type="text/plain")
+        os.utime(old.path, (1000, 1000))
+        new = ObjectStore(temp_dir, shard=True).create(b"b" * 32)
+        os.utime(new.path, (2000, 2000))
+
+        assert store.store_stats() == {"objects": 2, "bytes": 42, "oldest": 1000, "newest": 2000}
+
 
 class TestObjectStoreEdgeCases:
     """Edge case tests for ObjectStore."""
@@ -224,3 +251,1706 @@ class TestObjectStoreEdgeCases:
 
         assert len(results) == 20
         assert len(set(r.key for r in results)) == 20  # All unique keys
+
+
+class TestObjectStoreDedup:
+    """Tests for content-addressed (dedup) mode."""
+
+    @pytest.mark.p1
+    def test_identical_payloads_share_file(self, temp_dir):
+        """Test that storing the same payload twice reuses one file."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, dedup=True)
+
+        ref1 = store.create(b"same payload")
+        ref2 = store.create(b"same payload")
+
+        assert ref1.key == ref2.key
+        assert ref1.path == ref2.path
+        assert len(store.list_objects()) == 1
+        assert store.get(ref2) == b"same payload"
+
+    @pytest.mark.p1
+    def test_different_payloads_distinct_files(self, temp_dir):
+        """Test that different payloads get different keys and files."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, dedup=True)
+
+        ref1 = store.create({"value": 1})
+        ref2 = store.create({"value": 2})
+
+        assert ref1.key != ref2.key
+        assert len(store.list_objects()) == 2
+        assert store.get(ref1) == {"value": 1}
+        assert store.get(ref2) == {"value": 2}
+
+    @pytest.mark.p2
+    def test_dedup_disabled_by_default(self, object_store):
+        """Test that the default store keeps unique keys per create."""
+        ref1 = object_store.create(b"same payload")
+        ref2 = object_store.create(b"same payload")
+
+        assert ref1.key != ref2.key
+        assert len(object_store.list_objects()) == 2
+
+
+class TestObjectStoreDurability:
+    """Tests for atomic, fsync'd writes."""
+
+    @pytest.mark.p1
+    def test_create_leaves_no_temp_files(self, object_store):
+        """Test that a completed create leaves only the final object file."""
+        obj_ref = object_store.create(b"payload")
+
+        files = [p.name for p in object_store.base_path.iterdir()]
+        assert files == [Path(obj_ref.path).name]
+
+    @pytest.mark.p1
+    def test_create_non_durable(self, object_store):
+        """Test that durable=False still stores a readable object."""
+        obj_ref = object_store.create({"fast": True}, durable=False)
+
+        assert object_store.get(obj_ref) == {"fast": True}
+
+    @pytest.mark.p1
+    def test_create_fsyncs_when_durable(self, object_store, monkeypatch):
+        """Test that fsync is only called when durable is set."""
+        calls = []
+        real_fsync = os.fsync
+        monkeypatch.setattr(os, "fsync", lambda fd: calls.append(fd) or real_fsync(fd))
+
+        object_store.create(b"fast", durable=False)
+        assert calls == []
+
+        object_store.create(b"safe")
+        assert len(calls) == 2  # file + directory
+
+    @pytest.mark.p1
+    def test_periodic_sync_of_non_durable_writes(self, temp_dir, monkeypatch):
+        """Test that sync_interval fsyncs durable=False objects in the background."""
+        from anyserve.objects import ObjectStore
+
+        calls = []
+        real_fsync = os.fsync
+        monkeypatch.setattr(os, "fsync", lambda fd: calls.append(fd) or real_fsync(fd))
+        store = ObjectStore(temp_dir, sync_interval=0.05)
+        try:
+            store.create(b"fast", durable=False)
+            assert calls == []
+
+            deadline = time.time() + 5
+            while len(calls) < 2 and time.time() < deadline:
+                time.sleep(0.01)
+            assert len(calls) == 2  # file + directory
+        finally:
+            store.close()
+
+    @pytest.mark.p2
+    def test_sync_skips_deleted_objects(self, temp_dir):
+        """Test that sync() only counts objects that still exist, and close() syncs the rest."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, sync_interval=3600)
+        gone = store.create(b"gone", durable=False)
+        store.delete(gone)
+        store.create(b"kept", durable=False)
+        store.create(b"safe")  # durable writes are never queued
+
+        assert store.sync() == 1
+        assert store.sync() == 0
+
+        store.create(b"late", durable=False)
+        store.close()
+        assert store.sync() == 0
+
+    @pytest.mark.p2
+    def test_invalid_sync_interval(self, temp_dir):
+        """Test that a non-positive sync_interval is rejected."""
+        from anyserve.objects import ObjectStore
+
+        with pytest.raises(ValueError):
+            ObjectStore(temp_dir, sync_interval=0)
+
+    @pytest.mark.p2
+    def test_failed_write_cleans_up_temp_file(self, object_store, monkeypatch):
+        """Test that a failed rename doesn't leave a partial object behind."""
+        def fail_replace(src, dst):
+            raise OSError("disk error")
+        monkeypatch.setattr(os, "replace", fail_replace)
+
+        with pytest.raises(OSError):
+            object_store.create(b"payload", key="broken")
+
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p2
+    def test_list_objects_skips_in_flight_writes(self, object_store):
+        """Test that hidden temp files are not listed as objects."""
+        (object_store.base_path / ".obj-x.bin.1234.tmp").write_bytes(b"partial")
+        object_store.create(b"done")
+
+        assert len(object_store.list_objects()) == 1
+
+
+class TestObjectStoreNamed:
+    """Tests for ObjectStore.create_named()"""
+
+    @pytest.mark.p0
+    def test_create_named_roundtrip(self, object_store):
+        """Test storing and reading back under a caller-chosen name."""
+        obj_ref = object_store.create_named("model-weights-v1", b"weights")
+
+        assert obj_ref.key == "model-weights-v1"
+        assert Path(obj_ref.path).name == "model-weights-v1.bin"
+        assert object_store.get(obj_ref) == b"weights"
+
+    @pytest.mark.p0
+    def test_create_named_existing_fails(self, object_store):
+        """Test that reusing a name without overwrite raises FileExistsError."""
+        object_store.create_named("shard-0", b"first")
+
+        with pytest.raises(FileExistsError):
+            object_store.create_named("shard-0", b"second")
+
+        # A different content type under the same name is also a clash
+        with pytest.raises(FileExistsError):
+            object_store.create_named("shard-0", {"json": True})
+
+    @pytest.mark.p1
+    def test_create_named_overwrite(self, object_store):
+        """Test that overwrite=True replaces the existing object."""
+        object_store.create_named("shard-0", b"first")
+        obj_ref = object_store.create_named("shard-0", b"second", overwrite=True)
+
+        assert object_store.get(obj_ref) == b"second"
+
+    @pytest.mark.p1
+    def test_create_named_rejects_invalid_names(self, object_store):
+        """Test that path separators, traversal and hidden names are rejected."""
+        for name in ["", ".", "..", "../escape", "a/b", "a\\b", ".hidden", "nul\0byte"]:
+            with pytest.raises(ValueError):
+                object_store.create_named(name, b"x")
+
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p2
+    def test_create_named_overwrite_in_dedup_store(self, temp_dir):
+        """Test that named objects in a dedup store are still overwritten."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, dedup=True)
+
+        store.create_named("latest", b"v1")
+        obj_ref = store.create_named("latest", b"v2", overwrite=True)
+
+        assert store.get(obj_ref) == b"v2"
+
+
+class TestObjectStoreReplace:
+    """Tests for ObjectStore.replace()"""
+
+    @pytest.mark.p0
+    def test_concurrent_reads_see_whole_versions(self, object_store):
+        """Test that reads racing a series of replaces each return one complete version."""
+        import threading
+
+        size = 2 << 20  # past mmap_threshold, so get_buffer maps the file
+        obj_ref = object_store.create(bytes([0]) * size, key="live")
+        done = threading.Event()
+        torn = []
+
+        def read():
+            while not done.is_set():
+                for data in (object_store.get(obj_ref), bytes(object_store.get_buffer(obj_ref))):
+                    if len(data) != size or data.count(data[:1]) != size:
+                        torn.append(data[:16])
+
+        readers = [threading.Thread(target=read) for _ in range(4)]
+        for t in readers:
+            t.start()
+        try:
+            for version in range(1, 30):
+                object_store.replace(obj_ref, bytes([version]) * size, durable=False)
+        finally:
+            done.set()
+            for t in readers:
+                t.join()
+
+        assert torn == []
+        assert object_store.get(obj_ref) == bytes([29]) * size
+
+    @pytest.mark.p1
+    def test_mapped_reader_keeps_old_version(self, object_store):
+        """Test that a buffer taken before the replace still reads the old content."""
+        old = os.urandom(2 << 20)
+        obj_ref = object_store.create(old, key="mapped")
+        buf = object_store.get_buffer(obj_ref)
+
+        object_store.replace(obj_ref, b"new")
+
+        assert bytes(buf) == old
+        assert object_store.get(obj_ref) == b"new"
+
+    @pytest.mark.p1
+    def test_keeps_content_type_and_media_type(self, object_store):
+        """Test that the object's format and sidecar metadata survive, with the new size."""
+        obj_ref = object_store.create({"v": 1}, key="config", media_type="application/json")
+
+        new_ref = object_store.replace(obj_ref.path, {"v": 2, "extra": True})
+
+        assert new_ref.path == obj_ref.path
+        assert object_store.get(obj_ref) == {"v": 2, "extra": True}
+        meta = object_store.stat(obj_ref)
+        assert meta.content_type == "json"
+        assert meta.media_type == "application/json"
+        assert meta.size == new_ref.size == os.path.getsize(obj_ref.path)
+
+    @pytest.mark.p2
+    def test_missing_object(self, object_store):
+        """Test that replacing an object that doesn't exist raises FileNotFoundError."""
+        with pytest.raises(FileNotFoundError):
+            object_store.replace(str(object_store.base_path / "nothing.bin"), b"x")
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p1
+    def test_content_addressed_object_rejected(self, temp_dir):
+        """Test that a dedup object can't be replaced, since its key is the hash of its bytes."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, dedup=True)
+        obj_ref = store.create(b"shared")
+        named = store.create_named("latest", b"v1")
+
+        with pytest.raises(ValueError):
+            store.replace(obj_ref, b"other")
+
+        assert store.get(obj_ref) == b"shared"
+        assert store.get(store.create(b"shared")) == b"shared"
+        # Caller-named objects in the same store stay replaceable
+        assert store.get(store.replace(named, b"v2")) == b"v2"
+
+
+class TestObjectStoreBuffer:
+    """Tests for ObjectStore.get_buffer()"""
+
+    @pytest.mark.p1
+    def test_small_object_returns_bytes(self, object_store):
+        """Test that objects below the threshold are read into bytes."""
+        obj_ref = object_store.create(b"small")
+
+        buf = object_store.get_buffer(obj_ref)
+        assert isinstance(buf, bytes)
+        assert buf == b"small"
+
+    @pytest.mark.p1
+    def test_large_object_is_mapped(self, temp_dir):
+        """Test that objects at or above the threshold are memory-mapped."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, mmap_threshold=1024)
+        payload = os.urandom(4096)
+        obj_ref = store.create(payload)
+
+        buf = store.get_buffer(obj_ref)
+        assert isinstance(buf, memoryview)
+        assert buf.readonly
+        assert bytes(buf) == payload
+
+    @pytest.mark.p1
+    def test_mapped_buffer_outlives_delete(self, temp_dir):
+        """Test that a mapped view stays readable after the file is deleted."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, mmap_threshold=1)
+        obj_ref = store.create(b"x" * 100)
+
+        buf = store.get_buffer(obj_ref)
+        store.delete(obj_ref)
+        assert bytes(buf) == b"x" * 100
+
+    @pytest.mark.p2
+    def test_get_pickle_from_mapped_file(self, temp_dir):
+        """Test that pickled objects deserialize from the mapped path."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, mmap_threshold=1)
+        data = {"array": list(range(1000))}
+        obj_ref = store.create(data, content_type="pickle")
+
+        assert store.get(obj_ref) == data
+
+    @pytest.mark.p2
+    def test_get_buffer_nonexistent(self, object_store):
+        """Test that a missing object raises FileNotFoundError."""
+        with pytest.raises(FileNotFoundError):
+            object_store.get_buffer("/nonexistent/path.bin")
+
+
+class TestObjectStoreFederation:
+    """Tests for node-local federated reads."""
+
+    @staticmethod
+    def _instance_store(temp_dir, instance_id, federated=False):
+        from anyserve.objects import ObjectStore
+        return ObjectStore(os.path.join(temp_dir, "instances", instance_id, "objects"),
+                           federated=federated)
+
+    @pytest.mark.p1
+    def test_falls_back_to_sibling_instance(self, temp_dir):
+        """Test that a local miss is served from a sibling instance's store."""
+        writer = self._instance_store(temp_dir, "a")
+        reader = self._instance_store(temp_dir, "b", federated=True)
+        obj_ref = writer.create({"shared": True}, key="shared-obj")
+
+        # Reference rewritten to the reader's own directory, as if looked up by key
+        local_path = str(reader.base_path / Path(obj_ref.path).name)
+        local_ref = obj_ref.to_dict() | {"path": local_path}
+
+        assert reader.exists(local_ref)
+        assert reader.get(local_ref) == {"shared": True}
+        assert reader.get(local_path) == {"shared": True}
+
+    @pytest.mark.p1
+    def test_isolated_by_default(self, temp_dir):
+        """Test that without federation, sibling stores are not consulted."""
+        writer = self._instance_store(temp_dir, "a")
+        reader = self._instance_store(temp_dir, "b")
+        obj_ref = writer.create(b"private", key="private-obj")
+
+        local_path = str(reader.base_path / Path(obj_ref.path).name)
+        assert not reader.exists(local_path)
+        with pytest.raises(FileNotFoundError):
+            reader.get(local_path)
+
+    @pytest.mark.p2
+    def test_missing_everywhere(self, temp_dir):
+        """Test that a federated miss still raises FileNotFoundError."""
+        self._instance_store(temp_dir, "a")
+        reader = self._instance_store(temp_dir, "b", federated=True)
+
+        with pytest.raises(FileNotFoundError):
+            reader.get(str(reader.base_path / "nope.bin"))
+
+
+class TestObjectStoreStat:
+    """Tests for ObjectStore.stat() and object metadata."""
+
+    @pytest.mark.p0
+    def test_stat_with_media_type(self, object_store):
+        """Test that a caller-supplied media type is returned by stat."""
+        obj_ref = object_store.create(b"\x89PNG...", media_type="image/png")
+
+        meta = object_store.stat(obj_ref.path)
+        assert meta.key == obj_ref.key
+        assert meta.size == obj_ref.size
+        assert meta.created_at == obj_ref.created_at
+        assert meta.content_type == "bytes"
+        assert meta.media_type == "image/png"
+
+    @pytest.mark.p1
+    def test_stat_without_sidecar(self, object_store):
+        """Test that objects without metadata fall back to file attributes."""
+        obj_ref = object_store.create({"a": 1})
+
+        meta = object_store.stat(obj_ref)
+        assert meta.size == obj_ref.size
+        assert meta.content_type == "json"
+        assert meta.media_type is None
+
+    @pytest.mark.p1
+    def test_sidecar_hidden_and_removed(self, object_store):
+        """Test that sidecars are not listed or counted, and go away with the object."""
+        obj_ref = object_store.create(b"x", media_type="text/plain")
+        assert len(object_store.list_objects()) == 1
+
+        object_store.delete(obj_ref)
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p2
+    def test_clear_counts_objects_only(self, object_store):
+        """Test that clear() doesn't count metadata sidecars."""
+        object_store.create(b"x", media_type="text/plain")
+        object_store.create(b"y")
+
+        assert object_store.clear() == 2
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p0
+    def test_get_with_media_type(self, object_store):
+        """Test that reads can return the stored media type, defaulting to octet-stream."""
+        png = object_store.create(b"\x89PNG...", media_type="image/png")
+        plain = object_store.create({"a": 1})
+
+        assert object_store.get_with_media_type(png) == (b"\x89PNG...", "image/png")
+        assert object_store.get_with_media_type(plain.path) == ({"a": 1}, "application/octet-stream")
+        with pytest.raises(FileNotFoundError):
+            object_store.get_with_media_type(str(object_store.base_path / "missing.bin"))
+
+    @pytest.mark.p2
+    def test_stat_nonexistent(self, object_store):
+        """Test that stat on a missing object raises FileNotFoundError."""
+        with pytest.raises(FileNotFoundError):
+            object_store.stat("/nonexistent/path.bin")
+
+
+class TestObjectStoreListPage:
+    """Tests for ObjectStore.list_objects_page()"""
+
+    @pytest.mark.p1
+    def test_walks_all_pages(self, object_store):
+        """Test that paging returns every object exactly once."""
+        keys = {object_store.create(i, key=f"obj-{i:03d}").key for i in range(25)}
+
+        seen = []
+        token = None
+        while True:
+            page, token = object_store.list_objects_page(page_size=10, page_token=token)
+            seen.extend(ref.key for ref in page)
+            if token is None:
+                break
+
+        assert len(seen) == 25
+        assert set(seen) == keys
+
+    @pytest.mark.p1
+    def test_stable_under_concurrent_adds(self, object_store):
+        """Test that objects added mid-walk don't cause repeats or skips."""
+        for i in range(10):
+            object_store.create(i, key=f"obj-{i * 2:03d}")
+
+        page1, token = object_store.list_objects_page(page_size=5)
+        # Add objects both before and after the cursor
+        object_store.create("early", key="obj-001")
+        object_store.create("late", key="obj-999")
+        page2, token = object_store.list_objects_page(page_size=100, page_token=token)
+
+        keys = [ref.key for ref in page1 + page2]
+        assert len(keys) == len(set(keys))
+        assert {f"obj-{i * 2:03d}" for i in range(10)} <= set(keys)
+        assert "obj-999" in keys
+        assert token is None
+
+    @pytest.mark.p1
+    def test_unchanged_directory_read_once(self, object_store, monkeypatch):
+        """Test that paging through a settled store lists its directory once, not per page."""
+        for i in range(20):
+            object_store.create(i, key=f"obj-{i:03d}")
+        object_store._listings.SETTLE_NS = 0
+
+        scans = []
+        real_scandir = os.scandir
+        monkeypatch.setattr(os, "scandir", lambda path: scans.append(path) or real_scandir(path))
+
+        seen, token = [], None
+        while True:
+            page, token = object_store.list_objects_page(page_size=3, page_token=token)
+            seen.extend(ref.key for ref in page)
+            if token is None:
+                break
+
+        assert seen == [f"obj-{i:03d}" for i in range(20)]
+        assert len(scans) == 1
+
+    @pytest.mark.p2
+    def test_exact_page_boundary(self, object_store):
+        """Test that a store filling exactly one page has no next token."""
+        for i in range(5):
+            object_store.create(i)
+
+        page, token = object_store.list_objects_page(page_size=5)
+        assert len(page) == 5
+        assert token is None
+
+    @pytest.mark.p2
+    def test_invalid_page_size(self, object_store):
+        """Test that a non-positive page size is rejected."""
+        with pytest.raises(ValueError):
+            object_store.list_objects_page(page_size=0)
+
+
+class TestObjectStoreStreamingWrite:
+    """Tests for open_writer() / create_from_chunks()."""
+
+    @pytest.mark.p0
+    def test_many_chunks(self, object_store):
+        """Test that a large object written in many chunks reads back intact."""
+        chunk = bytes(range(256)) * 256  # 64KB
+        with object_store.open_writer() as writer:
+            for i in range(64):
+                writer.write(chunk[i:] + chunk[:i])
+            ref = writer.finish()
+
+        expected = b"".join(chunk[i:] + chunk[:i] for i in range(64))
+        assert ref.size == len(expected) == 4 * 1024 * 1024
+        assert ref.content_type == "bytes"
+        assert object_store.get(ref) == expected
+
+    @pytest.mark.p1
+    def test_not_visible_until_finish(self, object_store):
+        """Test that a partially written object isn't listed or readable."""
+        writer = object_store.open_writer(key="partial")
+        writer.write(b"half")
+        assert object_store.list_objects() == []
+
+        ref = writer.finish()
+        assert [r.key for r in object_store.list_objects()] == ["partial"]
+        assert object_store.get(ref) == b"half"
+
+    @pytest.mark.p1
+    def test_exception_discards_partial(self, object_store):
+        """Test that leaving the with-block without finish() removes the temp file."""
+        with pytest.raises(RuntimeError):
+            with object_store.open_writer(key="boom") as writer:
+                writer.write(b"data")
+                raise RuntimeError("source failed")
+
+        assert not any(object_store.base_path.iterdir())
+
+    @pytest.mark.p1
+    def test_dedup_uses_content_hash(self, temp_dir):
+        """Test that streamed and one-shot writes of the same bytes share a key in dedup mode."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(temp_dir, dedup=True)
+        one_shot = store.create(b"abcdef")
+        streamed = store.create_from_chunks([b"ab", b"cd", b"ef"])
+
+        assert streamed.key == one_shot.key
+        assert streamed.path == one_shot.path
+        assert len(store.list_objects()) == 1
+
+    @pytest.mark.p2
+    def test_write_after_finish_fails(self, object_store):
+        """Test that a finished writer rejects further chunks."""
+        writer = object_store.open_writer()
+        writer.write(b"x")
+        writer.finish()
+        with pytest.raises(ValueError):
+            writer.write(b"y")
+
+    @pytest.mark.p2
+    def test_no_overwrite(self, object_store):
+        """Test that overwrite=False refuses an existing key and keeps the old data."""
+        object_store.create(b"old", key="taken")
+        with pytest.raises(FileExistsError):
+            object_store.create_from_chunks([b"new"], key="taken", overwrite=False)
+        assert (object_store.base_path / "taken.bin").read_bytes() == b"old"
+
+    @pytest.mark.p2
+    def test_media_type(self, object_store):
+        """Test that media_type is recorded for streamed objects."""
+        ref = object_store.create_from_chunks([b"\x89PNG"], media_type="image/png")
+        assert object_store.stat(ref).media_type == "image/png"
+
+
+class TestObjectStorePeek:
+    """Tests for peek() existence/size checks."""
+
+    @pytest.mark.p0
+    def test_present_and_absent(self, object_store):
+        """Test that peek returns the size for present objects and None otherwise."""
+        obj_ref = object_store.create(b"12345", key="five")
+
+        assert object_store.peek(obj_ref) == 5
+        assert object_store.peek(obj_ref.path) == 5
+        assert object_store.peek(str(object_store.base_path / "nope.bin")) is None
+
+    @pytest.mark.p1
+    def test_owner_store(self, temp_dir):
+        """Test that peek checks a named peer's store without federation."""
+        owner = TestObjectStoreFederation._instance_store(temp_dir, "owner")
+        probe = TestObjectStoreFederation._instance_store(temp_dir, "probe")
+        obj_ref = owner.create(b"x" * 100, key="remote-obj")
+
+        assert probe.peek(obj_ref, owner="owner") == 100
+        assert probe.peek(obj_ref, owner="someone-else") is None
+        # Not federated, so without an owner only the probe's own store is checked
+        assert probe.peek(str(probe.base_path / Path(obj_ref.path).name)) is None
+
+    @pytest.mark.p2
+    def test_does_not_read(self, object_store, monkeypatch):
+        """Test that peek never opens the object file."""
+        obj_ref = object_store.create(b"payload", key="unread")
+
+        def no_read(*args, **kwargs):
+            raise AssertionError("peek must not read object data")
+
+        monkeypatch.setattr(Path, "read_bytes", no_read)
+        monkeypatch.setattr(Path, "open", no_read)
+        assert object_store.peek(obj_ref) == len(b"payload")
+
+
+@pytest.mark.skipif(os.name != "posix", reason="Unix permission bits")
+class TestObjectStorePermissions:
+    """Tests for the modes of files and directories the store creates."""
+
+    @staticmethod
+    def _mode(path):
+        return os.stat(path).st_mode & 0o777
+
+    @pytest.mark.p0
+    def test_default_modes(self, temp_dir):
+        """Test that new directories are 0700 and objects 0600 by default."""
+        from anyserve.objects import ObjectStore
+        from anyserve.objects.layout import meta_path
+        store = ObjectStore(os.path.join(temp_dir, "a", "objects"))
+
+        obj_ref = store.create(b"secret", key="private", media_type="text/plain")
+        with store.open_writer(key="streamed") as writer:
+            writer.write(b"chunk")
+            streamed = writer.finish()
+
+        assert self._mode(os.path.join(temp_dir, "a")) == 0o700
+        assert self._mode(store.base_path) == 0o700
+        assert self._mode(obj_ref.path) == 0o600
+        assert self._mode(streamed.path) == 0o600
+        meta = meta_path(Path(obj_ref.path))
+        assert self._mode(meta) == 0o600
+
+    @pytest.mark.p1
+    def test_custom_modes(self, temp_dir):
+        """Test that dir_mode and file_mode override the defaults."""
+        from anyserve.objects import ObjectStore
+        store = ObjectStore(os.path.join(temp_dir, "shared"), dir_mode=0o750, file_mode=0o640)
+
+        obj_ref = store.create(b"data", key="group-readable")
+
+        assert self._mode(store.base_path) == 0o750
+        assert self._mode(obj_ref.path) == 0o640
+
+    @pytest.mark.p1
+    def test_existing_directory_untouched(self, temp_dir, capsys):
+        """Test that a permissive existing directory is warned about, not changed."""
+        from anyserve.objects import ObjectStore
+        path = os.path.join(temp_dir, "open")
+        os.mkdir(path)
+        os.chmod(path, 0o755)
+
+        ObjectStore(path)
+
+        assert self._mode(path) == 0o755
+        assert "more permissive" in capsys.readouterr().out
+
+        ObjectStore(path, check_permissions=False)
+        assert capsys.readouterr().out == ""
+
+
+class TestObjectStoreDiskFull:
+    """Tests for out-of-space handling on writes."""
+
+    @staticmethod
+    def _fail_writes(store, monkeypatch, err=None):
+        import errno
+
+        err = errno.ENOSPC if err is None else err
+        real_open = store._open_temp
+
+        class _FullFile:
+            def __init__(self, f):
+                self._f = f
+
+            def write(self, data):
+                raise OSError(err, os.strerror(err))
+
+            def __getattr__(self, name):
+                return getattr(self._f, name)
+
+            def __enter__(self):
+                return self
+
+            def __exit__(self, *exc):
+                self._f.close()
+
+        monkeypatch.setattr(store, "_open_temp", lambda path: _FullFile(real_open(path)))
+
+    @pytest.mark.p0
+    def test_create_raises_full_error(self, object_store, monkeypatch):
+        """Test that ENOSPC becomes ObjectStoreFullError with store context."""
+        import errno
+        from anyserve.objects import ObjectStoreFullError
+
+        object_store.create(b"x" * 100, key="existing")
+        self._fail_writes(object_store, monkeypatch)
+
+        with pytest.raises(ObjectStoreFullError) as exc_info:
+            object_store.create(b"payload", key="new")
+
+        err = exc_info.value
+        assert isinstance(err, OSError)
+        assert err.errno == errno.ENOSPC
+        assert err.base_path == object_store.base_path
+        assert err.usage == 100
+        assert str(object_store.base_path) in str(err)
+        # No half-written object or temp file is left behind
+        assert sorted(p.name for p in object_store.base_path.iterdir()) == ["existing.bin"]
+
+    @pytest.mark.p1
+    def test_quota_exceeded(self, object_store, monkeypatch):
+        """Test that EDQUOT is reported the same way as ENOSPC."""
+        import errno
+        from anyserve.objects import ObjectStoreFullError
+
+        if not hasattr(errno, "EDQUOT"):
+            pytest.skip("EDQUOT not defined on this platform")
+        self._fail_writes(object_store, monkeypatch, errno.EDQUOT)
+
+        with pytest.raises(ObjectStoreFullError):
+            object_store.create(b"payload", key="new")
+
+    @pytest.mark.p1
+    def test_other_errors_unchanged(self, object_store, monkeypatch):
+        """Test that unrelated write errors keep their plain OSError type."""
+        import errno
+        from anyserve.objects import ObjectStoreFullError
+
+        self._fail_writes(object_store, monkeypatch, errno.EIO)
+
+        with pytest.raises(OSError) as exc_info:
+            object_store.create(b"payload", key="new")
+        assert not isinstance(exc_info.value, ObjectStoreFullError)
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p1
+    def test_streaming_writer_cleans_up(self, object_store, monkeypatch):
+        """Test that a writer hitting ENOSPC raises and removes its temp file."""
+        from anyserve.objects import ObjectStoreFullError
+
+        self._fail_writes(object_store, monkeypatch)
+        writer = object_store.open_writer(key="stream")
+
+        with pytest.raises(ObjectStoreFullError):
+            writer.write(b"chunk")
+        assert writer.closed
+        assert list(object_store.base_path.iterdir()) == []
+
+    @pytest.mark.p2
+    def test_storage_usage(self, object_store):
+        """Test that storage_usage sums the stored bytes."""
+        assert object_store.storage_usage() == 0
+        object_store.create(b"a" * 10, key="a")
+        object_store.create(b"b" * 32, key="b")
+
+        assert object_store.storage_usage() == 42
+
+
+class TestObjectStoreQuota:
+    """Tests for per-tenant byte quotas."""
+
+    @pytest.mark.p0
+    def test_write_past_quota_rejected(self, temp_dir):
+        """Test that writes fill a tenant up to its quota and the next one is rejected."""
+        import errno
+        from anyserve.objects import ObjectStore, ObjectStoreFullError, ObjectStoreQuotaError
+
+        store = ObjectStore(temp_dir, quotas={"team-a": 300})
+        store.create(b"a" * 100, tenant="team-a")
+        store.create_many([b"b" * 100, b"c" * 100], tenant="team-a")
+        assert store.tenant_usage("team-a") == 300
+
+        with pytest.raises(ObjectStoreQuotaError) as exc_info:
+            store.create(b"d", key="over", tenant="team-a")
+        err = exc_info.value
+        assert isinstance(err, ObjectStoreFullError)
+        assert err.errno == errno.EDQUOT
+        assert (err.tenant, err.usage, err.quota) == ("team-a", 300, 300)
+        assert not store.exists("over")
+        assert store.tenant_usage("team-a") == 300
+
+    @pytest.mark.p1
+    def test_other_tenants_unaffected(self, temp_dir):
+        """Test that a full tenant doesn't block other or untracked tenants."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, quotas={"team-a": 10, "team-b": 100})
+        store.create(b"a" * 10, tenant="team-a")
+        assert store.create(b"b" * 50, tenant="team-b").tenant == "team-b"
+        store.create(b"c" * 500, tenant="team-c")
+        store.create(b"d" * 500)
+        assert store.tenant_usage("team-b") == 50
+
+    @pytest.mark.p1
+    def test_usage_survives_restart(self, temp_dir):
+        """Test that a reopened store recomputes usage from the recorded tenants."""
+        from anyserve.objects import ObjectStore, ObjectStoreQuotaError
+
+        store = ObjectStore(temp_dir, quotas={"team-a": 200}, shard=True)
+        ref = store.create(b"a" * 150, tenant="team-a")
+        assert store.stat(ref).tenant == "team-a"
+
+        reopened = ObjectStore(temp_dir, quotas={"team-a": 200})
+        assert reopened.tenant_usage("team-a") == 150
+        with pytest.raises(ObjectStoreQuotaError):
+            reopened.create(b"b" * 100, tenant="team-a")
+
+    @pytest.mark.p1
+    def test_delete_and_overwrite_free_space(self, temp_dir):
+        """Test that deleting or overwriting an object returns its bytes to the tenant."""
+        from anyserve.objects import ObjectStore, ObjectStoreQuotaError
+
+        store = ObjectStore(temp_dir, quotas={"team-a": 100})
+        ref = store.create(b"a" * 80, key="obj", tenant="team-a")
+        store.create(b"b" * 90, key="obj", tenant="team-a")
+        assert store.tenant_usage("team-a") == 90
+
+        with pytest.raises(ObjectStoreQuotaError):
+            store.replace(ref, b"c" * 120)
+        store.replace(ref, b"c" * 20)
+        assert store.tenant_usage("team-a") == 20
+
+        store.delete(ref)
+        assert store.tenant_usage("team-a") == 0
+        store.create(b"d" * 100, tenant="team-a")
+
+    @pytest.mark.p1
+    def test_overwrite_credits_previous_tenant(self, temp_dir):
+        """Test that overwriting another tenant's object frees its bytes for that tenant, not the writer."""
+        from anyserve.objects import ObjectStore, ObjectStoreQuotaError
+
+        quotas = {"team-a": 100, "team-b": 100}
+        store = ObjectStore(temp_dir, quotas=quotas)
+        store.create(b"a" * 80, key="obj", tenant="team-a")
+        store.create(b"b" * 30, tenant="team-b")
+
+        # The 80 replaced bytes were team-a's, so they don't make room for team-b
+        with pytest.raises(ObjectStoreQuotaError):
+            store.create(b"c" * 90, key="obj", tenant="team-b")
+        assert (store.tenant_usage("team-a"), store.tenant_usage("team-b")) == (80, 30)
+
+        store.create(b"c" * 60, key="obj", tenant="team-b")
+        assert (store.tenant_usage("team-a"), store.tenant_usage("team-b")) == (0, 90)
+        reopened = ObjectStore(temp_dir, quotas=quotas)
+        assert (reopened.tenant_usage("team-a"), reopened.tenant_usage("team-b")) == (0, 90)
+
+        # An untracked writer takes the object over, and team-b's bytes with it
+        store.create(b"d" * 20, key="obj")
+        assert store.tenant_usage("team-b") == 30
+        assert ObjectStore(temp_dir, quotas=quotas).tenant_usage("team-b") == 30
+
+    @pytest.mark.p1
+    def test_streamed_writes_charged(self, temp_dir):
+        """Test that open_writer() counts against the tenant and leaves nothing behind when over."""
+        from anyserve.objects import ObjectStore, ObjectStoreQuotaError
+
+        store = ObjectStore(temp_dir, quotas={"team-a": 100})
+        ref = store.create_from_chunks([b"a" * 30, b"a" * 30], tenant="team-a")
+        assert store.stat(ref).tenant == "team-a"
+        assert store.tenant_usage("team-a") == 60
+
+        with pytest.raises(ObjectStoreQuotaError):
+            with store.open_writer(key="big", tenant="team-a") as writer:
+                writer.write(b"b" * 50)
+                writer.finish()
+        assert not store.exists("big")
+        assert list(store.base_path.glob(".*.tmp")) == []
+        assert store.tenant_usage("team-a") == 60
+
+    @pytest.mark.p1
+    def test_dedup_hit_keeps_owner(self, temp_dir):
+        """Test that storing bytes another tenant already stored doesn't change who is charged."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, dedup=True, quotas={"team-a": 100, "team-b": 100})
+        first = store.create(b"x" * 40, tenant="team-a")
+        again = store.create(b"x" * 40, tenant="team-b")
+        streamed = store.create_from_chunks([b"x" * 40], tenant="team-b")
+
+        assert again.path == first.path
+        assert again.tenant == streamed.tenant == store.stat(first).tenant == "team-a"
+        assert store.tenant_usage("team-a") == 40
+        assert store.tenant_usage("team-b") == 0
+        assert ObjectStore(temp_dir, quotas={"team-a": 100}).tenant_usage("team-a") == 40
+
+    @pytest.mark.p1
+    def test_fetch_and_replication_charged(self, temp_dir):
+        """Test that copies between instances' stores count against the tenant in the receiving store."""
+        from anyserve.objects import ObjectReplicationError, ObjectStore, ObjectStoreQuotaError
+
+        def instance(name):
+            return ObjectStore(os.path.join(temp_dir, "instances", name, "objects"),
+                               quotas={"team-a": 100})
+
+        owner, local = instance("a"), instance("b")
+        ref = owner.create(b"a" * 60, key="shared", tenant="team-a")
+        copy = local.fetch(ref, "a")
+        assert local.stat(copy).tenant == "team-a"
+        # The cached usage saw the copy, not just a rescan
+        assert local.tenant_usage("team-a") == 60
+
+        big = owner.create(b"b" * 40, key="big", tenant="team-a")
+        local.create(b"c" * 10, key="filler", tenant="team-a")
+        with pytest.raises(ObjectStoreQuotaError):
+            local.fetch(big, "a")
+        assert not local.exists("big.bin")
+
+        # team-a has 30 bytes left in b's store, so a 40-byte replica is refused there
+        with pytest.raises(ObjectReplicationError):
+            instance("c").create_replicated(b"d" * 40, ["b"], min_acks=1, tenant="team-a")
+        assert local.tenant_usage("team-a") == 70
+
+    @pytest.mark.p2
+    def test_negative_quota(self, temp_dir):
+        """Test that a negative quota is rejected."""
+        from anyserve.objects import ObjectStore
+
+        with pytest.raises(ValueError, match="quotas"):
+            ObjectStore(temp_dir, quotas={"team-a": -1})
+
+
+class TestObjectStoreRedirect:
+    """Tests for moved hints that point reads at another owner's store."""
+
+    @pytest.mark.p0
+    def test_get_follows_hint(self, temp_dir):
+        """Test that a local miss with a hint reads from the owner's store."""
+        owner = TestObjectStoreFederation._instance_store(temp_dir, "owner")
+        front = TestObjectStoreFederation._instance_store(temp_dir, "front")
+        obj_ref = owner.create(b"sharded", key="shard-obj")
+        local_path = str(front.base_path / Path(obj_ref.path).name)
+
+        front.mark_moved(obj_ref, "owner")
+
+        assert front.redirect_of(local_path) == "owner"
+        assert front.exists(local_path)
+        assert front.get(local_path) == b"sharded"
+        assert front.peek(local_path) == len(b"sharded")
+        # The hint is hidden, not an object
+        assert front.list_objects() == []
+
+    @pytest.mark.p1
+    def test_no_redirect_when_local(self, object_store):
+        """Test that a locally held object reports no redirect."""
+        obj_ref = object_store.create(b"here", key="local-obj")
+        object_store.mark_moved(obj_ref, "elsewhere")
+
+        assert object_store.redirect_of(obj_ref) is None
+        assert object_store.get(obj_ref) == b"here"
+
+    @pytest.mark.p1
+    def test_single_hop(self, temp_dir):
+        """Test that the owner's own hints are not followed, so redirects cannot loop."""
+        a = TestObjectStoreFederation._instance_store(temp_dir, "a")
+        b = TestObjectStoreFederation._instance_store(temp_dir, "b")
+        c = TestObjectStoreFederation._instance_store(temp_dir, "c")
+        obj_ref = c.create(b"far", key="far-obj")
+        name = Path(obj_ref.path).name
+
+        a.mark_moved(name, "b")
+        b.mark_moved(name, "c")
+        with pytest.raises(FileNotFoundError):
+            a.get(str(a.base_path / name))
+
+        # A loop back to the asker ends the same way
+        b.mark_moved(name, "a")
+        with pytest.raises(FileNotFoundError):
+            a.get(str(a.base_path / name))
+
+    @pytest.mark.p2
+    def test_stale_hint(self, temp_dir):
+        """Test that a hint to an owner that lost the object is a plain miss."""
+        front = TestObjectStoreFederation._instance_store(temp_dir, "front")
+        TestObjectStoreFederation._instance_store(temp_dir, "owner")
+        front.mark_moved("gone.bin", "owner")
+
+        assert front.redirect_of("gone.bin") == "owner"
+        assert not front.exists(str(front.base_path / "gone.bin"))
+
+    @pytest.mark.p2
+    def test_invalid_owner(self, object_store):
+        """Test that owners that could escape the root are rejected."""
+        with pytest.raises(ValueError):
+            object_store.mark_moved("x.bin", "../etc")
+
+
+class TestObjectStoreReplication:
+    """Tests for ObjectStore.create_replicated()."""
+
+    @staticmethod
+    def _stores(temp_dir, *ids):
+        return [TestObjectStoreFederation._instance_store(temp_dir, i) for i in ids]
+
+    @pytest.mark.p0
+    def test_copies_to_all_peers(self, temp_dir):
+        """Test that the object lands locally and in every peer store."""
+        local, b, c = self._stores(temp_dir, "a", "b", "c")
+
+        obj_ref = local.create_replicated(b"payload", ["b", "c"], min_acks=2,
+                                          key="replicated", media_type="text/plain")
+
+        name = Path(obj_ref.path).name
+        assert local.get(obj_ref) == b"payload"
+        for peer in (b, c):
+            copy = str(peer.base_path / name)
+            assert peer.get(copy) == b"payload"
+            assert peer.stat(copy).media_type == "text/plain"
+
+    @pytest.mark.p1
+    def test_copy_streamed_in_kernel(self, temp_dir, monkeypatch):
+        """Test that the payload is copied by the kernel, never read into Python."""
+        local, b = self._stores(temp_dir, "a", "b")
+        payload = os.urandom(4 * 1024 * 1024)
+        read = []
+        real_read_bytes = Path.read_bytes
+        monkeypatch.setattr(Path, "read_bytes", lambda self: read.append(self.name) or real_read_bytes(self))
+
+        obj_ref = local.create_replicated(payload, ["b"], min_acks=1, key="big")
+
+        name = Path(obj_ref.path).name
+        assert name not in read
+        assert b.get(str(b.base_path / name)) == payload
+
+    @pytest.mark.p1
+    def test_quorum_tolerates_failures(self, temp_dir, capsys):
+        """Test that peer failures within the quorum are logged, not raised."""
+        local, b = self._stores(temp_dir, "a", "b")
+
+        # "missing" has no store directory, so its copy fails
+        obj_ref = local.create_replicated(b"payload", ["b", "missing"], min_acks=1)
+
+        assert (b.base_path / Path(obj_ref.path).name).exists()
+        # Failures past the quorum are still logged once they complete
+        deadline = time.time() + 5
+        out = ""
+        while "missing" not in out and time.time() < deadline:
+            out += capsys.readouterr().out
+            time.sleep(0.01)
+        assert "Replicating" in out and "missing" in out
+
+    @pytest.mark.p0
+    def test_raises_below_quorum(self, temp_dir):
+        """Test that too few successful copies raise, keeping the local object."""
+        from anyserve.objects import ObjectReplicationError
+
+        local, _ = self._stores(temp_dir, "a", "b")
+
+        with pytest.raises(ObjectReplicationError) as exc_info:
+            local.create_replicated(b"payload", ["b", "gone-1", "gone-2"], min_acks=2)
+
+        err = exc_info.value
+        assert err.acks <= 1 and err.min_acks == 2
+        assert local.exists(err.obj_ref)
+
+    @pytest.mark.p1
+    def test_invalid_arguments(self, temp_dir):
+        """Test that impossible quorums and bad peer ids are rejected up front."""
+        (local,) = self._stores(temp_dir, "a")
+
+        with pytest.raises(ValueError):
+            local.create_replicated(b"x", ["b"], min_acks=2)
+        with pytest.raises(ValueError):
+            local.create_replicated(b"x", ["../b"], min_acks=1)
+        assert local.list_objects() == []
+
+    @pytest.mark.p2
+    def test_no_peers(self, temp_dir):
+        """Test that with no peers this is a plain create()."""
+        (local,) = self._stores(temp_dir, "a")
+
+        obj_ref = local.create_replicated(b"solo", [], min_acks=0)
+        assert local.get(obj_ref) == b"solo"
+
+
+class TestObjectStoreFetch:
+    """Tests for ObjectStore.fetch()."""
+
+    @pytest.mark.p0
+    def test_copies_into_local_store(self, temp_dir):
+        """Test that a fetched object is held locally under the same key."""
+        owner = TestObjectStoreFederation._instance_store(temp_dir, "a")
+        local = TestObjectStoreFederation._instance_store(temp_dir, "b")
+        payload = os.urandom(2 * 1024 * 1024)
+        obj_ref = owner.create(payload, key="weights", content_type="bytes", media_type="x/weights")
+
+        copy = local.fetch(obj_ref, "a")
+
+        assert copy.key == "weights"
+        assert Path(copy.path).parent == local.base_path
+        assert copy.media_type == "x/weights"
+        owner.delete(obj_ref)
+        assert local.get(copy) == payload
+
+    @pytest.mark.p1
+    def test_already_local(self, temp_dir):
+        """Test that fetching an object held here returns it without copying."""
+        local = TestObjectStoreFederation._instance_store(temp_dir, "b")
+        obj_ref = local.create({"here": True}, key="here")
+
+        assert local.fetch(obj_ref, "a").path == obj_ref.path
+
+    @pytest.mark.p1
+    def test_sharded_local_store(self, temp_dir):
+        """Test that the copy follows the local store's layout."""
+        from anyserve.objects import ObjectStore
+
+        owner = TestObjectStoreFederation._instance_store(temp_dir, "a")
+        local = ObjectStore(os.path.join(temp_dir, "instances", "b", "objects"), shard=True)
+        obj_ref = owner.create([1, 2, 3], key="listed")
+
+        copy = local.fetch(obj_ref, "a")
+        assert Path(copy.path).parent.parent == local.base_path
+        assert local.get(copy) == [1, 2, 3]
+
+    @pytest.mark.p2
+    def test_missing(self, temp_dir):
+        """Test that an object the owner doesn't hold raises FileNotFoundError."""
+        TestObjectStoreFederation._instance_store(temp_dir, "a")
+        local = TestObjectStoreFederation._instance_store(temp_dir, "b")
+
+        with pytest.raises(FileNotFoundError):
+            local.fetch("missing.bin", "a")
+        assert local.list_objects() == []
+
+
+class TestObjectStoreBatch:
+    """Tests for ObjectStore.create_many()."""
+
+    @pytest.mark.p0
+    def test_many_small_objects(self, object_store):
+        """Test that 100 objects written in one call read back individually, in order."""
+        datas = [f"tensor-{i}".encode() * (i + 1) for i in range(100)]
+
+        refs = object_store.create_many(datas)
+
+        assert len(refs) == 100
+        assert len({ref.key for ref in refs}) == 100
+        for data, ref in zip(datas, refs):
+            assert ref.size == len(data)
+            assert object_store.get(ref) == data
+        assert len(object_store.list_objects()) == 100
+
+    @pytest.mark.p1
+    def test_directory_synced_once(self, object_store, monkeypatch):
+        """Test that a durable batch fsyncs the directory once, not per object."""
+        synced = []
+        monkeypatch.setattr(object_store, "_fsync_dir", lambda path: synced.append(path))
+
+        object_store.create_many([b"a", b"b", b"c"])
+        assert synced == [object_store.base_path]
+
+        synced.clear()
+        object_store.create_many([b"d"], durable=False)
+        assert synced == []
+
+        # Outside a batch, each durable create syncs on its own again
+        object_store.create(b"e")
+        assert synced == [object_store.base_path]
+
+    @pytest.mark.p2
+    def test_mixed_content_types(self, object_store):
+        """Test that content types are detected per object."""
+        refs = object_store.create_many([b"raw", {"a": 1}, [1, 2]])
+
+        assert [ref.content_type for ref in refs] == ["bytes", "json", "json"]
+        assert object_store.get(refs[1]) == {"a": 1}
+
+
+class TestObjectStoreAudit:
+    """Tests for the object access audit log."""
+
+    @staticmethod
+    def _records(path):
+        import json
+        return [json.loads(line) for line in Path(path).read_text().splitlines()]
+
+    @pytest.mark.p0
+    def test_records_put_get_delete(self, temp_dir):
+        """Test that each operation appends a line with key and size."""
+        from anyserve.objects import AuditLog, ObjectStore
+
+        log_path = os.path.join(temp_dir, "audit.jsonl")
+        audit = AuditLog(log_path)
+        store = ObjectStore(os.path.join(temp_dir, "objects"), audit_log=audit)
+
+        obj_ref = store.create(b"12345", key="audited")
+        store.get(obj_ref)
+        store.get_buffer(obj_ref.path)
+        store.delete(obj_ref)
+        audit.close()
+
+        records = self._records(log_path)
+        assert [r["op"] for r in records] == ["put", "get", "get", "delete"]
+        assert all(r["key"] == "audited" and r["size"] == 5 and r["peer"] is None for r in records)
+        assert all(r["ts"].endswith("+00:00") for r in records)
+
+    @pytest.mark.p1
+    def test_peer_recorded_for_remote_access(self, temp_dir):
+        """Test that federated reads and replicated copies name the other instance."""
+        from anyserve.objects import AuditLog, ObjectStore
+
+        log_path = os.path.join(temp_dir, "audit.jsonl")
+        audit = AuditLog(log_path)
+        base = os.path.join(temp_dir, "instances")
+        a = ObjectStore(os.path.join(base, "a", "objects"), audit_log=audit)
+        b = ObjectStore(os.path.join(base, "b", "objects"), federated=True, audit_log=audit)
+
+        obj_ref = a.create_replicated(b"copy", ["b"], min_acks=1, key="rep")
+        only_a = a.create(b"only-a", key="only-a")
+        b.get(str(b.base_path / Path(only_a.path).name))
+        audit.close()
+
+        records = [(r["op"], r["key"], r["peer"]) for r in self._records(log_path)]
+        assert ("put", "rep", None) in records
+        assert ("put", "rep", "b") in records
+        assert ("get", "only-a", "a") in records
+        assert obj_ref.key == "rep"
+
+    @pytest.mark.p1
+    def test_background_flush_and_append(self, temp_dir):
+        """Test that records reach the file without close() and the file is only appended to."""
+        from anyserve.objects import AuditLog, ObjectStore
+
+        log_path = os.path.join(temp_dir, "audit.jsonl")
+        Path(log_path).write_text('{"op": "earlier"}\n')
+        store = ObjectStore(os.path.join(temp_dir, "objects"), audit_log=log_path)
+        assert isinstance(store.audit_log, AuditLog)
+        store.audit_log.flush_interval = 0.05
+
+        store.create(b"x", key="flushed")
+        deadline = time.time() + 5
+        while "flushed" not in Path(log_path).read_text() and time.time() < deadline:
+            time.sleep(0.05)
+
+        records = self._records(log_path)
+        assert records[0] == {"op": "earlier"}
+        assert records[1]["key"] == "flushed"
+        store.audit_log.close()
+
+    @pytest.mark.p0
+    def test_get_records_found_and_latency(self, temp_dir):
+        """Test that reads carry found, size and latency, and misses are only logged when enabled."""
+        from anyserve.objects import AuditLog, ObjectStore
+
+        log_path = os.path.join(temp_dir, "audit.jsonl")
+        audit = AuditLog(log_path, ops={"get"}, log_not_found=True)
+        store = ObjectStore(os.path.join(temp_dir, "objects"), audit_log=audit)
+
+        obj_ref = store.create(b"12345", key="hit")
+        store.get(obj_ref)
+        with pytest.raises(FileNotFoundError):
+            store.get(str(store.base_path / "missing.bin"))
+        audit.close()
+
+        hit, miss = self._records(log_path)
+        assert hit["op"] == "get" and hit["key"] == "hit"
+        assert hit["found"] is True and hit["size"] == 5 and hit["peer"] is None
+        assert hit["latency_ms"] >= 0
+        assert miss["key"] == "missing"
+        assert miss["found"] is False and miss["size"] is None
+        assert miss["latency_ms"] >= 0
+
+    @pytest.mark.p1
+    def test_not_found_skipped_by_default(self, temp_dir):
+        """Test that misses are not recorded unless log_not_found is set."""
+        from anyserve.objects import AuditLog, ObjectStore
+
+        log_path = os.path.join(temp_dir, "audit.jsonl")
+        audit = AuditLog(log_path)
+        store = ObjectStore(os.path.join(temp_dir, "objects"), audit_log=audit)
+
+        with pytest.raises(FileNotFoundError):
+            store.get_buffer(str(store.base_path / "missing.bin"))
+        store.create(b"x", key="written")
+        audit.close()
+
+        assert [(r["op"], r["key"]) for r in self._records(log_path)] == [("put", "written")]
+
+    @pytest.mark.p2
+    def test_no_log_by_default(self, object_store):
+        """Test that auditing is off unless configured."""
+        assert object_store.audit_log is None
+        object_store.create(b"x")
+
+
+class TestObjectStoreResumable:
+    """Tests for resumable uploads via open_writer(upload_id=...)."""
+
+    @pytest.mark.p0
+    def test_interrupted_upload_resumes(self, object_store):
+        """Test that data from a dropped upload is kept and the rest can be appended."""
+        payload = os.urandom(300_000)
+
+        with pytest.raises(ConnectionError):
+            with object_store.open_writer(key="big", upload_id="u1") as writer:
+                writer.write(payload[:100_000])
+                raise ConnectionError("link dropped")
+
+        offset = object_store.upload_offset("u1")
+        assert offset == 100_000
+
+        with object_store.open_writer(key="big", upload_id="u1") as writer:
+            assert writer.size == offset
+            writer.write(payload[offset:])
+            obj_ref = writer.finish()
+
+        assert object_store.get(obj_ref) == payload
+        assert obj_ref.size == len(payload)
+        assert object_store.upload_offset("u1") == 0
+        assert [p.name for p in object_store.base_path.iterdir()] == ["big.bin"]
+
+    @pytest.mark.p1
+    def test_resume_at_earlier_offset(self, object_store):
+        """Test that resuming below the stored size drops the unconfirmed tail."""
+        with object_store.open_writer(key="k", upload_id="u2") as writer:
+            writer.write(b"confirmed-unconfirmed")
+
+        with object_store.open_writer(key="k", upload_id="u2", offset=len(b"confirmed-")) as writer:
+            writer.write(b"resent")
+            obj_ref = writer.finish()
+
+        assert object_store.get(obj_ref) == b"confirmed-resent"
+
+    @pytest.mark.p1
+    def test_offset_past_received_data(self, object_store):
+        """Test that an offset beyond what was received is rejected without side effects."""
+        with pytest.raises(ValueError):
+            object_store.open_writer(upload_id="fresh", offset=10)
+        assert object_store.upload_offset("fresh") == 0
+        assert list(object_store.base_path.iterdir()) == []
+
+        with pytest.raises(ValueError):
+            object_store.open_writer(offset=0)
+
+    @pytest.mark.p1
+    def test_abort_and_discard(self, object_store):
+        """Test that abort() and discard_upload() drop the partial data."""
+        writer = object_store.open_writer(upload_id="u3")
+        writer.write(b"abc")
+        writer.abort()
+        assert object_store.upload_offset("u3") == 0
+
+        with object_store.open_writer(upload_id="u4") as writer:
+            writer.write(b"abc")
+        assert object_store.discard_upload("u4")
+        assert not object_store.discard_upload("u4")
+
+    @pytest.mark.p2
+    def test_dedup_key_covers_earlier_attempts(self, temp_dir):
+        """Test that a content-addressed key hashes bytes from before the resume too."""
+        import hashlib
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, dedup=True)
+        with store.open_writer(upload_id="u5") as writer:
+            writer.write(b"first half, ")
+        with store.open_writer(upload_id="u5") as writer:
+            writer.write(b"second half")
+            obj_ref = writer.finish()
+
+        assert obj_ref.key == "sha256-" + hashlib.sha256(b"first half, second half").hexdigest()
+
+    @pytest.mark.p2
+    def test_partial_not_listed(self, object_store):
+        """Test that in-progress uploads are not listed as objects."""
+        with object_store.open_writer(upload_id="u6") as writer:
+            writer.write(b"partial")
+
+        assert object_store.list_objects() == []
+        with pytest.raises(ValueError):
+            object_store.upload_offset("../escape")
+
+
+class TestObjectStoreGC:
+    """Tests for max_bytes / max_age eviction."""
+
+    @staticmethod
+    def _aged(store, payloads):
+        """Create objects whose last use is 100s, 99s, ... ago, oldest first."""
+        refs = []
+        now = time.time()
+        for i, payload in enumerate(payloads):
+            ref = store.create(payload, content_type="bytes")
+            last_used = now - 100 + i
+            os.utime(ref.path, (last_used, last_used))
+            refs.append(ref)
+        return refs
+
+    @pytest.mark.p0
+    def test_evicts_oldest_past_max_bytes_but_not_pinned(self, temp_dir):
+        """Test that the oldest objects go first until under the limit, skipping a pinned one."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, max_bytes=250, gc_interval=3600)
+        refs = self._aged(store, [bytes([i]) * 100 for i in range(5)])
+        try:
+            with store.pin(refs[0]):
+                evicted = store.gc()
+
+            assert evicted == 3
+            assert [store.exists(ref) for ref in refs] == [True, False, False, False, True]
+            assert store.get(refs[0]) == bytes([0]) * 100
+        finally:
+            store.close()
+
+    @pytest.mark.p1
+    def test_evicts_past_max_age(self, temp_dir):
+        """Test that objects unused for longer than max_age are evicted with their sidecars."""
+        from anyserve.objects import ObjectStore
+        from anyserve.objects.layout import meta_path
+
+        store = ObjectStore(temp_dir, max_age=60, gc_interval=3600)
+        old = store.create(b"old", content_type="bytes", media_type="text/plain")
+        fresh = store.create(b"fresh", content_type="bytes")
+        stale = time.time() - 120
+        os.utime(old.path, (stale, stale))
+        try:
+            assert store.gc() == 1
+            assert not store.exists(old)
+            assert not meta_path(Path(old.path)).exists()
+            assert store.exists(fresh)
+        finally:
+            store.close()
+
+    @pytest.mark.p1
+    def test_background_gc(self, temp_dir):
+        """Test that the GC thread enforces the limit without an explicit gc() call."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, max_bytes=100, gc_interval=0.05)
+        try:
+            refs = self._aged(store, [b"a" * 100, b"b" * 100])
+            deadline = time.time() + 5
+            while store.exists(refs[0]) and time.time() < deadline:
+                time.sleep(0.05)
+
+            assert not store.exists(refs[0])
+            assert store.exists(refs[1])
+        finally:
+            store.close()
+
+    @pytest.mark.p2
+    def test_disabled_by_default(self, object_store):
+        """Test that without limits gc() evicts nothing and no thread runs."""
+        object_store.create(b"x" * 1000, content_type="bytes")
+
+        assert object_store.gc() == 0
+        assert object_store._gc_thread is None
+
+
+class TestObjectStoreSharding:
+    """Tests for the sharded objects/<xx>/<file> layout."""
+
+    @pytest.mark.p0
+    def test_sharded_round_trip(self, temp_dir):
+        """Test that a sharded store writes into a subdirectory and reads, lists and deletes there."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, shard=True)
+        obj_ref = store.create(b"payload", key="sharded-key", content_type="bytes")
+        path = Path(obj_ref.path)
+
+        assert path.parent.parent == Path(temp_dir)
+        assert len(path.parent.name) == ObjectStore.SHARD_CHARS
+        assert store.get(obj_ref) == b"payload"
+        assert [ref.key for ref in store.list_objects()] == ["sharded-key"]
+        assert store.storage_usage() == len(b"payload")
+        assert store.delete(obj_ref)
+        assert not path.exists()
+
+    @pytest.mark.p0
+    def test_both_layouts_readable(self, temp_dir):
+        """Test that flat and sharded objects in one directory are all visible to either mode."""
+        from anyserve.objects import ObjectStore
+
+        flat_ref = ObjectStore(temp_dir).create(b"flat", key="old", content_type="bytes")
+        sharded = ObjectStore(temp_dir, shard=True)
+        sharded_ref = sharded.create(b"sharded", key="new", content_type="bytes")
+        flat = ObjectStore(temp_dir)
+
+        for store in (flat, sharded):
+            assert store.get(flat_ref) == b"flat"
+            assert store.get(sharded_ref) == b"sharded"
+            assert sorted(ref.key for ref in store.list_objects()) == ["new", "old"]
+            page, token = store.list_objects_page(page_size=1)
+            rest, _ = store.list_objects_page(page_size=1, page_token=token)
+            assert [ref.key for ref in page + rest] == ["new", "old"]
+
+        # A flat-style path still finds the sharded file
+        assert flat.get(os.path.join(temp_dir, "new.bin")) == b"sharded"
+        assert flat.delete(os.path.join(temp_dir, "new.bin"))
+        assert sharded.delete(flat_ref)
+        assert sharded.list_objects() == []
+
+    @pytest.mark.p1
+    def test_create_named_sees_flat_object(self, temp_dir):
+        """Test that a name taken in the flat layout is still taken after switching to sharding."""
+        from anyserve.objects import ObjectStore
+
+        ObjectStore(temp_dir).create_named("model-v1", b"weights", content_type="bytes")
+
+        with pytest.raises(FileExistsError):
+            ObjectStore(temp_dir, shard=True).create_named("model-v1", b"other", content_type="bytes")
+
+    @pytest.mark.p1
+    def test_federated_sibling_sharded(self, temp_dir):
+        """Test that federated reads find a sibling's sharded object."""
+        from anyserve.objects import ObjectStore
+
+        base = Path(temp_dir) / "instances"
+        writer = ObjectStore(str(base / "a" / "objects"), shard=True)
+        reader = ObjectStore(str(base / "b" / "objects"), federated=True)
+        obj_ref = writer.create(b"remote", key="shared", content_type="bytes")
+
+        assert reader.get(str(base / "b" / "objects" / "shared.bin")) == b"remote"
+        assert reader.peek(obj_ref, owner="a") == len(b"remote")
+
+    @pytest.mark.p2
+    def test_clear_and_gc_cover_shards(self, temp_dir):
+        """Test that clear() and gc() reach into shard subdirectories."""
+        from anyserve.objects import ObjectStore
+
+        store = ObjectStore(temp_dir, shard=True, max_bytes=0, gc_interval=3600)
+        try:
+            store.create(b"a", key="one", content_type="bytes")
+            store.create(b"b", key="two", content_type="bytes")
+            assert store.gc() == 2
+
+            store.create(b"c", key="three", content_type="bytes")
+            assert store.clear() == 1
+            assert store.list_objects() == []
+        finally:
+            store.close()
+
+
+class TestObjectStoreCache:
+    """Tests for the in-memory cache of objects read from other instances' stores."""
+
+    @staticmethod
+    def _stores(temp_dir, cache_bytes):
+        from anyserve.objects import ObjectStore
+
+        base = os.path.join(temp_dir, "instances")
+        owner = ObjectStore(os.path.join(base, "a", "objects"))
+        reader = ObjectStore(os.path.join(base, "b", "objects"), federated=True,
+                             cache_bytes=cache_bytes)
+        return owner, reader
+
+    @pytest.mark.p0
+    def test_repeated_remote_reads_hit(self, temp_dir):
+        """Test that the second read of a remote object comes from memory."""
+        owner, reader = self._stores(temp_dir, cache_bytes=1024)
+        owner.create(b"hot", key="hot", content_type="bytes")
+        remote = str(reader.base_path / "hot.bin")
+
+        first = reader.get(remote)
+        assert first == b"hot"
+        # The cached bytes object itself comes back, not a fresh read
+        assert reader.get(remote) is first
+        assert reader.cache_stats() == {"hits": 1, "misses": 1, "entries": 1, "bytes": 3}
+
+    @pytest.mark.p1
+    def test_local_reads_not_cached(self, temp_dir):
+        """Test that objects in the store's own directory bypass the cache."""
+        _, reader = self._stores(temp_dir, cache_bytes=1024)
+        obj_ref = reader.create(b"local", content_type="bytes")
+
+        assert reader.get(obj_ref) == b"local"
+        assert reader.cache_stats()["entries"] == 0
+        assert reader.cache_stats()["misses"] == 0
+
+    @pytest.mark.p1
+    def test_changed_remote_object_refetched(self, temp_dir):
+        """Test that a remote object rewritten since it was cached is read again."""
+        owner, reader = self._stores(temp_dir, cache_bytes=1024)
+        owner.create(b"v1", key="named", content_type="bytes")
+        remote = str(reader.base_path / "named.bin")
+        assert reader.get(remote) == b"v1"
+
+        owner.create(b"v2-longer", key="named", content_type="bytes")
+
+        assert reader.get(remote) == b"v2-longer"
+        assert reader.cache_stats()["misses"] == 2
+
+    @pytest.mark.p1
+    def test_lru_bound_and_clear(self, temp_dir):
+        """Test that the cache stays within cache_bytes, evicting the least recently used."""
+        owner, reader = self._stores(temp_dir, cache_bytes=10)
+        for key in ("x", "y", "z"):
+            owner.create(key.encode() * 4, key=key, content_type="bytes")
+        remote = {key: str(reader.base_path / f"{key}.bin") for key in ("x", "y", "z")}
+
+        reader.get(remote["x"])
+        reader.get(remote["y"])
+        reader.get(remote["x"])
+        reader.get(remote["z"])
+
+        assert sorted(name for _, name in reader._cache._entries) == ["x.bin", "z.bin"]
+        assert reader.cache_stats()["bytes"] == 8
+
+        reader.cache_clear()
+        assert reader.cache_stats()["entries"] == 0
+        assert reader.cache_stats()["hits"] == 1
+
+    @pytest.mark.p1
+    def test_delete_invalidates(self, temp_dir):
+        """Test that deleting an object drops its cached copy."""
+        owner, reader = self._stores(temp_dir, cache_bytes=1024)
+        obj_ref = owner.create(b"gone", key="gone", content_type="bytes")
+        reader.get(str(reader.base_path / "gone.bin"))
+
+        assert reader.delete(obj_ref)
+        assert reader.cache_stats()["entries"] == 0
+        with pytest.raises(FileNotFoundError):
+            reader.get(str(reader.base_path / "gone.bin"))
+
+    @pytest.mark.p0
+    def test_pinned_object_survives_lru(self, temp_dir):
+        """Test that a pinned object is still served from memory after the LRU overflows."""
+        owner, reader = self._stores(temp_dir, cache_bytes=10)
+        for key in ("hot", "x", "y", "z"):
+            owner.create(key.encode() * 4, key=key, content_type="bytes")
+        remote = {key: str(reader.base_path / f"{key}.bin") for key in ("hot", "x", "y", "z")}
+
+        reader.cache_pin(remote["hot"])
+        pinned = reader.get(remote["hot"])
+        for key in ("x", "y", "z"):
+            reader.get(remote[key])
+        reader.cache_clear()
+
+        assert reader.get(remote["hot"]) is pinned
+        assert reader.cache_stats()["entries"] == 0
+        assert reader.cache_unpin(remote["hot"])
+        assert not reader.cache_unpin(remote["hot"])
+        assert reader.get(remote["hot"]) is not pinned
+
+    @pytest.mark.p1
+    def test_pin_local_object(self, temp_dir):
+        """Test that local objects can be pinned even with the LRU disabled."""
+        _, reader = self._stores(temp_dir, cache_bytes=0)
+        obj_ref = reader.create(b"local", content_type="bytes")
+
+        reader.cache_pin(obj_ref)
+        first = reader.get(obj_ref)
+        assert reader.get(obj_ref) is first
+        assert reader.cache_stats()["hits"] == 2
+
+        reader.create(b"rewritten", key=obj_ref.key, content_type="bytes")
+        assert reader.get(obj_ref) == b"rewritten"
+
+    @pytest.mark.p1
+    def test_pin_missing_and_delete(self, temp_dir):
+        """Test that pinning a missing object raises and deleting a pinned one drops it."""
+        owner, reader = self._stores(temp_dir, cache_bytes=0)
+        with pytest.raises(FileNotFoundError):
+            reader.cache_pin(str(reader.base_path / "nothing.bin"))
+
+        obj_ref = owner.create(b"gone", key="gone", content_type="bytes")
+        reader.cache_pin(str(reader.base_path / "gone.bin"))
+        assert reader.delete(obj_ref)
+        assert reader._cache._pinned == {}
+        with pytest.raises(FileNotFoundError):
+            reader.get(str(reader.base_path / "gone.bin"))
+
+    @pytest.mark.p1
+    def test_large_remote_object_mapped_not_copied(self, temp_dir):
+        """Test that a remote object past mmap_threshold is mapped instead of read onto the heap."""
+        import tracemalloc
+
+        owner, reader = self._stores(temp_dir, cache_bytes=64 << 20)
+        payload = os.urandom(4 << 20)
+        owner.create(payload, key="big", content_type="bytes")
+        remote = str(reader.base_path / "big.bin")
+
+        tracemalloc.start()
+        try:
+            buf = reader.get_buffer(remote)
+            _, peak = tracemalloc.get_traced_memory()
+        finally:
+            tracemalloc.stop()
+
+        assert isinstance(buf, memoryview)
+        assert peak < len(payload) // 4
+        assert bytes(buf) == payload
+        assert reader.cache_stats()["entries"] == 0
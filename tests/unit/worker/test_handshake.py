@@ -0,0 +1,69 @@
+"""
+Unit tests for the worker ready handshake.
+"""
+
+import os
+import pytest
+
+from anyserve.worker.handshake import ready_message, shm_sizes, signal_ready
+
+
+@pytest.fixture
+def shm_fds(temp_dir):
+    """Two files standing in for the inherited H2D / D2H segments."""
+    fds = []
+    for name, size in (("h2d", 4096), ("d2h", 8192)):
+        fd = os.open(os.path.join(temp_dir, name), os.O_RDWR | os.O_CREAT, 0o600)
+        os.ftruncate(fd, size)
+        fds.append(fd)
+    yield fds
+    for fd in fds:
+        os.close(fd)
+
+
+class TestHandshake:
+    """Tests for shm_sizes() / ready_message() / signal_ready()"""
+
+    @pytest.mark.p0
+    def test_reports_mapped_sizes(self, shm_fds, monkeypatch):
+        """Test that the message carries the sizes of the inherited segments."""
+        monkeypatch.setenv("ANSERVE_H2D_FD", str(shm_fds[0]))
+        monkeypatch.setenv("ANSERVE_D2H_FD", str(shm_fds[1]))
+
+        assert shm_sizes() == (4096, 8192)
+        assert ready_message("unix:///tmp/w.sock") == \
+            b"ready addr=unix:///tmp/w.sock h2d_size=4096 d2h_size=8192"
+
+    @pytest.mark.p1
+    def test_no_shm(self, monkeypatch):
+        """Test that without SHM fds the message is just "ready"."""
+        monkeypatch.delenv("ANSERVE_H2D_FD", raising=False)
+        monkeypatch.delenv("ANSERVE_D2H_FD", raising=False)
+
+        assert shm_sizes() is None
+        assert ready_message() == b"ready"
+
+    @pytest.mark.p1
+    def test_bad_fd_raises(self, monkeypatch):
+        """Test that an fd that isn't open fails instead of reporting a size."""
+        read_fd, write_fd = os.pipe()
+        os.close(read_fd)
+        os.close(write_fd)
+        monkeypatch.setenv("ANSERVE_H2D_FD", str(read_fd))
+        monkeypatch.setenv("ANSERVE_D2H_FD", str(read_fd))
+
+        with pytest.raises(OSError):
+            shm_sizes()
+
+    @pytest.mark.p1
+    def test_signal_ready_writes_and_closes(self, monkeypatch):
+        """Test that signal_ready() writes the message and closes the fd."""
+        monkeypatch.delenv("ANSERVE_H2D_FD", raising=False)
+        monkeypatch.delenv("ANSERVE_D2H_FD", raising=False)
+        read_fd, write_fd = os.pipe()
+        monkeypatch.setenv("ANSERVE_READY_FD", str(write_fd))
+
+        signal_ready()
+
+        with os.fdopen(read_fd, "rb") as reader:
+            assert reader.read() == b"ready"
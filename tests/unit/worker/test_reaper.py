@@ -0,0 +1,143 @@
+"""
+Unit tests for the stale socket/SHM reaper.
+"""
+
+import os
+import subprocess
+import sys
+import pytest
+from pathlib import Path
+
+from anyserve.worker.reaper import (
+    Reaper,
+    default_socket_dir,
+    pid_alive,
+    pid_marker_path,
+    reap_stale_shm,
+    reap_stale_sockets,
+    write_pid_marker,
+)
+
+
+@pytest.fixture
+def dead_pid():
+    """A pid that belonged to a process which has already exited."""
+    proc = subprocess.Popen([sys.executable, "-c", "pass"])
+    proc.wait()
+    return proc.pid
+
+
+class TestPidAlive:
+    """Tests for pid_alive()"""
+
+    @pytest.mark.p1
+    def test_current_process_alive(self):
+        assert pid_alive(os.getpid())
+
+    @pytest.mark.p1
+    def test_exited_process_dead(self, dead_pid):
+        assert not pid_alive(dead_pid)
+
+    @pytest.mark.p2
+    def test_invalid_pid_dead(self):
+        assert not pid_alive(0)
+        assert not pid_alive(-1)
+
+
+class TestReapStaleSockets:
+    """Tests for reap_stale_sockets()"""
+
+    @pytest.mark.p0
+    def test_removes_socket_with_dead_pid(self, temp_dir, dead_pid):
+        """Test that a socket whose owner exited is removed with its marker."""
+        sock = Path(temp_dir) / "anyserve-worker-w1.sock"
+        sock.touch()
+        Path(pid_marker_path(str(sock))).write_text(str(dead_pid))
+
+        assert reap_stale_sockets(temp_dir) == 1
+        assert not sock.exists()
+        assert not Path(pid_marker_path(str(sock))).exists()
+
+    @pytest.mark.p0
+    def test_keeps_socket_with_live_pid(self, temp_dir):
+        """Test that a live instance's socket is never removed."""
+        sock = Path(temp_dir) / "anyserve-worker-w2.sock"
+        sock.touch()
+        write_pid_marker(str(sock))
+
+        assert reap_stale_sockets(temp_dir) == 0
+        assert sock.exists()
+
+    @pytest.mark.p1
+    def test_keeps_socket_without_marker(self, temp_dir):
+        """Test that sockets with unknown ownership are left alone."""
+        sock = Path(temp_dir) / "anyserve_12345.sock"
+        sock.touch()
+
+        assert reap_stale_sockets(temp_dir) == 0
+        assert sock.exists()
+
+    @pytest.mark.p2
+    def test_ignores_unrelated_files(self, temp_dir, dead_pid):
+        """Test that files not matching the socket patterns are untouched."""
+        other = Path(temp_dir) / "other.sock"
+        other.touch()
+        Path(pid_marker_path(str(other))).write_text(str(dead_pid))
+
+        assert reap_stale_sockets(temp_dir) == 0
+        assert other.exists()
+
+
+class TestDefaultSocketDir:
+    """Tests for default_socket_dir(), which must match anyserve_node's --uds-dir default."""
+
+    @pytest.mark.p1
+    def test_follows_tmpdir(self, temp_dir, monkeypatch, dead_pid):
+        """Test that $TMPDIR is used and swept when no directory is given."""
+        monkeypatch.setenv("TMPDIR", temp_dir + "/")
+        assert default_socket_dir() == temp_dir
+        assert Reaper().socket_dir == temp_dir
+
+        sock = Path(temp_dir) / "anyserve_1_2.sock"
+        sock.touch()
+        Path(pid_marker_path(str(sock))).write_text(str(dead_pid))
+        assert reap_stale_sockets() == 1
+        assert not sock.exists()
+
+    @pytest.mark.p2
+    def test_falls_back_to_tmp(self, monkeypatch):
+        """Test that an unset or empty $TMPDIR means /tmp."""
+        monkeypatch.delenv("TMPDIR", raising=False)
+        assert default_socket_dir() == "/tmp"
+        monkeypatch.setenv("TMPDIR", "")
+        assert default_socket_dir() == "/tmp"
+
+
+class TestReapStaleShm:
+    """Tests for reap_stale_shm()"""
+
+    @pytest.mark.p1
+    def test_removes_segment_with_dead_pid(self, temp_dir, dead_pid):
+        """Test that a segment named after an exited pid is removed."""
+        stale = Path(temp_dir) / f"as_{dead_pid}_deadbeef"
+        live = Path(temp_dir) / f"as_{os.getpid()}_cafef00d"
+        stale.touch()
+        live.touch()
+
+        assert reap_stale_shm(temp_dir) == 1
+        assert not stale.exists()
+        assert live.exists()
+
+    @pytest.mark.p2
+    def test_keeps_segments_without_pid(self, temp_dir):
+        """Test that legacy or foreign segment names are left alone."""
+        legacy = Path(temp_dir) / "as_deadbeef"
+        legacy.touch()
+
+        assert reap_stale_shm(temp_dir) == 0
+        assert legacy.exists()
+
+    @pytest.mark.p2
+    def test_missing_dir(self, temp_dir):
+        """Test that a missing SHM directory (e.g. macOS) is a no-op."""
+        assert reap_stale_shm(os.path.join(temp_dir, "nope")) == 0